@@ -1,3 +1,4 @@
+use crate::persistence::ModulationRate;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -17,6 +18,10 @@ pub struct Glue {
     pub reverse: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub out_of_range_behavior: Option<OutOfRangeBehavior>,
+    /// Non-linear response curve, applied before the control transformation and (inverted)
+    /// after the feedback transformation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub curve: Option<Curve>,
     //endregion
 
     //region Relevant for control only (might change in future)
@@ -36,9 +41,14 @@ pub struct Glue {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub control_transformation: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub control_transformation_kind: Option<TransformationKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub step_size_interval: Option<Interval<f64>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub step_factor_interval: Option<Interval<i32>>,
+    /// Amplifies increments of relative encoders depending on the turn speed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acceleration: Option<Acceleration>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub button_filter: Option<ButtonFilter>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -59,6 +69,52 @@ pub struct Glue {
     //endregion
 }
 
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind")]
+pub enum Curve {
+    Logarithmic,
+    Exponential,
+    SCurve {
+        /// 0.0 is linear, positive values make the S more pronounced, negative values (down to
+        /// -0.9) invert it.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tension: Option<f64>,
+    },
+    /// Maps the fader position to a linear amplitude with a range of 60 dB.
+    DbTaper,
+    Breakpoints {
+        /// At least 2 points, sorted by x and with monotonic y values (so the curve is
+        /// invertible for feedback).
+        points: Vec<CurvePoint>,
+    },
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CurvePoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Acceleration {
+    /// Time between two encoder ticks at or above which increments are not amplified.
+    /// Default: 100 ms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slow_interval_millis: Option<u64>,
+    /// Time between two encoder ticks at or below which increments are amplified by the maximum
+    /// factor. Default: 10 ms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fast_interval_millis: Option<u64>,
+    /// Maximum factor by which increments are multiplied. Default: 10.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_factor: Option<f64>,
+    /// Maps the turn speed to the factor. Default: Linear.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub curve: Option<Curve>,
+}
+
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "kind")]
 pub enum FeedbackValueTable {
@@ -106,6 +162,8 @@ pub enum FireMode {
     AfterTimeoutKeepFiring(AfterTimeoutKeepFiringFireMode),
     OnSinglePress(OnSinglePressFireMode),
     OnDoublePress(OnDoublePressFireMode),
+    OnNthPress(OnNthPressFireMode),
+    PressAndHold(PressAndHoldFireMode),
 }
 
 impl Default for FireMode {
@@ -147,6 +205,30 @@ pub struct OnSinglePressFireMode {
 #[serde(deny_unknown_fields)]
 pub struct OnDoublePressFireMode;
 
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct OnNthPressFireMode {
+    /// Number of presses, e.g. 3 for a triple press.
+    pub n: u32,
+    /// Maximum time in milliseconds between two consecutive presses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_interval: Option<u32>,
+}
+
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PressAndHoldFireMode {
+    /// Time in milliseconds the button needs to be held until `hold_value` is fired.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u32>,
+    /// Value fired when the button has been held long enough.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hold_value: Option<f64>,
+    /// Value fired when the button is released after having been held long enough.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_value: Option<f64>,
+}
+
 #[derive(PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum VirtualColor {
@@ -154,7 +236,7 @@ pub enum VirtualColor {
     Prop(PropColor),
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct RgbColor(pub u8, pub u8, pub u8);
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -176,6 +258,21 @@ impl Default for OutOfRangeBehavior {
     }
 }
 
+/// Scripting language in which a control or feedback transformation is written.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+pub enum TransformationKind {
+    Eel,
+    /// Not available for mappings which are controlled in real-time (e.g. with target
+    /// "MIDI: Send message"). Use EEL for those.
+    Lua,
+}
+
+impl Default for TransformationKind {
+    fn default() -> Self {
+        Self::Eel
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub enum TakeoverMode {
     PickUp,
@@ -240,6 +337,40 @@ pub struct NumericFeedback {
     pub commons: FeedbackCommons,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub transformation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transformation_kind: Option<TransformationKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub animation: Option<FeedbackAnimation>,
+}
+
+/// Makes numeric feedback change over time, e.g. in order to let an LED blink.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FeedbackAnimation {
+    pub effect: AnimationEffect,
+    /// Length of one animation cycle. Default: 2 cycles per second.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate: Option<ModulationRate>,
+    /// Only animates if the feedback value (after glue processing) is within this interval.
+    /// Default: Always animates.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_interval: Option<Interval<f64>>,
+    /// If the source is a MIDI source, sends the feedback on this MIDI channel (0 - 15) instead of
+    /// animating it in ReaLearn. Useful for controllers with built-in animations, e.g. Launchpads
+    /// flash on channel index 1 and pulse on channel index 2.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_midi_channel: Option<u8>,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind")]
+pub enum AnimationEffect {
+    /// Switches the feedback off during the second half of each cycle.
+    Blink,
+    /// Fades the feedback value out and in again during each cycle.
+    Pulse,
+    /// Uses the given color instead of the feedback color during the second half of each cycle.
+    AlternateColor { color: RgbColor },
 }
 
 #[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
@@ -251,5 +382,5 @@ pub struct TextFeedback {
     pub text_expression: Option<String>,
 }
 
-#[derive(Copy, Clone, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[derive(Copy, Clone, PartialEq, Debug, Default, Serialize, Deserialize, JsonSchema)]
 pub struct Interval<T>(pub T, pub T);
@@ -37,10 +37,39 @@ pub struct Mapping {
     pub glue: Option<Glue>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub target: Option<Target>,
+    /// Further targets which are controlled by the same source.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub additional_targets: Option<Vec<AdditionalTarget>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub unprocessed: Option<serde_json::Map<String, serde_json::Value>>,
 }
 
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AdditionalTarget {
+    pub target: Target,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub glue: Option<TargetGlue>,
+    /// If `true`, feedback is taken from this target instead of the main target.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feedback: Option<bool>,
+}
+
+/// The target-related subset of the glue settings. Everything else is taken from the glue of the
+/// mapping.
+#[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TargetGlue {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_interval: Option<Interval<f64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reverse: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub control_transformation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub control_transformation_kind: Option<TransformationKind>,
+}
+
 #[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
 pub struct LifecycleHook {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -67,6 +96,8 @@ pub enum ActivationCondition {
     Bank(BankActivationCondition),
     Eel(EelActivationCondition),
     Expression(ExpressionActivationCondition),
+    Lua(LuaActivationCondition),
+    TargetValue(TargetValueActivationCondition),
 }
 
 #[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
@@ -101,6 +132,25 @@ pub struct ExpressionActivationCondition {
     pub condition: String,
 }
 
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct LuaActivationCondition {
+    pub condition: String,
+}
+
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TargetValueActivationCondition {
+    /// ID of the mapping whose target value should be checked.
+    ///
+    /// Must be in the same compartment.
+    pub mapping: String,
+    /// Expression which must evaluate to a value > 0 in order to activate the mapping.
+    ///
+    /// The normalized target value of the referenced mapping is available as `y`.
+    pub condition: String,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum ParamRef {
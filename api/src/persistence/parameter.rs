@@ -18,4 +18,32 @@ pub struct Parameter {
     pub value_count: Option<NonZeroU32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value_labels: Option<Vec<String>>,
+    /// Natural value corresponding to the minimum parameter value. Only relevant for continuous
+    /// parameters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    /// Natural value corresponding to the maximum parameter value. Only relevant for continuous
+    /// parameters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    /// Unit which is displayed after the natural value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    /// Natural value which is used when resetting the parameter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_value: Option<f64>,
+    /// If set, the parameter value follows this source.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_source: Option<ParameterValueSource>,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind")]
+pub enum ParameterValueSource {
+    /// Project-specific extended state, containing the natural value as text.
+    ProjectExtState { section: String, key: String },
+    /// Global extended state, containing the natural value as text.
+    ExtState { section: String, key: String },
+    /// Parameter of another ReaLearn instance, identified by its session ID.
+    InstanceParameter { session_id: String, index: u32 },
 }
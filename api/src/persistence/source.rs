@@ -17,6 +17,8 @@ pub enum Source {
     MidiDeviceChanges(MidiDeviceChangesSource),
     RealearnInstanceStart(RealearnInstanceStartSource),
     Timer(TimerSource),
+    Lfo(LfoSource),
+    StepSequencer(StepSequencerSource),
     // MIDI
     MidiNoteVelocity(MidiNoteVelocitySource),
     MidiNoteKeyNumber(MidiNoteKeyNumberSource),
@@ -338,6 +340,58 @@ mod reaper {
     pub struct TimerSource {
         pub duration: u64,
     }
+
+    #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(deny_unknown_fields)]
+    pub struct LfoSource {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub shape: Option<LfoShape>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub rate: Option<ModulationRate>,
+        /// Phase offset between 0.0 and 1.0 (= one cycle).
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub phase: Option<f64>,
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+    pub enum LfoShape {
+        Sine,
+        Triangle,
+        Saw,
+        Square,
+        RandomSampleAndHold,
+    }
+
+    impl Default for LfoShape {
+        fn default() -> Self {
+            Self::Sine
+        }
+    }
+
+    /// Speed of a modulation source.
+    #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(tag = "kind")]
+    pub enum ModulationRate {
+        /// Number of cycles (LFO) or steps (step sequencer) per second.
+        Frequency { hertz: f64 },
+        /// Length of one cycle (LFO) or step (step sequencer) in beats, synced to the project.
+        Beats { beats: f64 },
+    }
+
+    impl Default for ModulationRate {
+        fn default() -> Self {
+            Self::Frequency { hertz: 1.0 }
+        }
+    }
+
+    #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+    #[serde(deny_unknown_fields)]
+    pub struct StepSequencerSource {
+        /// Values between 0.0 and 1.0, one for each step.
+        pub steps: Vec<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub rate: Option<ModulationRate>,
+    }
 }
 
 mod keyboard {
@@ -56,6 +56,8 @@ pub enum Target {
     ClipMatrixAction(ClipMatrixTarget),
     ClipSeek(ClipSeekTarget),
     ClipVolume(ClipVolumeTarget),
+    ClipPitch(ClipPitchTarget),
+    ClipMidiVelocity(ClipMidiVelocityTarget),
     ClipManagement(ClipManagementTarget),
     SendMidi(SendMidiTarget),
     SendOsc(SendOscTarget),
@@ -569,6 +571,26 @@ pub struct ClipVolumeTarget {
     pub slot: ClipSlotDescriptor,
 }
 
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ClipPitchTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    pub slot: ClipSlotDescriptor,
+}
+
+/// Controls the velocity scale of a clip's MIDI transformation.
+///
+/// The other parts of the transformation (output channel, note range, fixed note length etc.) are
+/// not exposed as targets yet. They can only be changed via the clip's MIDI settings.
+#[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ClipMidiVelocityTarget {
+    #[serde(flatten)]
+    pub commons: TargetCommons,
+    pub slot: ClipSlotDescriptor,
+}
+
 #[derive(PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct ClipManagementTarget {
@@ -586,6 +608,34 @@ pub enum ClipManagementAction {
     EditClip,
     CopyOrPasteClip,
     AdjustClipSectionLength(AdjustClipSectionLengthAction),
+    /// Multiplies the length of the clip canvas (the total time the clip consumes) with a factor,
+    /// e.g. to add silence at the end.
+    AdjustClipCanvasLength(AdjustClipCanvasLengthAction),
+    /// Moves the source section within the clip canvas, e.g. to add silence at the beginning
+    /// without shifting the downbeat.
+    MoveClipSectionInCanvas(MoveClipSectionInCanvasAction),
+    /// Detects the first transient and the tempo of an audio clip and adjusts its section start
+    /// and time base accordingly.
+    DetectStartTimeAndTempo,
+    /// Picks up the clip in this slot as source for a subsequent [`Self::DropClip`].
+    ///
+    /// Works across all instances sharing the same clip matrix.
+    PickUpClip,
+    /// Moves, copies or swaps the clip which has been picked up before to this slot.
+    DropClip(DropClipAction),
+    /// While on, clip volume changes during playback are written into the clip's volume envelope.
+    WriteEnvelopes,
+    /// Makes the next take of this slot the active one.
+    CycleTakes,
+    /// Discards the active take of this slot and activates the most recently recorded remaining
+    /// take.
+    DeleteTake,
+    /// Makes the given inactive take of this slot the active one.
+    SelectTake(SelectTakeAction),
+    /// Keeps the active take of this slot and discards all other takes.
+    ///
+    /// Combining parts of several takes into one (comping) is not supported.
+    KeepActiveTakeOnly,
 }
 
 impl Default for ClipManagementAction {
@@ -600,6 +650,48 @@ pub struct AdjustClipSectionLengthAction {
     pub factor: f64,
 }
 
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AdjustClipCanvasLengthAction {
+    pub factor: f64,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MoveClipSectionInCanvasAction {
+    /// Amount of seconds to move the section to the right (negative values move it to the left).
+    pub delta: f64,
+}
+
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DropClipAction {
+    pub mode: ClipDropMode,
+}
+
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SelectTakeAction {
+    /// Index of the take among the inactive takes of the slot, oldest first.
+    pub index: usize,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+pub enum ClipDropMode {
+    /// Moves the picked-up clip to the destination slot, which must be empty.
+    Move,
+    /// Copies the picked-up clip to the destination slot, which must be empty.
+    Copy,
+    /// Swaps the picked-up clip with the clip in the destination slot.
+    Swap,
+}
+
+impl Default for ClipDropMode {
+    fn default() -> Self {
+        Self::Move
+    }
+}
+
 #[derive(PartialEq, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct SendMidiTarget {
@@ -797,6 +889,13 @@ pub enum ClipTransportAction {
     /// - If slot recording, has no effect.
     #[display(fmt = "Looped")]
     Looped,
+    /// Changes the reverse setting.
+    ///
+    /// - If slot is filled, sets reversed on or off.
+    /// - If slot empty, has no effect.
+    /// - If slot recording, has no effect.
+    #[display(fmt = "Reversed")]
+    Reversed,
 }
 
 impl Default for ClipTransportAction {
@@ -823,6 +922,18 @@ impl Default for ClipTransportAction {
 pub enum ClipColumnAction {
     #[display(fmt = "Stop")]
     Stop,
+    #[display(fmt = "Insert empty column with new track")]
+    InsertColumnWithNewTrack,
+    #[display(fmt = "Remove")]
+    RemoveColumn,
+    #[display(fmt = "Move left")]
+    MoveColumnLeft,
+    #[display(fmt = "Move right")]
+    MoveColumnRight,
+    #[display(fmt = "Play on selected track")]
+    UseSelectedTrackForPlayback,
+    #[display(fmt = "Record from selected track")]
+    UseSelectedTrackForRecording,
 }
 
 impl Default for ClipColumnAction {
@@ -860,6 +971,16 @@ pub enum ClipRowAction {
     #[display(fmt = "Copy or paste")]
     #[serde(alias = "CopyOrPaste")]
     CopyOrPasteScene,
+    #[display(fmt = "Insert empty row above")]
+    InsertScene,
+    #[display(fmt = "Delete")]
+    DeleteScene,
+    #[display(fmt = "Duplicate")]
+    DuplicateScene,
+    #[display(fmt = "Move up")]
+    MoveSceneUp,
+    #[display(fmt = "Move down")]
+    MoveSceneDown,
 }
 
 impl Default for ClipRowAction {
@@ -892,6 +1013,8 @@ pub enum ClipMatrixAction {
     Redo,
     #[display(fmt = "Build scene")]
     BuildScene,
+    #[display(fmt = "Browse undo history")]
+    BrowseHistory,
 }
 
 impl Default for ClipMatrixAction {
@@ -2,7 +2,10 @@ use crate::application::{
     ActivationType, Affected, BankConditionModel, Change, GetProcessingRelevance,
     ModifierConditionModel, ProcessingRelevance,
 };
-use crate::domain::{ActivationCondition, EelCondition, ExpressionCondition};
+use crate::domain::{
+    ActivationCondition, BackboneState, EelCondition, ExpressionCondition, LuaCondition,
+    MappingKey, TargetValueCondition,
+};
 
 #[allow(clippy::enum_variant_names)]
 pub enum ActivationConditionCommand {
@@ -11,6 +14,7 @@ pub enum ActivationConditionCommand {
     SetModifierCondition2(ModifierConditionModel),
     SetBankCondition(BankConditionModel),
     SetScript(String),
+    SetLeadMapping(Option<MappingKey>),
 }
 
 #[derive(PartialEq)]
@@ -20,6 +24,7 @@ pub enum ActivationConditionProp {
     ModifierCondition2,
     BankCondition,
     Script,
+    LeadMapping,
 }
 
 impl GetProcessingRelevance for ActivationConditionProp {
@@ -35,6 +40,8 @@ pub struct ActivationConditionModel {
     modifier_condition_2: ModifierConditionModel,
     bank_condition: BankConditionModel,
     script: String,
+    /// The mapping whose target value is checked by the target value condition.
+    lead_mapping: Option<MappingKey>,
 }
 
 impl<'a> Change<'a> for ActivationConditionModel {
@@ -69,6 +76,10 @@ impl<'a> Change<'a> for ActivationConditionModel {
                 self.script = v;
                 One(P::Script)
             }
+            C::SetLeadMapping(v) => {
+                self.lead_mapping = v;
+                One(P::LeadMapping)
+            }
         };
         Some(affected)
     }
@@ -95,6 +106,10 @@ impl ActivationConditionModel {
         &self.script
     }
 
+    pub fn lead_mapping(&self) -> Option<&MappingKey> {
+        self.lead_mapping.as_ref()
+    }
+
     pub fn create_activation_condition(&self) -> ActivationCondition {
         use ActivationType::*;
         match self.activation_type() {
@@ -118,6 +133,23 @@ impl ActivationConditionModel {
                 Ok(e) => ActivationCondition::Expression(Box::new(e)),
                 Err(_) => ActivationCondition::Always,
             },
+            Lua => {
+                let lua = unsafe { BackboneState::main_thread_lua() };
+                match LuaCondition::compile(lua, self.script()) {
+                    Ok(c) => ActivationCondition::Lua(Box::new(c)),
+                    Err(_) => ActivationCondition::Always,
+                }
+            }
+            TargetValue => {
+                let lead_mapping = match self.lead_mapping.clone() {
+                    None => return ActivationCondition::Always,
+                    Some(k) => k,
+                };
+                match TargetValueCondition::compile(lead_mapping, self.script()) {
+                    Ok(c) => ActivationCondition::TargetValue(Box::new(c)),
+                    Err(_) => ActivationCondition::Always,
+                }
+            }
         }
     }
 
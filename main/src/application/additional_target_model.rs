@@ -0,0 +1,95 @@
+use crate::application::{Change, ModeCommand, ModeModel, TargetModel};
+use crate::domain::Compartment;
+use helgoboss_learn::{full_unit_interval, Interval, UnitValue};
+use realearn_api::persistence::TransformationKind;
+
+/// A further target of a mapping. It's controlled by the same source as the main target but has
+/// its own target-related glue settings.
+#[derive(Clone, Debug)]
+pub struct AdditionalTargetModel {
+    pub target_model: TargetModel,
+    pub target_value_interval: Interval<UnitValue>,
+    pub reverse: bool,
+    pub control_transformation: String,
+    pub control_transformation_kind: TransformationKind,
+    /// If `true`, feedback is taken from this target instead of the main target.
+    pub is_feedback_target: bool,
+}
+
+impl AdditionalTargetModel {
+    pub fn new(compartment: Compartment) -> Self {
+        Self {
+            target_model: TargetModel::default_for_compartment(compartment),
+            target_value_interval: full_unit_interval(),
+            reverse: false,
+            control_transformation: String::new(),
+            control_transformation_kind: Default::default(),
+            is_feedback_target: false,
+        }
+    }
+
+    /// Derives the mode model of this target from the mode model of the mapping.
+    ///
+    /// All settings which are not target-related are taken from the mapping.
+    pub fn derive_mode_model(&self, mapping_mode_model: &ModeModel) -> ModeModel {
+        use ModeCommand as C;
+        let mut mode_model = mapping_mode_model.clone();
+        mode_model.change(C::SetTargetValueInterval(self.target_value_interval));
+        mode_model.change(C::SetReverse(self.reverse));
+        mode_model.change(C::SetEelControlTransformation(
+            self.control_transformation.clone(),
+        ));
+        mode_model.change(C::SetControlTransformationKind(
+            self.control_transformation_kind,
+        ));
+        mode_model
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derive_mode_model() {
+        // Given
+        let mut mapping_mode_model = ModeModel::default();
+        mapping_mode_model.change(ModeCommand::SetSourceValueInterval(Interval::new(
+            UnitValue::new(0.1),
+            UnitValue::new(0.9),
+        )));
+        mapping_mode_model.change(ModeCommand::SetEelControlTransformation(
+            "y = x".to_string(),
+        ));
+        let additional_target = AdditionalTargetModel {
+            target_value_interval: Interval::new(UnitValue::new(0.2), UnitValue::new(0.4)),
+            reverse: true,
+            control_transformation: "return 1 - x".to_string(),
+            control_transformation_kind: TransformationKind::Lua,
+            ..AdditionalTargetModel::new(Compartment::Main)
+        };
+        // When
+        let mode_model = additional_target.derive_mode_model(&mapping_mode_model);
+        // Then
+        assert_eq!(
+            mode_model.target_value_interval(),
+            Interval::new(UnitValue::new(0.2), UnitValue::new(0.4))
+        );
+        assert!(mode_model.reverse());
+        assert_eq!(mode_model.eel_control_transformation(), "return 1 - x");
+        assert_eq!(
+            mode_model.control_transformation_kind(),
+            TransformationKind::Lua
+        );
+        // Not target-related, so taken from the mapping
+        assert_eq!(
+            mode_model.source_value_interval(),
+            Interval::new(UnitValue::new(0.1), UnitValue::new(0.9))
+        );
+        assert_eq!(
+            mapping_mode_model.target_value_interval(),
+            full_unit_interval()
+        );
+        assert!(!mapping_mode_model.reverse());
+    }
+}
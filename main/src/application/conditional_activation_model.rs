@@ -33,6 +33,12 @@ pub enum ActivationType {
     #[serde(rename = "expression")]
     #[display(fmt = "When expression > 0")]
     Expression,
+    #[serde(rename = "lua")]
+    #[display(fmt = "When Lua result is true")]
+    Lua,
+    #[serde(rename = "target-value")]
+    #[display(fmt = "When target value matches")]
+    TargetValue,
 }
 
 impl Default for ActivationType {
@@ -1,16 +1,16 @@
 use crate::application::{
     convert_factor_to_unit_value, merge_affected, ActivationConditionCommand,
-    ActivationConditionModel, ActivationConditionProp, Affected, Change, ChangeResult,
-    GetProcessingRelevance, MappingExtensionModel, ModeCommand, ModeModel, ModeProp,
+    ActivationConditionModel, ActivationConditionProp, AdditionalTargetModel, Affected, Change,
+    ChangeResult, GetProcessingRelevance, MappingExtensionModel, ModeCommand, ModeModel, ModeProp,
     ProcessingRelevance, SourceCommand, SourceModel, SourceProp, TargetCategory, TargetCommand,
     TargetModel, TargetModelFormatVeryShort, TargetModelWithContext, TargetProp,
 };
 use crate::domain::{
-    ActivationCondition, Compartment, CompoundMappingSource, CompoundMappingTarget,
-    ExtendedProcessorContext, ExtendedSourceCharacter, FeedbackSendBehavior, GroupId, MainMapping,
-    MappingId, MappingKey, Mode, PersistentMappingProcessingState, ProcessorMappingOptions,
-    QualifiedMappingId, RealearnTarget, ReaperTarget, Tag, TargetCharacter,
-    UnresolvedCompoundMappingTarget, VirtualFx, VirtualTrack,
+    ActivationCondition, AdditionalMappingTarget, Compartment, CompoundMappingSource,
+    CompoundMappingTarget, ExtendedProcessorContext, ExtendedSourceCharacter, FeedbackSendBehavior,
+    GroupId, MainMapping, MappingId, MappingKey, Mode, PersistentMappingProcessingState,
+    ProcessorMappingOptions, QualifiedMappingId, RealearnTarget, ReaperTarget, Tag,
+    TargetCharacter, UnresolvedCompoundMappingTarget, VirtualFx, VirtualTrack,
 };
 use helgoboss_learn::{
     AbsoluteMode, ControlType, DetailedSourceCharacter, Interval, ModeApplicabilityCheckInput,
@@ -34,6 +34,7 @@ pub enum MappingCommand {
     ChangeSource(SourceCommand),
     ChangeMode(ModeCommand),
     ChangeTarget(TargetCommand),
+    SetAdditionalTargets(Vec<AdditionalTargetModel>),
 }
 
 #[derive(PartialEq)]
@@ -51,6 +52,7 @@ pub enum MappingProp {
     InSource(Affected<SourceProp>),
     InMode(Affected<ModeProp>),
     InTarget(Affected<TargetProp>),
+    AdditionalTargets,
 }
 
 impl GetProcessingRelevance for MappingProp {
@@ -63,7 +65,8 @@ impl GetProcessingRelevance for MappingProp {
             | P::FeedbackIsEnabled
             | P::FeedbackSendBehavior
             | P::VisibleInProjection
-            | P::AdvancedSettings => Some(ProcessingRelevance::ProcessingRelevant),
+            | P::AdvancedSettings
+            | P::AdditionalTargets => Some(ProcessingRelevance::ProcessingRelevant),
             P::InActivationCondition(p) => p.processing_relevance(),
             P::InMode(p) => p.processing_relevance(),
             P::InSource(p) => p.processing_relevance(),
@@ -95,6 +98,7 @@ pub struct MappingModel {
     pub source_model: SourceModel,
     pub mode_model: ModeModel,
     pub target_model: TargetModel,
+    additional_targets: Vec<AdditionalTargetModel>,
     advanced_settings: Option<serde_yaml::mapping::Mapping>,
     extension_model: MappingExtensionModel,
 }
@@ -185,6 +189,10 @@ impl<'a> Change<'a> for MappingModel {
                     .change(cmd)
                     .map(|affected| One(P::InTarget(affected)));
             }
+            C::SetAdditionalTargets(v) => {
+                self.additional_targets = v;
+                One(P::AdditionalTargets)
+            }
         };
         Some(affected)
     }
@@ -208,6 +216,7 @@ impl MappingModel {
             source_model: Default::default(),
             mode_model: Default::default(),
             target_model: TargetModel::default_for_compartment(compartment),
+            additional_targets: vec![],
             advanced_settings: None,
             extension_model: Default::default(),
         }
@@ -249,6 +258,10 @@ impl MappingModel {
         &self.activation_condition_model
     }
 
+    pub fn additional_targets(&self) -> &[AdditionalTargetModel] {
+        &self.additional_targets
+    }
+
     pub fn reset_key(&mut self) {
         self.key = MappingKey::random();
     }
@@ -509,6 +522,26 @@ impl MappingModel {
         self.target_model.create_target(self.compartment).ok()
     }
 
+    /// Additional targets which are incomplete are left out.
+    fn create_additional_targets(&self) -> Vec<AdditionalMappingTarget> {
+        let possible_source_characters = self.source_model.possible_detailed_characters();
+        self.additional_targets
+            .iter()
+            .filter_map(|t| {
+                let unresolved_target = t.target_model.create_target(self.compartment).ok()?;
+                let mode = t.derive_mode_model(&self.mode_model).create_mode(
+                    self.base_mode_applicability_check_input(),
+                    &possible_source_characters,
+                );
+                Some(AdditionalMappingTarget::new(
+                    unresolved_target,
+                    mode,
+                    t.is_feedback_target,
+                ))
+            })
+            .collect()
+    }
+
     pub fn create_persistent_mapping_processing_state(&self) -> PersistentMappingProcessingState {
         PersistentMappingProcessingState {
             is_enabled: self.is_enabled(),
@@ -544,8 +577,12 @@ impl MappingModel {
             merged_tags,
             source,
             mode,
+            self.mode_model.create_acceleration(),
+            self.mode_model.press_gesture(),
+            self.mode_model.create_feedback_animation(),
             self.mode_model.group_interaction(),
             unresolved_target,
+            self.create_additional_targets(),
             group_data.activation_condition,
             activation_condition,
             options,
@@ -729,3 +766,59 @@ impl<'a> MappingModelWithContext<'a> {
             .with_context(self.context, self.mapping.compartment)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helgoboss_learn::AbsoluteValue;
+
+    fn mapping_with_additional_targets(feedback_flags: &[bool]) -> MainMapping {
+        let compartment = Compartment::Controller;
+        let mut model = MappingModel::new(compartment, GroupId::default(), MappingKey::random());
+        let additional_targets = feedback_flags
+            .iter()
+            .map(|is_feedback_target| AdditionalTargetModel {
+                is_feedback_target: *is_feedback_target,
+                ..AdditionalTargetModel::new(compartment)
+            })
+            .collect();
+        model.change(MappingCommand::SetAdditionalTargets(additional_targets));
+        model.create_main_mapping(GroupData::default())
+    }
+
+    fn value(v: f64) -> Option<AbsoluteValue> {
+        Some(AbsoluteValue::Continuous(UnitValue::new(v)))
+    }
+
+    #[test]
+    fn feedback_from_main_target() {
+        // Given
+        let mapping = mapping_with_additional_targets(&[false, false]);
+        // When
+        mapping.update_last_non_performance_target_value_if_appropriate(value(0.7));
+        // Then
+        assert_eq!(mapping.last_non_performance_target_value(), value(0.7));
+        for t in mapping.additional_targets() {
+            assert_eq!(t.last_non_performance_target_value(), None);
+        }
+    }
+
+    #[test]
+    fn feedback_from_additional_target() {
+        // Given
+        let mapping = mapping_with_additional_targets(&[false, true]);
+        // When
+        mapping.update_last_non_performance_target_value_if_appropriate(value(0.7));
+        // Then
+        let additional_targets = mapping.additional_targets();
+        assert_eq!(mapping.last_non_performance_target_value(), None);
+        assert_eq!(
+            additional_targets[0].last_non_performance_target_value(),
+            None
+        );
+        assert_eq!(
+            additional_targets[1].last_non_performance_target_value(),
+            value(0.7)
+        );
+    }
+}
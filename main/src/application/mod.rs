@@ -13,6 +13,9 @@ pub use target_model::*;
 mod mapping_model;
 pub use mapping_model::*;
 
+mod additional_target_model;
+pub use additional_target_model::*;
+
 mod group_model;
 pub use group_model::*;
 
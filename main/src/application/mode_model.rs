@@ -1,15 +1,23 @@
-use crate::domain::{EelTransformation, Mode, OutputVariable};
+use crate::domain::{
+    Acceleration, AnimationEffect, BackboneState, Curve, CurvePoint, CurvedTransformation,
+    EelTransformation, FeedbackAnimation, FlexibleTransformation, LuaTransformation, Mode,
+    ModulationRate, OutputVariable, PressGesture,
+};
 
 use helgoboss_learn::{
     check_mode_applicability, full_discrete_interval, full_unit_interval, AbsoluteMode,
     ButtonUsage, DetailedSourceCharacter, DiscreteIncrement, EncoderUsage, FeedbackType, FireMode,
     GroupInteraction, Interval, ModeApplicabilityCheckInput, ModeParameter, ModeSettings,
-    OutOfRangeBehavior, SoftSymmetricUnitValue, TakeoverMode, UnitValue, ValueSequence,
+    OutOfRangeBehavior, RgbColor, SoftSymmetricUnitValue, TakeoverMode, UnitValue, ValueSequence,
     VirtualColor,
 };
 
-use crate::application::{Affected, Change, GetProcessingRelevance, ProcessingRelevance};
-use realearn_api::persistence::FeedbackValueTable;
+use crate::application::{
+    convert_modulation_rate, Affected, Change, GetProcessingRelevance, ProcessingRelevance,
+};
+use helgoboss_midi::Channel;
+use realearn_api::persistence;
+use realearn_api::persistence::{FeedbackValueTable, TransformationKind};
 use std::time::Duration;
 
 pub enum ModeCommand {
@@ -36,6 +44,8 @@ pub enum ModeCommand {
     SetEncoderUsage(EncoderUsage),
     SetEelControlTransformation(String),
     SetEelFeedbackTransformation(String),
+    SetControlTransformationKind(TransformationKind),
+    SetFeedbackTransformationKind(TransformationKind),
     SetStepInterval(Interval<SoftSymmetricUnitValue>),
     SetMinStep(SoftSymmetricUnitValue),
     SetMaxStep(SoftSymmetricUnitValue),
@@ -48,6 +58,10 @@ pub enum ModeCommand {
     SetFeedbackColor(Option<VirtualColor>),
     SetFeedbackBackgroundColor(Option<VirtualColor>),
     SetFeedbackValueTable(Option<FeedbackValueTable>),
+    SetCurve(Option<persistence::Curve>),
+    SetFeedbackAnimation(Option<persistence::FeedbackAnimation>),
+    SetAcceleration(Option<persistence::Acceleration>),
+    SetPressGesture(Option<PressGesture>),
     /// This doesn't reset the mode type, just all the values.
     ResetWithinType,
 }
@@ -69,6 +83,8 @@ pub enum ModeProp {
     EncoderUsage,
     EelControlTransformation,
     EelFeedbackTransformation,
+    ControlTransformationKind,
+    FeedbackTransformationKind,
     StepInterval,
     Rotate,
     MakeAbsolute,
@@ -79,6 +95,10 @@ pub enum ModeProp {
     FeedbackColor,
     FeedbackBackgroundColor,
     FeedbackValueTable,
+    Curve,
+    FeedbackAnimation,
+    Acceleration,
+    PressGesture,
 }
 
 impl GetProcessingRelevance for ModeProp {
@@ -106,6 +126,8 @@ pub struct ModeModel {
     encoder_usage: EncoderUsage,
     eel_control_transformation: String,
     eel_feedback_transformation: String,
+    control_transformation_kind: TransformationKind,
+    feedback_transformation_kind: TransformationKind,
     // For relative control values.
     /// Depending on the target character, this is either a step count or a step size.
     ///
@@ -133,6 +155,11 @@ pub struct ModeModel {
     feedback_color: Option<VirtualColor>,
     feedback_background_color: Option<VirtualColor>,
     feedback_value_table: Option<FeedbackValueTable>,
+    curve: Option<persistence::Curve>,
+    feedback_animation: Option<persistence::FeedbackAnimation>,
+    acceleration: Option<persistence::Acceleration>,
+    /// Takes precedence over the fire mode.
+    press_gesture: Option<PressGesture>,
 }
 
 impl Default for ModeModel {
@@ -156,6 +183,8 @@ impl Default for ModeModel {
             encoder_usage: Default::default(),
             eel_control_transformation: String::new(),
             eel_feedback_transformation: String::new(),
+            control_transformation_kind: Default::default(),
+            feedback_transformation_kind: Default::default(),
             step_interval: Self::default_step_size_interval(),
             rotate: false,
             make_absolute: false,
@@ -166,6 +195,10 @@ impl Default for ModeModel {
             feedback_color: Default::default(),
             feedback_background_color: Default::default(),
             feedback_value_table: None,
+            curve: None,
+            feedback_animation: None,
+            acceleration: None,
+            press_gesture: None,
         }
     }
 }
@@ -249,7 +282,12 @@ impl<'a> Change<'a> for ModeModel {
             }
             C::SetFireMode(v) => {
                 self.fire_mode = v;
-                One(P::FireMode)
+                // The press gesture takes precedence, so choosing a fire mode must remove it
+                if self.press_gesture.take().is_some() {
+                    Multiple
+                } else {
+                    One(P::FireMode)
+                }
             }
             C::SetRoundTargetValue(v) => {
                 self.round_target_value = v;
@@ -275,6 +313,14 @@ impl<'a> Change<'a> for ModeModel {
                 self.eel_feedback_transformation = v;
                 One(P::EelFeedbackTransformation)
             }
+            C::SetControlTransformationKind(v) => {
+                self.control_transformation_kind = v;
+                One(P::ControlTransformationKind)
+            }
+            C::SetFeedbackTransformationKind(v) => {
+                self.feedback_transformation_kind = v;
+                One(P::FeedbackTransformationKind)
+            }
             C::SetStepInterval(v) => {
                 self.step_interval = v;
                 One(P::StepInterval)
@@ -321,6 +367,22 @@ impl<'a> Change<'a> for ModeModel {
                 self.feedback_value_table = v;
                 One(P::FeedbackValueTable)
             }
+            C::SetCurve(v) => {
+                self.curve = v;
+                One(P::Curve)
+            }
+            C::SetFeedbackAnimation(v) => {
+                self.feedback_animation = v;
+                One(P::FeedbackAnimation)
+            }
+            C::SetAcceleration(v) => {
+                self.acceleration = v;
+                One(P::Acceleration)
+            }
+            C::SetPressGesture(v) => {
+                self.press_gesture = v;
+                One(P::PressGesture)
+            }
             C::ResetWithinType => {
                 *self = Default::default();
                 Multiple
@@ -348,6 +410,35 @@ impl ModeModel {
         self.feedback_value_table.as_ref()
     }
 
+    pub fn curve(&self) -> Option<&persistence::Curve> {
+        self.curve.as_ref()
+    }
+
+    pub fn feedback_animation(&self) -> Option<&persistence::FeedbackAnimation> {
+        self.feedback_animation.as_ref()
+    }
+
+    /// Returns `None` if there's no animation, it's invalid or feedback is textual.
+    pub fn create_feedback_animation(&self) -> Option<FeedbackAnimation> {
+        if self.feedback_type.is_textual() {
+            return None;
+        }
+        convert_feedback_animation(self.feedback_animation.as_ref()?).ok()
+    }
+
+    pub fn acceleration(&self) -> Option<&persistence::Acceleration> {
+        self.acceleration.as_ref()
+    }
+
+    /// Returns `None` if there's no acceleration or it's invalid.
+    pub fn create_acceleration(&self) -> Option<Acceleration> {
+        convert_acceleration(self.acceleration.as_ref()?).ok()
+    }
+
+    pub fn press_gesture(&self) -> Option<PressGesture> {
+        self.press_gesture
+    }
+
     pub fn absolute_mode(&self) -> AbsoluteMode {
         self.absolute_mode
     }
@@ -408,6 +499,14 @@ impl ModeModel {
         &self.eel_feedback_transformation
     }
 
+    pub fn control_transformation_kind(&self) -> TransformationKind {
+        self.control_transformation_kind
+    }
+
+    pub fn feedback_transformation_kind(&self) -> TransformationKind {
+        self.feedback_transformation_kind
+    }
+
     pub fn step_interval(&self) -> Interval<SoftSymmetricUnitValue> {
         self.step_interval
     }
@@ -492,6 +591,8 @@ impl ModeModel {
             is_relevant(ModeParameter::StepSizeMax) || is_relevant(ModeParameter::SpeedMax);
         let min_step_count = convert_to_step_count(self.step_interval.min_val());
         let min_step_size = self.step_interval.min_val().abs();
+        // An invalid curve is ignored (the same as an invalid transformation).
+        let curve = self.curve.as_ref().and_then(|c| convert_curve(c).ok());
         Mode::new(ModeSettings {
             absolute_mode: if is_relevant(ModeParameter::AbsoluteMode) {
                 self.absolute_mode
@@ -590,13 +691,22 @@ impl ModeModel {
                 OutOfRangeBehavior::default()
             },
             control_transformation: if is_relevant(ModeParameter::ControlTransformation) {
-                EelTransformation::compile(&self.eel_control_transformation, OutputVariable::Y).ok()
+                compile_curved_transformation(
+                    curve.clone(),
+                    self.control_transformation_kind,
+                    &self.eel_control_transformation,
+                    OutputVariable::Y,
+                )
             } else {
                 None
             },
             feedback_transformation: if is_relevant(ModeParameter::FeedbackTransformation) {
-                EelTransformation::compile(&self.eel_feedback_transformation, OutputVariable::X)
-                    .ok()
+                compile_curved_transformation(
+                    curve,
+                    self.feedback_transformation_kind,
+                    &self.eel_feedback_transformation,
+                    OutputVariable::X,
+                )
             } else {
                 None
             },
@@ -658,3 +768,135 @@ pub fn convert_unit_value_to_factor(value: SoftSymmetricUnitValue) -> i32 {
 fn convert_to_step_count(value: SoftSymmetricUnitValue) -> DiscreteIncrement {
     DiscreteIncrement::new(convert_unit_value_to_factor(value))
 }
+
+/// Converts the curve from its persistent representation, validating it.
+pub fn convert_curve(curve: &persistence::Curve) -> Result<Curve, &'static str> {
+    use persistence::Curve as C;
+    let curve = match curve {
+        C::Logarithmic => Curve::Logarithmic,
+        C::Exponential => Curve::Exponential,
+        C::SCurve { tension } => Curve::checked_s_curve(tension.unwrap_or(1.0))?,
+        C::DbTaper => Curve::DbTaper,
+        C::Breakpoints { points } => Curve::breakpoints(
+            points
+                .iter()
+                .map(|p| CurvePoint { x: p.x, y: p.y })
+                .collect(),
+        )?,
+    };
+    Ok(curve)
+}
+
+/// Converts the feedback animation from its persistent representation, validating it.
+pub fn convert_feedback_animation(
+    animation: &persistence::FeedbackAnimation,
+) -> Result<FeedbackAnimation, &'static str> {
+    use persistence::AnimationEffect as E;
+    let effect = match animation.effect {
+        E::Blink => AnimationEffect::Blink,
+        E::Pulse => AnimationEffect::Pulse,
+        E::AlternateColor { color } => {
+            AnimationEffect::AlternateColor(RgbColor::new(color.0, color.1, color.2))
+        }
+    };
+    let rate = animation
+        .rate
+        .map(convert_modulation_rate)
+        .unwrap_or(ModulationRate::Frequency(DEFAULT_ANIMATION_FREQUENCY));
+    rate.validate()?;
+    let value_interval: Interval<UnitValue> = match animation.value_interval {
+        None => full_unit_interval(),
+        Some(i) => Interval::try_new(
+            i.0.try_into()
+                .map_err(|_| "animation value interval min invalid")?,
+            i.1.try_into()
+                .map_err(|_| "animation value interval max invalid")?,
+        )
+        .map_err(|_| "animation value interval invalid")?,
+    };
+    let native_midi_channel = match animation.native_midi_channel {
+        None => None,
+        Some(ch) => Some(Channel::try_from(ch).map_err(|_| "invalid native MIDI channel")?),
+    };
+    Ok(FeedbackAnimation::new(
+        effect,
+        rate,
+        value_interval,
+        native_midi_channel,
+    ))
+}
+
+const DEFAULT_ANIMATION_FREQUENCY: f64 = 2.0;
+
+/// Converts the acceleration from its persistent representation, validating it.
+pub fn convert_acceleration(
+    acceleration: &persistence::Acceleration,
+) -> Result<Acceleration, &'static str> {
+    let slow_interval = Duration::from_millis(
+        acceleration
+            .slow_interval_millis
+            .unwrap_or(DEFAULT_ACCELERATION_SLOW_INTERVAL_MILLIS),
+    );
+    let fast_interval = Duration::from_millis(
+        acceleration
+            .fast_interval_millis
+            .unwrap_or(DEFAULT_ACCELERATION_FAST_INTERVAL_MILLIS),
+    );
+    if fast_interval > slow_interval {
+        return Err("acceleration fast interval must not be greater than slow interval");
+    }
+    let max_factor = acceleration
+        .max_factor
+        .unwrap_or(DEFAULT_ACCELERATION_MAX_FACTOR);
+    if max_factor < 1.0 {
+        return Err("acceleration max factor must be at least 1.0");
+    }
+    let curve = match &acceleration.curve {
+        None => None,
+        Some(c) => Some(convert_curve(c)?),
+    };
+    Ok(Acceleration::new(
+        slow_interval,
+        fast_interval,
+        max_factor,
+        curve,
+    ))
+}
+
+const DEFAULT_ACCELERATION_SLOW_INTERVAL_MILLIS: u64 = 100;
+const DEFAULT_ACCELERATION_FAST_INTERVAL_MILLIS: u64 = 10;
+const DEFAULT_ACCELERATION_MAX_FACTOR: f64 = 10.0;
+
+fn compile_curved_transformation(
+    curve: Option<Curve>,
+    kind: TransformationKind,
+    script: &str,
+    output_var: OutputVariable,
+) -> Option<FlexibleTransformation<'static>> {
+    let custom = compile_transformation(kind, script, output_var);
+    match curve {
+        None => custom,
+        Some(curve) => {
+            let t = CurvedTransformation::new(curve, output_var, custom);
+            Some(FlexibleTransformation::Curved(Box::new(t)))
+        }
+    }
+}
+
+fn compile_transformation(
+    kind: TransformationKind,
+    script: &str,
+    output_var: OutputVariable,
+) -> Option<FlexibleTransformation<'static>> {
+    match kind {
+        TransformationKind::Eel => EelTransformation::compile(script, output_var)
+            .ok()
+            .map(FlexibleTransformation::Eel),
+        TransformationKind::Lua => {
+            let lua = unsafe { BackboneState::main_thread_lua() };
+            LuaTransformation::compile(lua, script, output_var)
+                .ok()
+                .map(FlexibleTransformation::Lua)
+        }
+    }
+}
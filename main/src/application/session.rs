@@ -451,6 +451,13 @@ impl Session {
             .do_sync(move |s, (compartment, _)| {
                 s.borrow_mut().mark_compartment_dirty(compartment);
             });
+        // Keep instance state informed about the session ID.
+        self.sync_id_to_instance_state();
+        when(self.id.changed())
+            .with(weak_session.clone())
+            .do_sync(move |s, _| {
+                s.borrow().sync_id_to_instance_state();
+            });
         // Keep adding/removing instance to/from upper floor.
         when(self.lives_on_upper_floor.changed())
             .with(weak_session.clone())
@@ -1922,11 +1929,15 @@ impl Session {
 
     fn reset_parameters(&self, compartment: Compartment) {
         let fx = self.processor_context.containing_fx().clone();
+        let default_values: Vec<_> =
+            convert_plugin_param_index_range_to_iter(&compartment.plugin_param_range())
+                .map(|i| (i, self.params.at(i).setting().default_raw_value()))
+                .collect();
         let _ = Global::task_support().do_later_in_main_thread_from_main_thread_asap(move || {
-            for i in convert_plugin_param_index_range_to_iter(&compartment.plugin_param_range()) {
+            for (i, value) in default_values {
                 let _ = fx
                     .parameter_by_index(i.get())
-                    .set_reaper_normalized_value(0.0);
+                    .set_reaper_normalized_value(value as f64);
             }
         });
     }
@@ -2169,6 +2180,12 @@ impl Session {
         AsyncNotifier::notify(&mut self.group_list_changed_subject, &compartment);
     }
 
+    fn sync_id_to_instance_state(&self) {
+        self.instance_state
+            .borrow_mut()
+            .set_session_id(self.id.get_ref().clone());
+    }
+
     fn sync_upper_floor_membership(&self) {
         let backbone_state = BackboneState::get();
         if self.lives_on_upper_floor.get() {
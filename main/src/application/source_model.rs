@@ -3,9 +3,9 @@ use crate::application::{
 };
 use crate::domain::{
     BackboneState, Compartment, CompoundMappingSource, EelMidiSourceScript,
-    ExtendedSourceCharacter, FlexibleMidiSourceScript, KeySource, Keystroke, LuaMidiSourceScript,
-    MidiSource, ReaperSource, TimerSource, VirtualControlElement, VirtualControlElementId,
-    VirtualSource, VirtualTarget,
+    ExtendedSourceCharacter, FlexibleMidiSourceScript, KeySource, Keystroke, LfoShape, LfoSource,
+    LuaMidiSourceScript, MidiSource, ModulationRate, ReaperSource, StepSequencerSource,
+    TimerSource, VirtualControlElement, VirtualControlElementId, VirtualSource, VirtualTarget,
 };
 use derive_more::Display;
 use enum_iterator::IntoEnumIterator;
@@ -16,6 +16,7 @@ use helgoboss_learn::{
 };
 use helgoboss_midi::{Channel, U14, U7};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
+use realearn_api::persistence;
 use realearn_api::persistence::MidiScriptKind;
 use serde::{Deserialize, Serialize};
 use serde_repr::*;
@@ -50,6 +51,8 @@ pub enum SourceCommand {
     SetOscFeedbackArgs(Vec<String>),
     SetReaperSourceType(ReaperSourceType),
     SetTimerMillis(u64),
+    SetLfo(persistence::LfoSource),
+    SetStepSequencer(persistence::StepSequencerSource),
     SetKeystroke(Option<Keystroke>),
     SetControlElementType(VirtualControlElementType),
     SetControlElementId(VirtualControlElementId),
@@ -82,6 +85,8 @@ pub enum SourceProp {
     ControlElementType,
     ControlElementId,
     TimerMillis,
+    Lfo,
+    StepSequencer,
     Keystroke,
 }
 
@@ -201,6 +206,14 @@ impl<'a> Change<'a> for SourceModel {
                 self.timer_millis = v;
                 One(P::TimerMillis)
             }
+            C::SetLfo(v) => {
+                self.lfo = v;
+                One(P::Lfo)
+            }
+            C::SetStepSequencer(v) => {
+                self.step_sequencer = v;
+                One(P::StepSequencer)
+            }
             C::SetKeystroke(v) => {
                 self.keystroke = v;
                 One(P::Keystroke)
@@ -239,6 +252,8 @@ pub struct SourceModel {
     // REAPER
     reaper_source_type: ReaperSourceType,
     timer_millis: u64,
+    lfo: persistence::LfoSource,
+    step_sequencer: persistence::StepSequencerSource,
     // Key
     keystroke: Option<Keystroke>,
     // Virtual
@@ -274,6 +289,8 @@ impl Default for SourceModel {
             osc_feedback_args: vec![],
             reaper_source_type: Default::default(),
             timer_millis: Default::default(),
+            lfo: Default::default(),
+            step_sequencer: Default::default(),
             keystroke: None,
         }
     }
@@ -376,6 +393,14 @@ impl SourceModel {
         self.timer_millis
     }
 
+    pub fn lfo(&self) -> &persistence::LfoSource {
+        &self.lfo
+    }
+
+    pub fn step_sequencer(&self) -> &persistence::StepSequencerSource {
+        &self.step_sequencer
+    }
+
     pub fn control_element_type(&self) -> VirtualControlElementType {
         self.control_element_type
     }
@@ -641,6 +666,10 @@ impl SourceModel {
                     MidiDeviceChanges => ReaperSource::MidiDeviceChanges,
                     RealearnInstanceStart => ReaperSource::RealearnInstanceStart,
                     Timer => ReaperSource::Timer(self.create_timer_source()),
+                    Lfo => ReaperSource::Lfo(self.create_lfo_source().ok()?),
+                    StepSequencer => {
+                        ReaperSource::StepSequencer(self.create_step_sequencer_source().ok()?)
+                    }
                 };
                 CompoundMappingSource::Reaper(reaper_source)
             }
@@ -658,6 +687,35 @@ impl SourceModel {
         TimerSource::new(Duration::from_millis(self.timer_millis))
     }
 
+    fn create_lfo_source(&self) -> Result<LfoSource, &'static str> {
+        let shape = {
+            use persistence::LfoShape as S;
+            match self.lfo.shape.unwrap_or_default() {
+                S::Sine => LfoShape::Sine,
+                S::Triangle => LfoShape::Triangle,
+                S::Saw => LfoShape::Saw,
+                S::Square => LfoShape::Square,
+                S::RandomSampleAndHold => LfoShape::RandomSampleAndHold,
+            }
+        };
+        LfoSource::new(
+            shape,
+            convert_modulation_rate(self.lfo.rate.unwrap_or_default()),
+            self.lfo.phase.unwrap_or_default(),
+        )
+    }
+
+    fn create_step_sequencer_source(&self) -> Result<StepSequencerSource, &'static str> {
+        StepSequencerSource::new(
+            self.step_sequencer
+                .steps
+                .iter()
+                .map(|v| UnitValue::new_clamped(*v))
+                .collect(),
+            convert_modulation_rate(self.step_sequencer.rate.unwrap_or_default()),
+        )
+    }
+
     fn display_spec(&self) -> DisplaySpec {
         use DisplayType::*;
         match self.display_type {
@@ -1132,6 +1190,12 @@ pub enum ReaperSourceType {
     #[serde(rename = "timer")]
     #[display(fmt = "Timer")]
     Timer,
+    #[serde(rename = "lfo")]
+    #[display(fmt = "LFO")]
+    Lfo,
+    #[serde(rename = "step-sequencer")]
+    #[display(fmt = "Step sequencer")]
+    StepSequencer,
 }
 
 impl Default for ReaperSourceType {
@@ -1147,10 +1211,19 @@ impl ReaperSourceType {
             MidiDeviceChanges => Self::MidiDeviceChanges,
             RealearnInstanceStart => Self::RealearnInstanceStart,
             Timer(_) => Self::Timer,
+            Lfo(_) => Self::Lfo,
+            StepSequencer(_) => Self::StepSequencer,
         }
     }
 }
 
+pub fn convert_modulation_rate(rate: persistence::ModulationRate) -> ModulationRate {
+    match rate {
+        persistence::ModulationRate::Frequency { hertz } => ModulationRate::Frequency(hertz),
+        persistence::ModulationRate::Beats { beats } => ModulationRate::Beats(beats),
+    }
+}
+
 pub fn parse_osc_feedback_args(text: &str) -> Vec<String> {
     text.split_whitespace().map(|s| s.to_owned()).collect()
 }
@@ -26,13 +26,13 @@ use crate::domain::{
     TrackRouteSelector, TrackRouteType, TransportAction, UnresolvedActionTarget,
     UnresolvedAllTrackFxEnableTarget, UnresolvedAnyOnTarget,
     UnresolvedAutomationModeOverrideTarget, UnresolvedClipColumnTarget,
-    UnresolvedClipManagementTarget, UnresolvedClipMatrixTarget, UnresolvedClipRowTarget,
-    UnresolvedClipSeekTarget, UnresolvedClipTransportTarget, UnresolvedClipVolumeTarget,
-    UnresolvedCompoundMappingTarget, UnresolvedEnableInstancesTarget,
-    UnresolvedEnableMappingsTarget, UnresolvedFxEnableTarget, UnresolvedFxNavigateTarget,
-    UnresolvedFxOnlineTarget, UnresolvedFxOpenTarget, UnresolvedFxParameterTarget,
-    UnresolvedFxParameterTouchStateTarget, UnresolvedFxPresetTarget, UnresolvedGoToBookmarkTarget,
-    UnresolvedLastTouchedTarget, UnresolvedLoadFxSnapshotTarget,
+    UnresolvedClipManagementTarget, UnresolvedClipMatrixTarget, UnresolvedClipMidiVelocityTarget,
+    UnresolvedClipPitchTarget, UnresolvedClipRowTarget, UnresolvedClipSeekTarget,
+    UnresolvedClipTransportTarget, UnresolvedClipVolumeTarget, UnresolvedCompoundMappingTarget,
+    UnresolvedEnableInstancesTarget, UnresolvedEnableMappingsTarget, UnresolvedFxEnableTarget,
+    UnresolvedFxNavigateTarget, UnresolvedFxOnlineTarget, UnresolvedFxOpenTarget,
+    UnresolvedFxParameterTarget, UnresolvedFxParameterTouchStateTarget, UnresolvedFxPresetTarget,
+    UnresolvedGoToBookmarkTarget, UnresolvedLastTouchedTarget, UnresolvedLoadFxSnapshotTarget,
     UnresolvedLoadMappingSnapshotTarget, UnresolvedMidiSendTarget,
     UnresolvedNavigateWithinGroupTarget, UnresolvedOscSendTarget, UnresolvedPlayrateTarget,
     UnresolvedReaperTarget, UnresolvedRouteAutomationModeTarget, UnresolvedRouteMonoTarget,
@@ -2024,6 +2024,14 @@ impl TargetModel {
                     ClipVolume => UnresolvedReaperTarget::ClipVolume(UnresolvedClipVolumeTarget {
                         slot: self.virtual_clip_slot()?,
                     }),
+                    ClipPitch => UnresolvedReaperTarget::ClipPitch(UnresolvedClipPitchTarget {
+                        slot: self.virtual_clip_slot()?,
+                    }),
+                    ClipMidiVelocity => {
+                        UnresolvedReaperTarget::ClipMidiVelocity(UnresolvedClipMidiVelocityTarget {
+                            slot: self.virtual_clip_slot()?,
+                        })
+                    }
                     ClipManagement => {
                         UnresolvedReaperTarget::ClipManagement(UnresolvedClipManagementTarget {
                             slot: self.virtual_clip_slot()?,
@@ -2486,7 +2494,7 @@ impl<'a> Display for TargetModelFormatMultiLine<'a> {
                 use ReaperTargetType::*;
                 let tt = self.target.r#type;
                 match tt {
-                    ClipTransport | ClipSeek | ClipVolume => {
+                    ClipTransport | ClipSeek | ClipVolume | ClipPitch | ClipMidiVelocity => {
                         write!(f, "{}", tt)
                     }
                     Action => write!(
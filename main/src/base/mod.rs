@@ -32,6 +32,8 @@ pub mod default_util;
 
 pub mod hash_util;
 
+pub mod script_util;
+
 mod channels;
 pub use channels::*;
 
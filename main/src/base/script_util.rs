@@ -0,0 +1,123 @@
+/// Describes the lexical elements of a scripting language which are relevant for finding
+/// identifiers.
+pub struct ScriptSyntax {
+    pub line_comment: &'static str,
+    pub block_comment: (&'static str, &'static str),
+    pub string_quotes: &'static [char],
+    /// Whether `.` can be part of an identifier (e.g. EEL namespaces such as `this.x`).
+    pub dot_in_identifier: bool,
+}
+
+pub const EEL_SYNTAX: ScriptSyntax = ScriptSyntax {
+    line_comment: "//",
+    block_comment: ("/*", "*/"),
+    string_quotes: &['"'],
+    dot_in_identifier: true,
+};
+
+pub const LUA_SYNTAX: ScriptSyntax = ScriptSyntax {
+    line_comment: "--",
+    block_comment: ("--[[", "]]"),
+    string_quotes: &['"', '\''],
+    dot_in_identifier: false,
+};
+
+/// Returns whether the given script refers to the given identifier.
+///
+/// Ignores comments, string literals and identifiers which just contain the given one
+/// (e.g. `my_rel_time` when looking for `rel_time`).
+pub fn script_refers_to_identifier(script: &str, syntax: &ScriptSyntax, identifier: &str) -> bool {
+    let is_identifier_char =
+        |c: char| c.is_alphanumeric() || c == '_' || (syntax.dot_in_identifier && c == '.');
+    let mut rest = script;
+    while let Some(c) = rest.chars().next() {
+        // Block comment start must be checked first because in Lua it starts with the line
+        // comment start.
+        if let Some(after_start) = rest.strip_prefix(syntax.block_comment.0) {
+            rest = match after_start.find(syntax.block_comment.1) {
+                None => "",
+                Some(i) => &after_start[i + syntax.block_comment.1.len()..],
+            };
+        } else if rest.starts_with(syntax.line_comment) {
+            rest = match rest.find('\n') {
+                None => "",
+                Some(i) => &rest[i..],
+            };
+        } else if syntax.string_quotes.contains(&c) {
+            rest = skip_string_literal(&rest[c.len_utf8()..], c);
+        } else if is_identifier_char(c) {
+            let end = rest
+                .find(|c: char| !is_identifier_char(c))
+                .unwrap_or(rest.len());
+            if &rest[..end] == identifier {
+                return true;
+            }
+            rest = &rest[end..];
+        } else {
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    false
+}
+
+/// Returns the remainder after the closing quote of a string literal.
+fn skip_string_literal(literal_and_rest: &str, quote: char) -> &str {
+    let mut chars = literal_and_rest.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c == quote {
+            return &literal_and_rest[i + c.len_utf8()..];
+        }
+    }
+    ""
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(syntax: &ScriptSyntax, cases: &[(&str, bool)]) {
+        // When
+        let results: Vec<_> = cases
+            .iter()
+            .map(|(script, _)| script_refers_to_identifier(script, syntax, "rel_time"))
+            .collect();
+        // Then
+        for ((script, expected), actual) in cases.iter().zip(results) {
+            assert_eq!(actual, *expected, "{}", script);
+        }
+    }
+
+    #[test]
+    fn eel_identifiers() {
+        // Given
+        let cases = [
+            ("y = rel_time / 500", true),
+            ("stop = rel_time>=500;", true),
+            ("/* comment */ y = rel_time", true),
+            ("y = my_rel_time", false),
+            ("y = rel_time2", false),
+            ("y = this.rel_time", false),
+            ("y = x; // rel_time", false),
+            ("y = x; /* rel_time */", false),
+            ("s = \"rel_time\"; y = x", false),
+        ];
+        check(&EEL_SYNTAX, &cases);
+    }
+
+    #[test]
+    fn lua_identifiers() {
+        // Given
+        let cases = [
+            ("return x * rel_time", true),
+            ("--[[ comment ]] return rel_time", true),
+            ("local my_rel_time = 1; return x", false),
+            ("return x -- rel_time", false),
+            ("--[[ rel_time ]] return x", false),
+            ("return 'rel_time'", false),
+            ("return \"a \\\" rel_time\"", false),
+        ];
+        check(&LUA_SYNTAX, &cases);
+    }
+}
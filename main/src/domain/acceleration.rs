@@ -0,0 +1,199 @@
+use crate::domain::{ControlEvent, ControlEventTimestamp, Curve};
+use helgoboss_learn::{ControlValue, DiscreteIncrement};
+use std::time::Duration;
+
+/// Amplifies relative increments depending on how fast an encoder is turned.
+///
+/// The turn speed is derived from the time between two consecutive ticks in the same direction.
+#[derive(Clone, Debug)]
+pub struct Acceleration {
+    /// Time between two ticks at or above which increments are not amplified at all.
+    slow_interval: Duration,
+    /// Time between two ticks at or below which increments are amplified by `max_factor`.
+    fast_interval: Duration,
+    max_factor: f64,
+    /// Maps the normalized turn speed (0.0 = slow, 1.0 = fast) to the normalized factor.
+    curve: Option<Curve>,
+    last_tick: Option<Tick>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Tick {
+    timestamp: ControlEventTimestamp,
+    is_positive: bool,
+}
+
+impl Acceleration {
+    pub fn new(
+        slow_interval: Duration,
+        fast_interval: Duration,
+        max_factor: f64,
+        curve: Option<Curve>,
+    ) -> Self {
+        Self {
+            slow_interval,
+            fast_interval: fast_interval.min(slow_interval),
+            max_factor: max_factor.max(1.0),
+            curve,
+            last_tick: None,
+        }
+    }
+
+    /// Returns the given control event with an amplified increment.
+    ///
+    /// Only affects discrete relative values. Changing the turn direction always starts slow.
+    pub fn accelerate(&mut self, evt: ControlEvent<ControlValue>) -> ControlEvent<ControlValue> {
+        let increment = match evt.payload() {
+            ControlValue::RelativeDiscrete(i) => i,
+            _ => return evt,
+        };
+        let tick = Tick {
+            timestamp: evt.timestamp(),
+            is_positive: increment.get() > 0,
+        };
+        let factor = match self.last_tick.replace(tick) {
+            Some(last_tick) if last_tick.is_positive == tick.is_positive => {
+                self.factor(tick.timestamp - last_tick.timestamp)
+            }
+            _ => return evt,
+        };
+        let accelerated_increment = (increment.get() as f64 * factor).round() as i32;
+        evt.with_payload(ControlValue::RelativeDiscrete(DiscreteIncrement::new(
+            accelerated_increment,
+        )))
+    }
+
+    fn factor(&self, time_between_ticks: Duration) -> f64 {
+        if time_between_ticks >= self.slow_interval {
+            return 1.0;
+        }
+        if time_between_ticks <= self.fast_interval {
+            return self.max_factor;
+        }
+        let span = (self.slow_interval - self.fast_interval).as_secs_f64();
+        let speed = (self.slow_interval - time_between_ticks).as_secs_f64() / span;
+        let speed = match &self.curve {
+            None => speed,
+            Some(c) => c.apply(speed),
+        };
+        1.0 + speed * (self.max_factor - 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helgoboss_learn::UnitValue;
+
+    fn acceleration() -> Acceleration {
+        Acceleration::new(
+            Duration::from_millis(100),
+            Duration::from_millis(20),
+            9.0,
+            None,
+        )
+    }
+
+    #[test]
+    fn factor() {
+        // Given
+        let acceleration = acceleration();
+        // When
+        let slow_factor = acceleration.factor(Duration::from_millis(200));
+        let medium_factor = acceleration.factor(Duration::from_millis(60));
+        let fast_factor = acceleration.factor(Duration::from_millis(5));
+        // Then
+        assert_eq!(slow_factor, 1.0);
+        assert_eq!(medium_factor, 5.0);
+        assert_eq!(fast_factor, 9.0);
+    }
+
+    #[test]
+    fn accelerate() {
+        // Given
+        let mut acceleration = acceleration();
+        let start = ControlEventTimestamp::now();
+        let tick = |increment, millis| {
+            ControlEvent::new(
+                ControlValue::RelativeDiscrete(DiscreteIncrement::new(increment)),
+                start + Duration::from_millis(millis),
+            )
+        };
+        // When
+        let first = acceleration.accelerate(tick(1, 0));
+        let medium = acceleration.accelerate(tick(1, 60));
+        let fast = acceleration.accelerate(tick(2, 65));
+        let slow = acceleration.accelerate(tick(1, 500));
+        // Then
+        assert_eq!(
+            first.payload(),
+            ControlValue::RelativeDiscrete(DiscreteIncrement::new(1))
+        );
+        assert_eq!(
+            medium.payload(),
+            ControlValue::RelativeDiscrete(DiscreteIncrement::new(5))
+        );
+        assert_eq!(
+            fast.payload(),
+            ControlValue::RelativeDiscrete(DiscreteIncrement::new(18))
+        );
+        assert_eq!(
+            slow.payload(),
+            ControlValue::RelativeDiscrete(DiscreteIncrement::new(1))
+        );
+    }
+
+    #[test]
+    fn accelerate_resets_on_direction_change() {
+        // Given
+        let mut acceleration = acceleration();
+        let start = ControlEventTimestamp::now();
+        let tick = |increment, millis| {
+            ControlEvent::new(
+                ControlValue::RelativeDiscrete(DiscreteIncrement::new(increment)),
+                start + Duration::from_millis(millis),
+            )
+        };
+        acceleration.accelerate(tick(1, 0));
+        // When
+        let reversed = acceleration.accelerate(tick(-1, 5));
+        let reversed_fast = acceleration.accelerate(tick(-1, 10));
+        // Then
+        assert_eq!(
+            reversed.payload(),
+            ControlValue::RelativeDiscrete(DiscreteIncrement::new(-1))
+        );
+        assert_eq!(
+            reversed_fast.payload(),
+            ControlValue::RelativeDiscrete(DiscreteIncrement::new(-9))
+        );
+    }
+
+    #[test]
+    fn accelerate_passes_through_non_discrete_values() {
+        // Given
+        let mut acceleration = acceleration();
+        let start = ControlEventTimestamp::now();
+        let absolute = ControlValue::AbsoluteContinuous(UnitValue::new(0.5));
+        acceleration.accelerate(ControlEvent::new(
+            ControlValue::RelativeDiscrete(DiscreteIncrement::new(1)),
+            start,
+        ));
+        // When
+        let passed = acceleration.accelerate(ControlEvent::new(
+            absolute,
+            start + Duration::from_millis(5),
+        ));
+        let next = acceleration.accelerate(ControlEvent::new(
+            ControlValue::RelativeDiscrete(DiscreteIncrement::new(1)),
+            start + Duration::from_millis(10),
+        ));
+        // Then
+        assert_eq!(passed.payload(), absolute);
+        // Non-discrete values don't interrupt the tick series
+        assert_eq!(
+            next.payload(),
+            ControlValue::RelativeDiscrete(DiscreteIncrement::new(9))
+        );
+    }
+}
@@ -238,8 +238,8 @@ impl RealearnAudioHook {
 
     fn process_clip_record_task(&mut self, args: &OnAudioBufferArgs) {
         if let Some(t) = &mut self.clip_record_task {
-            let its_our_turn = (t.destination.is_midi_overdub && args.is_post)
-                || (!t.destination.is_midi_overdub && !args.is_post);
+            let its_our_turn = (t.destination.is_overdub && args.is_post)
+                || (!t.destination.is_overdub && !args.is_post);
             if its_our_turn && !process_clip_record_task(args, t) {
                 tracing_debug!("Clearing clip record task from audio hook");
                 self.clip_record_task = None;
@@ -289,6 +289,22 @@ impl BackboneState {
         self.instance_states.borrow_mut().remove(id);
     }
 
+    /// Instances which are currently borrowed are ignored.
+    pub fn find_instance_state_by_session_id(
+        &self,
+        session_id: &str,
+    ) -> Option<SharedInstanceState> {
+        self.instance_states
+            .borrow()
+            .values()
+            .filter_map(|s| s.upgrade())
+            .find(|s| {
+                s.try_borrow()
+                    .map(|s| s.session_id() == session_id)
+                    .unwrap_or(false)
+            })
+    }
+
     pub fn control_is_allowed(
         &self,
         instance_id: &InstanceId,
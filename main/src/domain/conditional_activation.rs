@@ -1,10 +1,13 @@
 use crate::base::eel;
 use crate::domain::{
-    CompartmentParamIndex, CompartmentParams, EffectiveParamValue, ExpressionEvaluator,
-    RawParamValue, COMPARTMENT_PARAMETER_COUNT,
+    CompartmentParamIndex, CompartmentParams, ExpressionEvaluator, MappingKey, RawParamValue,
+    SafeLua, COMPARTMENT_PARAMETER_COUNT,
 };
+use mlua::{Function, Table, ToLua, Value};
+use std::cell::Cell;
 use std::collections::HashSet;
 use std::error::Error;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub enum ActivationCondition {
@@ -17,6 +20,8 @@ pub enum ActivationCondition {
     // Boxed in order to keep the enum variants at a similar size (clippy gave that hint)
     Eel(Box<EelCondition>),
     Expression(Box<ExpressionCondition>),
+    Lua(Box<LuaCondition<'static>>),
+    TargetValue(Box<TargetValueCondition>),
 }
 
 impl ActivationCondition {
@@ -41,6 +46,31 @@ impl ActivationCondition {
                 condition.is_fulfilled()
             }
             Expression(condition) => condition.is_fulfilled(params),
+            Lua(condition) => {
+                condition.notify_params_changed(params);
+                condition.is_fulfilled()
+            }
+            TargetValue(condition) => condition.is_fulfilled(params),
+        }
+    }
+
+    /// Returns the key of the mapping whose target value this activation condition depends on.
+    pub fn lead_mapping_key(&self) -> Option<&MappingKey> {
+        match self {
+            ActivationCondition::TargetValue(condition) => Some(condition.lead_mapping_key()),
+            _ => None,
+        }
+    }
+
+    /// Informs this activation condition about the current target value of the lead mapping.
+    ///
+    /// Returns true if activation might have changed.
+    pub fn notify_lead_target_value_changed(&self, value: Option<f64>) -> bool {
+        match self {
+            ActivationCondition::TargetValue(condition) => {
+                condition.notify_lead_target_value_changed(value)
+            }
+            _ => false,
         }
     }
 
@@ -96,6 +126,15 @@ impl ActivationCondition {
                 condition.is_fulfilled()
             }
             Expression(condition) => condition.is_fulfilled(params),
+            TargetValue(condition) => condition.is_fulfilled(params),
+            Lua(condition) => {
+                let is_affected = condition
+                    .notify_param_changed(index, params.at(index).effective_value().into());
+                if !is_affected {
+                    return None;
+                }
+                condition.is_fulfilled()
+            }
             Always => return None,
         };
         Some(is_fulfilled)
@@ -116,14 +155,7 @@ fn program_condition_is_fulfilled(
     program_index: u32,
     params: &CompartmentParams,
 ) -> bool {
-    let current_program_index = match params.at(param_index).effective_value() {
-        EffectiveParamValue::Continuous(v) => {
-            // If no count given for the parameter, we just assume a count of 100.
-            (v * 99.0).round() as u32
-        }
-        EffectiveParamValue::Discrete(v) => v,
-    };
-    current_program_index == program_index
+    params.at(param_index).program_index() == Some(program_index)
 }
 
 fn param_value_is_on(value: f32) -> bool {
@@ -254,6 +286,126 @@ impl EelCondition {
     }
 }
 
+/// Activation condition which depends on the current target value of another mapping (the lead
+/// mapping) in the same compartment.
+///
+/// The expression sees the normalized target value of the lead mapping as `y` (and the parameters
+/// as `p1`, `p2`, ...). If the lead mapping doesn't exist or its target is not resolved, the
+/// condition is not fulfilled.
+#[derive(Debug)]
+pub struct TargetValueCondition {
+    lead_mapping_key: MappingKey,
+    evaluator: ExpressionEvaluator,
+    /// Last known target value of the lead mapping.
+    lead_target_value: Cell<Option<f64>>,
+}
+
+impl TargetValueCondition {
+    pub fn compile(lead_mapping_key: MappingKey, expression: &str) -> Result<Self, Box<dyn Error>> {
+        let condition = Self {
+            lead_mapping_key,
+            evaluator: ExpressionEvaluator::compile(expression)?,
+            lead_target_value: Cell::new(None),
+        };
+        Ok(condition)
+    }
+
+    pub fn lead_mapping_key(&self) -> &MappingKey {
+        &self.lead_mapping_key
+    }
+
+    /// Returns true if activation might have changed.
+    pub fn notify_lead_target_value_changed(&self, value: Option<f64>) -> bool {
+        self.lead_target_value.replace(value) != value
+    }
+
+    pub fn is_fulfilled(&self, params: &CompartmentParams) -> bool {
+        let y = match self.lead_target_value.get() {
+            None => return false,
+            Some(v) => v,
+        };
+        let result = self
+            .evaluator
+            .evaluate_with_additional_vars(params, |name, _| match name {
+                "y" => Some(y),
+                _ => None,
+            });
+        result.map(|v| v > 0.0).unwrap_or(false)
+    }
+}
+
+/// Activation conditions are evaluated on each parameter change, so they must be fast.
+const MAX_LUA_CONDITION_EXECUTION_DURATION: Duration = Duration::from_millis(10);
+
+/// Activation condition written in Lua.
+///
+/// Sees the same parameter variables as [`EelCondition`] (`p1`, `p2`, ...). The script must return
+/// `true` or a number greater than zero in order to activate the mapping.
+#[derive(Debug)]
+pub struct LuaCondition<'lua> {
+    lua: &'lua SafeLua,
+    function: Function<'lua>,
+    env: Table<'lua>,
+    /// Only contains the parameters which are actually used in the script.
+    params: Vec<(CompartmentParamIndex, Value<'lua>)>,
+}
+
+unsafe impl<'a> Send for LuaCondition<'a> {}
+
+impl<'lua> LuaCondition<'lua> {
+    /// Compiles the given script in a fresh environment.
+    pub fn compile(lua: &'lua SafeLua, lua_script: &str) -> Result<Self, Box<dyn Error>> {
+        if lua_script.trim().is_empty() {
+            return Err("script empty".into());
+        }
+        let env = lua.create_fresh_environment(false)?;
+        let function = lua.compile_as_function("Activation condition", lua_script, env.clone())?;
+        let mut params = Vec::new();
+        for i in extract_used_param_indexes(lua_script) {
+            let key = format!("p{}", i + 1).to_lua(lua.as_ref())?;
+            // Initialized to zero, just like in the EEL condition.
+            env.raw_set(key.clone(), 0.0)?;
+            params.push((CompartmentParamIndex::try_from(i)?, key));
+        }
+        let condition = Self {
+            lua,
+            function,
+            env,
+            params,
+        };
+        Ok(condition)
+    }
+
+    pub fn notify_params_changed(&self, params: &CompartmentParams) {
+        for (i, key) in &self.params {
+            let value: f64 = params.at(*i).effective_value().into();
+            let _ = self.env.raw_set(key.clone(), value);
+        }
+    }
+
+    /// Returns true if activation might have changed.
+    pub fn notify_param_changed(&self, param_index: CompartmentParamIndex, value: f64) -> bool {
+        if let Some((_, key)) = self.params.iter().find(|(i, _)| *i == param_index) {
+            let _ = self.env.raw_set(key.clone(), value);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn is_fulfilled(&self) -> bool {
+        let result = self
+            .lua
+            .call_with_execution_time_limit(&self.function, MAX_LUA_CONDITION_EXECUTION_DURATION);
+        match result {
+            Ok(Value::Boolean(v)) => v,
+            Ok(Value::Number(v)) => v > 0.0,
+            Ok(Value::Integer(v)) => v > 0,
+            _ => false,
+        }
+    }
+}
+
 fn extract_used_param_indexes(eel_script: &str) -> HashSet<u32> {
     let param_regex = regex!(r#"\bp([0-9]+)\b"#);
     param_regex
@@ -263,3 +415,63 @@ fn extract_used_param_indexes(eel_script: &str) -> HashSet<u32> {
         .map(|i: u32| i - 1)
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_value_condition_without_lead_value() {
+        // Given
+        let condition = TargetValueCondition::compile(lead_key(), "1").unwrap();
+        // When
+        let fulfilled = condition.is_fulfilled(&CompartmentParams::default());
+        // Then
+        assert!(!fulfilled);
+    }
+
+    #[test]
+    fn target_value_condition_with_lead_value() {
+        // Given
+        let condition = TargetValueCondition::compile(lead_key(), "y > 0.5").unwrap();
+        let params = CompartmentParams::default();
+        // When
+        let first_change = condition.notify_lead_target_value_changed(Some(0.7));
+        let fulfilled_when_high = condition.is_fulfilled(&params);
+        let repeated_change = condition.notify_lead_target_value_changed(Some(0.7));
+        condition.notify_lead_target_value_changed(Some(0.2));
+        let fulfilled_when_low = condition.is_fulfilled(&params);
+        condition.notify_lead_target_value_changed(None);
+        let fulfilled_when_unresolved = condition.is_fulfilled(&params);
+        // Then
+        assert!(first_change);
+        assert!(!repeated_change);
+        assert!(fulfilled_when_high);
+        assert!(!fulfilled_when_low);
+        assert!(!fulfilled_when_unresolved);
+    }
+
+    #[test]
+    fn target_value_condition_with_params() {
+        // Given
+        let condition = TargetValueCondition::compile(lead_key(), "y > p1").unwrap();
+        let mut params = CompartmentParams::default();
+        params
+            .at_mut(CompartmentParamIndex::try_from(0).unwrap())
+            .set_raw_value(0.5);
+        condition.notify_lead_target_value_changed(Some(0.6));
+        // When
+        let fulfilled_below_lead_value = condition.is_fulfilled(&params);
+        params
+            .at_mut(CompartmentParamIndex::try_from(0).unwrap())
+            .set_raw_value(0.8);
+        let fulfilled_above_lead_value = condition.is_fulfilled(&params);
+        // Then
+        assert!(fulfilled_below_lead_value);
+        assert!(!fulfilled_above_lead_value);
+    }
+
+    fn lead_key() -> MappingKey {
+        MappingKey::from("lead".to_owned())
+    }
+}
@@ -1,6 +1,6 @@
 use helgoboss_learn::AbstractTimestamp;
 use std::fmt::{Display, Formatter};
-use std::ops::Sub;
+use std::ops::{Add, Sub};
 use std::time::{Duration, Instant};
 
 pub type ControlEvent<P> = helgoboss_learn::ControlEvent<P, ControlEventTimestamp>;
@@ -29,6 +29,14 @@ impl Sub for ControlEventTimestamp {
     }
 }
 
+impl Add<Duration> for ControlEventTimestamp {
+    type Output = Self;
+
+    fn add(self, rhs: Duration) -> Self::Output {
+        Self(self.0 + rhs)
+    }
+}
+
 impl Display for ControlEventTimestamp {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self.0)
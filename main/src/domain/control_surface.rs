@@ -2,7 +2,7 @@ use crate::base::{Global, NamedChannelSender, SenderToNormalThread};
 use crate::domain::{
     BackboneState, CompoundMappingSource, ControlEvent, ControlEventTimestamp,
     DeviceChangeDetector, DeviceControlInput, DeviceFeedbackOutput, DomainEventHandler,
-    EelTransformation, FeedbackOutput, FeedbackRealTimeTask, InstanceId, LifecycleMidiData,
+    FeedbackOutput, FeedbackRealTimeTask, FlexibleTransformation, InstanceId, LifecycleMidiData,
     MainProcessor, MidiCaptureSender, MidiDeviceChangePayload, NormalRealTimeTask, OscDeviceId,
     OscInputDevice, OscScanResult, QualifiedClipMatrixEvent, RealTimeCompoundMappingTarget,
     RealTimeMapping, RealTimeMappingUpdate, RealTimeTargetUpdate, ReaperMessage, ReaperTarget,
@@ -69,7 +69,7 @@ pub enum Garbage {
     RealTimeProcessor(SharedRealTimeProcessor),
     LifecycleMidiData(LifecycleMidiData),
     ResolvedTarget(Option<RealTimeCompoundMappingTarget>),
-    Mode(ModeGarbage<EelTransformation>),
+    Mode(ModeGarbage<FlexibleTransformation<'static>>),
     MappingSource(CompoundMappingSource),
     RealTimeMappings(Vec<RealTimeMapping>),
     BoxedRealTimeMapping(Box<Option<RealTimeMapping>>),
@@ -0,0 +1,248 @@
+/// Range covered by the dB taper curve.
+const DB_TAPER_RANGE_DB: f64 = 60.0;
+
+/// The minimum S-curve tension. Lower values would make the curve degenerate.
+const MIN_S_CURVE_TENSION: f64 = -0.9;
+
+/// A monotonic response curve which maps the unit interval to the unit interval.
+///
+/// Because it's monotonic, it can be inverted, which is what makes it usable for control *and*
+/// feedback.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Curve {
+    Logarithmic,
+    Exponential,
+    SCurve { tension: f64 },
+    DbTaper,
+    Breakpoints(Vec<CurvePoint>),
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CurvePoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Curve {
+    pub fn s_curve(tension: f64) -> Self {
+        Self::SCurve {
+            tension: tension.max(MIN_S_CURVE_TENSION),
+        }
+    }
+
+    /// Returns an error if the given tension is not a finite number.
+    pub fn checked_s_curve(tension: f64) -> Result<Self, &'static str> {
+        if !tension.is_finite() {
+            return Err("S-curve tension must be a finite number");
+        }
+        Ok(Self::s_curve(tension))
+    }
+
+    /// Returns an error if the given points don't describe an invertible curve.
+    pub fn breakpoints(points: Vec<CurvePoint>) -> Result<Self, &'static str> {
+        if points.len() < 2 {
+            return Err("curve needs at least 2 breakpoints");
+        }
+        // Also rejects NaN
+        let is_in_unit_interval = |v: f64| (0.0..=1.0).contains(&v);
+        if points
+            .iter()
+            .any(|p| !is_in_unit_interval(p.x) || !is_in_unit_interval(p.y))
+        {
+            return Err("curve breakpoints must be between 0.0 and 1.0");
+        }
+        if points.windows(2).any(|w| w[0].x >= w[1].x) {
+            return Err("curve breakpoints must be sorted by x");
+        }
+        let is_ascending = points.windows(2).all(|w| w[0].y <= w[1].y);
+        let is_descending = points.windows(2).all(|w| w[0].y >= w[1].y);
+        if !is_ascending && !is_descending {
+            return Err("curve breakpoints must have monotonic y values");
+        }
+        Ok(Self::Breakpoints(points))
+    }
+
+    /// Used for control.
+    pub fn apply(&self, x: f64) -> f64 {
+        let x = x.clamp(0.0, 1.0);
+        use Curve::*;
+        match self {
+            Logarithmic => (1.0 + 9.0 * x).log10(),
+            Exponential => (10f64.powf(x) - 1.0) / 9.0,
+            SCurve { tension } => s_curve(x, 1.0 + tension),
+            DbTaper => {
+                if x == 0.0 {
+                    0.0
+                } else {
+                    db_to_amplitude((x - 1.0) * DB_TAPER_RANGE_DB)
+                }
+            }
+            Breakpoints(points) => interpolate(points.iter().map(|p| (p.x, p.y)), x),
+        }
+    }
+
+    /// Used for feedback.
+    pub fn apply_inverse(&self, y: f64) -> f64 {
+        let y = y.clamp(0.0, 1.0);
+        use Curve::*;
+        match self {
+            Logarithmic => (10f64.powf(y) - 1.0) / 9.0,
+            Exponential => (1.0 + 9.0 * y).log10(),
+            SCurve { tension } => s_curve(y, 1.0 / (1.0 + tension)),
+            DbTaper => {
+                if y == 0.0 {
+                    0.0
+                } else {
+                    (1.0 + amplitude_to_db(y) / DB_TAPER_RANGE_DB).max(0.0)
+                }
+            }
+            Breakpoints(points) => interpolate(points.iter().map(|p| (p.y, p.x)), y),
+        }
+    }
+}
+
+fn s_curve(x: f64, exponent: f64) -> f64 {
+    let a = x.powf(exponent);
+    let b = (1.0 - x).powf(exponent);
+    a / (a + b)
+}
+
+fn db_to_amplitude(db: f64) -> f64 {
+    10f64.powf(db / 20.0)
+}
+
+fn amplitude_to_db(amplitude: f64) -> f64 {
+    20.0 * amplitude.log10()
+}
+
+/// Piecewise linear interpolation along the given (monotonic) points.
+fn interpolate(points: impl Iterator<Item = (f64, f64)> + Clone, input: f64) -> f64 {
+    let mut segments = points.clone().zip(points.clone().skip(1));
+    let segment = segments.find(|((in_a, _), (in_b, _))| {
+        let (min, max) = if in_a <= in_b {
+            (*in_a, *in_b)
+        } else {
+            (*in_b, *in_a)
+        };
+        input >= min && input <= max
+    });
+    if let Some(((in_a, out_a), (in_b, out_b))) = segment {
+        if in_a == in_b {
+            return out_a;
+        }
+        return out_a + (input - in_a) / (in_b - in_a) * (out_b - out_a);
+    }
+    // Outside of the defined range. Use the output of the closest point.
+    points
+        .min_by(|(a, _), (b, _)| {
+            let distance_a = (a - input).abs();
+            let distance_b = (b - input).abs();
+            distance_a.total_cmp(&distance_b)
+        })
+        .map(|(_, out)| out)
+        .unwrap_or(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn presets_are_invertible() {
+        // Given
+        let curves = [
+            Curve::Logarithmic,
+            Curve::Exponential,
+            Curve::s_curve(1.0),
+            Curve::s_curve(-0.5),
+            Curve::DbTaper,
+        ];
+        for curve in curves {
+            for x in [0.0, 0.1, 0.25, 0.5, 0.75, 1.0] {
+                // When
+                let y = curve.apply(x);
+                // Then
+                assert_abs_diff_eq!(curve.apply_inverse(y), x, epsilon = 0.000_001);
+            }
+        }
+    }
+
+    #[test]
+    fn breakpoints() {
+        // Given
+        let curve = Curve::breakpoints(vec![
+            CurvePoint { x: 0.2, y: 0.0 },
+            CurvePoint { x: 0.5, y: 0.8 },
+            CurvePoint { x: 1.0, y: 1.0 },
+        ])
+        .unwrap();
+        // When
+        let below_first_point = curve.apply(0.0);
+        let in_first_segment = curve.apply(0.35);
+        let in_second_segment = curve.apply(0.75);
+        let inverse_in_first_segment = curve.apply_inverse(0.4);
+        let inverse_at_last_point = curve.apply_inverse(1.0);
+        // Then
+        assert_abs_diff_eq!(below_first_point, 0.0, epsilon = 0.000_001);
+        assert_abs_diff_eq!(in_first_segment, 0.4, epsilon = 0.000_001);
+        assert_abs_diff_eq!(in_second_segment, 0.9, epsilon = 0.000_001);
+        assert_abs_diff_eq!(inverse_in_first_segment, 0.35, epsilon = 0.000_001);
+        assert_abs_diff_eq!(inverse_at_last_point, 1.0, epsilon = 0.000_001);
+    }
+
+    #[test]
+    fn breakpoints_with_nan_input() {
+        // Given
+        let curve = Curve::breakpoints(vec![
+            CurvePoint { x: 0.0, y: 0.0 },
+            CurvePoint { x: 1.0, y: 1.0 },
+        ])
+        .unwrap();
+        // When
+        let y = curve.apply(f64::NAN);
+        let x = curve.apply_inverse(f64::NAN);
+        // Then
+        assert!(y.is_finite());
+        assert!(x.is_finite());
+    }
+
+    #[test]
+    fn invalid_breakpoints() {
+        // Given
+        let too_few = vec![CurvePoint { x: 0.0, y: 0.0 }];
+        let not_monotonic = vec![
+            CurvePoint { x: 0.0, y: 0.0 },
+            CurvePoint { x: 0.5, y: 1.0 },
+            CurvePoint { x: 1.0, y: 0.0 },
+        ];
+        let not_a_number = vec![
+            CurvePoint { x: 0.0, y: 0.0 },
+            CurvePoint {
+                x: f64::NAN,
+                y: 1.0,
+            },
+        ];
+        let infinite = vec![
+            CurvePoint { x: 0.0, y: 0.0 },
+            CurvePoint {
+                x: 1.0,
+                y: f64::INFINITY,
+            },
+        ];
+        // When
+        let results = [too_few, not_monotonic, not_a_number, infinite].map(Curve::breakpoints);
+        // Then
+        assert!(results.iter().all(|r| r.is_err()));
+    }
+
+    #[test]
+    fn invalid_s_curve_tension() {
+        // Given
+        let tensions = [f64::NAN, f64::INFINITY, f64::NEG_INFINITY];
+        // When
+        let results = tensions.map(Curve::checked_s_curve);
+        // Then
+        assert!(results.iter().all(|r| r.is_err()));
+    }
+}
@@ -1,11 +1,15 @@
 use crate::base::eel;
+use crate::base::script_util::{script_refers_to_identifier, EEL_SYNTAX};
 use helgoboss_learn::Transformation;
 
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Default)]
 pub struct AdditionalEelTransformationInput {
     pub y_last: f64,
+    /// Time elapsed since the control event which started this transformation.
+    pub rel_time: Duration,
 }
 
 #[derive(Debug)]
@@ -17,9 +21,11 @@ struct EelUnit {
     x: eel::Variable,
     y: eel::Variable,
     y_last: eel::Variable,
+    rel_time: eel::Variable,
+    stop: eel::Variable,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Copy, Clone, Debug)]
 pub enum OutputVariable {
     X,
     Y,
@@ -31,6 +37,8 @@ pub struct EelTransformation {
     // Arc because EelUnit is not cloneable
     eel_unit: Arc<EelUnit>,
     output_var: OutputVariable,
+    /// `true` if the script refers to `rel_time`, which makes it time-aware.
+    wants_to_be_polled: bool,
 }
 
 impl EelTransformation {
@@ -47,18 +55,37 @@ impl EelTransformation {
         let x = vm.register_variable("x");
         let y = vm.register_variable("y");
         let y_last = vm.register_variable("y_last");
+        let rel_time = vm.register_variable("rel_time");
+        let stop = vm.register_variable("stop");
         let eel_unit = EelUnit {
             program,
             _vm: vm,
             x,
             y,
             y_last,
+            rel_time,
+            stop,
         };
         Ok(EelTransformation {
             eel_unit: Arc::new(eel_unit),
             output_var: result_var,
+            wants_to_be_polled: script_refers_to_identifier(eel_script, &EEL_SYNTAX, "rel_time"),
         })
     }
+
+    /// Returns whether this transformation wants to be invoked repeatedly after a control event
+    /// (until it sets `stop` to a non-zero value).
+    ///
+    /// That's the case if the script uses `rel_time`. Enables glides, slew limiting and one-shot
+    /// envelopes.
+    pub fn wants_to_be_polled(&self) -> bool {
+        self.wants_to_be_polled
+    }
+
+    /// Returns whether the last execution of the script set `stop` to a non-zero value.
+    pub fn has_stopped(&self) -> bool {
+        unsafe { self.eel_unit.stop.get() != 0.0 }
+    }
 }
 
 impl Transformation for EelTransformation {
@@ -79,9 +106,71 @@ impl Transformation for EelTransformation {
             input_var.set(input_value);
             output_var.set(output_value);
             self.eel_unit.y_last.set(additional_input.y_last);
+            self.eel_unit
+                .rel_time
+                .set(additional_input.rel_time.as_millis() as f64);
+            self.eel_unit.stop.set(0.0);
             self.eel_unit.program.execute();
             output_var.get()
         };
         Ok(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    fn input(y_last: f64, rel_time_millis: u64) -> AdditionalEelTransformationInput {
+        AdditionalEelTransformationInput {
+            y_last,
+            rel_time: Duration::from_millis(rel_time_millis),
+        }
+    }
+
+    #[test]
+    fn not_time_aware() {
+        // Given
+        let transformation = EelTransformation::compile(
+            "// rel_time is not used\nmy_rel_time = 1; y = x",
+            OutputVariable::Y,
+        )
+        .unwrap();
+        // When
+        let result = transformation.transform(0.5, 0.0, input(0.0, 0)).unwrap();
+        // Then
+        assert!(!transformation.wants_to_be_polled());
+        assert_abs_diff_eq!(result, 0.5);
+    }
+
+    #[test]
+    fn rel_time_and_stop() {
+        // Given
+        let transformation = EelTransformation::compile(
+            "y = y_last + (x - y_last) * min(rel_time / 500, 1); stop = rel_time >= 500",
+            OutputVariable::Y,
+        )
+        .unwrap();
+        // When
+        let start = transformation.transform(1.0, 0.0, input(0.0, 0)).unwrap();
+        let stopped_at_start = transformation.has_stopped();
+        let middle = transformation.transform(1.0, 0.0, input(0.0, 250)).unwrap();
+        let stopped_in_middle = transformation.has_stopped();
+        let end = transformation.transform(1.0, 0.0, input(0.0, 500)).unwrap();
+        let stopped_at_end = transformation.has_stopped();
+        let restart = transformation.transform(0.0, 0.0, input(1.0, 0)).unwrap();
+        let stopped_after_restart = transformation.has_stopped();
+        // Then
+        assert!(transformation.wants_to_be_polled());
+        assert_abs_diff_eq!(start, 0.0);
+        assert!(!stopped_at_start);
+        assert_abs_diff_eq!(middle, 0.5);
+        assert!(!stopped_in_middle);
+        assert_abs_diff_eq!(end, 1.0);
+        assert!(stopped_at_end);
+        // "stop" is reset before each invocation
+        assert_abs_diff_eq!(restart, 1.0);
+        assert!(!stopped_after_restart);
+    }
+}
@@ -0,0 +1,202 @@
+use crate::domain::{play_position_in_beats, ModulationRate};
+use helgoboss_learn::{
+    AbsoluteValue, FeedbackValue, Interval, NumericFeedbackValue, RgbColor, UnitValue,
+};
+use helgoboss_midi::Channel;
+use reaper_high::Project;
+use std::f64::consts::PI;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Minimum time between two frames rendered by ReaLearn.
+///
+/// Also used for animations whose frames change continuously (e.g. pulse) or whose timing can't be
+/// predicted (e.g. synced to the project).
+const MIN_FRAME_DURATION: Duration = Duration::from_millis(30);
+
+/// Makes numeric feedback change over time, e.g. in order to let an LED blink.
+#[derive(Clone, PartialEq, Debug)]
+pub struct FeedbackAnimation {
+    effect: AnimationEffect,
+    rate: ModulationRate,
+    value_interval: Interval<UnitValue>,
+    native_midi_channel: Option<Channel>,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum AnimationEffect {
+    /// Switches the feedback off during the second half of each cycle.
+    Blink,
+    /// Fades the feedback value out and in again during each cycle.
+    Pulse,
+    /// Uses the given color during the second half of each cycle.
+    AlternateColor(RgbColor),
+}
+
+impl FeedbackAnimation {
+    pub fn new(
+        effect: AnimationEffect,
+        rate: ModulationRate,
+        value_interval: Interval<UnitValue>,
+        native_midi_channel: Option<Channel>,
+    ) -> Self {
+        Self {
+            effect,
+            rate,
+            value_interval,
+            native_midi_channel,
+        }
+    }
+
+    /// If this returns `Some`, the controller animates the feedback itself as soon as it receives
+    /// it on the returned channel. ReaLearn doesn't need to render anything in this case.
+    pub fn native_midi_channel(&self) -> Option<Channel> {
+        self.native_midi_channel
+    }
+
+    /// Returns whether the given feedback value (after glue processing) should be animated.
+    pub fn applies_to(&self, value: &FeedbackValue) -> bool {
+        match value {
+            FeedbackValue::Numeric(v) => self.value_interval.contains(v.value.to_unit_value()),
+            _ => false,
+        }
+    }
+
+    /// Returns the current position within the animation cycle (0.0 to 1.0).
+    pub fn current_pos_in_cycle(&self, project: Project) -> f64 {
+        let cycle_pos = match self.rate {
+            ModulationRate::Frequency(hertz) => {
+                // Measured from a fixed point in time so that all animations with the same rate
+                // are in sync.
+                let since_epoch = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default();
+                since_epoch.as_secs_f64() * hertz
+            }
+            ModulationRate::Beats(beats) => play_position_in_beats(project) / beats,
+        };
+        cycle_pos - cycle_pos.floor()
+    }
+
+    /// Returns how long the frame at the given position within the animation cycle stays valid,
+    /// that is, when ReaLearn needs to render the next frame.
+    pub fn frame_duration(&self, pos_in_cycle: f64) -> Duration {
+        let cycle_secs = match self.rate {
+            ModulationRate::Frequency(hertz) => 1.0 / hertz,
+            ModulationRate::Beats(_) => return MIN_FRAME_DURATION,
+        };
+        use AnimationEffect::*;
+        match self.effect {
+            Pulse => MIN_FRAME_DURATION,
+            Blink | AlternateColor(_) => {
+                // The frame only changes when entering the other half of the cycle.
+                let remaining_pos = if pos_in_cycle < 0.5 {
+                    0.5 - pos_in_cycle
+                } else {
+                    1.0 - pos_in_cycle
+                };
+                Duration::from_secs_f64(remaining_pos * cycle_secs).max(MIN_FRAME_DURATION)
+            }
+        }
+    }
+
+    /// Returns the given value as it should look at the given position within the animation
+    /// cycle or `None` if the feedback should be switched off.
+    pub fn animate(
+        &self,
+        value: NumericFeedbackValue,
+        pos_in_cycle: f64,
+    ) -> Option<NumericFeedbackValue> {
+        let is_second_half = pos_in_cycle >= 0.5;
+        use AnimationEffect::*;
+        match self.effect {
+            Blink => {
+                if is_second_half {
+                    None
+                } else {
+                    Some(value)
+                }
+            }
+            Pulse => {
+                let factor = (1.0 + (2.0 * PI * pos_in_cycle).cos()) / 2.0;
+                let faded_value = value.value.to_unit_value().get() * factor;
+                Some(NumericFeedbackValue::new(
+                    value.style,
+                    AbsoluteValue::Continuous(UnitValue::new_clamped(faded_value)),
+                ))
+            }
+            AlternateColor(color) => {
+                if is_second_half {
+                    let mut style = value.style;
+                    style.color = Some(color);
+                    Some(NumericFeedbackValue::new(style, value.value))
+                } else {
+                    Some(value)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use helgoboss_learn::FeedbackStyle;
+
+    #[test]
+    fn blink_and_pulse() {
+        // Given
+        let value = NumericFeedbackValue::new(
+            FeedbackStyle::default(),
+            AbsoluteValue::Continuous(UnitValue::MAX),
+        );
+        let create = |effect| {
+            FeedbackAnimation::new(
+                effect,
+                ModulationRate::Frequency(2.0),
+                Interval::new(UnitValue::MIN, UnitValue::MAX),
+                None,
+            )
+        };
+        let blink = create(AnimationEffect::Blink);
+        let pulse = create(AnimationEffect::Pulse);
+        // When
+        let blink_on = blink.animate(value.clone(), 0.25);
+        let blink_off = blink.animate(value.clone(), 0.75);
+        let faded = pulse.animate(value.clone(), 0.5).unwrap();
+        let full = pulse.animate(value.clone(), 0.0).unwrap();
+        // Then
+        assert_eq!(blink_on, Some(value));
+        assert_eq!(blink_off, None);
+        assert_abs_diff_eq!(faded.value.to_unit_value().get(), 0.0);
+        assert_abs_diff_eq!(full.value.to_unit_value().get(), 1.0);
+    }
+
+    #[test]
+    fn frame_duration() {
+        // Given
+        let create = |effect, rate| {
+            FeedbackAnimation::new(
+                effect,
+                rate,
+                Interval::new(UnitValue::MIN, UnitValue::MAX),
+                None,
+            )
+        };
+        let blink = create(AnimationEffect::Blink, ModulationRate::Frequency(2.0));
+        let pulse = create(AnimationEffect::Pulse, ModulationRate::Frequency(2.0));
+        let synced_blink = create(AnimationEffect::Blink, ModulationRate::Beats(1.0));
+        // When
+        let blink_in_first_half = blink.frame_duration(0.1);
+        let blink_in_second_half = blink.frame_duration(0.75);
+        let blink_right_before_switch = blink.frame_duration(0.499);
+        let pulse_duration = pulse.frame_duration(0.1);
+        let synced_blink_duration = synced_blink.frame_duration(0.1);
+        // Then
+        assert_eq!(blink_in_first_half, Duration::from_millis(200));
+        assert_eq!(blink_in_second_half, Duration::from_millis(125));
+        assert_eq!(blink_right_before_switch, MIN_FRAME_DURATION);
+        assert_eq!(pulse_duration, MIN_FRAME_DURATION);
+        assert_eq!(synced_blink_duration, MIN_FRAME_DURATION);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::domain::{
+    AdditionalEelTransformationInput, Curve, EelTransformation, LuaTransformation, OutputVariable,
+};
+use helgoboss_learn::Transformation;
+
+#[derive(Clone, Debug)]
+pub enum FlexibleTransformation<'a> {
+    Eel(EelTransformation),
+    Lua(LuaTransformation<'a>),
+    // Boxed because it contains a transformation itself
+    Curved(Box<CurvedTransformation<'a>>),
+}
+
+/// Applies a response curve in combination with an optional custom transformation.
+///
+/// For control (output variable `y`), the curve is applied first and the custom transformation
+/// afterwards. For feedback (output variable `x`), it's the other way around and the curve is
+/// inverted.
+#[derive(Clone, Debug)]
+pub struct CurvedTransformation<'a> {
+    curve: Curve,
+    output_var: OutputVariable,
+    custom: Option<FlexibleTransformation<'a>>,
+}
+
+impl<'a> CurvedTransformation<'a> {
+    pub fn new(
+        curve: Curve,
+        output_var: OutputVariable,
+        custom: Option<FlexibleTransformation<'a>>,
+    ) -> Self {
+        Self {
+            curve,
+            output_var,
+            custom,
+        }
+    }
+
+    fn transform_custom(
+        &self,
+        input_value: f64,
+        output_value: f64,
+        additional_input: AdditionalEelTransformationInput,
+    ) -> Result<f64, &'static str> {
+        match &self.custom {
+            None => Ok(input_value),
+            Some(t) => t.transform(input_value, output_value, additional_input),
+        }
+    }
+}
+
+impl<'a> FlexibleTransformation<'a> {
+    pub fn wants_to_be_polled(&self) -> bool {
+        match self {
+            FlexibleTransformation::Eel(t) => t.wants_to_be_polled(),
+            FlexibleTransformation::Lua(t) => t.wants_to_be_polled(),
+            FlexibleTransformation::Curved(t) => t
+                .custom
+                .as_ref()
+                .map(|c| c.wants_to_be_polled())
+                .unwrap_or(false),
+        }
+    }
+
+    pub fn has_stopped(&self) -> bool {
+        match self {
+            FlexibleTransformation::Eel(t) => t.has_stopped(),
+            FlexibleTransformation::Lua(t) => t.has_stopped(),
+            FlexibleTransformation::Curved(t) => {
+                t.custom.as_ref().map(|c| c.has_stopped()).unwrap_or(true)
+            }
+        }
+    }
+}
+
+impl<'a> Transformation for FlexibleTransformation<'a> {
+    type AdditionalInput = AdditionalEelTransformationInput;
+
+    fn transform(
+        &self,
+        input_value: f64,
+        output_value: f64,
+        additional_input: AdditionalEelTransformationInput,
+    ) -> Result<f64, &'static str> {
+        match self {
+            FlexibleTransformation::Eel(t) => {
+                t.transform(input_value, output_value, additional_input)
+            }
+            FlexibleTransformation::Lua(t) => {
+                t.transform(input_value, output_value, additional_input)
+            }
+            FlexibleTransformation::Curved(t) => match t.output_var {
+                OutputVariable::Y => {
+                    let curved_value = t.curve.apply(input_value);
+                    t.transform_custom(curved_value, output_value, additional_input)
+                }
+                OutputVariable::X => {
+                    let value = t.transform_custom(input_value, output_value, additional_input)?;
+                    Ok(t.curve.apply_inverse(value))
+                }
+            },
+        }
+    }
+}
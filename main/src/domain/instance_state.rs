@@ -9,7 +9,8 @@ use rxrust::prelude::*;
 use crate::base::{NamedChannelSender, Prop, SenderToNormalThread, SenderToRealTimeThread};
 use crate::domain::{
     BackboneState, Compartment, FxInputClipRecordTask, GroupId, HardwareInputClipRecordTask,
-    InstanceId, MappingId, NormalAudioHookTask, NormalRealTimeTask, QualifiedMappingId, Tag,
+    InstanceId, MappingId, NormalAudioHookTask, NormalRealTimeTask, PluginParamIndex, PluginParams,
+    QualifiedMappingId, RawParamValue, Tag,
 };
 use playtime_clip_engine::main::{
     ApiClipWithColumn, ClipMatrixEvent, ClipMatrixHandler, ClipRecordInput, ClipRecordTask, Matrix,
@@ -26,6 +27,17 @@ pub type RealearnClipMatrix = Matrix<RealearnClipMatrixHandler>;
 #[derive(Debug)]
 pub struct InstanceState {
     instance_id: InstanceId,
+    /// The ID of the session which belongs to this instance.
+    ///
+    /// - Set by the session whenever its ID changes.
+    /// - Used for looking up instances by session ID, e.g. for parameter value sources.
+    session_id: String,
+    /// Mirror of the plug-in parameters.
+    ///
+    /// - Automatically filled by main processor whenever parameters change.
+    /// - Completely derived from the parameters, so it's redundant state.
+    /// - Used by other instances, e.g. for parameter value sources.
+    params: PluginParams,
     clip_matrix_ref: Option<ClipMatrixRef>,
     instance_feedback_event_sender: SenderToNormalThread<InstanceStateChanged>,
     clip_matrix_event_sender: SenderToNormalThread<QualifiedClipMatrixEvent>,
@@ -154,6 +166,8 @@ impl InstanceState {
     ) -> Self {
         Self {
             instance_id,
+            session_id: String::new(),
+            params: Default::default(),
             clip_matrix_ref: None,
             instance_feedback_event_sender,
             clip_matrix_event_sender,
@@ -176,6 +190,26 @@ impl InstanceState {
         self.instance_id
     }
 
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    pub fn set_session_id(&mut self, session_id: String) {
+        self.session_id = session_id;
+    }
+
+    pub fn params(&self) -> &PluginParams {
+        &self.params
+    }
+
+    pub fn set_params(&mut self, params: PluginParams) {
+        self.params = params;
+    }
+
+    pub fn set_param_raw_value(&mut self, index: PluginParamIndex, value: RawParamValue) {
+        self.params.at_mut(index).set_raw_value(value);
+    }
+
     pub fn is_interested_in_clip_matrix_events_from(&self, instance_id: InstanceId) -> bool {
         use ClipMatrixRef::*;
         let our_instance_id = match self.clip_matrix_ref {
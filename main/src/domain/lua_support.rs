@@ -62,14 +62,23 @@ impl SafeLua {
             .set_name(name)?
             .set_mode(ChunkMode::Text)
             .set_environment(env)?;
-        let value = lua_chunk.eval().map_err(|e| match e {
-            // Box the cause if it's a callback error (used for the execution time limit feature).
-            mlua::Error::CallbackError { cause, .. } => {
-                let boxed: Box<dyn Error> = Box::new(cause);
-                boxed
-            }
-            e => Box::new(e),
-        })?;
+        let value = lua_chunk.eval().map_err(box_lua_error)?;
+        Ok(value)
+    }
+
+    /// Calls the given function and aborts it if it takes longer than the given duration.
+    ///
+    /// In contrast to [`Self::start_execution_time_limit_countdown`], the countdown starts anew
+    /// with each call, so this is suited for repeated execution.
+    pub fn call_with_execution_time_limit<'a>(
+        &'a self,
+        function: &Function<'a>,
+        max_duration: Duration,
+    ) -> Result<Value<'a>, Box<dyn Error>> {
+        self.set_execution_time_limit_hook(max_duration)?;
+        let result = function.call(());
+        self.0.remove_hook();
+        let value = result.map_err(box_lua_error)?;
         Ok(value)
     }
 
@@ -89,6 +98,11 @@ impl SafeLua {
         self,
         max_duration: Duration,
     ) -> Result<Self, Box<dyn Error>> {
+        self.set_execution_time_limit_hook(max_duration)?;
+        Ok(self)
+    }
+
+    fn set_execution_time_limit_hook(&self, max_duration: Duration) -> Result<(), Box<dyn Error>> {
         let instant = Instant::now();
         self.0.set_hook(
             HookTriggers::every_nth_instruction(10),
@@ -102,7 +116,15 @@ impl SafeLua {
                 }
             },
         )?;
-        Ok(self)
+        Ok(())
+    }
+}
+
+fn box_lua_error(error: mlua::Error) -> Box<dyn Error> {
+    match error {
+        // Box the cause if it's a callback error (used for the execution time limit feature).
+        mlua::Error::CallbackError { cause, .. } => Box::new(cause),
+        e => Box::new(e),
     }
 }
 
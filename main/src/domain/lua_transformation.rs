@@ -0,0 +1,239 @@
+use crate::base::script_util::{script_refers_to_identifier, LUA_SYNTAX};
+use crate::domain::{AdditionalEelTransformationInput, OutputVariable, SafeLua};
+use helgoboss_learn::Transformation;
+use mlua::{Function, Table, ToLua, Value};
+use std::error::Error;
+use std::thread;
+use std::thread::ThreadId;
+use std::time::Duration;
+
+/// Lua transformations are executed very often, so they must be fast.
+const MAX_EXECUTION_DURATION: Duration = Duration::from_millis(10);
+
+/// Represents a value transformation done via Lua.
+///
+/// Offers the same variables as [`crate::domain::EelTransformation`]. The script can either
+/// assign the output variable or return the output value.
+///
+/// Only usable in the thread in which it has been compiled (the main thread), because the Lua
+/// state is shared among all mappings. That's why mappings which are controlled in real-time
+/// (e.g. with target "MIDI: Send message") can't use Lua transformations. For them,
+/// [`Transformation::transform`] always returns an error.
+#[derive(Clone, Debug)]
+pub struct LuaTransformation<'lua> {
+    lua: &'lua SafeLua,
+    function: Function<'lua>,
+    env: Table<'lua>,
+    input_key: Value<'lua>,
+    output_key: Value<'lua>,
+    y_last_key: Value<'lua>,
+    rel_time_key: Value<'lua>,
+    stop_key: Value<'lua>,
+    /// `true` if the script refers to `rel_time`, which makes it time-aware.
+    wants_to_be_polled: bool,
+    /// The only thread in which the Lua state may be used.
+    lua_thread: ThreadId,
+}
+
+unsafe impl<'a> Send for LuaTransformation<'a> {}
+
+impl<'lua> LuaTransformation<'lua> {
+    /// Compiles the given script in a fresh environment (so each mapping gets its own one).
+    pub fn compile(
+        lua: &'lua SafeLua,
+        lua_script: &str,
+        result_var: OutputVariable,
+    ) -> Result<Self, Box<dyn Error>> {
+        if lua_script.trim().is_empty() {
+            return Err("script empty".into());
+        }
+        let env = lua.create_fresh_environment(false)?;
+        let function = lua.compile_as_function("Transformation", lua_script, env.clone())?;
+        let (input_var, output_var) = match result_var {
+            OutputVariable::X => ("y", "x"),
+            OutputVariable::Y => ("x", "y"),
+        };
+        let lua_ref = lua.as_ref();
+        let transformation = Self {
+            lua,
+            function,
+            env,
+            input_key: input_var.to_lua(lua_ref)?,
+            output_key: output_var.to_lua(lua_ref)?,
+            y_last_key: "y_last".to_lua(lua_ref)?,
+            rel_time_key: "rel_time".to_lua(lua_ref)?,
+            stop_key: "stop".to_lua(lua_ref)?,
+            wants_to_be_polled: script_refers_to_identifier(lua_script, &LUA_SYNTAX, "rel_time"),
+            lua_thread: thread::current().id(),
+        };
+        Ok(transformation)
+    }
+
+    /// See [`crate::domain::EelTransformation::wants_to_be_polled`].
+    pub fn wants_to_be_polled(&self) -> bool {
+        self.wants_to_be_polled
+    }
+
+    /// Returns whether the last execution of the script set `stop` to `true` or a non-zero value.
+    pub fn has_stopped(&self) -> bool {
+        match self.env.raw_get(self.stop_key.clone()) {
+            Ok(Value::Boolean(v)) => v,
+            Ok(Value::Number(v)) => v != 0.0,
+            Ok(Value::Integer(v)) => v != 0,
+            _ => false,
+        }
+    }
+
+    fn set(&self, key: &Value<'lua>, value: Value<'lua>) -> Result<(), &'static str> {
+        self.env
+            .raw_set(key.clone(), value)
+            .map_err(|_| "couldn't set Lua variable")
+    }
+}
+
+impl<'a> Transformation for LuaTransformation<'a> {
+    type AdditionalInput = AdditionalEelTransformationInput;
+
+    fn transform(
+        &self,
+        input_value: f64,
+        output_value: f64,
+        additional_input: AdditionalEelTransformationInput,
+    ) -> Result<f64, &'static str> {
+        // The Lua state is shared among all mappings and may only be used from the main thread.
+        // Mappings that are controlled in real-time (e.g. "Send MIDI") can't use Lua.
+        if thread::current().id() != self.lua_thread {
+            return Err("Lua transformations can only be executed in the main thread");
+        }
+        self.set(&self.input_key, Value::Number(input_value))?;
+        self.set(&self.output_key, Value::Number(output_value))?;
+        self.set(&self.y_last_key, Value::Number(additional_input.y_last))?;
+        self.set(
+            &self.rel_time_key,
+            Value::Number(additional_input.rel_time.as_millis() as f64),
+        )?;
+        self.set(&self.stop_key, Value::Nil)?;
+        let return_value = self
+            .lua
+            .call_with_execution_time_limit(&self.function, MAX_EXECUTION_DURATION)
+            .map_err(|_| "failed to invoke Lua transformation")?;
+        let result = match return_value {
+            Value::Number(v) => v,
+            Value::Integer(v) => v as f64,
+            _ => match self.env.raw_get(self.output_key.clone()) {
+                Ok(Value::Number(v)) => v,
+                Ok(Value::Integer(v)) => v as f64,
+                _ => return Err("Lua transformation didn't produce a number"),
+            },
+        };
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(y_last: f64, rel_time_millis: u64) -> AdditionalEelTransformationInput {
+        AdditionalEelTransformationInput {
+            y_last,
+            rel_time: Duration::from_millis(rel_time_millis),
+        }
+    }
+
+    #[test]
+    fn assign_output_variable() {
+        // Given
+        let lua = SafeLua::new().unwrap();
+        let transformation =
+            LuaTransformation::compile(&lua, "y = x / 2", OutputVariable::Y).unwrap();
+        // When
+        let result = transformation.transform(0.5, 0.0, input(0.0, 0));
+        // Then
+        assert_eq!(result, Ok(0.25));
+        assert!(!transformation.wants_to_be_polled());
+    }
+
+    #[test]
+    fn return_output_value() {
+        // Given
+        let lua = SafeLua::new().unwrap();
+        let transformation =
+            LuaTransformation::compile(&lua, "return 1 - x", OutputVariable::Y).unwrap();
+        // When
+        let result = transformation.transform(0.25, 0.0, input(0.0, 0));
+        // Then
+        assert_eq!(result, Ok(0.75));
+    }
+
+    #[test]
+    fn feedback_direction() {
+        // Given
+        let lua = SafeLua::new().unwrap();
+        let transformation =
+            LuaTransformation::compile(&lua, "x = y * 2", OutputVariable::X).unwrap();
+        // When
+        let result = transformation.transform(0.3, 0.0, input(0.0, 0));
+        // Then
+        assert_eq!(result, Ok(0.6));
+    }
+
+    #[test]
+    fn last_output_value() {
+        // Given
+        let lua = SafeLua::new().unwrap();
+        let transformation =
+            LuaTransformation::compile(&lua, "return y_last + x", OutputVariable::Y).unwrap();
+        // When
+        let result = transformation.transform(0.25, 0.0, input(0.5, 0));
+        // Then
+        assert_eq!(result, Ok(0.75));
+    }
+
+    #[test]
+    fn time_aware() {
+        // Given
+        let lua = SafeLua::new().unwrap();
+        let transformation =
+            LuaTransformation::compile(&lua, "stop = rel_time >= 500; return x", OutputVariable::Y)
+                .unwrap();
+        // When
+        let result_before_stop = transformation.transform(0.5, 0.0, input(0.0, 400));
+        let stopped_before = transformation.has_stopped();
+        let result_after_stop = transformation.transform(0.5, 0.0, input(0.0, 600));
+        // Then
+        assert!(transformation.wants_to_be_polled());
+        assert_eq!(result_before_stop, Ok(0.5));
+        assert!(!stopped_before);
+        assert_eq!(result_after_stop, Ok(0.5));
+        assert!(transformation.has_stopped());
+    }
+
+    #[test]
+    fn no_number_produced() {
+        // Given
+        let lua = SafeLua::new().unwrap();
+        let transformation =
+            LuaTransformation::compile(&lua, "return 'foo'", OutputVariable::Y).unwrap();
+        // When
+        let result = transformation.transform(0.5, 0.0, input(0.0, 0));
+        // Then
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn not_executable_in_other_thread() {
+        // Given
+        let lua = SafeLua::new().unwrap();
+        let transformation = LuaTransformation::compile(&lua, "y = x", OutputVariable::Y).unwrap();
+        // When
+        let result = thread::scope(|scope| {
+            scope
+                .spawn(move || transformation.transform(0.5, 0.0, input(0.0, 0)))
+                .join()
+                .unwrap()
+        });
+        // Then
+        assert!(result.is_err());
+    }
+}
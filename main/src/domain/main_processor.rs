@@ -1,6 +1,6 @@
 use crate::domain::{
-    aggregate_target_values, AdditionalFeedbackEvent, BackboneState, Compartment,
-    CompoundChangeEvent, CompoundFeedbackValue, CompoundMappingSource,
+    aggregate_target_values, convert_plugin_param_index_range_to_iter, AdditionalFeedbackEvent,
+    BackboneState, Compartment, CompoundChangeEvent, CompoundFeedbackValue, CompoundMappingSource,
     CompoundMappingSourceAddress, CompoundMappingTarget, ControlContext, ControlEvent,
     ControlEventTimestamp, ControlInput, ControlMode, ControlOutcome, DeviceFeedbackOutput,
     DomainEvent, DomainEventHandler, ExtendedProcessorContext, FeedbackAudioHookTask,
@@ -8,11 +8,11 @@ use crate::domain::{
     FeedbackSendBehavior, GroupId, HitInstructionContext, InstanceContainer,
     InstanceOrchestrationEvent, InstanceStateChanged, IoUpdatedEvent, KeyMessage,
     LimitedAsciiString, MainMapping, MainSourceMessage, MappingActivationEffect,
-    MappingControlResult, MappingId, MappingInfo, MessageCaptureEvent, MessageCaptureResult,
-    MidiControlInput, MidiDestination, MidiScanResult, NormalRealTimeTask, OrderedMappingIdSet,
-    OrderedMappingMap, OscDeviceId, OscFeedbackTask, PluginParamIndex, PluginParams,
-    ProcessorContext, QualifiedClipMatrixEvent, QualifiedMappingId, QualifiedSource, RawParamValue,
-    RealFeedbackValue, RealTimeMappingUpdate, RealTimeTargetUpdate,
+    MappingControlResult, MappingId, MappingInfo, MappingKey, MessageCaptureEvent,
+    MessageCaptureResult, MidiControlInput, MidiDestination, MidiScanResult, NormalRealTimeTask,
+    OrderedMappingIdSet, OrderedMappingMap, OscDeviceId, OscFeedbackTask, PluginParamIndex,
+    PluginParams, ProcessorContext, QualifiedClipMatrixEvent, QualifiedMappingId, QualifiedSource,
+    RawParamValue, RealFeedbackValue, RealTimeMappingUpdate, RealTimeTargetUpdate,
     RealearnMonitoringFxParameterValueChangedEvent, ReaperMessage, ReaperTarget,
     SharedInstanceState, SourceFeedbackValue, SourceReleasedEvent, SpecificCompoundFeedbackValue,
     TargetValueChangedEvent, UpdatedSingleMappingOnStateEvent, VirtualControlElement,
@@ -49,6 +49,7 @@ use std::fmt;
 use std::fmt::Display;
 use std::hash::{Hash, Hasher};
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 // This can be come pretty big when multiple track volumes are adjusted at once.
 const FEEDBACK_TASK_QUEUE_SIZE: usize = 20_000;
@@ -56,6 +57,7 @@ const NORMAL_TASK_BULK_SIZE: usize = 32;
 const FEEDBACK_TASK_BULK_SIZE: usize = 64;
 const CONTROL_TASK_BULK_SIZE: usize = 32;
 const PARAMETER_TASK_BULK_SIZE: usize = 32;
+const PARAM_VALUE_SOURCE_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 pub type SharedMainProcessors<EH> = Rc<RefCell<Vec<MainProcessor<EH>>>>;
 
@@ -65,6 +67,8 @@ pub struct MainProcessor<EH: DomainEventHandler> {
     collections: Collections,
     /// Contains IDs of those mappings who need to be polled as frequently as possible.
     poll_control_mappings: EnumMap<Compartment, OrderedMappingIdSet>,
+    /// When parameter value sources should be polled next.
+    next_param_value_source_poll: Instant,
 }
 
 #[derive(Debug)]
@@ -204,7 +208,18 @@ struct Collections {
     ///  could be optimized. However, this is what makes the seek target work currently when
     ///  changing cursor position while stopped.
     milli_dependent_feedback_mappings: EnumMap<Compartment, OrderedMappingIdSet>,
+    /// Contains IDs of those mappings whose activation depends on the target value of another
+    /// mapping, indexed by the ID of that lead mapping.
+    target_value_dependent_activation_mappings:
+        EnumMap<Compartment, HashMap<MappingId, OrderedMappingIdSet>>,
+    /// Contains IDs of those mappings whose feedback is animated by ReaLearn.
+    animated_feedback_mappings: EnumMap<Compartment, OrderedMappingIdSet>,
     parameters: PluginParams,
+    /// Contains indexes of those parameters whose value follows a value source.
+    params_with_value_source: Vec<PluginParamIndex>,
+    /// Contains the raw value which has last been written to a parameter because its value
+    /// source changed.
+    last_param_values_from_value_source: HashMap<PluginParamIndex, RawParamValue>,
     previous_target_values: EnumMap<Compartment, HashMap<MappingId, AbsoluteValue>>,
 }
 
@@ -295,10 +310,15 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 target_touch_dependent_mappings: Default::default(),
                 beat_dependent_feedback_mappings: Default::default(),
                 milli_dependent_feedback_mappings: Default::default(),
+                target_value_dependent_activation_mappings: Default::default(),
+                animated_feedback_mappings: Default::default(),
                 parameters: Default::default(),
+                params_with_value_source: Default::default(),
+                last_param_values_from_value_source: Default::default(),
                 previous_target_values: Default::default(),
             },
             poll_control_mappings: Default::default(),
+            next_param_value_source_poll: Instant::now(),
         }
     }
 
@@ -476,9 +496,27 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                         // single press would be discarded - or worse, fired when the mapping
                         // is enabled again.
                         (false, mode_poll_result)
+                    } else if let Some(res) = m.poll_press_gesture(
+                        control_context,
+                        &self.basics.logger,
+                        processor_context,
+                        timestamp,
+                    ) {
+                        // Button has been held long enough for a press-and-hold gesture.
+                        (false, res)
+                    } else if let Some(res) = m.poll_time_aware_control_transformation(
+                        control_context,
+                        &self.basics.logger,
+                        processor_context,
+                        timestamp,
+                    ) {
+                        // A time-aware control transformation (one that uses "rel_time") is
+                        // still running. Keep re-invoking it until it sets "stop".
+                        (false, res)
                     } else if m.source().wants_to_be_polled() && m.control_is_effectively_on() {
                         // Mode was either not polled at all or without result, poll source.
-                        let res = if let Some(source_control_value) = m.poll_source() {
+                        let project = self.basics.context.project_or_current_project();
+                        let res = if let Some(source_control_value) = m.poll_source(project) {
                             let control_event = ControlEvent::new(source_control_value, timestamp);
                             control_mapping_stage_one(
                                 &self.basics,
@@ -598,6 +636,71 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         self.process_feedback_tasks();
         self.process_instance_feedback_events();
         self.poll_for_feedback();
+        self.animate_feedback();
+        self.follow_param_value_sources();
+    }
+
+    /// Lets parameters which have a value source follow that source.
+    ///
+    /// Sets the parameter via REAPER so that the host gets informed about the new value, too.
+    /// Each source value is written only once, so the parameter can still be changed manually
+    /// until the source changes again.
+    fn follow_param_value_sources(&mut self) {
+        if self.collections.params_with_value_source.is_empty() {
+            return;
+        }
+        let now = Instant::now();
+        if now < self.next_param_value_source_poll {
+            return;
+        }
+        self.next_param_value_source_poll = now + PARAM_VALUE_SOURCE_POLL_INTERVAL;
+        let project = self.basics.context.project_or_current_project();
+        for i in &self.collections.params_with_value_source {
+            let param = self.collections.parameters.at(*i);
+            let value_source = match &param.setting().value_source {
+                None => continue,
+                Some(s) => s,
+            };
+            let new_value = match value_source.read_raw_value(param.setting(), project) {
+                None => continue,
+                Some(v) => v,
+            };
+            let last_value = self
+                .collections
+                .last_param_values_from_value_source
+                .insert(*i, new_value);
+            if last_value == Some(new_value) || new_value == param.raw_value() {
+                continue;
+            }
+            let _ = self
+                .basics
+                .context
+                .containing_fx()
+                .parameter_by_index(i.get())
+                .set_reaper_normalized_value(ReaperNormalizedFxParamValue::new(new_value as _));
+        }
+    }
+
+    /// Renders the next frame of all animated feedback whose current frame has expired.
+    fn animate_feedback(&self) {
+        let now = Instant::now();
+        for compartment in Compartment::enum_iter() {
+            let mapping_ids = &self.collections.animated_feedback_mappings[compartment];
+            if mapping_ids.is_empty() {
+                continue;
+            }
+            let feedback_values = mapping_ids
+                .iter()
+                .filter_map(|id| {
+                    let m = self.collections.mappings[compartment].get(id)?;
+                    if !m.animation_frame_is_due(now) || !m.feedback_is_effectively_on() {
+                        return None;
+                    }
+                    m.feedback(false, self.basics.control_context())
+                })
+                .collect::<Vec<_>>();
+            self.send_feedback(FeedbackReason::Normal, feedback_values);
+        }
     }
 
     /// This goes through all mappings that returned "high" feedback resolution - which they do if
@@ -610,7 +713,8 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 if let Some(m) = self.collections.mappings[compartment].get(mapping_id) {
                     let previous_target_values = &mut self.collections.previous_target_values;
                     let control_context = self.basics.control_context();
-                    self.basics
+                    let target_value_changed = self
+                        .basics
                         .process_feedback_related_reaper_event_for_mapping(
                             m,
                             &self.collections.mappings_with_virtual_targets,
@@ -660,6 +764,10 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                                         m.update_last_non_performance_target_value_if_appropriate(
                                             new_value,
                                         );
+                                        m.update_y_last_of_non_feedback_targets(
+                                            None,
+                                            control_context,
+                                        );
                                     }
                                     (affected, new_value)
                                 } else {
@@ -685,6 +793,9 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                                 }
                             },
                         );
+                    if target_value_changed {
+                        self.notify_lead_target_value_changed(m);
+                    }
                 }
             }
         }
@@ -906,6 +1017,10 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         let param = self.collections.parameters.at_mut(index);
         let previous_value = param.raw_value();
         param.set_raw_value(value);
+        self.basics
+            .instance_state
+            .borrow_mut()
+            .set_param_raw_value(index, value);
         self.basics
             .event_handler
             .handle_event(DomainEvent::UpdatedSingleParameterValue { index, value });
@@ -993,9 +1108,24 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         )
     }
 
+    fn index_params_with_value_source(&mut self) {
+        let params = &self.collections.parameters;
+        self.collections.params_with_value_source = Compartment::enum_iter()
+            .flat_map(|c| convert_plugin_param_index_range_to_iter(&c.plugin_param_range()))
+            .filter(|i| params.at(*i).setting().value_source.is_some())
+            .collect();
+        // Value sources might have changed, so write their values again
+        self.collections.last_param_values_from_value_source.clear();
+    }
+
     fn update_all_params(&mut self, params: PluginParams) {
         debug!(self.basics.logger, "Updating all parameters...");
         self.collections.parameters = params.clone();
+        self.index_params_with_value_source();
+        self.basics
+            .instance_state
+            .borrow_mut()
+            .set_params(params.clone());
         self.basics
             .event_handler
             .handle_event(DomainEvent::UpdatedAllParameters(params));
@@ -1066,6 +1196,9 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 RefreshAllTargets => {
                     self.refresh_all_targets();
                 }
+                UpdateTargetValueDependentActivations(lead_id) => {
+                    self.update_activations_depending_on_target_value_of(lead_id);
+                }
                 UpdateSingleMapping(mapping) => {
                     self.update_single_mapping(mapping);
                 }
@@ -1203,6 +1336,153 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
             );
         }
         self.update_on_mappings();
+        // Resolved targets might have changed, so target values of lead mappings as well.
+        self.update_target_value_dependent_activations();
+    }
+
+    /// Rebuilds the index of mappings whose activation depends on the target value of another
+    /// mapping.
+    fn index_target_value_dependent_activation_mappings(&mut self, compartment: Compartment) {
+        let mapping_ids_by_key: HashMap<&str, MappingId> = self
+            .all_mappings_in_compartment(compartment)
+            .map(|m| (m.key(), m.id()))
+            .collect();
+        let mut index: HashMap<MappingId, OrderedMappingIdSet> = HashMap::new();
+        for m in self.all_mappings_in_compartment(compartment) {
+            for key in m.activation_lead_mapping_keys() {
+                if let Some(lead_id) = mapping_ids_by_key.get(key.as_ref()) {
+                    index.entry(*lead_id).or_default().insert(m.id());
+                }
+            }
+        }
+        self.collections.target_value_dependent_activation_mappings[compartment] = index;
+    }
+
+    /// Makes sure that activation conditions which depend on the target value of the given
+    /// mapping are re-evaluated in the next main loop cycle.
+    ///
+    /// We don't have mutable access to self here (for reentrancy reasons), that's why we defer it.
+    fn notify_lead_target_value_changed(&self, m: &MainMapping) {
+        if !self.collections.target_value_dependent_activation_mappings[m.compartment()]
+            .contains_key(&m.id())
+        {
+            return;
+        }
+        self.basics.channels.self_normal_sender.send_complaining(
+            NormalMainTask::UpdateTargetValueDependentActivations(m.qualified_id()),
+        );
+    }
+
+    fn current_lead_target_value(&self, lead: &MainMapping) -> Option<f64> {
+        lead.current_aggregated_target_value(self.basics.control_context())
+            .map(|v| v.to_unit_value().get())
+    }
+
+    /// Re-evaluates the activation of all mappings which depend on the target value of another
+    /// mapping.
+    fn update_target_value_dependent_activations(&mut self) {
+        for compartment in Compartment::enum_iter() {
+            // 1. Read current target values of lead mappings
+            let lead_target_values: HashMap<&str, Option<f64>> =
+                self.collections.target_value_dependent_activation_mappings[compartment]
+                    .keys()
+                    .filter_map(|id| self.get_normal_or_virtual_target_mapping(compartment, *id))
+                    .map(|m| (m.key(), self.current_lead_target_value(m)))
+                    .collect();
+            // 2. Pass them to the dependent mappings (also those whose lead mapping is gone)
+            let affected_mappings: Vec<MappingId> = self
+                .all_mappings_in_compartment(compartment)
+                .filter(|m| {
+                    let mut is_affected = false;
+                    for key in m.activation_lead_mapping_keys() {
+                        let value = lead_target_values.get(key.as_ref()).copied().flatten();
+                        if m.notify_lead_target_value_changed(key, value) {
+                            is_affected = true;
+                        }
+                    }
+                    is_affected
+                })
+                .map(|m| m.id())
+                .collect();
+            // 3. Update activation
+            self.update_activations_affected_by_target_values(compartment, affected_mappings);
+        }
+    }
+
+    /// Re-evaluates the activation of the mappings which depend on the target value of the given
+    /// lead mapping, but only if that target value actually changed.
+    fn update_activations_depending_on_target_value_of(&mut self, lead_id: QualifiedMappingId) {
+        let compartment = lead_id.compartment;
+        let dependent_mappings = match self.collections.target_value_dependent_activation_mappings
+            [compartment]
+            .get(&lead_id.id)
+        {
+            None => return,
+            Some(ids) => ids,
+        };
+        let lead = match self.get_normal_or_virtual_target_mapping(compartment, lead_id.id) {
+            None => return,
+            Some(m) => m,
+        };
+        let lead_key = MappingKey::from(lead.key().to_owned());
+        let lead_target_value = self.current_lead_target_value(lead);
+        let affected_mappings: Vec<MappingId> = dependent_mappings
+            .iter()
+            .copied()
+            .filter(|id| {
+                self.get_normal_or_virtual_target_mapping(compartment, *id)
+                    .map(|m| m.notify_lead_target_value_changed(&lead_key, lead_target_value))
+                    .unwrap_or(false)
+            })
+            .collect();
+        self.update_activations_affected_by_target_values(compartment, affected_mappings);
+    }
+
+    /// The given mappings must have been notified about the changed lead target values already.
+    fn update_activations_affected_by_target_values(
+        &mut self,
+        compartment: Compartment,
+        affected_mappings: Vec<MappingId>,
+    ) {
+        if affected_mappings.is_empty() {
+            return;
+        }
+        let mut unused_sources = self.currently_feedback_enabled_sources(compartment, true);
+        let mut mapping_updates: Vec<RealTimeMappingUpdate> = vec![];
+        let mut changed_mappings = vec![];
+        for id in affected_mappings {
+            let m = match get_normal_or_virtual_target_mapping_mut(
+                &mut self.collections.mappings,
+                &mut self.collections.mappings_with_virtual_targets,
+                compartment,
+                id,
+            ) {
+                None => continue,
+                Some(m) => m,
+            };
+            if let Some(update) = m.update_activation(&self.collections.parameters) {
+                mapping_updates.push(update);
+                changed_mappings.push(id);
+            }
+        }
+        if mapping_updates.is_empty() {
+            return;
+        }
+        for m in self.all_mappings_in_compartment(compartment) {
+            if m.feedback_is_effectively_on() {
+                // Mark source as used
+                if let Some(addr) = m.source().extract_feedback_address() {
+                    unused_sources.remove(&addr);
+                }
+            }
+        }
+        self.process_mapping_updates_due_to_parameter_changes(
+            compartment,
+            mapping_updates,
+            vec![],
+            unused_sources,
+            changed_mappings.into_iter(),
+        );
     }
 
     fn update_settings(&mut self, settings: BasicSettings) {
@@ -1226,6 +1506,7 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         self.collections.target_touch_dependent_mappings[compartment].clear();
         self.collections.beat_dependent_feedback_mappings[compartment].clear();
         self.collections.milli_dependent_feedback_mappings[compartment].clear();
+        self.collections.animated_feedback_mappings[compartment].clear();
         self.collections.previous_target_values[compartment].clear();
         self.poll_control_mappings[compartment].clear();
         // Refresh and splinter real-time mappings
@@ -1265,6 +1546,9 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
                 if m.wants_to_be_polled_for_control() {
                     self.poll_control_mappings[compartment].insert(m.id());
                 }
+                if m.has_animated_feedback() {
+                    self.collections.animated_feedback_mappings[compartment].insert(m.id());
+                }
                 m.splinter_real_time_mapping()
             })
             .collect();
@@ -1284,6 +1568,7 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         } else {
             self.collections.mappings[compartment] = mapping_tuples.collect();
         }
+        self.index_target_value_dependent_activation_mappings(compartment);
         // Sync to real-time processor
         self.basics
             .channels
@@ -1300,6 +1585,7 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         // lower-floor instances.
         self.handle_feedback_after_having_updated_all_mappings(compartment, unused_sources);
         self.update_on_mappings();
+        self.update_target_value_dependent_activations();
     }
 
     fn process_normal_tasks_from_real_time_processor(&mut self) {
@@ -1526,12 +1812,16 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         m: &MainMapping,
         f: &mut impl FnMut(&MainMapping, &ReaperTarget) -> (bool, Option<AbsoluteValue>),
     ) {
-        self.basics
+        let target_value_changed = self
+            .basics
             .process_feedback_related_reaper_event_for_mapping(
                 m,
                 &self.collections.mappings_with_virtual_targets,
                 f,
             );
+        if target_value_changed {
+            self.notify_lead_target_value_changed(m);
+        }
     }
 
     pub fn notify_target_touched(&self) {
@@ -2179,8 +2469,10 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
             &mapping,
         );
         self.update_map_entries(compartment, *mapping);
+        self.index_target_value_dependent_activation_mappings(compartment);
         self.send_diff_feedback(diff_feedback);
         self.update_single_mapping_on_state(id);
+        self.update_target_value_dependent_activations();
     }
 
     fn update_persistent_mapping_processing_state(
@@ -2320,6 +2612,11 @@ impl<EH: DomainEventHandler> MainProcessor<EH> {
         } else {
             self.poll_control_mappings[compartment].shift_remove(&m.id());
         }
+        if m.has_animated_feedback() {
+            self.collections.animated_feedback_mappings[compartment].insert(m.id());
+        } else {
+            self.collections.animated_feedback_mappings[compartment].shift_remove(&m.id());
+        }
         let relevant_map = if m.has_virtual_target() {
             self.collections.mappings[compartment].shift_remove(&m.id());
             &mut self.collections.mappings_with_virtual_targets
@@ -2410,6 +2707,8 @@ pub enum NormalMainTask {
         value: AbsoluteValue,
     },
     RefreshAllTargets,
+    /// Re-evaluates activation conditions which depend on the target value of the given mapping.
+    UpdateTargetValueDependentActivations(QualifiedMappingId),
     UpdateSettings(BasicSettings),
     /// This is a hacky way to notify a ReaLearn instance on the monitoring FX chain
     /// that it might have been enabled or disabled (unfortunately, REAPER doesn't
@@ -2896,6 +3195,8 @@ impl<EH: DomainEventHandler> Basics<EH> {
     }
 
     /// The given function f is NOW required to return the current target value.
+    ///
+    /// Returns `true` if the target value of the given mapping changed.
     // https://github.com/rust-lang/rust-clippy/issues/6066
     #[allow(clippy::needless_collect)]
     pub fn process_feedback_related_reaper_event_for_mapping(
@@ -2903,12 +3204,12 @@ impl<EH: DomainEventHandler> Basics<EH> {
         m: &MainMapping,
         mappings_with_virtual_targets: &OrderedMappingMap<MainMapping>,
         f: &mut impl FnMut(&MainMapping, &ReaperTarget) -> (bool, Option<AbsoluteValue>),
-    ) {
+    ) -> bool {
         // It's enough if one of the resolved targets is affected. Then we are going to need the
         // values of all of them!
         let mut at_least_one_target_is_affected = false;
         let new_values: Vec<Option<AbsoluteValue>> = m
-            .targets()
+            .feedback_targets()
             .iter()
             .filter_map(|target| {
                 let target = match target {
@@ -2934,7 +3235,7 @@ impl<EH: DomainEventHandler> Basics<EH> {
             })
             .collect();
         if !at_least_one_target_is_affected {
-            return;
+            return false;
         }
         let new_target_value = aggregate_target_values(new_values.into_iter());
         if let Some(new_value) = new_target_value {
@@ -2957,6 +3258,9 @@ impl<EH: DomainEventHandler> Basics<EH> {
                 feedback_value,
             );
             self.notify_target_value_changed(m, new_value);
+            true
+        } else {
+            false
         }
     }
 
@@ -2966,7 +3270,7 @@ impl<EH: DomainEventHandler> Basics<EH> {
             .handle_event(DomainEvent::TargetValueChanged(TargetValueChangedEvent {
                 compartment: m.compartment(),
                 mapping_id: m.id(),
-                targets: m.targets(),
+                targets: m.feedback_targets(),
                 new_value,
             }));
     }
@@ -3459,7 +3763,6 @@ fn control_mapping_stage_one<EH: DomainEventHandler>(
         basics.control_context(),
         &basics.logger,
         ExtendedProcessorContext::new(&basics.context, params, basics.control_context()),
-        m.last_non_performance_target_value(),
     )
 }
 
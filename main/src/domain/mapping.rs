@@ -1,13 +1,13 @@
 use crate::domain::{
-    get_prop_value, prop_feedback_resolution, prop_is_affected_by, ActivationChange,
+    get_prop_value, prop_feedback_resolution, prop_is_affected_by, Acceleration, ActivationChange,
     ActivationCondition, CompartmentParamIndex, CompoundChangeEvent, ControlContext, ControlEvent,
-    ControlEventTimestamp, ControlOptions, ExtendedProcessorContext, FeedbackResolution, GroupId,
-    HitInstructionReturnValue, KeyMessage, KeySource, MappingActivationEffect,
-    MappingControlContext, MappingData, MappingInfo, MessageCaptureEvent, MidiScanResult,
-    MidiSource, Mode, OscDeviceId, OscScanResult, PersistentMappingProcessingState,
-    PluginParamIndex, PluginParams, RealTimeMappingUpdate, RealTimeReaperTarget,
-    RealTimeTargetUpdate, RealearnTarget, ReaperMessage, ReaperSource, ReaperTarget,
-    ReaperTargetType, Tag, TargetCharacter, TrackExclusivity, UnresolvedReaperTarget,
+    ControlEventTimestamp, ControlOptions, ExtendedProcessorContext, FeedbackAnimation,
+    FeedbackResolution, GroupId, HitInstructionReturnValue, KeyMessage, KeySource,
+    MappingActivationEffect, MappingControlContext, MappingData, MappingInfo, MessageCaptureEvent,
+    MidiScanResult, MidiSource, Mode, OscDeviceId, OscScanResult, PersistentMappingProcessingState,
+    PluginParamIndex, PluginParams, PressGesture, PressGestureDetector, RealTimeMappingUpdate,
+    RealTimeReaperTarget, RealTimeTargetUpdate, RealearnTarget, ReaperMessage, ReaperSource,
+    ReaperTarget, ReaperTargetType, Tag, TargetCharacter, TrackExclusivity, UnresolvedReaperTarget,
     VirtualControlElement, VirtualFeedbackValue, VirtualSource, VirtualSourceAddress,
     VirtualSourceValue, VirtualTarget, COMPARTMENT_PARAMETER_COUNT,
 };
@@ -15,13 +15,13 @@ use derive_more::Display;
 use enum_iterator::IntoEnumIterator;
 use enum_map::Enum;
 use helgoboss_learn::{
-    format_percentage_without_unit, parse_percentage_without_unit, AbsoluteValue, ControlType,
-    ControlValue, FeedbackValue, GroupInteraction, MidiSourceAddress, MidiSourceValue,
-    ModeControlOptions, ModeControlResult, ModeFeedbackOptions, NumericFeedbackValue, NumericValue,
-    OscSource, OscSourceAddress, PropValue, RawMidiEvent, SourceCharacter, Target, UnitValue,
-    ValueFormatter, ValueParser,
+    format_percentage_without_unit, parse_percentage_without_unit, AbsoluteMode, AbsoluteValue,
+    ButtonUsage, ControlType, ControlValue, FeedbackValue, FireMode, GroupInteraction,
+    MidiSourceAddress, MidiSourceValue, ModeControlOptions, ModeControlResult, ModeFeedbackOptions,
+    NumericFeedbackValue, NumericValue, OscSource, OscSourceAddress, PropValue, RawMidiEvent,
+    SourceCharacter, Target, UnitValue, ValueFormatter, ValueParser,
 };
-use helgoboss_midi::{Channel, RawShortMessage, ShortMessage};
+use helgoboss_midi::{Channel, RawShortMessage, ShortMessage, ShortMessageFactory};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use std::borrow::Cow;
 use std::cell::Cell;
@@ -33,6 +33,7 @@ use reaper_high::{Fx, Project, Track, TrackRoute};
 use reaper_medium::MidiInputDeviceId;
 use rosc::OscMessage;
 use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
 use std::collections::HashSet;
 use std::convert::TryInto;
 use std::fmt;
@@ -179,10 +180,17 @@ pub struct MainMapping {
     key: Rc<str>,
     name: Option<String>,
     tags: Vec<Tag>,
+    feedback_animation: Option<FeedbackAnimation>,
+    /// Point in time when ReaLearn needs to render the next frame of the feedback animation.
+    ///
+    /// Is `None` if the feedback is currently not animated, e.g. because the feedback value is
+    /// outside of the animation's value interval.
+    next_animation_frame: Cell<Option<Instant>>,
     /// Is `Some` if the user-provided target data is complete.
     unresolved_target: Option<UnresolvedCompoundMappingTarget>,
     /// Is non-empty if the target resolved successfully.
     targets: Vec<CompoundMappingTarget>,
+    additional_targets: Vec<AdditionalMappingTarget>,
     activation_condition_1: ActivationCondition,
     activation_condition_2: ActivationCondition,
     activation_state: ActivationState,
@@ -190,6 +198,133 @@ pub struct MainMapping {
     initial_target_value: Option<AbsoluteValue>,
     /// Called "y_last" in the control transformation formula.
     last_non_performance_target_value: Cell<Option<AbsoluteValue>>,
+    /// Is `Some` as long as a time-aware control transformation hasn't stopped yet.
+    time_aware_control: Option<TimeAwareControl>,
+}
+
+/// A further target of a mapping. It's controlled by the same source as the main target but has
+/// its own mode (e.g. for building macro knobs).
+#[derive(Debug)]
+pub struct AdditionalMappingTarget {
+    unresolved_target: UnresolvedCompoundMappingTarget,
+    mode: Mode,
+    /// If `true`, feedback is taken from this target instead of the main target.
+    is_feedback_target: bool,
+    /// Is non-empty if the target resolved successfully.
+    targets: Vec<CompoundMappingTarget>,
+    initial_target_value: Option<AbsoluteValue>,
+    /// Called "y_last" in the control transformation formula of this target's mode.
+    last_non_performance_target_value: Cell<Option<AbsoluteValue>>,
+}
+
+impl AdditionalMappingTarget {
+    pub fn new(
+        unresolved_target: UnresolvedCompoundMappingTarget,
+        mode: Mode,
+        is_feedback_target: bool,
+    ) -> Self {
+        Self {
+            unresolved_target,
+            mode,
+            is_feedback_target,
+            targets: vec![],
+            initial_target_value: None,
+            last_non_performance_target_value: Cell::new(None),
+        }
+    }
+
+    pub fn last_non_performance_target_value(&self) -> Option<AbsoluteValue> {
+        self.last_non_performance_target_value.get()
+    }
+}
+
+/// "y_last" of the main target followed by the ones of the additional targets.
+type LastNonPerformanceTargetValues = SmallVec<[Option<AbsoluteValue>; 4]>;
+
+/// The control event which started a time-aware control transformation.
+#[derive(Clone, Debug)]
+struct TimeAwareControl {
+    control_event: ControlEvent<ControlValue>,
+    options: ControlOptions,
+    /// Snapshot of "y_last" at the time of the control event, so that it doesn't change while
+    /// the transformation is still running.
+    last_non_performance_target_values: LastNonPerformanceTargetValues,
+}
+
+impl TimeAwareControl {
+    /// Returns the running time-aware control after the given control event has been processed.
+    ///
+    /// Returns `None` if the transformation doesn't need to be re-invoked.
+    fn start(
+        control_event: ControlEvent<ControlValue>,
+        options: ControlOptions,
+        last_non_performance_target_values: LastNonPerformanceTargetValues,
+        control_was_successful: bool,
+        transformation: TimeAwareTransformationState,
+    ) -> Option<Self> {
+        // Re-invoking only makes sense for absolute values. Relative values would accumulate.
+        let is_absolute = matches!(
+            control_event.payload(),
+            ControlValue::AbsoluteContinuous(_) | ControlValue::AbsoluteDiscrete(_)
+        );
+        if !control_was_successful
+            || !is_absolute
+            || !transformation.is_time_aware
+            || transformation.has_stopped
+        {
+            return None;
+        }
+        let tac = Self {
+            control_event,
+            options,
+            last_non_performance_target_values,
+        };
+        Some(tac)
+    }
+
+    /// Time elapsed since the control event which started the transformation.
+    fn rel_time(&self, timestamp: ControlEventTimestamp) -> Duration {
+        timestamp - self.control_event.timestamp()
+    }
+
+    /// Returns whether the transformation needs to be re-invoked again after a poll.
+    ///
+    /// If the mode filtered out the value, the transformation might not have been executed at
+    /// all, so we better stop instead of polling forever.
+    fn continues_after_poll(
+        control_was_successful: bool,
+        transformation: TimeAwareTransformationState,
+    ) -> bool {
+        control_was_successful && transformation.is_time_aware && !transformation.has_stopped
+    }
+}
+
+/// Describes how [`MainMapping::control_internal`] controls the targets.
+#[derive(Clone, Debug, Default)]
+struct TargetControlOptions {
+    is_polling: bool,
+    /// Passed to the control transformation as `rel_time`.
+    rel_time: Duration,
+    /// If `None`, the current "y_last" of each target is used.
+    last_non_performance_target_values: Option<LastNonPerformanceTargetValues>,
+}
+
+impl TargetControlOptions {
+    fn polling() -> Self {
+        Self {
+            is_polling: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// State of the control transformation as far as time-awareness is concerned.
+#[derive(Copy, Clone, Debug)]
+struct TimeAwareTransformationState {
+    /// Whether the transformation is time-aware and may be re-invoked with the current mode.
+    is_time_aware: bool,
+    /// Whether the last invocation set `stop`.
+    has_stopped: bool,
 }
 
 #[derive(Default, Debug)]
@@ -215,8 +350,12 @@ impl MainMapping {
         tags: Vec<Tag>,
         source: CompoundMappingSource,
         mode: Mode,
+        acceleration: Option<Acceleration>,
+        press_gesture: Option<PressGesture>,
+        feedback_animation: Option<FeedbackAnimation>,
         group_interaction: GroupInteraction,
         unresolved_target: Option<UnresolvedCompoundMappingTarget>,
+        additional_targets: Vec<AdditionalMappingTarget>,
         activation_condition_1: ActivationCondition,
         activation_condition_2: ActivationCondition,
         options: ProcessorMappingOptions,
@@ -229,6 +368,8 @@ impl MainMapping {
                 group_id,
                 source,
                 mode,
+                acceleration,
+                press_gesture: press_gesture.map(PressGestureDetector::new),
                 group_interaction,
                 options,
                 time_of_last_control: None,
@@ -239,14 +380,18 @@ impl MainMapping {
             },
             name: Some(name),
             tags,
+            feedback_animation,
+            next_animation_frame: Cell::new(None),
             unresolved_target,
             targets: vec![],
+            additional_targets,
             activation_condition_1,
             activation_condition_2,
             activation_state: Default::default(),
             extension,
             initial_target_value: None,
             last_non_performance_target_value: Cell::new(None),
+            time_aware_control: None,
         }
     }
 
@@ -289,6 +434,9 @@ impl MainMapping {
                     true,
                 )
             };
+        if handle_performance_mapping {
+            self.update_y_last_of_non_feedback_targets(Some(evt), context);
+        }
         if is_affected {
             let new_value = new_value.or_else(|| target.current_value(context));
             if handle_performance_mapping {
@@ -300,23 +448,65 @@ impl MainMapping {
         }
     }
 
+    /// Updates y_last of the feedback targets with the given feedback target value.
     pub fn update_last_non_performance_target_value_if_appropriate(
         &self,
         value: Option<AbsoluteValue>,
     ) {
         if let Some(v) = value {
             if self.control_is_enabled() && !self.is_echo() {
-                self.last_non_performance_target_value.set(Some(v));
+                let y_last = match self.feedback_target() {
+                    None => &self.last_non_performance_target_value,
+                    Some(t) => &t.last_non_performance_target_value,
+                };
+                y_last.set(Some(v));
             }
         }
     }
 
-    pub fn is_echo(&self) -> bool {
-        self.core.is_echo()
+    /// Updates y_last of the targets which are not used for feedback (the main target if an
+    /// additional target is the feedback target, and all other additional targets).
+    ///
+    /// If no change event is given, the values are updated without checking whether they are
+    /// affected.
+    pub fn update_y_last_of_non_feedback_targets(
+        &self,
+        evt: Option<CompoundChangeEvent>,
+        context: ControlContext,
+    ) {
+        if self.additional_targets.is_empty() || !self.control_is_enabled() || self.is_echo() {
+            return;
+        }
+        let main_target = if self.feedback_target().is_some() {
+            Some((&self.targets, &self.last_non_performance_target_value))
+        } else {
+            None
+        };
+        let additional_targets = self
+            .additional_targets
+            .iter()
+            .filter(|t| !t.is_feedback_target)
+            .map(|t| (&t.targets, &t.last_non_performance_target_value));
+        for (targets, y_last) in main_target.into_iter().chain(additional_targets) {
+            let is_affected = match evt {
+                None => true,
+                Some(evt) => targets.iter().any(|t| match t {
+                    CompoundMappingTarget::Reaper(t) => t.process_change_event(evt, context).0,
+                    CompoundMappingTarget::Virtual(_) => false,
+                }),
+            };
+            if !is_affected {
+                continue;
+            }
+            let value = aggregate_target_values(targets.iter().map(|t| t.current_value(context)));
+            if value.is_some() {
+                y_last.set(value);
+            }
+        }
     }
 
-    pub fn update_last_non_performance_target_value(&self, value: AbsoluteValue) {
-        self.last_non_performance_target_value.set(Some(value));
+    pub fn is_echo(&self) -> bool {
+        self.core.is_echo()
     }
 
     pub fn last_non_performance_target_value(&self) -> Option<AbsoluteValue> {
@@ -333,6 +523,19 @@ impl MainMapping {
         self.initial_target_value
     }
 
+    /// Returns `true` if the main target or any additional target has an initial value.
+    pub fn has_initial_target_values(&self) -> bool {
+        self.initial_target_value.is_some()
+            || self
+                .additional_targets
+                .iter()
+                .any(|t| t.initial_target_value.is_some())
+    }
+
+    pub fn additional_targets(&self) -> &[AdditionalMappingTarget] {
+        &self.additional_targets
+    }
+
     pub fn update_persistent_processing_state(&mut self, state: PersistentMappingProcessingState) {
         self.core.options.persistent_processing_state = state;
     }
@@ -361,6 +564,10 @@ impl MainMapping {
         self.core.id
     }
 
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
     pub fn qualified_id(&self) -> QualifiedMappingId {
         QualifiedMappingId::new(self.core.compartment, self.core.id)
     }
@@ -390,6 +597,7 @@ impl MainMapping {
                 UnresolvedCompoundMappingTarget::Virtual(_) => UnresolvedTargetCategory::Virtual,
             }),
             target_is_resolved: !self.targets.is_empty(),
+            has_additional_targets: !self.additional_targets.is_empty(),
             resolved_target: self.splinter_first_real_time_target(),
             lifecycle_midi_data: self
                 .extension
@@ -455,10 +663,15 @@ impl MainMapping {
 
     /// Returns if this target is dynamic.
     pub fn target_can_be_affected_by_parameters(&self) -> bool {
-        match &self.unresolved_target {
-            Some(UnresolvedCompoundMappingTarget::Reaper(t)) => t.can_be_affected_by_parameters(),
-            _ => false,
-        }
+        let main_target_can_be_affected = match &self.unresolved_target {
+            Some(t) => t.can_be_affected_by_parameters(),
+            None => false,
+        };
+        main_target_can_be_affected
+            || self
+                .additional_targets
+                .iter()
+                .any(|t| t.unresolved_target.can_be_affected_by_parameters())
     }
 
     /// Returns if this activation condition is affected by parameter changes in general.
@@ -509,12 +722,46 @@ impl MainMapping {
         let (targets, is_active) = self.resolve_target(context, control_context);
         self.targets = targets;
         self.core.options.target_is_active = is_active;
+        self.resolve_additional_targets(context, control_context, false);
         self.update_activation(context.params());
-        let target_value = self.current_aggregated_target_value(control_context);
+        let target_value = aggregate_target_values(
+            self.targets
+                .iter()
+                .map(|t| t.current_value(control_context)),
+        );
         self.initial_target_value = target_value;
         self.last_non_performance_target_value = Cell::new(target_value);
     }
 
+    /// Additional targets don't influence the activation state of the mapping.
+    fn resolve_additional_targets(
+        &mut self,
+        context: ExtendedProcessorContext,
+        control_context: ControlContext,
+        only_dynamic_ones: bool,
+    ) {
+        for t in &mut self.additional_targets {
+            if only_dynamic_ones && !t.unresolved_target.can_be_affected_by_change_events() {
+                continue;
+            }
+            let targets = t
+                .unresolved_target
+                .resolve(context, self.core.compartment)
+                .unwrap_or_default();
+            if let Some(first) = targets.first() {
+                t.mode.update_from_target(first, control_context);
+            }
+            if !only_dynamic_ones {
+                let target_value = aggregate_target_values(
+                    targets.iter().map(|t| t.current_value(control_context)),
+                );
+                t.initial_target_value = target_value;
+                t.last_non_performance_target_value = Cell::new(target_value);
+            }
+            t.targets = targets;
+        }
+    }
+
     fn resolve_target(
         &mut self,
         context: ExtendedProcessorContext,
@@ -551,7 +798,10 @@ impl MainMapping {
 
     /// `None` means that no polling is necessary for feedback because we are notified via events.
     pub fn feedback_resolution(&self) -> Option<FeedbackResolution> {
-        let t = self.unresolved_target.as_ref()?;
+        let t = match self.feedback_target() {
+            None => self.unresolved_target.as_ref()?,
+            Some(ft) => &ft.unresolved_target,
+        };
         let max_resolution_required_by_props = self
             .core
             .mode
@@ -559,7 +809,7 @@ impl MainMapping {
             .iter()
             .filter_map(|p| prop_feedback_resolution(p, self, t))
             .max();
-        if self.mode().wants_textual_feedback() {
+        if self.feedback_mode().wants_textual_feedback() {
             // For textual feedback, we just need to look at the props.
             max_resolution_required_by_props
         } else {
@@ -573,7 +823,49 @@ impl MainMapping {
     }
 
     pub fn wants_to_be_polled_for_control(&self) -> bool {
-        self.core.source.wants_to_be_polled() || self.core.mode.wants_to_be_polled()
+        self.core.source.wants_to_be_polled()
+            || self.core.mode.wants_to_be_polled()
+            || self.core.press_gesture_wants_to_be_polled()
+            || self.has_time_aware_control_transformation()
+    }
+
+    /// Returns `true` if the control transformation is time-aware and the mode allows re-invoking
+    /// it.
+    ///
+    /// Re-invoking passes the same control event through the complete mode again. That's only
+    /// correct if the mode doesn't keep state across control events. Toggle buttons would flip
+    /// the target on each invocation, fire modes and button filters would count presses and
+    /// takeover would compare against the moving target value. In such cases, the
+    /// transformation is just executed once per control event.
+    fn has_time_aware_control_transformation(&self) -> bool {
+        let settings = self.core.mode.settings();
+        let transformation_is_time_aware = settings
+            .control_transformation
+            .as_ref()
+            .map(|t| t.wants_to_be_polled())
+            .unwrap_or(false);
+        transformation_is_time_aware
+            && matches!(settings.absolute_mode, AbsoluteMode::Normal)
+            && matches!(settings.fire_mode, FireMode::Normal)
+            && settings.press_duration_interval.min_val() == Duration::ZERO
+            && settings.press_duration_interval.max_val() == Duration::ZERO
+            && matches!(settings.button_usage, ButtonUsage::Both)
+            && settings.jump_interval.min_val() == UnitValue::MIN
+            && settings.jump_interval.max_val() == UnitValue::MAX
+    }
+
+    fn time_aware_transformation_state(&self) -> TimeAwareTransformationState {
+        TimeAwareTransformationState {
+            is_time_aware: self.has_time_aware_control_transformation(),
+            has_stopped: self
+                .core
+                .mode
+                .settings()
+                .control_transformation
+                .as_ref()
+                .map(|t| t.has_stopped())
+                .unwrap_or(true),
+        }
     }
 
     /// The boolean return value tells if the resolved target changed in some way, the activation
@@ -584,6 +876,9 @@ impl MainMapping {
         context: ExtendedProcessorContext,
         control_context: ControlContext,
     ) -> Option<RealTimeTargetUpdate> {
+        // Additional targets are controlled in the main thread only, so they never cause a
+        // real-time target update.
+        self.resolve_additional_targets(context, control_context, true);
         match self.unresolved_target.as_ref() {
             None => return None,
             Some(t) => {
@@ -619,6 +914,34 @@ impl MainMapping {
         Some(update)
     }
 
+    /// Returns the keys of the mappings whose target values affect the activation of this mapping.
+    pub fn activation_lead_mapping_keys(&self) -> impl Iterator<Item = &MappingKey> {
+        self.activation_condition_1
+            .lead_mapping_key()
+            .into_iter()
+            .chain(self.activation_condition_2.lead_mapping_key())
+    }
+
+    /// Informs the activation conditions about the current target value of the given lead mapping.
+    ///
+    /// Returns `true` if activation might have changed. In that case, call
+    /// [`Self::update_activation`].
+    pub fn notify_lead_target_value_changed(
+        &self,
+        lead_mapping_key: &MappingKey,
+        value: Option<f64>,
+    ) -> bool {
+        let mut is_affected = false;
+        for condition in [&self.activation_condition_1, &self.activation_condition_2] {
+            if condition.lead_mapping_key() == Some(lead_mapping_key)
+                && condition.notify_lead_target_value_changed(value)
+            {
+                is_affected = true;
+            }
+        }
+        is_affected
+    }
+
     pub fn update_activation(&mut self, params: &PluginParams) -> Option<RealTimeMappingUpdate> {
         let was_active_before = self.is_active_in_terms_of_activation_state();
         let compartment_params = params.compartment_params(self.core.compartment);
@@ -697,11 +1020,91 @@ impl MainMapping {
             context,
             logger,
             processor_context,
-            true,
-            |_, context, mode, target| mode.poll(target, context, timestamp),
+            TargetControlOptions::polling(),
+            |_, context, mode, target, _| mode.poll(target, context, timestamp),
         )
     }
 
+    /// This makes the press-and-hold gesture fire while the button is still being held.
+    ///
+    /// Returns `None` if the press gesture detector has nothing to fire.
+    #[must_use]
+    pub fn poll_press_gesture(
+        &mut self,
+        context: ControlContext,
+        logger: &slog::Logger,
+        processor_context: ExtendedProcessorContext,
+        timestamp: ControlEventTimestamp,
+    ) -> Option<MappingControlResult> {
+        let control_event = self.core.press_gesture.as_mut()?.poll(timestamp)?;
+        let result = self.control_internal(
+            ControlOptions::default(),
+            context,
+            logger,
+            processor_context,
+            TargetControlOptions::polling(),
+            |options, context, mode, target, _| {
+                mode.control_with_options(
+                    control_event,
+                    target,
+                    context,
+                    options.mode_control_options,
+                    context.mapping_data.last_non_performance_target_value,
+                )
+            },
+        );
+        Some(result)
+    }
+
+    /// Re-invokes the mode with the control event that started a time-aware control
+    /// transformation, passing the elapsed time as `rel_time`.
+    ///
+    /// Returns `None` if there's no time-aware control transformation running.
+    #[must_use]
+    pub fn poll_time_aware_control_transformation(
+        &mut self,
+        context: ControlContext,
+        logger: &slog::Logger,
+        processor_context: ExtendedProcessorContext,
+        timestamp: ControlEventTimestamp,
+    ) -> Option<MappingControlResult> {
+        let tac = self.time_aware_control.take()?;
+        let target_control_options = TargetControlOptions {
+            is_polling: true,
+            rel_time: tac.rel_time(timestamp),
+            last_non_performance_target_values: Some(
+                tac.last_non_performance_target_values.clone(),
+            ),
+        };
+        let result = self.control_internal(
+            tac.options,
+            context,
+            logger,
+            processor_context,
+            target_control_options,
+            |options, context, mode, target, _| {
+                mode.control_with_options(
+                    tac.control_event,
+                    target,
+                    context,
+                    options.mode_control_options,
+                    context.mapping_data.last_non_performance_target_value,
+                )
+            },
+        );
+        if TimeAwareControl::continues_after_poll(
+            result.successful,
+            self.time_aware_transformation_state(),
+        ) {
+            self.time_aware_control = Some(tac);
+        }
+        Some(result)
+    }
+
+    pub fn has_running_time_aware_control_transformation(&self) -> bool {
+        self.time_aware_control.is_some()
+    }
+
     pub fn group_interaction(&self) -> GroupInteraction {
         self.core.group_interaction
     }
@@ -718,24 +1121,38 @@ impl MainMapping {
         context: ControlContext,
         logger: &slog::Logger,
         processor_context: ExtendedProcessorContext,
-        last_non_performance_target_value: Option<AbsoluteValue>,
     ) -> MappingControlResult {
-        self.control_internal(
+        let source_control_event = match self.core.detect_press_gesture(source_control_event) {
+            None => return MappingControlResult::default(),
+            Some(e) => e,
+        };
+        let source_control_event = self.core.accelerate(source_control_event);
+        // Snapshot in case this starts a time-aware control transformation
+        let last_non_performance_target_values = self.last_non_performance_target_values();
+        let result = self.control_internal(
             options,
             context,
             logger,
             processor_context,
-            false,
-            |options, context, mode, target| {
+            TargetControlOptions::default(),
+            |options, context, mode, target, _| {
                 mode.control_with_options(
                     source_control_event,
                     target,
                     context,
                     options.mode_control_options,
-                    last_non_performance_target_value,
+                    context.mapping_data.last_non_performance_target_value,
                 )
             },
-        )
+        );
+        self.time_aware_control = TimeAwareControl::start(
+            source_control_event,
+            options,
+            last_non_performance_target_values,
+            result.successful,
+            self.time_aware_transformation_state(),
+        );
+        result
     }
 
     /// Controls target directly without using mode.
@@ -757,8 +1174,8 @@ impl MainMapping {
             context,
             logger,
             processor_context,
-            false,
-            |_, _, mode, target| {
+            TargetControlOptions::default(),
+            |_, _, mode, target, _| {
                 let mut v = value;
                 let control_type = target.control_type(context);
                 // This is very similar to the mode logic, but just a small subset.
@@ -783,14 +1200,29 @@ impl MainMapping {
         )
     }
 
-    fn data(&self) -> MappingData {
+    fn data(
+        &self,
+        rel_time: Duration,
+        last_non_performance_target_value: Option<AbsoluteValue>,
+    ) -> MappingData {
         MappingData {
             mapping_id: self.core.id,
             group_id: self.core.group_id,
-            last_non_performance_target_value: self.last_non_performance_target_value(),
+            last_non_performance_target_value,
+            rel_time,
         }
     }
 
+    fn last_non_performance_target_values(&self) -> LastNonPerformanceTargetValues {
+        std::iter::once(self.last_non_performance_target_value())
+            .chain(
+                self.additional_targets
+                    .iter()
+                    .map(|t| t.last_non_performance_target_value()),
+            )
+            .collect()
+    }
+
     #[must_use]
     pub fn control_from_target_directly(
         &mut self,
@@ -806,8 +1238,8 @@ impl MainMapping {
             context,
             logger,
             processor_context,
-            false,
-            |_, _, _, _| {
+            TargetControlOptions::default(),
+            |_, _, _, _, _| {
                 Some(ModeControlResult::hit_target(ControlValue::from_absolute(
                     value,
                 )))
@@ -815,6 +1247,38 @@ impl MainMapping {
         )
     }
 
+    /// Sets the main target and each additional target to the value it had when the mapping was
+    /// initialized.
+    #[must_use]
+    pub fn control_from_initial_target_values(
+        &mut self,
+        context: ControlContext,
+        logger: &slog::Logger,
+        processor_context: ExtendedProcessorContext,
+    ) -> MappingControlResult {
+        let result = self.control_internal(
+            ControlOptions::default(),
+            context,
+            logger,
+            processor_context,
+            TargetControlOptions::default(),
+            |_, _, _, _, initial_target_value| {
+                Some(ModeControlResult::hit_target(ControlValue::from_absolute(
+                    initial_target_value?,
+                )))
+            },
+        );
+        if result.successful {
+            self.last_non_performance_target_value
+                .set(self.initial_target_value);
+            for t in &self.additional_targets {
+                t.last_non_performance_target_value
+                    .set(t.initial_target_value);
+            }
+        }
+        result
+    }
+
     #[must_use]
     fn control_internal(
         &mut self,
@@ -822,14 +1286,17 @@ impl MainMapping {
         context: ControlContext,
         logger: &slog::Logger,
         processor_context: ExtendedProcessorContext,
-        is_polling: bool,
+        target_control_options: TargetControlOptions,
+        // The last argument is the initial value of the target which is being controlled.
         get_mode_control_result: impl Fn(
             ControlOptions,
             MappingControlContext,
             &mut Mode,
             &ReaperTarget,
+            Option<AbsoluteValue>,
         ) -> Option<ModeControlResult<ControlValue>>,
     ) -> MappingControlResult {
+        let is_polling = target_control_options.is_polling;
         let mut send_manual_feedback_because_of_target = false;
         let mut at_least_one_relevant_target_exists = false;
         let mut at_least_one_target_was_reached = false;
@@ -844,63 +1311,93 @@ impl MainMapping {
         } else {
             vec![]
         };
-        let ctx = MappingControlContext {
-            control_context: context,
-            mapping_data: self.data(),
+        let last_non_performance_target_values = target_control_options
+            .last_non_performance_target_values
+            .unwrap_or_else(|| self.last_non_performance_target_values());
+        let y_last = |index: usize| {
+            last_non_performance_target_values
+                .get(index)
+                .copied()
+                .flatten()
         };
+        let main_target_data = self.data(target_control_options.rel_time, y_last(0));
         let actual_targets = if options.enforce_target_refresh {
             &mut fresh_targets
         } else {
             &mut self.targets
         };
-        for target in actual_targets {
-            let target = if let CompoundMappingTarget::Reaper(t) = target {
-                t
-            } else {
-                continue;
+        // Each additional target is controlled with its own mode and its own y_last.
+        let additional_targets = self
+            .additional_targets
+            .iter_mut()
+            .enumerate()
+            .map(|(i, t)| {
+                let data = MappingData {
+                    last_non_performance_target_value: y_last(i + 1),
+                    ..main_target_data
+                };
+                (&mut t.mode, &mut t.targets, data, t.initial_target_value)
+            });
+        let targets_with_mode = std::iter::once((
+            &mut self.core.mode,
+            actual_targets,
+            main_target_data,
+            self.initial_target_value,
+        ))
+        .chain(additional_targets);
+        for (mode, targets, mapping_data, initial_target_value) in targets_with_mode {
+            let ctx = MappingControlContext {
+                control_context: context,
+                mapping_data,
             };
-            at_least_one_relevant_target_exists = true;
-            match get_mode_control_result(options, ctx, &mut self.core.mode, target) {
-                None => {
-                    // The incoming source value doesn't reach the target because the source value
-                    // was filtered out. If `send_feedback_after_control` is enabled, we
-                    // still send feedback - this can be useful with controllers which insist on
-                    // controlling the LED on their own. The feedback sent by ReaLearn
-                    // will fix this self-controlled LED state.
-                }
-                Some(HitTarget { value }) => {
-                    at_least_one_target_was_reached = true;
-                    if !is_polling {
-                        self.core.time_of_last_control = Some(Instant::now());
+            for target in targets.iter_mut() {
+                let target = if let CompoundMappingTarget::Reaper(t) = target {
+                    t
+                } else {
+                    continue;
+                };
+                at_least_one_relevant_target_exists = true;
+                match get_mode_control_result(options, ctx, mode, target, initial_target_value) {
+                    None => {
+                        // The incoming source value doesn't reach the target because the source
+                        // value was filtered out. If `send_feedback_after_control` is enabled, we
+                        // still send feedback - this can be useful with controllers which insist
+                        // on controlling the LED on their own. The feedback sent by ReaLearn
+                        // will fix this self-controlled LED state.
                     }
-                    // Be graceful here.
-                    match target.hit(value, ctx) {
-                        // TODO-low For now, the first hit instruction wins (at the moment we don't
-                        // have multi-targets in which multiple targets send hit instructions
-                        // anyway).
-                        Ok(hi) => {
-                            if hit_instruction.is_none() {
-                                hit_instruction = hi;
+                    Some(HitTarget { value }) => {
+                        at_least_one_target_was_reached = true;
+                        if !is_polling {
+                            self.core.time_of_last_control = Some(Instant::now());
+                        }
+                        // Be graceful here.
+                        match target.hit(value, ctx) {
+                            // TODO-low For now, the first hit instruction wins.
+                            Ok(hi) => {
+                                if hit_instruction.is_none() {
+                                    hit_instruction = hi;
+                                }
                             }
+                            Err(msg) => slog::debug!(logger, "Control failed: {}", msg),
+                        }
+                        if should_send_manual_feedback_due_to_target(
+                            target,
+                            &self.core.options,
+                            &self.activation_state,
+                            self.unresolved_target.as_ref(),
+                        ) {
+                            send_manual_feedback_because_of_target = true;
                         }
-                        Err(msg) => slog::debug!(logger, "Control failed: {}", msg),
                     }
-                    if should_send_manual_feedback_due_to_target(
-                        target,
-                        &self.core.options,
-                        &self.activation_state,
-                        self.unresolved_target.as_ref(),
-                    ) {
-                        send_manual_feedback_because_of_target = true;
+                    Some(LeaveTargetUntouched(_)) => {
+                        // The target already has the desired value.
+                        // If `send_feedback_after_control` is enabled, we still send feedback -
+                        // this can be useful with controllers which insist on controlling the LED
+                        // on their own. The feedback sent by ReaLearn will fix this
+                        // self-controlled LED state.
+                        at_least_one_target_was_reached = true;
                     }
                 }
-                Some(LeaveTargetUntouched(_)) => {
-                    // The target already has the desired value.
-                    // If `send_feedback_after_control` is enabled, we still send feedback - this
-                    // can be useful with controllers which insist on controlling the LED on their
-                    // own. The feedback sent by ReaLearn will fix this self-controlled LED state.
-                    at_least_one_target_was_reached = true;
-                }
             }
         }
         if send_manual_feedback_because_of_target {
@@ -1003,17 +1500,12 @@ impl MainMapping {
         //   form of feedback it sends, it just provides us with options and we can choose.
         // - This leaves us with asking the mode. That means the user needs to explicitly choose
         //   whether it wants numerical or textual feedback.
-        let feedback_value = if self.core.mode.wants_textual_feedback() {
-            let v = self
-                .core
-                .mode
-                .query_textual_feedback(&|key| get_prop_value(key, self, control_context));
+        let mode = self.feedback_mode();
+        let feedback_value = if mode.wants_textual_feedback() {
+            let v = mode.query_textual_feedback(&|key| get_prop_value(key, self, control_context));
             FeedbackValue::Textual(v)
         } else {
-            let style = self
-                .core
-                .mode
-                .feedback_style(&|key| get_prop_value(key, self, control_context));
+            let style = mode.feedback_style(&|key| get_prop_value(key, self, control_context));
             FeedbackValue::Numeric(NumericFeedbackValue::new(style, combined_target_value))
         };
         let source_feedback_is_okay = if self.core.options.feedback_send_behavior
@@ -1023,23 +1515,97 @@ impl MainMapping {
         } else {
             true
         };
-        self.feedback_given_target_value(
+        let destinations = FeedbackDestinations {
+            with_projection_feedback,
+            with_source_feedback: with_source_feedback && source_feedback_is_okay,
+        };
+        let mode_value = mode.feedback_with_options_detail(
             Cow::Owned(feedback_value),
-            FeedbackDestinations {
-                with_projection_feedback,
-                with_source_feedback: with_source_feedback && source_feedback_is_okay,
-            },
-        )
+            self.mode_feedback_options(),
+            Default::default(),
+        )?;
+        let animation = match &self.feedback_animation {
+            Some(a) if a.applies_to(&mode_value) => a,
+            _ => {
+                self.next_animation_frame.set(None);
+                return self.feedback_given_mode_value(mode_value, destinations);
+            }
+        };
+        if let Some(channel) = animation.native_midi_channel() {
+            // The controller animates the feedback itself.
+            self.next_animation_frame.set(None);
+            return self
+                .feedback_given_mode_value(mode_value, destinations)
+                .map(|v| v.with_midi_channel(channel));
+        }
+        let pos_in_cycle = animation.current_pos_in_cycle(
+            control_context
+                .processor_context
+                .project_or_current_project(),
+        );
+        self.next_animation_frame.set(Some(
+            Instant::now() + animation.frame_duration(pos_in_cycle),
+        ));
+        let animated_value = match mode_value.into_owned() {
+            FeedbackValue::Numeric(v) => animation.animate(v, pos_in_cycle),
+            _ => None,
+        };
+        let animated_value = match animated_value {
+            Some(v) => FeedbackValue::Numeric(v),
+            None => FeedbackValue::Off,
+        };
+        self.feedback_given_mode_value(Cow::Owned(animated_value), destinations)
+    }
+
+    /// Returns `true` if it's time to render the next frame of the feedback animation.
+    ///
+    /// Always returns `false` while the feedback is not animated.
+    pub fn animation_frame_is_due(&self, now: Instant) -> bool {
+        matches!(self.next_animation_frame.get(), Some(t) if now >= t)
+    }
+
+    /// Returns `true` if ReaLearn needs to render the feedback of this mapping over time.
+    pub fn has_animated_feedback(&self) -> bool {
+        match &self.feedback_animation {
+            None => false,
+            Some(a) => a.native_midi_channel().is_none(),
+        }
     }
 
     pub fn current_aggregated_target_value(
         &self,
         context: ControlContext,
     ) -> Option<AbsoluteValue> {
-        let values = self.targets.iter().map(|t| t.current_value(context));
+        let values = self
+            .feedback_targets()
+            .iter()
+            .map(|t| t.current_value(context));
         aggregate_target_values(values)
     }
 
+    /// Returns the additional target which is used for feedback, if any.
+    fn feedback_target(&self) -> Option<&AdditionalMappingTarget> {
+        self.additional_targets
+            .iter()
+            .find(|t| t.is_feedback_target)
+    }
+
+    /// Returns the resolved targets which are used for feedback.
+    pub fn feedback_targets(&self) -> &[CompoundMappingTarget] {
+        match self.feedback_target() {
+            None => &self.targets,
+            Some(t) => &t.targets,
+        }
+    }
+
+    /// Returns the mode which is used for feedback.
+    fn feedback_mode(&self) -> &Mode {
+        match self.feedback_target() {
+            None => &self.core.mode,
+            Some(t) => &t.mode,
+        }
+    }
+
     pub fn mode(&self) -> &Mode {
         &self.core.mode
     }
@@ -1058,18 +1624,21 @@ impl MainMapping {
         feedback_value: Cow<FeedbackValue>,
         destinations: FeedbackDestinations,
     ) -> Option<SpecificCompoundFeedbackValue> {
-        let options = ModeFeedbackOptions {
-            source_is_virtual: self.core.source.is_virtual(),
-            max_discrete_source_value: self.core.source.max_discrete_value(),
-        };
         let mode_value = self.core.mode.feedback_with_options_detail(
             feedback_value,
-            options,
+            self.mode_feedback_options(),
             Default::default(),
         )?;
         self.feedback_given_mode_value(mode_value, destinations)
     }
 
+    fn mode_feedback_options(&self) -> ModeFeedbackOptions {
+        ModeFeedbackOptions {
+            source_is_virtual: self.core.source.is_virtual(),
+            max_discrete_source_value: self.core.source.max_discrete_value(),
+        }
+    }
+
     fn feedback_given_mode_value(
         &self,
         mode_value: Cow<FeedbackValue>,
@@ -1150,9 +1719,9 @@ impl MainMapping {
     }
 
     /// Polls the source.
-    pub fn poll_source(&mut self) -> Option<ControlValue> {
+    pub fn poll_source(&mut self, project: Project) -> Option<ControlValue> {
         match &mut self.core.source {
-            CompoundMappingSource::Reaper(s) => s.poll(),
+            CompoundMappingSource::Reaper(s) => s.poll(project),
             _ => None,
         }
     }
@@ -1209,6 +1778,8 @@ pub struct RealTimeMapping {
     /// Is `Some` if user-provided target data is complete.
     target_category: Option<UnresolvedTargetCategory>,
     target_is_resolved: bool,
+    /// Additional targets are controlled in the main thread only.
+    pub has_additional_targets: bool,
     /// Is `Some` if virtual or this target needs to be processed in real-time.
     pub resolved_target: Option<RealTimeCompoundMappingTarget>,
     pub lifecycle_midi_data: LifecycleMidiData,
@@ -1361,6 +1932,10 @@ pub struct MappingCore {
     group_id: GroupId,
     pub source: CompoundMappingSource,
     pub mode: Mode,
+    /// Applied to incoming relative values before they reach the mode.
+    acceleration: Option<Acceleration>,
+    /// Applied to incoming button presses before they reach the mode.
+    press_gesture: Option<PressGestureDetector>,
     group_interaction: GroupInteraction,
     options: ProcessorMappingOptions,
     /// Used for preventing echo feedback.
@@ -1368,6 +1943,31 @@ pub struct MappingCore {
 }
 
 impl MappingCore {
+    pub fn accelerate(&mut self, evt: ControlEvent<ControlValue>) -> ControlEvent<ControlValue> {
+        match &mut self.acceleration {
+            None => evt,
+            Some(a) => a.accelerate(evt),
+        }
+    }
+
+    /// Returns `None` if the event should be filtered out because it doesn't complete a gesture.
+    pub fn detect_press_gesture(
+        &mut self,
+        evt: ControlEvent<ControlValue>,
+    ) -> Option<ControlEvent<ControlValue>> {
+        match &mut self.press_gesture {
+            None => Some(evt),
+            Some(d) => d.process(evt),
+        }
+    }
+
+    pub fn press_gesture_wants_to_be_polled(&self) -> bool {
+        self.press_gesture
+            .as_ref()
+            .map(|d| d.wants_to_be_polled())
+            .unwrap_or(false)
+    }
+
     fn is_echo(&self) -> bool {
         if let Some(t) = self.time_of_last_control {
             t.elapsed() <= MAX_ECHO_FEEDBACK_DELAY
@@ -1679,6 +2279,20 @@ impl SpecificCompoundFeedbackValue {
         };
         Some(val)
     }
+
+    /// Makes plain MIDI source feedback use the given channel instead.
+    pub fn with_midi_channel(self, channel: Channel) -> Self {
+        match self {
+            SpecificCompoundFeedbackValue::Real(RealFeedbackValue {
+                projection,
+                source: Some(source),
+            }) => SpecificCompoundFeedbackValue::Real(RealFeedbackValue {
+                projection,
+                source: Some(source.with_midi_channel(channel)),
+            }),
+            v => v,
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -1741,6 +2355,20 @@ impl SourceFeedbackValue {
             Osc(v) => Some(CompoundMappingSourceAddress::Osc(v.addr.clone())),
         }
     }
+
+    /// Makes plain MIDI messages use the given channel instead. Leaves everything else untouched.
+    pub fn with_midi_channel(self, channel: Channel) -> Self {
+        match self {
+            SourceFeedbackValue::Midi(MidiSourceValue::Plain(msg)) if msg.channel().is_some() => {
+                let (status_byte, data_byte_1, data_byte_2) = msg.to_bytes();
+                let status_byte = (status_byte & 0xf0) | channel.get();
+                let msg = RawShortMessage::from_bytes((status_byte, data_byte_1, data_byte_2))
+                    .unwrap_or(msg);
+                SourceFeedbackValue::Midi(MidiSourceValue::Plain(msg))
+            }
+            v => v,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -1786,6 +2414,14 @@ impl UnresolvedCompoundMappingTarget {
         }
     }
 
+    pub fn can_be_affected_by_parameters(&self) -> bool {
+        use UnresolvedCompoundMappingTarget::*;
+        match self {
+            Reaper(t) => t.can_be_affected_by_parameters(),
+            Virtual(_) => false,
+        }
+    }
+
     /// `None` means that no polling is necessary for feedback because we are notified via events.
     pub fn feedback_resolution(&self) -> Option<FeedbackResolution> {
         use UnresolvedCompoundMappingTarget::*;
@@ -2260,6 +2896,8 @@ fn match_partially(
     // TODO-medium If we want to support fire after timeout and turbo for mappings with
     //  virtual targets one day, we need to poll this in real-time processor and OSC
     //  processing, too!
+    let control_event = core.detect_press_gesture(control_event)?;
+    let control_event = core.accelerate(control_event);
     let res = core.mode.control_with_options(
         control_event,
         target,
@@ -2424,3 +3062,89 @@ pub enum ControlOutcome<T> {
     Consumed,
     Matched(T),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helgoboss_learn::DiscreteIncrement;
+
+    fn running() -> TimeAwareTransformationState {
+        TimeAwareTransformationState {
+            is_time_aware: true,
+            has_stopped: false,
+        }
+    }
+
+    fn absolute_event(timestamp: ControlEventTimestamp) -> ControlEvent<ControlValue> {
+        ControlEvent::new(
+            ControlValue::AbsoluteContinuous(UnitValue::new(0.5)),
+            timestamp,
+        )
+    }
+
+    #[test]
+    fn time_aware_control_lifecycle() {
+        // Given
+        let start = ControlEventTimestamp::now();
+        let stopped = TimeAwareTransformationState {
+            is_time_aware: true,
+            has_stopped: true,
+        };
+        // When
+        let tac = TimeAwareControl::start(
+            absolute_event(start),
+            Default::default(),
+            Default::default(),
+            true,
+            running(),
+        )
+        .expect("should start");
+        let rel_time = tac.rel_time(start + Duration::from_millis(250));
+        let continues_while_running = TimeAwareControl::continues_after_poll(true, running());
+        let continues_after_stop = TimeAwareControl::continues_after_poll(true, stopped);
+        let continues_after_filtered_value =
+            TimeAwareControl::continues_after_poll(false, running());
+        // Then
+        assert_eq!(rel_time, Duration::from_millis(250));
+        assert!(continues_while_running);
+        assert!(!continues_after_stop);
+        assert!(!continues_after_filtered_value);
+    }
+
+    #[test]
+    fn time_aware_control_does_not_start() {
+        // Given
+        let start = ControlEventTimestamp::now();
+        let relative_event = ControlEvent::new(
+            ControlValue::RelativeDiscrete(DiscreteIncrement::new(1)),
+            start,
+        );
+        let not_time_aware = TimeAwareTransformationState {
+            is_time_aware: false,
+            has_stopped: false,
+        };
+        let stopped_immediately = TimeAwareTransformationState {
+            is_time_aware: true,
+            has_stopped: true,
+        };
+        let start_with = |event, successful, state| {
+            TimeAwareControl::start(
+                event,
+                Default::default(),
+                Default::default(),
+                successful,
+                state,
+            )
+        };
+        // When
+        let relative = start_with(relative_event, true, running());
+        let unsuccessful = start_with(absolute_event(start), false, running());
+        let not_time_aware = start_with(absolute_event(start), true, not_time_aware);
+        let stopped_immediately = start_with(absolute_event(start), true, stopped_immediately);
+        // Then
+        assert!(relative.is_none());
+        assert!(unsuccessful.is_none());
+        assert!(not_time_aware.is_none());
+        assert!(stopped_immediately.is_none());
+    }
+}
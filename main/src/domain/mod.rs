@@ -22,6 +22,24 @@ pub use midi_source::*;
 mod eel_transformation;
 pub use eel_transformation::*;
 
+mod lua_transformation;
+pub use lua_transformation::*;
+
+mod flexible_transformation;
+pub use flexible_transformation::*;
+
+mod curve;
+pub use curve::*;
+
+mod feedback_animation;
+pub use feedback_animation::*;
+
+mod acceleration;
+pub use acceleration::*;
+
+mod press_gesture;
+pub use press_gesture::*;
+
 mod eel_midi_source_script;
 pub use eel_midi_source_script::*;
 
@@ -117,6 +135,9 @@ pub use accelerator::*;
 mod parameter;
 pub use parameter::*;
 
+mod param_value_source;
+pub use param_value_source::*;
+
 mod control_event;
 pub use control_event::*;
 
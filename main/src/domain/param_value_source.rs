@@ -0,0 +1,81 @@
+use crate::domain::{BackboneState, ParamSetting, PluginParamIndex, RawParamValue};
+use reaper_high::{Project, Reaper};
+use serde::{Deserialize, Serialize};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+
+/// Maximum length of extended state values which are read as parameter values.
+const EXT_STATE_BUFFER_SIZE: usize = 256;
+
+/// An external source which the value of a compartment parameter follows.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum ParamValueSource {
+    /// Project-specific extended state, containing the natural value as text.
+    ProjectExtState { section: String, key: String },
+    /// Global extended state, containing the natural value as text.
+    ExtState { section: String, key: String },
+    /// Parameter of another ReaLearn instance. The raw value is taken over as is.
+    InstanceParameter { session_id: String, index: u32 },
+}
+
+impl ParamValueSource {
+    /// Returns `None` if the source is not available or doesn't contain a valid value.
+    pub fn read_raw_value(
+        &self,
+        setting: &ParamSetting,
+        project: Project,
+    ) -> Option<RawParamValue> {
+        use ParamValueSource::*;
+        match self {
+            ProjectExtState { section, key } => {
+                let text = read_project_ext_state(project, section, key)?;
+                setting.parse_to_raw_value(&text).ok()
+            }
+            ExtState { section, key } => {
+                let text = read_ext_state(section, key)?;
+                setting.parse_to_raw_value(&text).ok()
+            }
+            InstanceParameter { session_id, index } => {
+                let index = PluginParamIndex::try_from(*index).ok()?;
+                let instance_state =
+                    BackboneState::get().find_instance_state_by_session_id(session_id)?;
+                let instance_state = instance_state.try_borrow().ok()?;
+                Some(instance_state.params().at(index).raw_value())
+            }
+        }
+    }
+}
+
+fn read_ext_state(section: &str, key: &str) -> Option<String> {
+    let section = CString::new(section).ok()?;
+    let key = CString::new(key).ok()?;
+    let reaper = Reaper::get().medium_reaper().low();
+    let value = unsafe { reaper.GetExtState(section.as_ptr(), key.as_ptr()) };
+    if value.is_null() {
+        return None;
+    }
+    let value = unsafe { CStr::from_ptr(value) };
+    Some(value.to_str().ok()?.to_owned())
+}
+
+fn read_project_ext_state(project: Project, section: &str, key: &str) -> Option<String> {
+    let section = CString::new(section).ok()?;
+    let key = CString::new(key).ok()?;
+    let reaper = Reaper::get().medium_reaper().low();
+    let mut buffer = [0 as c_char; EXT_STATE_BUFFER_SIZE];
+    let len = unsafe {
+        reaper.GetProjExtState(
+            project.raw().as_ptr(),
+            section.as_ptr(),
+            key.as_ptr(),
+            buffer.as_mut_ptr(),
+            EXT_STATE_BUFFER_SIZE as _,
+        )
+    };
+    if len <= 0 {
+        return None;
+    }
+    let value = unsafe { CStr::from_ptr(buffer.as_ptr()) };
+    Some(value.to_str().ok()?.to_owned())
+}
@@ -1,5 +1,5 @@
 use crate::base::default_util::is_default;
-use crate::domain::Compartment;
+use crate::domain::{Compartment, ParamValueSource};
 use derive_more::Display;
 use enum_map::EnumMap;
 use helgoboss_learn::UnitValue;
@@ -80,11 +80,45 @@ pub struct ParamSetting {
     pub value_count: Option<NonZeroU32>,
     #[serde(default, skip_serializing_if = "is_default")]
     pub value_labels: Vec<String>,
+    /// Natural value corresponding to the minimum raw value. Only used for continuous parameters.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub min: Option<f64>,
+    /// Natural value corresponding to the maximum raw value. Only used for continuous parameters.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub max: Option<f64>,
+    /// Displayed after the natural value.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub unit: String,
+    /// Natural value which is used when resetting the parameter.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub default_value: Option<f64>,
+    /// If set, the parameter value follows this source.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub value_source: Option<ParamValueSource>,
 }
 
 impl ParamSetting {
-    fn is_default(&self) -> bool {
-        self.key.is_none() && self.name.is_empty() && self.value_count.is_none()
+    pub fn is_default(&self) -> bool {
+        self.key.is_none()
+            && self.name.is_empty()
+            && self.value_count.is_none()
+            && self.min.is_none()
+            && self.max.is_none()
+            && self.unit.is_empty()
+            && self.default_value.is_none()
+            && self.value_source.is_none()
+    }
+
+    /// Returns the custom range of natural values if a valid one has been defined.
+    pub fn range(&self) -> Option<(f64, f64)> {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if min < max => Some((min, max)),
+            _ => None,
+        }
+    }
+
+    fn effective_range(&self) -> (f64, f64) {
+        self.range().unwrap_or((0.0, 1.0))
     }
 
     pub fn discrete_values(&self) -> Option<impl Iterator<Item = Cow<str>> + '_> {
@@ -119,25 +153,38 @@ impl ParamSetting {
             let scaled = raw_value.get() * (value_count.get() - 1) as f64;
             EffectiveParamValue::Discrete(scaled.round() as u32)
         } else {
-            EffectiveParamValue::Continuous(raw_value.get())
+            let (min, max) = self.effective_range();
+            EffectiveParamValue::Continuous(min + raw_value.get() * (max - min))
         }
     }
 
-    fn convert_to_raw_value(&self, effective_value: f64) -> RawParamValue {
+    /// Converts the given natural value to a raw value.
+    pub fn convert_to_raw_value(&self, effective_value: f64) -> RawParamValue {
         let raw_value = if let Some(value_count) = self.value_count {
             effective_value / (value_count.get() - 1) as f64
         } else {
-            effective_value
+            let (min, max) = self.effective_range();
+            (effective_value - min) / (max - min)
         };
         UnitValue::new_clamped(raw_value).get() as RawParamValue
     }
 
+    /// Returns the raw value which should be used when resetting the parameter.
+    pub fn default_raw_value(&self) -> RawParamValue {
+        match self.default_value {
+            None => 0.0,
+            Some(v) => self.convert_to_raw_value(v),
+        }
+    }
+
     fn find_label_for_value(&self, value: u32) -> Option<&str> {
         self.value_labels.get(value as usize).map(|s| s.as_str())
     }
 
     /// Attempts to parse the given text to a raw parameter value.
     pub fn parse_to_raw_value(&self, text: &str) -> Result<RawParamValue, &'static str> {
+        let text = text.trim();
+        let text = text.strip_suffix(self.unit.as_str()).unwrap_or(text).trim();
         let effective_value: f64 = text.parse().map_err(|_| "couldn't parse as number")?;
         Ok(self.convert_to_raw_value(effective_value))
     }
@@ -156,7 +203,11 @@ impl<'a> Display for SettingAndValue<'a> {
                 return label.fmt(f);
             }
         }
-        effective_value.fmt(f)
+        effective_value.fmt(f)?;
+        if !self.setting.unit.is_empty() {
+            write!(f, " {}", self.setting.unit)?;
+        }
+        Ok(())
     }
 }
 
@@ -178,6 +229,27 @@ impl Param {
         self.setting.convert_to_value(self.value)
     }
 
+    /// Returns the index of the bank/program which is currently selected via this parameter.
+    ///
+    /// Returns `None` if the natural value is negative, in which case no bank is selected.
+    pub fn program_index(&self) -> Option<u32> {
+        match self.effective_value() {
+            EffectiveParamValue::Continuous(v) => {
+                let index = if self.setting.range().is_some() {
+                    v.round()
+                } else {
+                    // If no count given for the parameter, we just assume a count of 100.
+                    (v * 99.0).round()
+                };
+                if index < 0.0 {
+                    return None;
+                }
+                Some(index as u32)
+            }
+            EffectiveParamValue::Discrete(v) => Some(v),
+        }
+    }
+
     /// Returns the setting of this parameter.
     pub fn setting(&self) -> &ParamSetting {
         &self.setting
@@ -393,3 +465,106 @@ impl TryFrom<u32> for CompartmentParamIndex {
         Ok(Self(value))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db_setting() -> ParamSetting {
+        ParamSetting {
+            min: Some(-24.0),
+            max: Some(24.0),
+            unit: "dB".to_owned(),
+            default_value: Some(-12.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn convert_to_value_with_range() {
+        // Given
+        let setting = db_setting();
+        // When
+        let min_value = setting.convert_to_value(0.0);
+        let center_value = setting.convert_to_value(0.5);
+        let value = setting.convert_to_value(0.75);
+        // Then
+        assert_eq!(min_value, EffectiveParamValue::Continuous(-24.0));
+        assert_eq!(center_value, EffectiveParamValue::Continuous(0.0));
+        assert_eq!(value, EffectiveParamValue::Continuous(12.0));
+    }
+
+    #[test]
+    fn convert_to_raw_value() {
+        // Given
+        let setting = db_setting();
+        let discrete_setting = ParamSetting {
+            value_count: NonZeroU32::new(5),
+            ..Default::default()
+        };
+        // When
+        let raw_value = setting.convert_to_raw_value(12.0);
+        let out_of_range_raw_value = setting.convert_to_raw_value(-30.0);
+        let discrete_raw_value = discrete_setting.convert_to_raw_value(2.0);
+        // Then
+        assert_eq!(raw_value, 0.75);
+        assert_eq!(out_of_range_raw_value, 0.0);
+        assert_eq!(discrete_raw_value, 0.5);
+    }
+
+    #[test]
+    fn convert_to_raw_value_ignores_invalid_range() {
+        // Given
+        let setting = ParamSetting {
+            min: Some(10.0),
+            max: Some(10.0),
+            ..Default::default()
+        };
+        // When
+        let raw_value = setting.convert_to_raw_value(0.25);
+        // Then
+        assert_eq!(raw_value, 0.25);
+    }
+
+    #[test]
+    fn parse_to_raw_value_with_unit_suffix() {
+        // Given
+        let setting = db_setting();
+        // When
+        let with_space = setting.parse_to_raw_value("12 dB");
+        let without_space = setting.parse_to_raw_value("12dB");
+        let without_unit = setting.parse_to_raw_value(" -24 ");
+        let invalid = setting.parse_to_raw_value("loud dB");
+        // Then
+        assert_eq!(with_space, Ok(0.75));
+        assert_eq!(without_space, Ok(0.75));
+        assert_eq!(without_unit, Ok(0.0));
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn default_raw_value() {
+        // Given
+        let setting = db_setting();
+        let setting_without_default = ParamSetting::default();
+        // When
+        let raw_value = setting.default_raw_value();
+        let raw_value_without_default = setting_without_default.default_raw_value();
+        // Then
+        assert_eq!(raw_value, 0.25);
+        assert_eq!(raw_value_without_default, 0.0);
+    }
+
+    #[test]
+    fn program_index_with_range() {
+        // Given
+        let negative_param = Param::new(db_setting(), 0.25);
+        let positive_param = Param::new(db_setting(), 0.75);
+        // When
+        let negative_index = negative_param.program_index();
+        let positive_index = positive_param.program_index();
+        // Then
+        assert_eq!(negative_index, None);
+        assert_eq!(positive_index, Some(12));
+    }
+}
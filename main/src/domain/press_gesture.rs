@@ -0,0 +1,225 @@
+use crate::domain::{ControlEvent, ControlEventTimestamp};
+use helgoboss_learn::{ControlValue, UnitValue};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Button gesture which is detected in addition to the fire modes provided by the mode.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum PressGesture {
+    /// Fires only the press which completes a series of `n` presses and the release which follows
+    /// it.
+    #[serde(rename_all = "camelCase")]
+    OnNthPress {
+        n: u32,
+        /// Maximum time between two consecutive presses of the series.
+        max_interval_millis: u64,
+    },
+    /// Fires `hold_value` as soon as the button has been held for `timeout_millis` and
+    /// `release_value` when it's released after that. Short presses are ignored.
+    #[serde(rename_all = "camelCase")]
+    PressAndHold {
+        timeout_millis: u64,
+        hold_value: f64,
+        release_value: f64,
+    },
+}
+
+/// Detects a press gesture by looking at incoming button presses and releases.
+#[derive(Clone, Debug)]
+pub struct PressGestureDetector {
+    gesture: PressGesture,
+    press_count: u32,
+    last_press: Option<ControlEventTimestamp>,
+    /// Whether the current press has fired, so the following release must be passed on as well.
+    fired: bool,
+}
+
+impl PressGestureDetector {
+    pub fn new(gesture: PressGesture) -> Self {
+        Self {
+            gesture,
+            press_count: 0,
+            last_press: None,
+            fired: false,
+        }
+    }
+
+    /// Returns whether the detector needs to be polled in order to fire after a timeout.
+    pub fn wants_to_be_polled(&self) -> bool {
+        matches!(self.gesture, PressGesture::PressAndHold { .. })
+    }
+
+    /// Returns the event which should be passed on to the mode or `None` if it should be
+    /// filtered out.
+    ///
+    /// Relative values are passed on unchanged.
+    pub fn process(
+        &mut self,
+        evt: ControlEvent<ControlValue>,
+    ) -> Option<ControlEvent<ControlValue>> {
+        let is_press = match evt.payload() {
+            ControlValue::AbsoluteContinuous(_) | ControlValue::AbsoluteDiscrete(_) => {
+                evt.payload().is_on()
+            }
+            _ => return Some(evt),
+        };
+        match self.gesture {
+            PressGesture::OnNthPress {
+                n,
+                max_interval_millis,
+            } => {
+                if !is_press {
+                    if !self.fired {
+                        return None;
+                    }
+                    self.fired = false;
+                    return Some(evt);
+                }
+                let max_interval = Duration::from_millis(max_interval_millis);
+                let continues_series = self
+                    .last_press
+                    .map(|t| evt.timestamp() - t <= max_interval)
+                    .unwrap_or(false);
+                self.press_count = if continues_series {
+                    self.press_count + 1
+                } else {
+                    1
+                };
+                self.last_press = Some(evt.timestamp());
+                if self.press_count < n {
+                    return None;
+                }
+                self.press_count = 0;
+                self.last_press = None;
+                self.fired = true;
+                Some(evt)
+            }
+            PressGesture::PressAndHold { release_value, .. } => {
+                if is_press {
+                    self.last_press = Some(evt.timestamp());
+                    self.fired = false;
+                    return None;
+                }
+                self.last_press = None;
+                if !self.fired {
+                    return None;
+                }
+                self.fired = false;
+                Some(evt.with_payload(unit_control_value(release_value)))
+            }
+        }
+    }
+
+    /// Returns the hold event as soon as the button has been held long enough.
+    pub fn poll(&mut self, timestamp: ControlEventTimestamp) -> Option<ControlEvent<ControlValue>> {
+        let (timeout_millis, hold_value) = match self.gesture {
+            PressGesture::PressAndHold {
+                timeout_millis,
+                hold_value,
+                ..
+            } => (timeout_millis, hold_value),
+            PressGesture::OnNthPress { .. } => return None,
+        };
+        let pressed_at = self.last_press?;
+        if self.fired || timestamp - pressed_at < Duration::from_millis(timeout_millis) {
+            return None;
+        }
+        self.fired = true;
+        Some(ControlEvent::new(unit_control_value(hold_value), timestamp))
+    }
+}
+
+fn unit_control_value(value: f64) -> ControlValue {
+    ControlValue::AbsoluteContinuous(UnitValue::new_clamped(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event_at(
+        start: ControlEventTimestamp,
+        value: UnitValue,
+        millis: u64,
+    ) -> ControlEvent<ControlValue> {
+        ControlEvent::new(
+            ControlValue::AbsoluteContinuous(value),
+            start + Duration::from_millis(millis),
+        )
+    }
+
+    #[test]
+    fn on_nth_press() {
+        // Given
+        let mut detector = PressGestureDetector::new(PressGesture::OnNthPress {
+            n: 3,
+            max_interval_millis: 300,
+        });
+        let start = ControlEventTimestamp::now();
+        let press = |millis| event_at(start, UnitValue::MAX, millis);
+        // When
+        let first = detector.process(press(0));
+        let second = detector.process(press(200));
+        // Too slow, starts a new series
+        let third_too_slow = detector.process(press(600));
+        let release = detector.process(event_at(start, UnitValue::MIN, 700));
+        let fourth = detector.process(press(800));
+        let fifth = detector.process(press(1000));
+        let release_after_series = detector.process(event_at(start, UnitValue::MIN, 1100));
+        let next_press = detector.process(press(1200));
+        let release_within_next_series = detector.process(event_at(start, UnitValue::MIN, 1300));
+        // Then
+        assert!(first.is_none());
+        assert!(second.is_none());
+        assert!(third_too_slow.is_none());
+        assert!(release.is_none());
+        assert!(fourth.is_none());
+        assert!(fifth.is_some());
+        assert_eq!(
+            release_after_series.map(|e| e.payload()),
+            Some(ControlValue::AbsoluteContinuous(UnitValue::MIN))
+        );
+        assert!(next_press.is_none());
+        assert!(release_within_next_series.is_none());
+    }
+
+    #[test]
+    fn press_and_hold() {
+        // Given
+        let mut detector = PressGestureDetector::new(PressGesture::PressAndHold {
+            timeout_millis: 500,
+            hold_value: 0.8,
+            release_value: 0.2,
+        });
+        let start = ControlEventTimestamp::now();
+        let press = |millis| event_at(start, UnitValue::MAX, millis);
+        let release = |millis| event_at(start, UnitValue::MIN, millis);
+        // When
+        let short_press = detector.process(press(0));
+        let short_press_poll = detector.poll(start + Duration::from_millis(300));
+        let short_release = detector.process(release(400));
+        let late_poll = detector.poll(start + Duration::from_millis(1000));
+        let long_press = detector.process(press(1000));
+        let early_poll = detector.poll(start + Duration::from_millis(1400));
+        let hold = detector.poll(start + Duration::from_millis(1500));
+        let repeated_hold = detector.poll(start + Duration::from_millis(1600));
+        let long_release = detector.process(release(2000));
+        // Then
+        assert!(short_press.is_none());
+        assert!(short_press_poll.is_none());
+        assert!(short_release.is_none());
+        assert!(late_poll.is_none());
+        assert!(long_press.is_none());
+        assert!(early_poll.is_none());
+        assert_eq!(
+            hold.map(|e| e.payload()),
+            Some(ControlValue::AbsoluteContinuous(UnitValue::new(0.8)))
+        );
+        assert!(repeated_hold.is_none());
+        assert_eq!(
+            long_release.map(|e| e.payload()),
+            Some(ControlValue::AbsoluteContinuous(UnitValue::new(0.2)))
+        );
+    }
+}
@@ -1475,9 +1475,20 @@ fn process_real_mapping(
     if let Some(RealTimeCompoundMappingTarget::Reaper(reaper_target)) =
         mapping.resolved_target.as_mut()
     {
-        if reaper_target.wants_real_time_control(caller) {
+        // Press gestures which fire after a timeout need polling, which only the main processor
+        // does. Additional targets are controlled by the main processor only, so it must control
+        // the main target as well.
+        if reaper_target.wants_real_time_control(caller)
+            && !mapping.core.press_gesture_wants_to_be_polled()
+            && !mapping.has_additional_targets
+        {
             // Try to process directly here in real-time.
             let control_context = RealTimeControlContext { clip_matrix };
+            let pure_control_event = mapping
+                .core
+                .detect_press_gesture(pure_control_event)
+                .ok_or("press gesture not completed")?;
+            let pure_control_event = mapping.core.accelerate(pure_control_event);
             let control_value: Option<ControlValue> = mapping
                 .core
                 .mode
@@ -10,18 +10,19 @@ use crate::domain::{
     OrderedMappingMap, OscFeedbackTask, ProcessorContext, RealTimeReaperTarget, ReaperTarget,
     SharedInstanceState, Tag, TagScope, TargetCharacter, TrackExclusivity, ACTION_TARGET,
     ALL_TRACK_FX_ENABLE_TARGET, ANY_ON_TARGET, AUTOMATION_MODE_OVERRIDE_TARGET, CLIP_COLUMN_TARGET,
-    CLIP_MANAGEMENT_TARGET, CLIP_MATRIX_TARGET, CLIP_ROW_TARGET, CLIP_SEEK_TARGET,
-    CLIP_TRANSPORT_TARGET, CLIP_VOLUME_TARGET, ENABLE_INSTANCES_TARGET, ENABLE_MAPPINGS_TARGET,
-    FX_ENABLE_TARGET, FX_NAVIGATE_TARGET, FX_ONLINE_TARGET, FX_OPEN_TARGET, FX_PARAMETER_TARGET,
-    FX_PARAMETER_TOUCH_STATE_TARGET, FX_PRESET_TARGET, GO_TO_BOOKMARK_TARGET,
-    LOAD_FX_SNAPSHOT_TARGET, LOAD_MAPPING_SNAPSHOT_TARGET, MIDI_SEND_TARGET,
-    NAVIGATE_WITHIN_GROUP_TARGET, OSC_SEND_TARGET, PLAYRATE_TARGET, ROUTE_AUTOMATION_MODE_TARGET,
-    ROUTE_MONO_TARGET, ROUTE_MUTE_TARGET, ROUTE_PAN_TARGET, ROUTE_PHASE_TARGET,
-    ROUTE_TOUCH_STATE_TARGET, ROUTE_VOLUME_TARGET, SEEK_TARGET, SELECTED_TRACK_TARGET,
-    TEMPO_TARGET, TRACK_ARM_TARGET, TRACK_AUTOMATION_MODE_TARGET, TRACK_MONITORING_MODE_TARGET,
-    TRACK_MUTE_TARGET, TRACK_PAN_TARGET, TRACK_PEAK_TARGET, TRACK_PHASE_TARGET,
-    TRACK_SELECTION_TARGET, TRACK_SHOW_TARGET, TRACK_SOLO_TARGET, TRACK_TOOL_TARGET,
-    TRACK_TOUCH_STATE_TARGET, TRACK_VOLUME_TARGET, TRACK_WIDTH_TARGET, TRANSPORT_TARGET,
+    CLIP_MANAGEMENT_TARGET, CLIP_MATRIX_TARGET, CLIP_MIDI_VELOCITY_TARGET, CLIP_PITCH_TARGET,
+    CLIP_ROW_TARGET, CLIP_SEEK_TARGET, CLIP_TRANSPORT_TARGET, CLIP_VOLUME_TARGET,
+    ENABLE_INSTANCES_TARGET, ENABLE_MAPPINGS_TARGET, FX_ENABLE_TARGET, FX_NAVIGATE_TARGET,
+    FX_ONLINE_TARGET, FX_OPEN_TARGET, FX_PARAMETER_TARGET, FX_PARAMETER_TOUCH_STATE_TARGET,
+    FX_PRESET_TARGET, GO_TO_BOOKMARK_TARGET, LOAD_FX_SNAPSHOT_TARGET, LOAD_MAPPING_SNAPSHOT_TARGET,
+    MIDI_SEND_TARGET, NAVIGATE_WITHIN_GROUP_TARGET, OSC_SEND_TARGET, PLAYRATE_TARGET,
+    ROUTE_AUTOMATION_MODE_TARGET, ROUTE_MONO_TARGET, ROUTE_MUTE_TARGET, ROUTE_PAN_TARGET,
+    ROUTE_PHASE_TARGET, ROUTE_TOUCH_STATE_TARGET, ROUTE_VOLUME_TARGET, SEEK_TARGET,
+    SELECTED_TRACK_TARGET, TEMPO_TARGET, TRACK_ARM_TARGET, TRACK_AUTOMATION_MODE_TARGET,
+    TRACK_MONITORING_MODE_TARGET, TRACK_MUTE_TARGET, TRACK_PAN_TARGET, TRACK_PEAK_TARGET,
+    TRACK_PHASE_TARGET, TRACK_SELECTION_TARGET, TRACK_SHOW_TARGET, TRACK_SOLO_TARGET,
+    TRACK_TOOL_TARGET, TRACK_TOUCH_STATE_TARGET, TRACK_VOLUME_TARGET, TRACK_WIDTH_TARGET,
+    TRANSPORT_TARGET,
 };
 use enum_dispatch::enum_dispatch;
 use enum_iterator::IntoEnumIterator;
@@ -40,6 +41,7 @@ use std::borrow::Cow;
 use std::collections::HashSet;
 use std::convert::TryInto;
 use std::fmt::{Debug, Display, Formatter};
+use std::time::Duration;
 
 #[enum_dispatch(ReaperTarget)]
 pub trait RealearnTarget {
@@ -433,6 +435,7 @@ impl<'a> TransformationInputProvider<AdditionalEelTransformationInput>
                 .last_non_performance_target_value
                 .map(|v| v.to_unit_value().get())
                 .unwrap_or_default(),
+            rel_time: self.mapping_data.rel_time,
         }
     }
 }
@@ -448,6 +451,10 @@ pub struct MappingData {
     pub mapping_id: MappingId,
     pub group_id: GroupId,
     pub last_non_performance_target_value: Option<AbsoluteValue>,
+    /// Time elapsed since the control event which is currently being processed.
+    ///
+    /// Only non-zero when re-invoking time-aware control transformations.
+    pub rel_time: Duration,
 }
 
 pub type HitInstructionReturnValue = Option<Box<dyn HitInstruction>>;
@@ -547,6 +554,8 @@ pub enum ReaperTargetType {
     ClipTransport = 31,
     ClipSeek = 32,
     ClipVolume = 33,
+    ClipPitch = 53,
+    ClipMidiVelocity = 54,
 
     // Clip column targets
     ClipColumn = 50,
@@ -655,6 +664,8 @@ impl ReaperTargetType {
             ClipRow => &CLIP_ROW_TARGET,
             ClipSeek => &CLIP_SEEK_TARGET,
             ClipVolume => &CLIP_VOLUME_TARGET,
+            ClipPitch => &CLIP_PITCH_TARGET,
+            ClipMidiVelocity => &CLIP_MIDI_VELOCITY_TARGET,
             ClipManagement => &CLIP_MANAGEMENT_TARGET,
             ClipMatrix => &CLIP_MATRIX_TARGET,
             SendMidi => &MIDI_SEND_TARGET,
@@ -4,17 +4,21 @@ use helgoboss_learn::{
     format_percentage_without_unit, parse_percentage_without_unit, ControlValue,
     DetailedSourceCharacter, SourceCharacter, UnitValue,
 };
+use reaper_high::{Project, Reaper};
 use reaper_medium::{MidiInputDeviceId, MidiOutputDeviceId};
 use std::collections::HashSet;
 use std::convert::TryInto;
+use std::f64::consts::PI;
 use std::fmt::{Display, Formatter};
 use std::time::{Duration, Instant};
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum ReaperSource {
     MidiDeviceChanges,
     RealearnInstanceStart,
     Timer(TimerSource),
+    Lfo(LfoSource),
+    StepSequencer(StepSequencerSource),
 }
 
 #[derive(Copy, Clone, PartialEq, Debug)]
@@ -51,10 +55,199 @@ impl TimerSource {
     }
 }
 
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum LfoShape {
+    Sine,
+    Triangle,
+    Saw,
+    Square,
+    RandomSampleAndHold,
+}
+
+impl LfoShape {
+    /// Returns the value of this shape at the given position within the cycle (0.0 to 1.0).
+    fn value_at(&self, cycle_index: i64, pos_in_cycle: f64) -> f64 {
+        use LfoShape::*;
+        match self {
+            Sine => (1.0 + (2.0 * PI * pos_in_cycle).sin()) / 2.0,
+            Triangle => 1.0 - (2.0 * pos_in_cycle - 1.0).abs(),
+            Saw => pos_in_cycle,
+            Square => {
+                if pos_in_cycle < 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            RandomSampleAndHold => pseudo_random_unit_value(cycle_index),
+        }
+    }
+}
+
+/// Speed of a modulation source.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ModulationRate {
+    /// Number of cycles (or steps) per second.
+    Frequency(f64),
+    /// Length of one cycle (or step) in beats, synced to the project position.
+    Beats(f64),
+}
+
+impl ModulationRate {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        let v = match *self {
+            ModulationRate::Frequency(hertz) => hertz,
+            ModulationRate::Beats(beats) => beats,
+        };
+        if !v.is_finite() || v <= 0.0 {
+            return Err("modulation rate must be a positive number");
+        }
+        Ok(())
+    }
+}
+
+/// Keeps track of the time since a modulation source was started.
+#[derive(Copy, Clone, PartialEq, Debug)]
+struct ModulationClock {
+    rate: ModulationRate,
+    start: Option<Instant>,
+}
+
+impl ModulationClock {
+    fn new(rate: ModulationRate) -> Self {
+        Self { rate, start: None }
+    }
+
+    /// Returns the number of elapsed cycles (or steps), including the fraction of the current one.
+    fn cycle_pos(&mut self, project: Project) -> f64 {
+        match self.rate {
+            ModulationRate::Frequency(hertz) => {
+                let now = Instant::now();
+                let start = *self.start.get_or_insert(now);
+                (now - start).as_secs_f64() * hertz
+            }
+            ModulationRate::Beats(beats) => play_position_in_beats(project) / beats,
+        }
+    }
+}
+
+/// Returns the current play position of the given project in beats.
+pub fn play_position_in_beats(project: Project) -> f64 {
+    let reaper = Reaper::get().medium_reaper();
+    let pos = reaper.get_play_position_2_ex(project.context());
+    let beat_info = reaper.time_map_2_time_to_beats(project.context(), pos);
+    beat_info.full_beats.get()
+}
+
+/// Low-frequency oscillator which emits a continuous value whenever it changes.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct LfoSource {
+    shape: LfoShape,
+    clock: ModulationClock,
+    phase: f64,
+    last_value: Option<UnitValue>,
+}
+
+impl LfoSource {
+    /// The phase must be between 0.0 and 1.0.
+    pub fn new(shape: LfoShape, rate: ModulationRate, phase: f64) -> Result<Self, &'static str> {
+        rate.validate()?;
+        if !(0.0..=1.0).contains(&phase) {
+            return Err("LFO phase must be between 0.0 and 1.0");
+        }
+        let source = Self {
+            shape,
+            clock: ModulationClock::new(rate),
+            phase,
+            last_value: None,
+        };
+        Ok(source)
+    }
+
+    pub fn poll(&mut self, project: Project) -> Option<ControlValue> {
+        let cycle_pos = self.clock.cycle_pos(project);
+        self.poll_at(cycle_pos)
+    }
+
+    /// Emits the value at the given number of elapsed cycles unless it's the same as last time.
+    fn poll_at(&mut self, cycle_pos: f64) -> Option<ControlValue> {
+        let cycle_pos = cycle_pos + self.phase;
+        let cycle_index = cycle_pos.floor();
+        let pos_in_cycle = cycle_pos - cycle_index;
+        let value = UnitValue::new_clamped(self.shape.value_at(cycle_index as i64, pos_in_cycle));
+        if self.last_value == Some(value) {
+            return None;
+        }
+        self.last_value = Some(value);
+        Some(ControlValue::AbsoluteContinuous(value))
+    }
+}
+
+/// Maximum number of steps of a step sequencer source.
+pub const MAX_STEP_SEQUENCER_STEP_COUNT: usize = 64;
+
+/// Step sequencer which emits the value of the current step whenever a new step begins.
+#[derive(Clone, PartialEq, Debug)]
+pub struct StepSequencerSource {
+    steps: Box<[UnitValue]>,
+    clock: ModulationClock,
+    last_step_index: Option<i64>,
+}
+
+impl StepSequencerSource {
+    /// Fails if there are more than [`MAX_STEP_SEQUENCER_STEP_COUNT`] steps.
+    pub fn new(steps: Vec<UnitValue>, rate: ModulationRate) -> Result<Self, &'static str> {
+        rate.validate()?;
+        if steps.len() > MAX_STEP_SEQUENCER_STEP_COUNT {
+            return Err("too many step sequencer steps");
+        }
+        let source = Self {
+            steps: steps.into_boxed_slice(),
+            clock: ModulationClock::new(rate),
+            last_step_index: None,
+        };
+        Ok(source)
+    }
+
+    pub fn poll(&mut self, project: Project) -> Option<ControlValue> {
+        let cycle_pos = self.clock.cycle_pos(project);
+        self.poll_at(cycle_pos)
+    }
+
+    /// Emits the value of the step at the given number of elapsed steps if it's a new step.
+    fn poll_at(&mut self, cycle_pos: f64) -> Option<ControlValue> {
+        if self.steps.is_empty() {
+            return None;
+        }
+        let step_index = cycle_pos.floor() as i64;
+        if self.last_step_index == Some(step_index) {
+            return None;
+        }
+        self.last_step_index = Some(step_index);
+        let value = self.steps[step_index.rem_euclid(self.steps.len() as i64) as usize];
+        Some(ControlValue::AbsoluteContinuous(value))
+    }
+}
+
+/// Returns a value between 0.0 and 1.0 which looks random but is always the same for one seed.
+///
+/// Good enough for "sample & hold" and makes it reproducible when synced to the project.
+fn pseudo_random_unit_value(seed: i64) -> f64 {
+    // SplitMix64
+    let mut z = (seed as u64).wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
 impl ReaperSource {
     /// If this returns `true`, the `poll` method should be called, on a regular basis.
     pub fn wants_to_be_polled(&self) -> bool {
-        matches!(self, ReaperSource::Timer(_))
+        matches!(
+            self,
+            ReaperSource::Timer(_) | ReaperSource::Lfo(_) | ReaperSource::StepSequencer(_)
+        )
     }
 
     pub fn possible_detailed_characters(&self) -> Vec<DetailedSourceCharacter> {
@@ -63,6 +256,7 @@ impl ReaperSource {
             MidiDeviceChanges => vec![DetailedSourceCharacter::MomentaryOnOffButton],
             RealearnInstanceStart => vec![DetailedSourceCharacter::MomentaryOnOffButton],
             Timer(_) => vec![DetailedSourceCharacter::PressOnlyButton],
+            Lfo(_) | StepSequencer(_) => vec![DetailedSourceCharacter::RangeControl],
         }
     }
 
@@ -76,14 +270,23 @@ impl ReaperSource {
     }
 
     pub fn character(&self) -> SourceCharacter {
-        SourceCharacter::MomentaryButton
+        use ReaperSource::*;
+        match self {
+            MidiDeviceChanges | RealearnInstanceStart | Timer(_) => {
+                SourceCharacter::MomentaryButton
+            }
+            Lfo(_) | StepSequencer(_) => SourceCharacter::RangeElement,
+        }
     }
 
-    pub fn poll(&mut self) -> Option<ControlValue> {
-        if let ReaperSource::Timer(t) = self {
-            t.poll()
-        } else {
-            None
+    /// The project is used for modulation sources which are synced to the project position.
+    pub fn poll(&mut self, project: Project) -> Option<ControlValue> {
+        use ReaperSource::*;
+        match self {
+            Timer(t) => t.poll(),
+            Lfo(s) => s.poll(project),
+            StepSequencer(s) => s.poll(project),
+            MidiDeviceChanges | RealearnInstanceStart => None,
         }
     }
 
@@ -133,3 +336,134 @@ impl Display for MidiDeviceChangePayload {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn lfo_shape_values() {
+        // Given
+        let positions = [0.0, 0.25, 0.5, 0.75];
+        // When
+        let sine = positions.map(|pos| LfoShape::Sine.value_at(0, pos));
+        let triangle = positions.map(|pos| LfoShape::Triangle.value_at(0, pos));
+        let saw = positions.map(|pos| LfoShape::Saw.value_at(0, pos));
+        let square = positions.map(|pos| LfoShape::Square.value_at(0, pos));
+        // Then
+        for (actual, expected) in sine.iter().zip([0.5, 1.0, 0.5, 0.0]) {
+            assert_abs_diff_eq!(*actual, expected, epsilon = 1e-9);
+        }
+        assert_eq!(triangle, [0.0, 0.5, 1.0, 0.5]);
+        assert_eq!(saw, [0.0, 0.25, 0.5, 0.75]);
+        assert_eq!(square, [1.0, 1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn lfo_shape_random_holds_value_within_cycle() {
+        // Given
+        let shape = LfoShape::RandomSampleAndHold;
+        // When
+        let first = shape.value_at(3, 0.1);
+        let second = shape.value_at(3, 0.9);
+        let next_cycle = shape.value_at(4, 0.1);
+        // Then
+        assert_eq!(first, second);
+        assert_ne!(first, next_cycle);
+    }
+
+    #[test]
+    fn pseudo_random_unit_value_is_reproducible_and_in_range() {
+        // Given
+        let seeds = -1000..1000;
+        // When
+        let values: Vec<_> = seeds.clone().map(pseudo_random_unit_value).collect();
+        // Then
+        assert!(values.iter().all(|v| (0.0..1.0).contains(v)));
+        assert_eq!(
+            values,
+            seeds.map(pseudo_random_unit_value).collect::<Vec<_>>()
+        );
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        assert_abs_diff_eq!(mean, 0.5, epsilon = 0.05);
+    }
+
+    #[test]
+    fn lfo_source_applies_phase_and_skips_unchanged_values() {
+        // Given
+        let mut source =
+            LfoSource::new(LfoShape::Saw, ModulationRate::Frequency(1.0), 0.25).unwrap();
+        // When
+        let first = source.poll_at(0.0);
+        let repeated = source.poll_at(0.0);
+        let next = source.poll_at(0.5);
+        // Then
+        assert_eq!(
+            first,
+            Some(ControlValue::AbsoluteContinuous(UnitValue::new(0.25)))
+        );
+        assert_eq!(repeated, None);
+        assert_eq!(
+            next,
+            Some(ControlValue::AbsoluteContinuous(UnitValue::new(0.75)))
+        );
+    }
+
+    #[test]
+    fn lfo_source_rejects_invalid_rate_and_phase() {
+        // Given
+        let shape = LfoShape::Sine;
+        // When
+        let negative_rate = LfoSource::new(shape, ModulationRate::Frequency(-1.0), 0.0);
+        let zero_beats = LfoSource::new(shape, ModulationRate::Beats(0.0), 0.0);
+        let nan_rate = LfoSource::new(shape, ModulationRate::Frequency(f64::NAN), 0.0);
+        let phase_too_high = LfoSource::new(shape, ModulationRate::Frequency(1.0), 1.5);
+        let nan_phase = LfoSource::new(shape, ModulationRate::Frequency(1.0), f64::NAN);
+        // Then
+        assert!(negative_rate.is_err());
+        assert!(zero_beats.is_err());
+        assert!(nan_rate.is_err());
+        assert!(phase_too_high.is_err());
+        assert!(nan_phase.is_err());
+    }
+
+    #[test]
+    fn step_sequencer_emits_each_new_step_and_wraps_around() {
+        // Given
+        let steps = vec![
+            UnitValue::new(0.1),
+            UnitValue::new(0.5),
+            UnitValue::new(0.9),
+        ];
+        let mut source = StepSequencerSource::new(steps, ModulationRate::Frequency(4.0)).unwrap();
+        // When
+        let values: Vec<_> = [0.0, 0.5, 1.2, 2.0, 3.7, 4.1]
+            .into_iter()
+            .map(|pos| source.poll_at(pos))
+            .collect();
+        // Then
+        let v = |v| Some(ControlValue::AbsoluteContinuous(UnitValue::new(v)));
+        assert_eq!(values, vec![v(0.1), None, v(0.5), v(0.9), v(0.1), v(0.5)]);
+    }
+
+    #[test]
+    fn step_sequencer_without_steps_emits_nothing() {
+        // Given
+        let mut source = StepSequencerSource::new(vec![], ModulationRate::Frequency(1.0)).unwrap();
+        // When
+        let value = source.poll_at(3.0);
+        // Then
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    fn step_sequencer_rejects_too_many_steps() {
+        // Given
+        let steps = vec![UnitValue::MIN; MAX_STEP_SEQUENCER_STEP_COUNT + 1];
+        // When
+        let result = StepSequencerSource::new(steps, ModulationRate::Frequency(1.0));
+        // Then
+        assert!(result.is_err());
+    }
+}
@@ -30,11 +30,12 @@ use crate::base::Global;
 use crate::domain::ui_util::convert_bool_to_unit_value;
 use crate::domain::{
     handle_exclusivity, ActionTarget, AllTrackFxEnableTarget, AutomationModeOverrideTarget, Caller,
-    ClipColumnTarget, ClipManagementTarget, ClipMatrixTarget, ClipRowTarget, ClipSeekTarget,
-    ClipTransportTarget, ClipVolumeTarget, ControlContext, FxEnableTarget, FxNavigateTarget,
-    FxOnlineTarget, FxOpenTarget, FxParameterTarget, FxParameterTouchStateTarget, FxPresetTarget,
-    GoToBookmarkTarget, HierarchyEntry, HierarchyEntryProvider, LoadFxSnapshotTarget,
-    MappingControlContext, MidiSendTarget, OscSendTarget, PlayrateTarget, RealTimeClipColumnTarget,
+    ClipColumnTarget, ClipManagementTarget, ClipMatrixTarget, ClipMidiVelocityTarget,
+    ClipPitchTarget, ClipRowTarget, ClipSeekTarget, ClipTransportTarget, ClipVolumeTarget,
+    ControlContext, FxEnableTarget, FxNavigateTarget, FxOnlineTarget, FxOpenTarget,
+    FxParameterTarget, FxParameterTouchStateTarget, FxPresetTarget, GoToBookmarkTarget,
+    HierarchyEntry, HierarchyEntryProvider, LoadFxSnapshotTarget, MappingControlContext,
+    MidiSendTarget, OscSendTarget, PlayrateTarget, RealTimeClipColumnTarget,
     RealTimeClipMatrixTarget, RealTimeClipRowTarget, RealTimeClipTransportTarget,
     RealTimeControlContext, RealTimeFxParameterTarget, RouteMuteTarget, RoutePanTarget,
     RouteTouchStateTarget, RouteVolumeTarget, SeekTarget, SelectedTrackTarget, TempoTarget,
@@ -133,6 +134,8 @@ pub enum ReaperTarget {
     ClipRow(ClipRowTarget),
     ClipSeek(ClipSeekTarget),
     ClipVolume(ClipVolumeTarget),
+    ClipPitch(ClipPitchTarget),
+    ClipMidiVelocity(ClipMidiVelocityTarget),
     ClipManagement(ClipManagementTarget),
     LoadMappingSnapshot(LoadMappingSnapshotTarget),
     EnableMappings(EnableMappingsTarget),
@@ -614,6 +617,8 @@ impl<'a> Target<'a> for ReaperTarget {
             ClipRow(t) => t.current_value(context),
             ClipSeek(t) => t.current_value(context),
             ClipVolume(t) => t.current_value(context),
+            ClipPitch(t) => t.current_value(context),
+            ClipMidiVelocity(t) => t.current_value(context),
             ClipManagement(t) => t.current_value(context),
             ClipMatrix(t) => t.current_value(context),
             LoadMappingSnapshot(t) => t.current_value(context),
@@ -1,13 +1,16 @@
 use crate::domain::{
     format_value_as_on_off, BackboneState, Compartment, CompoundChangeEvent, ControlContext,
     ExtendedProcessorContext, HitInstructionReturnValue, MappingControlContext,
-    RealTimeControlContext, RealTimeReaperTarget, RealearnTarget, ReaperTarget, ReaperTargetType,
-    TargetCharacter, TargetTypeDef, UnresolvedReaperTargetDef, VirtualClipColumn, DEFAULT_TARGET,
+    RealTimeControlContext, RealTimeReaperTarget, RealearnClipMatrix, RealearnTarget, ReaperTarget,
+    ReaperTargetType, TargetCharacter, TargetTypeDef, UnresolvedReaperTargetDef, VirtualClipColumn,
+    DEFAULT_TARGET,
 };
 use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target, UnitValue};
 use playtime_clip_engine::main::ClipMatrixEvent;
 use playtime_clip_engine::rt::{ClipChangeEvent, QualifiedClipChangeEvent};
 use realearn_api::persistence::ClipColumnAction;
+use reaper_high::{OrCurrentProject, Track};
+use reaper_medium::MasterTrackBehavior;
 use std::borrow::Cow;
 
 #[derive(Debug)]
@@ -54,16 +57,39 @@ impl RealearnTarget for ClipColumnTarget {
         value: ControlValue,
         context: MappingControlContext,
     ) -> Result<HitInstructionReturnValue, &'static str> {
-        BackboneState::get().with_clip_matrix(
+        if !value.is_on() {
+            return Ok(None);
+        }
+        BackboneState::get().with_clip_matrix_mut(
             context.control_context.instance_state,
             |matrix| -> Result<(), &'static str> {
                 match self.action {
                     ClipColumnAction::Stop => {
-                        if !value.is_on() {
-                            return Ok(());
-                        }
                         matrix.stop_column(self.column_index)?;
                     }
+                    ClipColumnAction::InsertColumnWithNewTrack => {
+                        matrix.insert_column(self.column_index, true)?;
+                    }
+                    ClipColumnAction::RemoveColumn => {
+                        matrix.remove_column(self.column_index)?;
+                    }
+                    ClipColumnAction::MoveColumnLeft => {
+                        if self.column_index == 0 {
+                            return Err("column is already the leftmost one");
+                        }
+                        matrix.move_column(self.column_index, self.column_index - 1)?;
+                    }
+                    ClipColumnAction::MoveColumnRight => {
+                        matrix.move_column(self.column_index, self.column_index + 1)?;
+                    }
+                    ClipColumnAction::UseSelectedTrackForPlayback => {
+                        let track = first_selected_track(matrix)?;
+                        matrix.set_column_playback_track(self.column_index, track)?;
+                    }
+                    ClipColumnAction::UseSelectedTrackForRecording => {
+                        let track = first_selected_track(matrix)?;
+                        matrix.set_column_recording_track(self.column_index, Some(&track))?;
+                    }
                 }
                 Ok(())
             },
@@ -91,6 +117,12 @@ impl RealearnTarget for ClipColumnTarget {
                 },
                 _ => (false, None),
             },
+            ClipColumnAction::InsertColumnWithNewTrack
+            | ClipColumnAction::RemoveColumn
+            | ClipColumnAction::MoveColumnLeft
+            | ClipColumnAction::MoveColumnRight
+            | ClipColumnAction::UseSelectedTrackForPlayback
+            | ClipColumnAction::UseSelectedTrackForRecording => (false, None),
         }
     }
 
@@ -116,18 +148,27 @@ impl RealearnTarget for ClipColumnTarget {
     fn is_available(&self, _: ControlContext) -> bool {
         true
     }
+
+    fn can_report_current_value(&self) -> bool {
+        matches!(self.action, ClipColumnAction::Stop)
+    }
 }
 
 impl<'a> Target<'a> for ClipColumnTarget {
     type Context = ControlContext<'a>;
 
     fn current_value(&self, context: ControlContext<'a>) -> Option<AbsoluteValue> {
-        let is_on = BackboneState::get()
-            .with_clip_matrix(context.instance_state, |matrix| match self.action {
-                ClipColumnAction::Stop => matrix.column_is_stoppable(self.column_index),
-            })
-            .ok()?;
-        Some(AbsoluteValue::from_bool(is_on))
+        match self.action {
+            ClipColumnAction::Stop => {
+                let is_on = BackboneState::get()
+                    .with_clip_matrix(context.instance_state, |matrix| {
+                        matrix.column_is_stoppable(self.column_index)
+                    })
+                    .ok()?;
+                Some(AbsoluteValue::from_bool(is_on))
+            }
+            _ => None,
+        }
     }
 
     fn control_type(&self, context: Self::Context) -> ControlType {
@@ -156,6 +197,7 @@ impl RealTimeClipColumnTarget {
                 let matrix = matrix.lock();
                 matrix.stop_column(self.column_index)
             }
+            _ => Err("only column stop is supported in real-time"),
         }
     }
 }
@@ -171,6 +213,7 @@ impl<'a> Target<'a> for RealTimeClipColumnTarget {
                 let is_stoppable = matrix.column_is_stoppable(self.column_index);
                 Some(AbsoluteValue::from_bool(is_stoppable))
             }
+            _ => None,
         }
     }
 
@@ -188,9 +231,23 @@ pub const CLIP_COLUMN_TARGET: TargetTypeDef = TargetTypeDef {
 fn control_type_and_character(action: ClipColumnAction) -> (ControlType, TargetCharacter) {
     use ClipColumnAction::*;
     match action {
-        Stop => (
+        Stop
+        | InsertColumnWithNewTrack
+        | RemoveColumn
+        | MoveColumnLeft
+        | MoveColumnRight
+        | UseSelectedTrackForPlayback
+        | UseSelectedTrackForRecording => (
             ControlType::AbsoluteContinuousRetriggerable,
             TargetCharacter::Trigger,
         ),
     }
 }
+
+fn first_selected_track(matrix: &RealearnClipMatrix) -> Result<Track, &'static str> {
+    matrix
+        .permanent_project()
+        .or_current_project()
+        .first_selected_track(MasterTrackBehavior::ExcludeMasterTrack)
+        .ok_or("no track selected")
+}
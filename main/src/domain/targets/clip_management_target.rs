@@ -5,9 +5,10 @@ use crate::domain::{
     ReaperTarget, ReaperTargetType, TargetCharacter, TargetTypeDef, UnresolvedReaperTargetDef,
     VirtualClipSlot, DEFAULT_TARGET,
 };
-use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, PropValue, Target};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, NumericValue, PropValue, Target};
+use playtime_api::persistence::ClipEnvelopeMode;
 use playtime_clip_engine::main::ClipSlotCoordinates;
-use realearn_api::persistence::ClipManagementAction;
+use realearn_api::persistence::{ClipDropMode, ClipManagementAction};
 
 #[derive(Debug)]
 pub struct UnresolvedClipManagementTarget {
@@ -56,11 +57,22 @@ impl RealearnTarget for ClipManagementTarget {
             A::ClearSlot
             | A::FillSlotWithSelectedItem
             | A::CopyOrPasteClip
-            | A::AdjustClipSectionLength(_) => (
+            | A::AdjustClipSectionLength(_)
+            | A::AdjustClipCanvasLength(_)
+            | A::MoveClipSectionInCanvas(_)
+            | A::DetectStartTimeAndTempo
+            | A::PickUpClip
+            | A::DropClip(_)
+            | A::CycleTakes
+            | A::DeleteTake
+            | A::SelectTake(_)
+            | A::KeepActiveTakeOnly => (
                 ControlType::AbsoluteContinuousRetriggerable,
                 TargetCharacter::Trigger,
             ),
-            A::EditClip => (ControlType::AbsoluteContinuous, TargetCharacter::Switch),
+            A::EditClip | A::WriteEnvelopes => {
+                (ControlType::AbsoluteContinuous, TargetCharacter::Switch)
+            }
         }
     }
 
@@ -97,6 +109,15 @@ impl RealearnTarget for ClipManagementTarget {
                 }
                 Ok(None)
             })?,
+            A::WriteEnvelopes => self.with_matrix(context, |matrix| {
+                let mode = if value.is_on() {
+                    ClipEnvelopeMode::Write
+                } else {
+                    ClipEnvelopeMode::Read
+                };
+                matrix.set_clip_envelope_mode(self.slot_coordinates, mode)?;
+                Ok(None)
+            })?,
             A::AdjustClipSectionLength(a) => {
                 if !value.is_on() {
                     return Ok(None);
@@ -106,6 +127,102 @@ impl RealearnTarget for ClipManagementTarget {
                     Ok(None)
                 })?
             }
+            A::AdjustClipCanvasLength(a) => {
+                if !value.is_on() {
+                    return Ok(None);
+                }
+                self.with_matrix(context, |matrix| {
+                    matrix.adjust_clip_canvas_length(self.slot_coordinates, a.factor)?;
+                    Ok(None)
+                })?
+            }
+            A::MoveClipSectionInCanvas(a) => {
+                if !value.is_on() {
+                    return Ok(None);
+                }
+                self.with_matrix(context, |matrix| {
+                    matrix.move_clip_section_in_canvas(self.slot_coordinates, a.delta)?;
+                    Ok(None)
+                })?
+            }
+            A::DetectStartTimeAndTempo => {
+                if !value.is_on() {
+                    return Ok(None);
+                }
+                self.with_matrix(context, |matrix| {
+                    matrix.detect_clip_start_time_and_tempo(self.slot_coordinates)?;
+                    Ok(None)
+                })?
+            }
+            A::CycleTakes => {
+                if !value.is_on() {
+                    return Ok(None);
+                }
+                self.with_matrix(context, |matrix| {
+                    matrix.cycle_clip_takes(self.slot_coordinates)?;
+                    Ok(None)
+                })?
+            }
+            A::DeleteTake => {
+                if !value.is_on() {
+                    return Ok(None);
+                }
+                self.with_matrix(context, |matrix| {
+                    matrix.delete_active_clip_take(self.slot_coordinates)?;
+                    Ok(None)
+                })?
+            }
+            A::SelectTake(a) => {
+                if !value.is_on() {
+                    return Ok(None);
+                }
+                self.with_matrix(context, |matrix| {
+                    matrix.select_clip_take(self.slot_coordinates, a.index)?;
+                    Ok(None)
+                })?
+            }
+            A::KeepActiveTakeOnly => {
+                if !value.is_on() {
+                    return Ok(None);
+                }
+                self.with_matrix(context, |matrix| {
+                    matrix.keep_active_clip_take_only(self.slot_coordinates)?;
+                    Ok(None)
+                })?
+            }
+            A::PickUpClip => {
+                if !value.is_on() {
+                    return Ok(None);
+                }
+                self.with_matrix(context, |matrix| {
+                    if matrix.slot_is_empty(self.slot_coordinates) {
+                        return Err("no clip to pick up");
+                    }
+                    matrix.set_picked_up_clip_slot(Some(self.slot_coordinates));
+                    Ok(None)
+                })?
+            }
+            A::DropClip(a) => {
+                if !value.is_on() {
+                    return Ok(None);
+                }
+                self.with_matrix(context, |matrix| {
+                    let source = matrix.picked_up_clip_slot().ok_or("no clip picked up")?;
+                    match a.mode {
+                        ClipDropMode::Move => {
+                            matrix.move_clip(source, self.slot_coordinates)?;
+                        }
+                        ClipDropMode::Copy => {
+                            // Keep it picked up, so it can be copied to multiple slots.
+                            matrix.copy_clip(source, self.slot_coordinates)?;
+                        }
+                        ClipDropMode::Swap => {
+                            matrix.swap_clips(source, self.slot_coordinates)?;
+                        }
+                    }
+                    Ok(None)
+                })?
+            }
             A::CopyOrPasteClip => {
                 if !value.is_on() {
                     return Ok(None);
@@ -159,6 +276,12 @@ impl RealearnTarget for ClipManagementTarget {
                     Some(PropValue::Text(name.to_string().into()))
                 })
                 .ok()?,
+            "clip.take_count" => BackboneState::get()
+                .with_clip_matrix(context.instance_state, |matrix| {
+                    let count = matrix.clip_take_count(self.slot_coordinates);
+                    Some(PropValue::Numeric(NumericValue::Discrete(count as i32)))
+                })
+                .ok()?,
             _ => None,
         }
     }
@@ -177,7 +300,22 @@ impl<'a> Target<'a> for ClipManagementTarget {
             A::ClearSlot
             | A::FillSlotWithSelectedItem
             | A::CopyOrPasteClip
-            | A::AdjustClipSectionLength(_) => Some(AbsoluteValue::default()),
+            | A::AdjustClipSectionLength(_)
+            | A::AdjustClipCanvasLength(_)
+            | A::MoveClipSectionInCanvas(_)
+            | A::DetectStartTimeAndTempo
+            | A::DropClip(_)
+            | A::CycleTakes
+            | A::DeleteTake
+            | A::SelectTake(_)
+            | A::KeepActiveTakeOnly => Some(AbsoluteValue::default()),
+            A::PickUpClip => BackboneState::get()
+                .with_clip_matrix(context.instance_state, |matrix| {
+                    let is_picked_up = matrix.picked_up_clip_slot() == Some(self.slot_coordinates);
+                    let value = convert_bool_to_unit_value(is_picked_up);
+                    Some(AbsoluteValue::Continuous(value))
+                })
+                .ok()?,
             A::EditClip => BackboneState::get()
                 .with_clip_matrix(context.instance_state, |matrix| {
                     let is_editing = matrix.is_editing_clip(self.slot_coordinates);
@@ -185,6 +323,14 @@ impl<'a> Target<'a> for ClipManagementTarget {
                     Some(AbsoluteValue::Continuous(value))
                 })
                 .ok()?,
+            A::WriteEnvelopes => BackboneState::get()
+                .with_clip_matrix(context.instance_state, |matrix| {
+                    let envelopes = matrix.clip_envelopes(self.slot_coordinates).ok()?;
+                    let is_writing = envelopes.mode == ClipEnvelopeMode::Write;
+                    let value = convert_bool_to_unit_value(is_writing);
+                    Some(AbsoluteValue::Continuous(value))
+                })
+                .ok()?,
         }
     }
 
@@ -1,10 +1,14 @@
+use crate::domain::ui_util::parse_unit_value_from_percentage;
 use crate::domain::{
-    format_value_as_on_off, BackboneState, Compartment, CompoundChangeEvent, ControlContext,
-    ExtendedProcessorContext, HitInstructionReturnValue, MappingControlContext,
-    RealTimeControlContext, RealTimeReaperTarget, RealearnTarget, ReaperTarget, ReaperTargetType,
-    TargetCharacter, TargetTypeDef, UnresolvedReaperTargetDef, DEFAULT_TARGET,
+    convert_count_to_step_size, format_value_as_on_off, BackboneState, Compartment,
+    CompoundChangeEvent, ControlContext, ExtendedProcessorContext, HitInstructionReturnValue,
+    MappingControlContext, RealTimeControlContext, RealTimeReaperTarget, RealearnTarget,
+    ReaperTarget, ReaperTargetType, TargetCharacter, TargetTypeDef, UnresolvedReaperTargetDef,
+    DEFAULT_TARGET,
+};
+use helgoboss_learn::{
+    AbsoluteValue, ControlType, ControlValue, Fraction, PropValue, Target, UnitValue,
 };
-use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, Target, UnitValue};
 use playtime_clip_engine::main::ClipMatrixEvent;
 use playtime_clip_engine::rt::{ClipChangeEvent, QualifiedClipChangeEvent};
 use realearn_api::persistence::ClipMatrixAction;
@@ -33,12 +37,111 @@ pub struct ClipMatrixTarget {
     pub action: ClipMatrixAction,
 }
 
+impl ClipMatrixTarget {
+    fn history_entry_count(&self, context: ControlContext) -> u32 {
+        BackboneState::get()
+            .with_clip_matrix(context.instance_state, |matrix| {
+                matrix.history_entry_count() as u32
+            })
+            .unwrap_or(0)
+    }
+
+    fn history_label(&self, context: ControlContext, label: HistoryLabel) -> Option<String> {
+        BackboneState::get()
+            .with_clip_matrix(context.instance_state, |matrix| {
+                let label = match label {
+                    HistoryLabel::Undo => matrix.undo_label()?.to_string(),
+                    HistoryLabel::Redo => matrix.redo_label()?.to_string(),
+                    HistoryLabel::Current => matrix.history_entries().find(|e| e.is_current)?.label,
+                };
+                Some(label)
+            })
+            .ok()?
+    }
+
+    fn convert_unit_value_to_history_index(&self, value: UnitValue, count: u32) -> u32 {
+        if count == 0 {
+            return 0;
+        }
+        (value.get() * (count - 1) as f64).round() as u32
+    }
+}
+
 impl RealearnTarget for ClipMatrixTarget {
-    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+    fn control_type_and_character(
+        &self,
+        context: ControlContext,
+    ) -> (ControlType, TargetCharacter) {
+        if self.action == ClipMatrixAction::BrowseHistory {
+            let count = self.history_entry_count(context);
+            return (
+                ControlType::AbsoluteDiscrete {
+                    atomic_step_size: convert_count_to_step_size(count),
+                    is_retriggerable: false,
+                },
+                TargetCharacter::Discrete,
+            );
+        }
         control_type_and_character(self.action)
     }
 
-    fn format_value(&self, value: UnitValue, _: ControlContext) -> String {
+    fn parse_as_value(
+        &self,
+        text: &str,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        if self.action == ClipMatrixAction::BrowseHistory {
+            self.parse_value_from_discrete_value(text, context)
+        } else {
+            parse_unit_value_from_percentage(text)
+        }
+    }
+
+    fn parse_as_step_size(
+        &self,
+        text: &str,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        if self.action == ClipMatrixAction::BrowseHistory {
+            self.parse_value_from_discrete_value(text, context)
+        } else {
+            parse_unit_value_from_percentage(text)
+        }
+    }
+
+    fn convert_unit_value_to_discrete_value(
+        &self,
+        input: UnitValue,
+        context: ControlContext,
+    ) -> Result<u32, &'static str> {
+        if self.action != ClipMatrixAction::BrowseHistory {
+            return Err("not supported");
+        }
+        let count = self.history_entry_count(context);
+        Ok(self.convert_unit_value_to_history_index(input, count))
+    }
+
+    fn convert_discrete_value_to_unit_value(
+        &self,
+        value: u32,
+        context: ControlContext,
+    ) -> Result<UnitValue, &'static str> {
+        if self.action != ClipMatrixAction::BrowseHistory {
+            return Err("not supported");
+        }
+        let count = self.history_entry_count(context);
+        if count <= 1 {
+            return Ok(UnitValue::MIN);
+        }
+        let value = (value as f64 / (count - 1) as f64).min(1.0);
+        Ok(UnitValue::new(value))
+    }
+
+    fn format_value(&self, value: UnitValue, context: ControlContext) -> String {
+        if self.action == ClipMatrixAction::BrowseHistory {
+            let count = self.history_entry_count(context);
+            return (self.convert_unit_value_to_history_index(value, count) + 1).to_string();
+        }
         format_value_as_on_off(value).to_string()
     }
 
@@ -47,6 +150,20 @@ impl RealearnTarget for ClipMatrixTarget {
         value: ControlValue,
         context: MappingControlContext,
     ) -> Result<HitInstructionReturnValue, &'static str> {
+        if self.action == ClipMatrixAction::BrowseHistory {
+            let count = self.history_entry_count(context.control_context);
+            let index = match value.to_absolute_value()? {
+                AbsoluteValue::Continuous(v) => self.convert_unit_value_to_history_index(v, count),
+                AbsoluteValue::Discrete(f) => f.actual(),
+            };
+            return BackboneState::get().with_clip_matrix_mut(
+                context.control_context.instance_state,
+                |matrix| {
+                    matrix.jump_to_history_entry(index as usize)?;
+                    Ok(None)
+                },
+            )?;
+        }
         BackboneState::get().with_clip_matrix_mut(
             context.control_context.instance_state,
             |matrix| {
@@ -66,6 +183,7 @@ impl RealearnTarget for ClipMatrixTarget {
                     ClipMatrixAction::BuildScene => {
                         matrix.build_scene_in_first_empty_row()?;
                     }
+                    ClipMatrixAction::BrowseHistory => {}
                 }
                 Ok(None)
             },
@@ -89,15 +207,38 @@ impl RealearnTarget for ClipMatrixTarget {
                 },
                 _ => (false, None),
             },
-            ClipMatrixAction::Undo | ClipMatrixAction::Redo => match evt {
-                CompoundChangeEvent::ClipMatrix(ClipMatrixEvent::AllClipsChanged) => (true, None),
-                _ => (false, None),
-            },
+            ClipMatrixAction::Undo | ClipMatrixAction::Redo | ClipMatrixAction::BrowseHistory => {
+                match evt {
+                    CompoundChangeEvent::ClipMatrix(
+                        ClipMatrixEvent::AllClipsChanged | ClipMatrixEvent::HistoryChanged,
+                    ) => (true, None),
+                    _ => (false, None),
+                }
+            }
         }
     }
 
     fn text_value(&self, context: ControlContext) -> Option<Cow<'static, str>> {
-        Some(format_value_as_on_off(self.current_value(context)?.to_unit_value()).into())
+        let label = match self.action {
+            ClipMatrixAction::Undo => self.history_label(context, HistoryLabel::Undo),
+            ClipMatrixAction::Redo => self.history_label(context, HistoryLabel::Redo),
+            ClipMatrixAction::BrowseHistory => self.history_label(context, HistoryLabel::Current),
+            _ => {
+                return Some(
+                    format_value_as_on_off(self.current_value(context)?.to_unit_value()).into(),
+                )
+            }
+        };
+        Some(label?.into())
+    }
+
+    fn prop_value(&self, key: &str, context: ControlContext) -> Option<PropValue> {
+        let label = match key {
+            "clip_matrix.undo_label" => self.history_label(context, HistoryLabel::Undo),
+            "clip_matrix.redo_label" => self.history_label(context, HistoryLabel::Redo),
+            _ => return None,
+        };
+        Some(PropValue::Text(label.unwrap_or_default().into()))
     }
 
     fn reaper_target_type(&self) -> Option<ReaperTargetType> {
@@ -129,6 +270,12 @@ impl<'a> Target<'a> for ClipMatrixTarget {
                     ClipMatrixAction::Stop | ClipMatrixAction::BuildScene => matrix.is_stoppable(),
                     ClipMatrixAction::Undo => matrix.can_undo(),
                     ClipMatrixAction::Redo => matrix.can_redo(),
+                    ClipMatrixAction::BrowseHistory => {
+                        let max_index = matrix.history_entry_count().saturating_sub(1);
+                        let current_index = matrix.current_history_index().unwrap_or(0);
+                        let fraction = Fraction::new(current_index as u32, max_index as u32);
+                        return Some(AbsoluteValue::Discrete(fraction));
+                    }
                 };
                 Some(AbsoluteValue::from_bool(bool_value))
             })
@@ -192,6 +339,8 @@ pub const CLIP_MATRIX_TARGET: TargetTypeDef = TargetTypeDef {
     ..DEFAULT_TARGET
 };
 
+/// For browsing the history, the control type depends on the number of history entries, so
+/// this returns just a fallback for that action.
 fn control_type_and_character(action: ClipMatrixAction) -> (ControlType, TargetCharacter) {
     use ClipMatrixAction::*;
     match action {
@@ -199,5 +348,13 @@ fn control_type_and_character(action: ClipMatrixAction) -> (ControlType, TargetC
             ControlType::AbsoluteContinuousRetriggerable,
             TargetCharacter::Trigger,
         ),
+        BrowseHistory => (ControlType::AbsoluteContinuous, TargetCharacter::Discrete),
     }
 }
+
+#[derive(Copy, Clone)]
+enum HistoryLabel {
+    Undo,
+    Redo,
+    Current,
+}
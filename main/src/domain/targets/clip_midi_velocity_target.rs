@@ -0,0 +1,162 @@
+use crate::domain::{
+    interpret_current_clip_slot_value, BackboneState, Compartment, CompoundChangeEvent,
+    ControlContext, ExtendedProcessorContext, HitInstructionReturnValue, MappingControlContext,
+    RealearnTarget, ReaperTarget, ReaperTargetType, TargetCharacter, TargetTypeDef,
+    UnresolvedReaperTargetDef, VirtualClipSlot, DEFAULT_TARGET,
+};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, NumericValue, Target, UnitValue};
+use playtime_api::persistence::MidiTransformation;
+use playtime_clip_engine::main::{ClipMatrixEvent, ClipSlotCoordinates};
+use playtime_clip_engine::rt::{ClipChangeEvent, QualifiedClipChangeEvent};
+use std::borrow::Cow;
+
+#[derive(Debug)]
+pub struct UnresolvedClipMidiVelocityTarget {
+    pub slot: VirtualClipSlot,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedClipMidiVelocityTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        compartment: Compartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        let target = ClipMidiVelocityTarget {
+            slot_coordinates: self.slot.resolve(context, compartment)?,
+        };
+        Ok(vec![ReaperTarget::ClipMidiVelocity(target)])
+    }
+
+    fn clip_slot_descriptor(&self) -> Option<&VirtualClipSlot> {
+        Some(&self.slot)
+    }
+}
+
+/// Controls the velocity scale factor of the MIDI transformation of a clip.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClipMidiVelocityTarget {
+    pub slot_coordinates: ClipSlotCoordinates,
+}
+
+impl RealearnTarget for ClipMidiVelocityTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (ControlType::AbsoluteContinuous, TargetCharacter::Continuous)
+    }
+
+    fn parse_as_value(&self, text: &str, _: ControlContext) -> Result<UnitValue, &'static str> {
+        let percent: f64 = text.parse().map_err(|_| "not a decimal value")?;
+        Ok(scale_unit_value(percent / 100.0))
+    }
+
+    fn format_value_without_unit(&self, value: UnitValue, _: ControlContext) -> String {
+        format!("{:.0}", scale_from_unit_value(value) * 100.0)
+    }
+
+    fn value_unit(&self, _: ControlContext) -> &'static str {
+        "%"
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        context: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        let scale = scale_from_unit_value(value.to_unit_value()?);
+        BackboneState::get().with_clip_matrix_mut(
+            context.control_context.instance_state,
+            |matrix| {
+                let transformation = MidiTransformation {
+                    velocity_scale: scale,
+                    ..matrix.clip_midi_transformation(self.slot_coordinates)?
+                };
+                matrix.set_clip_midi_transformation(self.slot_coordinates, transformation)?;
+                Ok(None)
+            },
+        )?
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        // TODO-medium With clip targets we should check the control context (instance state) if
+        //  slot filled.
+        true
+    }
+
+    fn process_change_event(
+        &self,
+        evt: CompoundChangeEvent,
+        _: ControlContext,
+    ) -> (bool, Option<AbsoluteValue>) {
+        match evt {
+            CompoundChangeEvent::ClipMatrix(ClipMatrixEvent::ClipChanged(
+                QualifiedClipChangeEvent {
+                    slot_coordinates: si,
+                    event: ClipChangeEvent::ClipMidiTransformation(new_value),
+                },
+            )) if *si == self.slot_coordinates => (
+                true,
+                Some(AbsoluteValue::Continuous(scale_unit_value(
+                    new_value.velocity_scale,
+                ))),
+            ),
+            _ => (false, None),
+        }
+    }
+
+    fn text_value(&self, context: ControlContext) -> Option<Cow<'static, str>> {
+        let scale = self.velocity_scale(context)?;
+        Some(format!("{:.0} %", scale * 100.0).into())
+    }
+
+    fn numeric_value(&self, context: ControlContext) -> Option<NumericValue> {
+        Some(NumericValue::Decimal(self.velocity_scale(context)? * 100.0))
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::ClipMidiVelocity)
+    }
+}
+
+impl ClipMidiVelocityTarget {
+    fn velocity_scale(&self, context: ControlContext) -> Option<f64> {
+        BackboneState::get()
+            .with_clip_matrix(context.instance_state, |matrix| {
+                matrix
+                    .clip_midi_transformation(self.slot_coordinates)
+                    .ok()
+                    .map(|t| t.velocity_scale)
+            })
+            .ok()?
+    }
+}
+
+impl<'a> Target<'a> for ClipMidiVelocityTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, context: ControlContext<'a>) -> Option<AbsoluteValue> {
+        let val = self
+            .velocity_scale(context)
+            .map(scale_unit_value)
+            .map(AbsoluteValue::Continuous);
+        interpret_current_clip_slot_value(val)
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const CLIP_MIDI_VELOCITY_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Clip: MIDI velocity",
+    short_name: "Clip MIDI velocity",
+    supports_clip_slot: true,
+    ..DEFAULT_TARGET
+};
+
+/// The complete target value range corresponds to velocity scale factors from zero to the maximum.
+fn scale_unit_value(scale: f64) -> UnitValue {
+    UnitValue::new_clamped(scale / MidiTransformation::MAX_VELOCITY_SCALE)
+}
+
+fn scale_from_unit_value(value: UnitValue) -> f64 {
+    value.get() * MidiTransformation::MAX_VELOCITY_SCALE
+}
@@ -0,0 +1,156 @@
+use crate::domain::{
+    interpret_current_clip_slot_value, BackboneState, Compartment, CompoundChangeEvent,
+    ControlContext, ExtendedProcessorContext, HitInstructionReturnValue, MappingControlContext,
+    RealearnTarget, ReaperTarget, ReaperTargetType, TargetCharacter, TargetTypeDef,
+    UnresolvedReaperTargetDef, VirtualClipSlot, DEFAULT_TARGET,
+};
+use helgoboss_learn::{AbsoluteValue, ControlType, ControlValue, NumericValue, Target, UnitValue};
+use playtime_api::persistence::ClipPitch;
+use playtime_clip_engine::main::{ClipMatrixEvent, ClipSlotCoordinates};
+use playtime_clip_engine::rt::{ClipChangeEvent, QualifiedClipChangeEvent};
+use std::borrow::Cow;
+
+#[derive(Debug)]
+pub struct UnresolvedClipPitchTarget {
+    pub slot: VirtualClipSlot,
+}
+
+impl UnresolvedReaperTargetDef for UnresolvedClipPitchTarget {
+    fn resolve(
+        &self,
+        context: ExtendedProcessorContext,
+        compartment: Compartment,
+    ) -> Result<Vec<ReaperTarget>, &'static str> {
+        let target = ClipPitchTarget {
+            slot_coordinates: self.slot.resolve(context, compartment)?,
+        };
+        Ok(vec![ReaperTarget::ClipPitch(target)])
+    }
+
+    fn clip_slot_descriptor(&self) -> Option<&VirtualClipSlot> {
+        Some(&self.slot)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClipPitchTarget {
+    pub slot_coordinates: ClipSlotCoordinates,
+}
+
+impl RealearnTarget for ClipPitchTarget {
+    fn control_type_and_character(&self, _: ControlContext) -> (ControlType, TargetCharacter) {
+        (ControlType::AbsoluteContinuous, TargetCharacter::Continuous)
+    }
+
+    fn parse_as_value(&self, text: &str, _: ControlContext) -> Result<UnitValue, &'static str> {
+        let semitones: f64 = text.parse().map_err(|_| "not a decimal value")?;
+        let pitch = ClipPitch::from_semitones(semitones)?;
+        Ok(pitch_unit_value(pitch))
+    }
+
+    fn format_value_without_unit(&self, value: UnitValue, _: ControlContext) -> String {
+        format!("{:.2}", semitones_from_unit_value(value))
+    }
+
+    fn value_unit(&self, _: ControlContext) -> &'static str {
+        "semitones"
+    }
+
+    fn hit(
+        &mut self,
+        value: ControlValue,
+        context: MappingControlContext,
+    ) -> Result<HitInstructionReturnValue, &'static str> {
+        let semitones = semitones_from_unit_value(value.to_unit_value()?);
+        let pitch = ClipPitch::from_semitones(semitones)?;
+        BackboneState::get().with_clip_matrix_mut(
+            context.control_context.instance_state,
+            |matrix| {
+                matrix.set_clip_pitch(self.slot_coordinates, pitch)?;
+                Ok(None)
+            },
+        )?
+    }
+
+    fn is_available(&self, _: ControlContext) -> bool {
+        // TODO-medium With clip targets we should check the control context (instance state) if
+        //  slot filled.
+        true
+    }
+
+    fn process_change_event(
+        &self,
+        evt: CompoundChangeEvent,
+        _: ControlContext,
+    ) -> (bool, Option<AbsoluteValue>) {
+        match evt {
+            CompoundChangeEvent::ClipMatrix(ClipMatrixEvent::ClipChanged(
+                QualifiedClipChangeEvent {
+                    slot_coordinates: si,
+                    event: ClipChangeEvent::ClipPitch(new_value),
+                },
+            )) if *si == self.slot_coordinates => (
+                true,
+                Some(AbsoluteValue::Continuous(pitch_unit_value(*new_value))),
+            ),
+            _ => (false, None),
+        }
+    }
+
+    fn text_value(&self, context: ControlContext) -> Option<Cow<'static, str>> {
+        let pitch = self.pitch(context)?;
+        Some(format!("{:+.2} semitones", pitch.in_semitones()).into())
+    }
+
+    fn numeric_value(&self, context: ControlContext) -> Option<NumericValue> {
+        Some(NumericValue::Decimal(self.pitch(context)?.in_semitones()))
+    }
+
+    fn reaper_target_type(&self) -> Option<ReaperTargetType> {
+        Some(ReaperTargetType::ClipPitch)
+    }
+}
+
+impl ClipPitchTarget {
+    fn pitch(&self, context: ControlContext) -> Option<ClipPitch> {
+        BackboneState::get()
+            .with_clip_matrix(context.instance_state, |matrix| {
+                matrix.clip_pitch(self.slot_coordinates).ok()
+            })
+            .ok()?
+    }
+}
+
+impl<'a> Target<'a> for ClipPitchTarget {
+    type Context = ControlContext<'a>;
+
+    fn current_value(&self, context: ControlContext<'a>) -> Option<AbsoluteValue> {
+        let val = self
+            .pitch(context)
+            .map(pitch_unit_value)
+            .map(AbsoluteValue::Continuous);
+        interpret_current_clip_slot_value(val)
+    }
+
+    fn control_type(&self, context: Self::Context) -> ControlType {
+        self.control_type_and_character(context).0
+    }
+}
+
+pub const CLIP_PITCH_TARGET: TargetTypeDef = TargetTypeDef {
+    name: "Clip: Pitch",
+    short_name: "Clip pitch",
+    supports_clip_slot: true,
+    ..DEFAULT_TARGET
+};
+
+/// The complete target value range corresponds to the maximum transposition in both directions.
+fn pitch_unit_value(pitch: ClipPitch) -> UnitValue {
+    let max = ClipPitch::MAX_SEMITONES;
+    UnitValue::new_clamped((pitch.in_semitones() + max) / (2.0 * max))
+}
+
+fn semitones_from_unit_value(value: UnitValue) -> f64 {
+    let max = ClipPitch::MAX_SEMITONES;
+    value.get() * 2.0 * max - max
+}
@@ -122,6 +122,56 @@ impl RealearnTarget for ClipRowTarget {
                     Ok(None)
                 })?
             }
+            ClipRowAction::InsertScene => {
+                if !value.is_on() {
+                    return Ok(None);
+                }
+                self.with_matrix(context.control_context, |matrix| {
+                    matrix.insert_scene(self.basics.row_index)?;
+                    Ok(None)
+                })?
+            }
+            ClipRowAction::DeleteScene => {
+                if !value.is_on() {
+                    return Ok(None);
+                }
+                self.with_matrix(context.control_context, |matrix| {
+                    matrix.delete_scene(self.basics.row_index)?;
+                    Ok(None)
+                })?
+            }
+            ClipRowAction::DuplicateScene => {
+                if !value.is_on() {
+                    return Ok(None);
+                }
+                self.with_matrix(context.control_context, |matrix| {
+                    matrix.duplicate_scene(self.basics.row_index)?;
+                    Ok(None)
+                })?
+            }
+            ClipRowAction::MoveSceneUp => {
+                if !value.is_on() {
+                    return Ok(None);
+                }
+                let row_index = self.basics.row_index;
+                if row_index == 0 {
+                    return Err("row is already at the top");
+                }
+                self.with_matrix(context.control_context, |matrix| {
+                    matrix.move_scene(row_index, row_index - 1)?;
+                    Ok(None)
+                })?
+            }
+            ClipRowAction::MoveSceneDown => {
+                if !value.is_on() {
+                    return Ok(None);
+                }
+                let row_index = self.basics.row_index;
+                self.with_matrix(context.control_context, |matrix| {
+                    matrix.move_scene(row_index, row_index + 1)?;
+                    Ok(None)
+                })?
+            }
         }
     }
 
@@ -149,6 +199,11 @@ impl RealearnTarget for ClipRowTarget {
             ClipRowAction::BuildScene => false,
             ClipRowAction::CopyOrPasteScene => true,
             ClipRowAction::ClearScene => true,
+            ClipRowAction::InsertScene => false,
+            ClipRowAction::DeleteScene => false,
+            ClipRowAction::DuplicateScene => false,
+            ClipRowAction::MoveSceneUp => false,
+            ClipRowAction::MoveSceneDown => false,
         }
     }
 }
@@ -159,8 +214,8 @@ impl<'a> Target<'a> for ClipRowTarget {
     fn current_value(&self, context: ControlContext<'a>) -> Option<AbsoluteValue> {
         use ClipRowAction::*;
         match self.basics.action {
-            PlayScene => None,
-            BuildScene => None,
+            PlayScene | BuildScene | InsertScene | DeleteScene | DuplicateScene | MoveSceneUp
+            | MoveSceneDown => None,
             CopyOrPasteScene | ClearScene => {
                 let row_is_empty = self
                     .with_matrix(context, |matrix| {
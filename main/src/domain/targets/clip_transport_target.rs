@@ -180,6 +180,9 @@ impl RealearnTarget for ClipTransportTarget {
                     Looped => {
                         matrix.toggle_looped(self.basics.slot_coordinates)?;
                     }
+                    Reversed => {
+                        matrix.toggle_reversed(self.basics.slot_coordinates)?;
+                    }
                 };
                 Ok(None)
             },
@@ -227,6 +230,15 @@ impl RealearnTarget for ClipTransportTarget {
                         ),
                         _ => (false, None),
                     },
+                    ClipChangeEvent::ClipReversed(new_state) => match self.basics.action {
+                        Reversed => (
+                            true,
+                            Some(AbsoluteValue::Continuous(transport_is_enabled_unit_value(
+                                *new_state,
+                            ))),
+                        ),
+                        _ => (false, None),
+                    },
                     ClipChangeEvent::Removed => {
                         tracing_debug!("Reacting to clip-removed event");
                         (true, None)
@@ -248,7 +260,10 @@ impl RealearnTarget for ClipTransportTarget {
 
     fn splinter_real_time_target(&self) -> Option<RealTimeReaperTarget> {
         use ClipTransportAction::*;
-        if matches!(self.basics.action, RecordStop | RecordPlayStop | Looped) {
+        if matches!(
+            self.basics.action,
+            RecordStop | RecordPlayStop | Looped | Reversed
+        ) {
             // These are not for real-time usage.
             return None;
         }
@@ -292,6 +307,11 @@ impl<'a> Target<'a> for ClipTransportTarget {
                         let is_looped = matrix.clip_looped(self.basics.slot_coordinates).ok()?;
                         transport_is_enabled_unit_value(is_looped)
                     }
+                    Reversed => {
+                        let is_reversed =
+                            matrix.clip_reversed(self.basics.slot_coordinates).ok()?;
+                        transport_is_enabled_unit_value(is_reversed)
+                    }
                 };
                 Some(AbsoluteValue::Continuous(val))
             })
@@ -357,6 +377,7 @@ impl RealTimeClipTransportTarget {
             }
             RecordStop | RecordPlayStop => Err("record not supported for real-time target"),
             Looped => Err("setting looped not supported for real-time target"),
+            Reversed => Err("setting reversed not supported for real-time target"),
         }
     }
 }
@@ -388,6 +409,7 @@ impl<'a> Target<'a> for RealTimeClipTransportTarget {
                 clip_play_state_unit_value(self.basics.action, clip.play_state())
             }
             Looped => transport_is_enabled_unit_value(clip.looped()),
+            Reversed => transport_is_enabled_unit_value(clip.reversed()),
         };
         Some(AbsoluteValue::Continuous(val))
     }
@@ -413,7 +435,7 @@ fn control_type_and_character(action: ClipTransportAction) -> (ControlType, Targ
             ControlType::AbsoluteContinuousRetriggerable,
             TargetCharacter::Switch,
         ),
-        Stop | Pause | RecordStop | Looped => {
+        Stop | Pause | RecordStop | Looped | Reversed => {
             (ControlType::AbsoluteContinuous, TargetCharacter::Switch)
         }
     }
@@ -76,19 +76,15 @@ impl RealearnTarget for LoadMappingSnapshotTarget {
                     if self.active_mappings_only && !m.is_effectively_on() {
                         continue;
                     }
-                    if let Some(inital_value) = m.initial_target_value() {
+                    if m.has_initial_target_values() {
                         context
                             .domain_event_handler
                             .notify_mapping_matched(m.compartment(), m.id());
-                        let res = m.control_from_target_directly(
+                        let res = m.control_from_initial_target_values(
                             context.control_context,
                             context.logger,
                             context.processor_context,
-                            inital_value,
                         );
-                        if res.successful {
-                            m.update_last_non_performance_target_value(inital_value);
-                        }
                         control_results.push(res);
                     }
                 }
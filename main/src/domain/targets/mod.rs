@@ -22,6 +22,12 @@ pub use clip_seek_target::*;
 mod clip_volume_target;
 pub use clip_volume_target::*;
 
+mod clip_pitch_target;
+pub use clip_pitch_target::*;
+
+mod clip_midi_velocity_target;
+pub use clip_midi_velocity_target::*;
+
 mod clip_management_target;
 pub use clip_management_target::*;
 
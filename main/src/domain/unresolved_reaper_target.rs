@@ -4,12 +4,13 @@ use crate::domain::{
     BackboneState, Compartment, CompartmentParamIndex, CompartmentParams, ExtendedProcessorContext,
     FeedbackResolution, ReaperTarget, UnresolvedActionTarget, UnresolvedAllTrackFxEnableTarget,
     UnresolvedAnyOnTarget, UnresolvedAutomationModeOverrideTarget, UnresolvedClipColumnTarget,
-    UnresolvedClipManagementTarget, UnresolvedClipMatrixTarget, UnresolvedClipRowTarget,
-    UnresolvedClipSeekTarget, UnresolvedClipTransportTarget, UnresolvedClipVolumeTarget,
-    UnresolvedEnableInstancesTarget, UnresolvedEnableMappingsTarget, UnresolvedFxEnableTarget,
-    UnresolvedFxNavigateTarget, UnresolvedFxOnlineTarget, UnresolvedFxOpenTarget,
-    UnresolvedFxParameterTarget, UnresolvedFxParameterTouchStateTarget, UnresolvedFxPresetTarget,
-    UnresolvedGoToBookmarkTarget, UnresolvedLastTouchedTarget, UnresolvedLoadFxSnapshotTarget,
+    UnresolvedClipManagementTarget, UnresolvedClipMatrixTarget, UnresolvedClipMidiVelocityTarget,
+    UnresolvedClipPitchTarget, UnresolvedClipRowTarget, UnresolvedClipSeekTarget,
+    UnresolvedClipTransportTarget, UnresolvedClipVolumeTarget, UnresolvedEnableInstancesTarget,
+    UnresolvedEnableMappingsTarget, UnresolvedFxEnableTarget, UnresolvedFxNavigateTarget,
+    UnresolvedFxOnlineTarget, UnresolvedFxOpenTarget, UnresolvedFxParameterTarget,
+    UnresolvedFxParameterTouchStateTarget, UnresolvedFxPresetTarget, UnresolvedGoToBookmarkTarget,
+    UnresolvedLastTouchedTarget, UnresolvedLoadFxSnapshotTarget,
     UnresolvedLoadMappingSnapshotTarget, UnresolvedMidiSendTarget,
     UnresolvedNavigateWithinGroupTarget, UnresolvedOscSendTarget, UnresolvedPlayrateTarget,
     UnresolvedRouteAutomationModeTarget, UnresolvedRouteMonoTarget, UnresolvedRouteMuteTarget,
@@ -91,6 +92,8 @@ pub enum UnresolvedReaperTarget {
     ClipRow(UnresolvedClipRowTarget),
     ClipSeek(UnresolvedClipSeekTarget),
     ClipVolume(UnresolvedClipVolumeTarget),
+    ClipPitch(UnresolvedClipPitchTarget),
+    ClipMidiVelocity(UnresolvedClipMidiVelocityTarget),
     ClipManagement(UnresolvedClipManagementTarget),
     ClipMatrix(UnresolvedClipMatrixTarget),
     LoadMappingSnapshot(UnresolvedLoadMappingSnapshotTarget),
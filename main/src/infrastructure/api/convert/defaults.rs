@@ -24,6 +24,10 @@ pub const FIRE_MODE_PRESS_DURATION_INTERVAL: Interval<u32> = Interval(0, 0);
 pub const FIRE_MODE_TIMEOUT: u32 = 0;
 pub const FIRE_MODE_RATE: u32 = 0;
 pub const FIRE_MODE_SINGLE_PRESS_MAX_DURATION: u32 = 0;
+pub const FIRE_MODE_NTH_PRESS_MAX_INTERVAL: u32 = 300;
+pub const FIRE_MODE_HOLD_TIMEOUT: u32 = 500;
+pub const FIRE_MODE_HOLD_VALUE: f64 = 1.0;
+pub const FIRE_MODE_RELEASE_VALUE: f64 = 0.0;
 
 pub const TARGET_TRACK_MUST_BE_SELECTED: bool = false;
 pub const TARGET_FX_MUST_HAVE_FOCUS: bool = false;
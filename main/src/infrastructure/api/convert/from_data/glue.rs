@@ -1,3 +1,4 @@
+use crate::domain::PressGesture;
 use crate::infrastructure::api::convert::from_data::ConversionStyle;
 use crate::infrastructure::api::convert::{defaults, ConversionResult};
 use crate::infrastructure::data::ModeModelData;
@@ -70,6 +71,7 @@ pub fn convert_glue(
             defaults::GLUE_ROUND_TARGET_VALUE,
         ),
         control_transformation: style.required_value(data.eel_control_transformation),
+        control_transformation_kind: style.required_value(data.control_transformation_kind),
         button_filter: {
             use persistence::ButtonFilter as T;
             use ButtonUsage::*;
@@ -119,6 +121,8 @@ pub fn convert_glue(
                         data.feedback_background_color,
                     )?,
                     transformation: style.required_value(data.eel_feedback_transformation),
+                    transformation_kind: style.required_value(data.feedback_transformation_kind),
+                    animation: data.feedback_animation,
                 }),
                 Textual => T::Text(TextFeedback {
                     commons: convert_feedback_commons(
@@ -133,48 +137,78 @@ pub fn convert_glue(
         fire_mode: {
             use persistence::FireMode as T;
             use FireMode::*;
-            let v = match data.fire_mode {
-                Normal => T::Normal(persistence::NormalFireMode {
-                    press_duration_interval: {
-                        let interval = persistence::Interval(
-                            data.min_press_millis as _,
-                            data.max_press_millis as _,
-                        );
-                        style.required_value_with_default(
-                            interval,
-                            defaults::FIRE_MODE_PRESS_DURATION_INTERVAL,
-                        )
-                    },
+            let v = match data.press_gesture {
+                Some(PressGesture::OnNthPress {
+                    n,
+                    max_interval_millis,
+                }) => T::OnNthPress(persistence::OnNthPressFireMode {
+                    n,
+                    max_interval: style.required_value_with_default(
+                        max_interval_millis as _,
+                        defaults::FIRE_MODE_NTH_PRESS_MAX_INTERVAL,
+                    ),
                 }),
-                AfterTimeout => T::AfterTimeout(persistence::AfterTimeoutFireMode {
+                Some(PressGesture::PressAndHold {
+                    timeout_millis,
+                    hold_value,
+                    release_value,
+                }) => T::PressAndHold(persistence::PressAndHoldFireMode {
                     timeout: style.required_value_with_default(
-                        data.min_press_millis as _,
-                        defaults::FIRE_MODE_TIMEOUT,
+                        timeout_millis as _,
+                        defaults::FIRE_MODE_HOLD_TIMEOUT,
+                    ),
+                    hold_value: style
+                        .required_value_with_default(hold_value, defaults::FIRE_MODE_HOLD_VALUE),
+                    release_value: style.required_value_with_default(
+                        release_value,
+                        defaults::FIRE_MODE_RELEASE_VALUE,
                     ),
                 }),
-                AfterTimeoutKeepFiring => {
-                    T::AfterTimeoutKeepFiring(persistence::AfterTimeoutKeepFiringFireMode {
+                None => match data.fire_mode {
+                    Normal => T::Normal(persistence::NormalFireMode {
+                        press_duration_interval: {
+                            let interval = persistence::Interval(
+                                data.min_press_millis as _,
+                                data.max_press_millis as _,
+                            );
+                            style.required_value_with_default(
+                                interval,
+                                defaults::FIRE_MODE_PRESS_DURATION_INTERVAL,
+                            )
+                        },
+                    }),
+                    AfterTimeout => T::AfterTimeout(persistence::AfterTimeoutFireMode {
                         timeout: style.required_value_with_default(
                             data.min_press_millis as _,
                             defaults::FIRE_MODE_TIMEOUT,
                         ),
-                        rate: style.required_value_with_default(
-                            data.turbo_rate as _,
-                            defaults::FIRE_MODE_RATE,
+                    }),
+                    AfterTimeoutKeepFiring => {
+                        T::AfterTimeoutKeepFiring(persistence::AfterTimeoutKeepFiringFireMode {
+                            timeout: style.required_value_with_default(
+                                data.min_press_millis as _,
+                                defaults::FIRE_MODE_TIMEOUT,
+                            ),
+                            rate: style.required_value_with_default(
+                                data.turbo_rate as _,
+                                defaults::FIRE_MODE_RATE,
+                            ),
+                        })
+                    }
+                    OnSinglePress => T::OnSinglePress(persistence::OnSinglePressFireMode {
+                        max_duration: style.required_value_with_default(
+                            data.max_press_millis as _,
+                            defaults::FIRE_MODE_SINGLE_PRESS_MAX_DURATION,
                         ),
-                    })
-                }
-                OnSinglePress => T::OnSinglePress(persistence::OnSinglePressFireMode {
-                    max_duration: style.required_value_with_default(
-                        data.max_press_millis as _,
-                        defaults::FIRE_MODE_SINGLE_PRESS_MAX_DURATION,
-                    ),
-                }),
-                OnDoublePress => T::OnDoublePress(persistence::OnDoublePressFireMode),
+                    }),
+                    OnDoublePress => T::OnDoublePress(persistence::OnDoublePressFireMode),
+                },
             };
             style.required_value(v)
         },
         feedback_value_table: data.feedback_value_table,
+        curve: data.curve,
+        acceleration: data.acceleration,
     };
     Ok(glue)
 }
@@ -195,7 +229,7 @@ fn convert_absolute_mode(
     style.required_value(mode)
 }
 
-fn convert_unit_interval(min: UnitValue, max: UnitValue) -> persistence::Interval<f64> {
+pub fn convert_unit_interval(min: UnitValue, max: UnitValue) -> persistence::Interval<f64> {
     persistence::Interval(min.get(), max.get())
 }
 
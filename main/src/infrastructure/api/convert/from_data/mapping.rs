@@ -3,10 +3,10 @@ use crate::application::{
 };
 use crate::infrastructure::api::convert::from_data::{
     convert_activation_condition, convert_glue, convert_source, convert_tags, convert_target,
-    ConversionStyle, NewSourceProps,
+    convert_unit_interval, ConversionStyle, NewSourceProps,
 };
 use crate::infrastructure::api::convert::{defaults, ConversionResult};
-use crate::infrastructure::data::MappingModelData;
+use crate::infrastructure::data::{AdditionalTargetModelData, MappingModelData};
 use realearn_api::persistence;
 use realearn_api::persistence::LifecycleHook;
 
@@ -45,11 +45,40 @@ pub fn convert_mapping(
         },
         glue: style.required_value(convert_glue(data.mode, style)?),
         target: style.required_value(convert_target(data.target, style)?),
+        additional_targets: {
+            let targets: Result<Vec<_>, _> = data
+                .additional_targets
+                .into_iter()
+                .map(|t| convert_additional_target(t, style))
+                .collect();
+            style.required_value(targets?)
+        },
         unprocessed: style.optional_value(advanced.unprocessed),
     };
     Ok(mapping)
 }
 
+fn convert_additional_target(
+    data: AdditionalTargetModelData,
+    style: ConversionStyle,
+) -> ConversionResult<persistence::AdditionalTarget> {
+    let glue = persistence::TargetGlue {
+        target_interval: style.required_value_with_default(
+            convert_unit_interval(data.min_target_value, data.max_target_value),
+            defaults::GLUE_TARGET_INTERVAL,
+        ),
+        reverse: style.required_value_with_default(data.reverse_is_enabled, defaults::GLUE_REVERSE),
+        control_transformation: style.required_value(data.control_transformation),
+        control_transformation_kind: style.required_value(data.control_transformation_kind),
+    };
+    let v = persistence::AdditionalTarget {
+        target: convert_target(data.target, style)?,
+        glue: style.required_value(glue),
+        feedback: style.required_value(data.is_feedback_target),
+    };
+    Ok(v)
+}
+
 #[derive(Default)]
 struct AdvancedDesc {
     extension_desc: ExtensionDesc,
@@ -209,5 +209,18 @@ fn convert_activation_condition(
             };
             Some(T::Expression(condition))
         }
+        Lua => {
+            let condition = persistence::LuaActivationCondition {
+                condition: condition_data.eel_condition,
+            };
+            Some(T::Lua(condition))
+        }
+        TargetValue => {
+            let condition = persistence::TargetValueActivationCondition {
+                mapping: condition_data.lead_mapping.map(String::from)?,
+                condition: condition_data.eel_condition,
+            };
+            Some(T::TargetValue(condition))
+        }
     }
 }
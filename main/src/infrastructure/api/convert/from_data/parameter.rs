@@ -1,4 +1,4 @@
-use crate::domain::ParamSetting;
+use crate::domain::{ParamSetting, ParamValueSource};
 use crate::infrastructure::api::convert::from_data::ConversionStyle;
 use crate::infrastructure::api::convert::ConversionResult;
 use realearn_api::persistence;
@@ -14,6 +14,21 @@ pub fn convert_parameter(
         name: Some(data.name),
         value_count: data.value_count,
         value_labels: style.required_value(data.value_labels),
+        min: data.min,
+        max: data.max,
+        unit: style.required_value(data.unit),
+        default_value: data.default_value,
+        value_source: data.value_source.map(convert_value_source),
     };
     Ok(p)
 }
+
+fn convert_value_source(s: ParamValueSource) -> persistence::ParameterValueSource {
+    use persistence::ParameterValueSource as T;
+    use ParamValueSource::*;
+    match s {
+        ProjectExtState { section, key } => T::ProjectExtState { section, key },
+        ExtState { section, key } => T::ExtState { section, key },
+        InstanceParameter { session_id, index } => T::InstanceParameter { session_id, index },
+    }
+}
@@ -195,6 +195,8 @@ pub fn convert_source(
                 Timer => persistence::Source::Timer(persistence::TimerSource {
                     duration: data.timer_millis,
                 }),
+                Lfo => persistence::Source::Lfo(data.lfo),
+                StepSequencer => persistence::Source::StepSequencer(data.step_sequencer),
             }
         }
         Virtual => {
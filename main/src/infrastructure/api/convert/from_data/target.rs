@@ -21,18 +21,19 @@ use realearn_api::persistence;
 use realearn_api::persistence::{
     AllTrackFxOnOffStateTarget, AnyOnTarget, AutomationModeOverrideTarget, BookmarkDescriptor,
     BookmarkRef, ClipColumnDescriptor, ClipColumnTarget, ClipManagementTarget, ClipMatrixTarget,
-    ClipRowTarget, ClipSeekTarget, ClipTransportActionTarget, ClipVolumeTarget,
-    CycleThroughFxPresetsTarget, CycleThroughFxTarget, CycleThroughGroupMappingsTarget,
-    CycleThroughTracksTarget, EnableInstancesTarget, EnableMappingsTarget, FxOnOffStateTarget,
-    FxOnlineOfflineStateTarget, FxParameterAutomationTouchStateTarget, FxParameterValueTarget,
-    FxVisibilityTarget, GoToBookmarkTarget, LastTouchedTarget, LoadFxSnapshotTarget,
-    LoadMappingSnapshotsTarget, PlayRateTarget, ReaperActionTarget, RouteAutomationModeTarget,
-    RouteMonoStateTarget, RouteMuteStateTarget, RoutePanTarget, RoutePhaseTarget,
-    RouteTouchStateTarget, RouteVolumeTarget, SeekTarget, SendMidiTarget, SendOscTarget,
-    TempoTarget, TrackArmStateTarget, TrackAutomationModeTarget, TrackAutomationTouchStateTarget,
-    TrackMonitoringModeTarget, TrackMuteStateTarget, TrackPanTarget, TrackPeakTarget,
-    TrackPhaseTarget, TrackSelectionStateTarget, TrackSoloStateTarget, TrackToolTarget,
-    TrackVisibilityTarget, TrackVolumeTarget, TrackWidthTarget, TransportActionTarget,
+    ClipMidiVelocityTarget, ClipPitchTarget, ClipRowTarget, ClipSeekTarget,
+    ClipTransportActionTarget, ClipVolumeTarget, CycleThroughFxPresetsTarget, CycleThroughFxTarget,
+    CycleThroughGroupMappingsTarget, CycleThroughTracksTarget, EnableInstancesTarget,
+    EnableMappingsTarget, FxOnOffStateTarget, FxOnlineOfflineStateTarget,
+    FxParameterAutomationTouchStateTarget, FxParameterValueTarget, FxVisibilityTarget,
+    GoToBookmarkTarget, LastTouchedTarget, LoadFxSnapshotTarget, LoadMappingSnapshotsTarget,
+    PlayRateTarget, ReaperActionTarget, RouteAutomationModeTarget, RouteMonoStateTarget,
+    RouteMuteStateTarget, RoutePanTarget, RoutePhaseTarget, RouteTouchStateTarget,
+    RouteVolumeTarget, SeekTarget, SendMidiTarget, SendOscTarget, TempoTarget, TrackArmStateTarget,
+    TrackAutomationModeTarget, TrackAutomationTouchStateTarget, TrackMonitoringModeTarget,
+    TrackMuteStateTarget, TrackPanTarget, TrackPeakTarget, TrackPhaseTarget,
+    TrackSelectionStateTarget, TrackSoloStateTarget, TrackToolTarget, TrackVisibilityTarget,
+    TrackVolumeTarget, TrackWidthTarget, TransportActionTarget,
 };
 
 pub fn convert_target(
@@ -305,6 +306,14 @@ fn convert_real_target(
             commons,
             slot: data.clip_slot.unwrap_or_default(),
         }),
+        ClipPitch => T::ClipPitch(ClipPitchTarget {
+            commons,
+            slot: data.clip_slot.unwrap_or_default(),
+        }),
+        ClipMidiVelocity => T::ClipMidiVelocity(ClipMidiVelocityTarget {
+            commons,
+            slot: data.clip_slot.unwrap_or_default(),
+        }),
         ClipManagement => T::ClipManagement(ClipManagementTarget {
             commons,
             slot: data.clip_slot.unwrap_or_default(),
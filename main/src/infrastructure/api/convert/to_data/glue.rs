@@ -1,3 +1,6 @@
+use crate::application::{convert_acceleration, convert_curve, convert_feedback_animation};
+use crate::base::script_util::{script_refers_to_identifier, EEL_SYNTAX, LUA_SYNTAX};
+use crate::domain::PressGesture;
 use crate::infrastructure::api::convert::defaults;
 use crate::infrastructure::api::convert::ConversionResult;
 use crate::infrastructure::data::ModeModelData;
@@ -43,6 +46,8 @@ pub fn convert_glue(g: Glue) -> ConversionResult<ModeModelData> {
         feedback_type: helgoboss_learn::FeedbackType,
         commons: FbCommonsData,
         transformation: String,
+        transformation_kind: TransformationKind,
+        animation: Option<FeedbackAnimation>,
     }
     let fb_data = {
         use helgoboss_learn::FeedbackType as T;
@@ -58,11 +63,15 @@ pub fn convert_glue(g: Glue) -> ConversionResult<ModeModelData> {
                 feedback_type: T::Numerical,
                 commons: convert_fb_commons(fb.commons),
                 transformation: fb.transformation.unwrap_or_default(),
+                transformation_kind: fb.transformation_kind.unwrap_or_default(),
+                animation: fb.animation,
             },
             Text(fb) => FbData {
                 feedback_type: T::Textual,
                 commons: convert_fb_commons(fb.commons),
                 transformation: fb.text_expression.unwrap_or_default(),
+                transformation_kind: Default::default(),
+                animation: None,
             },
         }
     };
@@ -94,7 +103,37 @@ pub fn convert_glue(g: Glue) -> ConversionResult<ModeModelData> {
                 let min = m.timeout.unwrap_or(defaults::FIRE_MODE_TIMEOUT) as u64;
                 (min, min)
             }
-            OnDoublePress(_) => (0, 0),
+            OnDoublePress(_) | OnNthPress(_) | PressAndHold(_) => (0, 0),
+        }
+    };
+    let press_gesture = {
+        use FireMode::*;
+        match &fire_mode {
+            OnNthPress(m) => {
+                if m.n == 0 {
+                    return Err("on-nth-press fire mode requires n to be at least 1".into());
+                }
+                let max_interval = m
+                    .max_interval
+                    .unwrap_or(defaults::FIRE_MODE_NTH_PRESS_MAX_INTERVAL);
+                Some(PressGesture::OnNthPress {
+                    n: m.n,
+                    max_interval_millis: max_interval as u64,
+                })
+            }
+            PressAndHold(m) => {
+                let hold_value = m.hold_value.unwrap_or(defaults::FIRE_MODE_HOLD_VALUE);
+                let release_value = m.release_value.unwrap_or(defaults::FIRE_MODE_RELEASE_VALUE);
+                let _: UnitValue = hold_value.try_into()?;
+                let _: UnitValue = release_value.try_into()?;
+                let timeout = m.timeout.unwrap_or(defaults::FIRE_MODE_HOLD_TIMEOUT);
+                Some(PressGesture::PressAndHold {
+                    timeout_millis: timeout as u64,
+                    hold_value,
+                    release_value,
+                })
+            }
+            _ => None,
         }
     };
     let data = ModeModelData {
@@ -128,6 +167,8 @@ pub fn convert_glue(g: Glue) -> ConversionResult<ModeModelData> {
         },
         eel_control_transformation: g.control_transformation.unwrap_or_default(),
         eel_feedback_transformation: fb_data.transformation,
+        control_transformation_kind: g.control_transformation_kind.unwrap_or_default(),
+        feedback_transformation_kind: fb_data.transformation_kind,
         reverse_is_enabled: g.reverse.unwrap_or(defaults::GLUE_REVERSE),
         feedback_color: fb_data.commons.color,
         feedback_background_color: fb_data.commons.background_color,
@@ -150,6 +191,8 @@ pub fn convert_glue(g: Glue) -> ConversionResult<ModeModelData> {
                 AfterTimeoutKeepFiring(_) => T::AfterTimeoutKeepFiring,
                 OnSinglePress(_) => T::OnSinglePress,
                 OnDoublePress(_) => T::OnDoublePress,
+                // Press gestures are detected before the mode is invoked.
+                OnNthPress(_) | PressAndHold(_) => T::Normal,
             }
         },
         round_target_value: g
@@ -214,10 +257,57 @@ pub fn convert_glue(g: Glue) -> ConversionResult<ModeModelData> {
         },
         feedback_type: fb_data.feedback_type,
         feedback_value_table: g.feedback_value_table,
+        curve: if let Some(c) = g.curve {
+            convert_curve(&c)?;
+            Some(c)
+        } else {
+            None
+        },
+        feedback_animation: if let Some(a) = fb_data.animation {
+            convert_feedback_animation(&a)?;
+            Some(a)
+        } else {
+            None
+        },
+        acceleration: if let Some(a) = g.acceleration {
+            convert_acceleration(&a)?;
+            Some(a)
+        } else {
+            None
+        },
+        press_gesture,
     };
+    validate_time_aware_control_transformation(&data)?;
     Ok(data)
 }
 
+/// A control transformation which uses `rel_time` is re-invoked with the same control event
+/// until it stops, which only works if the mode doesn't keep state across control events.
+fn validate_time_aware_control_transformation(data: &ModeModelData) -> ConversionResult<()> {
+    let syntax = match data.control_transformation_kind {
+        TransformationKind::Eel => &EEL_SYNTAX,
+        TransformationKind::Lua => &LUA_SYNTAX,
+    };
+    if !script_refers_to_identifier(&data.eel_control_transformation, syntax, "rel_time") {
+        return Ok(());
+    }
+    let mode_is_stateless = data.r#type == helgoboss_learn::AbsoluteMode::Normal
+        && matches!(data.fire_mode, helgoboss_learn::FireMode::Normal)
+        && data.min_press_millis == 0
+        && data.max_press_millis == 0
+        && matches!(data.button_usage, helgoboss_learn::ButtonUsage::Both)
+        && data.min_target_jump == UnitValue::MIN
+        && data.max_target_jump == UnitValue::MAX;
+    if !mode_is_stateless {
+        return Err(
+            "control transformations which use `rel_time` can't be combined with \
+            absolute modes other than `Normal`, fire modes, button filters or jump intervals"
+                .into(),
+        );
+    }
+    Ok(())
+}
+
 fn convert_step_factor_interval(
     i: Interval<i32>,
 ) -> ConversionResult<helgoboss_learn::Interval<SoftSymmetricUnitValue>> {
@@ -239,7 +329,7 @@ fn convert_step_size_interval(
     Ok(result)
 }
 
-fn convert_unit_value_interval(
+pub fn convert_unit_value_interval(
     interval: Interval<f64>,
 ) -> ConversionResult<helgoboss_learn::Interval<UnitValue>> {
     let result =
@@ -4,13 +4,15 @@ use crate::application::{
     LifecycleMidiMessageModel, LifecycleModel, MappingExtensionModel, RawByteArrayMidiMessage,
 };
 use crate::domain::Tag;
-use crate::infrastructure::api::convert::to_data::glue::convert_glue;
+use crate::infrastructure::api::convert::to_data::glue::{
+    convert_glue, convert_unit_value_interval,
+};
 use crate::infrastructure::api::convert::to_data::target::convert_target;
 use crate::infrastructure::api::convert::to_data::{
     convert_activation, ApiToDataConversionContext,
 };
 use crate::infrastructure::api::convert::{defaults, ConversionResult};
-use crate::infrastructure::data::{EnabledData, MappingModelData};
+use crate::infrastructure::data::{AdditionalTargetModelData, EnabledData, MappingModelData};
 use realearn_api::persistence::*;
 use std::convert::TryInto;
 use std::str::FromStr;
@@ -43,6 +45,17 @@ pub fn convert_mapping(
         } else {
             (false, false)
         };
+    let additional_targets =
+        convert_additional_targets(m.target.as_ref(), m.additional_targets.unwrap_or_default())?;
+    // Mappings with virtual targets are not polled, so the hold timeout would never fire.
+    if matches!(m.target, Some(Target::Virtual(_)))
+        && matches!(
+            m.glue.as_ref().and_then(|g| g.fire_mode.as_ref()),
+            Some(FireMode::PressAndHold(_))
+        )
+    {
+        return Err("press-and-hold fire mode is not supported for virtual targets".into());
+    }
     let v = MappingModelData {
         id: m.id.map(|id| id.into()),
         key: None,
@@ -52,6 +65,7 @@ pub fn convert_mapping(
         source: convert_source(m.source.unwrap_or_default())?,
         mode: convert_glue(m.glue.unwrap_or_default())?,
         target: convert_target(m.target.unwrap_or_default())?,
+        additional_targets,
         is_enabled: m.enabled.unwrap_or(defaults::MAPPING_ENABLED),
         enabled_data: {
             EnabledData {
@@ -78,6 +92,48 @@ pub fn convert_mapping(
     Ok(v)
 }
 
+fn convert_additional_targets(
+    main_target: Option<&Target>,
+    targets: Vec<AdditionalTarget>,
+) -> ConversionResult<Vec<AdditionalTargetModelData>> {
+    if targets.is_empty() {
+        return Ok(vec![]);
+    }
+    // Additional targets are controlled in the main thread, so the main target is controlled in
+    // the main thread as well. Sending MIDI to the FX output must happen in real-time though.
+    if let Some(Target::SendMidi(t)) = main_target {
+        if t.destination == Some(MidiDestination::FxOutput) {
+            return Err(
+                "additional targets are not supported if the main target sends MIDI to FX output"
+                    .into(),
+            );
+        }
+    }
+    let feedback_target_count = targets.iter().filter(|t| t.feedback == Some(true)).count();
+    if feedback_target_count > 1 {
+        return Err("at most one additional target can have feedback enabled".into());
+    }
+    targets.into_iter().map(convert_additional_target).collect()
+}
+
+fn convert_additional_target(t: AdditionalTarget) -> ConversionResult<AdditionalTargetModelData> {
+    let glue = t.glue.unwrap_or_default();
+    let target_interval = convert_unit_value_interval(
+        glue.target_interval
+            .unwrap_or(defaults::GLUE_TARGET_INTERVAL),
+    )?;
+    let v = AdditionalTargetModelData {
+        target: convert_target(t.target)?,
+        min_target_value: target_interval.min_val(),
+        max_target_value: target_interval.max_val(),
+        reverse_is_enabled: glue.reverse.unwrap_or(defaults::GLUE_REVERSE),
+        control_transformation: glue.control_transformation.unwrap_or_default(),
+        control_transformation_kind: glue.control_transformation_kind.unwrap_or_default(),
+        is_feedback_target: t.feedback.unwrap_or_default(),
+    };
+    Ok(v)
+}
+
 pub fn convert_tags(tag_strings: Vec<String>) -> ConversionResult<Vec<Tag>> {
     tag_strings.into_iter().map(convert_tag).collect()
 }
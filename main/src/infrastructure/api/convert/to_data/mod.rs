@@ -135,6 +135,17 @@ fn convert_activation(
             eel_condition: c.condition,
             ..Default::default()
         },
+        Lua(c) => ActivationConditionData {
+            activation_type: ActivationType::Lua,
+            eel_condition: c.condition,
+            ..Default::default()
+        },
+        TargetValue(c) => ActivationConditionData {
+            activation_type: ActivationType::TargetValue,
+            eel_condition: c.condition,
+            lead_mapping: Some(c.mapping.into()),
+            ..Default::default()
+        },
     };
     Ok(data)
 }
@@ -1,13 +1,38 @@
-use crate::domain::ParamSetting;
+use crate::domain::{ParamSetting, ParamValueSource};
 use crate::infrastructure::api::convert::ConversionResult;
 use realearn_api::persistence::*;
 
 pub fn convert_parameter(p: Parameter) -> ConversionResult<ParamSetting> {
+    match (p.min, p.max) {
+        (None, None) => {}
+        (Some(min), Some(max)) => {
+            if min >= max {
+                return Err("parameter min must be less than max".into());
+            }
+        }
+        _ => return Err("parameter min and max must be given both or not at all".into()),
+    }
     let data = ParamSetting {
         key: p.id,
         name: p.name.unwrap_or_default(),
         value_count: p.value_count,
         value_labels: p.value_labels.unwrap_or_default(),
+        min: p.min,
+        max: p.max,
+        unit: p.unit.unwrap_or_default(),
+        default_value: p.default_value,
+        value_source: p.value_source.map(convert_value_source),
     };
     Ok(data)
 }
+
+fn convert_value_source(s: ParameterValueSource) -> ParamValueSource {
+    use ParameterValueSource::*;
+    match s {
+        ProjectExtState { section, key } => ParamValueSource::ProjectExtState { section, key },
+        ExtState { section, key } => ParamValueSource::ExtState { section, key },
+        InstanceParameter { session_id, index } => {
+            ParamValueSource::InstanceParameter { session_id, index }
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use crate::application::{MidiSourceType, ReaperSourceType, SourceCategory};
+use crate::application::{
+    convert_modulation_rate, MidiSourceType, ReaperSourceType, SourceCategory,
+};
+use crate::domain::MAX_STEP_SEQUENCER_STEP_COUNT;
 use crate::infrastructure::api::convert::to_data::{
     convert_control_element_id, convert_control_element_type, convert_keystroke,
     convert_osc_arg_type, convert_osc_value_range,
@@ -113,21 +116,58 @@ pub fn convert_source(s: Source) -> ConversionResult<SourceModelData> {
             MidiDeviceChanges(_) => ReaperSourceType::MidiDeviceChanges,
             RealearnInstanceStart(_) => ReaperSourceType::RealearnInstanceStart,
             Timer(_) => ReaperSourceType::Timer,
+            Lfo(_) => ReaperSourceType::Lfo,
+            StepSequencer(_) => ReaperSourceType::StepSequencer,
             _ => Default::default(),
         },
         timer_millis: match &s {
             Timer(t) => t.duration,
             _ => Default::default(),
         },
+        lfo: match &s {
+            Lfo(l) => {
+                validate_modulation_rate(l.rate)?;
+                if let Some(phase) = l.phase {
+                    if !(0.0..=1.0).contains(&phase) {
+                        return Err("LFO phase must be between 0.0 and 1.0".into());
+                    }
+                }
+                l.clone()
+            }
+            _ => Default::default(),
+        },
+        step_sequencer: match &s {
+            StepSequencer(s) => {
+                validate_modulation_rate(s.rate)?;
+                if s.steps.len() > MAX_STEP_SEQUENCER_STEP_COUNT {
+                    return Err(format!(
+                        "Step sequencer supports at most {} steps",
+                        MAX_STEP_SEQUENCER_STEP_COUNT
+                    )
+                    .into());
+                }
+                s.clone()
+            }
+            _ => Default::default(),
+        },
     };
     Ok(data)
 }
 
+fn validate_modulation_rate(rate: Option<ModulationRate>) -> ConversionResult<()> {
+    if let Some(rate) = rate {
+        convert_modulation_rate(rate).validate()?;
+    }
+    Ok(())
+}
+
 fn convert_category(s: &Source) -> SourceCategory {
     use Source::*;
     match s {
         NoneSource => SourceCategory::Never,
-        MidiDeviceChanges(_) | RealearnInstanceStart(_) | Timer(_) => SourceCategory::Reaper,
+        MidiDeviceChanges(_) | RealearnInstanceStart(_) | Timer(_) | Lfo(_) | StepSequencer(_) => {
+            SourceCategory::Reaper
+        }
         MidiNoteVelocity(_)
         | MidiNoteKeyNumber(_)
         | MidiPolyphonicKeyPressureAmount(_)
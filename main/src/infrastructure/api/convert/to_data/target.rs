@@ -676,6 +676,18 @@ pub fn convert_target(t: Target) -> ConversionResult<TargetModelData> {
             clip_slot: Some(d.slot),
             ..init(d.commons)
         },
+        Target::ClipPitch(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::ClipPitch,
+            clip_slot: Some(d.slot),
+            ..init(d.commons)
+        },
+        Target::ClipMidiVelocity(d) => TargetModelData {
+            category: TargetCategory::Reaper,
+            r#type: ReaperTargetType::ClipMidiVelocity,
+            clip_slot: Some(d.slot),
+            ..init(d.commons)
+        },
         Target::ClipManagement(d) => TargetModelData {
             category: TargetCategory::Reaper,
             r#type: ReaperTargetType::ClipManagement,
@@ -3,6 +3,7 @@ use crate::application::{
     Change, ModifierConditionModel,
 };
 use crate::base::default_util::is_default;
+use crate::domain::MappingKey;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
@@ -18,6 +19,8 @@ pub struct ActivationConditionData {
     pub program_condition: BankConditionModel,
     #[serde(default, skip_serializing_if = "is_default")]
     pub eel_condition: String,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub lead_mapping: Option<MappingKey>,
 }
 
 impl ActivationConditionData {
@@ -28,6 +31,7 @@ impl ActivationConditionData {
             modifier_condition_2: model.modifier_condition_2(),
             program_condition: model.bank_condition(),
             eel_condition: model.script().to_owned(),
+            lead_mapping: model.lead_mapping().cloned(),
         }
     }
 
@@ -38,5 +42,6 @@ impl ActivationConditionData {
         model.change(V::SetModifierCondition2(self.modifier_condition_2));
         model.change(V::SetBankCondition(self.program_condition));
         model.change(V::SetScript(self.eel_condition.clone()));
+        model.change(V::SetLeadMapping(self.lead_mapping.clone()));
     }
 }
@@ -48,7 +48,9 @@ pub(super) fn create_clip_matrix_from_legacy_slots(
                                 start_timing: None,
                                 stop_timing: None,
                                 looped: desc.descriptor.repeat,
+                                reversed: false,
                                 volume: api::Db::new(0.0).unwrap(),
+                                pitch: Default::default(),
                                 color: api::ClipColor::PlayTrackColor,
                                 section: api::Section {
                                     start_pos: api::PositiveSecond::new(0.0).unwrap(),
@@ -56,11 +58,14 @@ pub(super) fn create_clip_matrix_from_legacy_slots(
                                 },
                                 audio_settings: Default::default(),
                                 midi_settings: Default::default(),
+                                canvas: None,
+                                envelopes: Default::default(),
                             };
                             let api_slot = api::Slot {
                                 // In the previous clip system, we had only one dimension.
                                 row: 0,
                                 clip: Some(api_clip),
+                                takes: vec![],
                             };
                             Some(vec![api_slot])
                         },
@@ -1,5 +1,7 @@
-use crate::application::{Change, MappingCommand, MappingModel};
-use crate::base::default_util::{bool_true, is_bool_true, is_default};
+use crate::application::{AdditionalTargetModel, Change, MappingCommand, MappingModel};
+use crate::base::default_util::{
+    bool_true, is_bool_true, is_default, is_unit_value_one, unit_value_one,
+};
 use crate::domain::{
     Compartment, ExtendedProcessorContext, FeedbackSendBehavior, GroupId, GroupKey, MappingKey, Tag,
 };
@@ -8,6 +10,8 @@ use crate::infrastructure::data::{
     ModeModelData, ModelToDataConversionContext, SourceModelData, TargetModelData,
 };
 use crate::infrastructure::plugin::App;
+use helgoboss_learn::{Interval, UnitValue};
+use realearn_api::persistence::TransformationKind;
 use semver::Version;
 use serde::{Deserialize, Serialize};
 
@@ -31,6 +35,8 @@ pub struct MappingModelData {
     pub source: SourceModelData,
     pub mode: ModeModelData,
     pub target: TargetModelData,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub additional_targets: Vec<AdditionalTargetModelData>,
     #[serde(default = "bool_true", skip_serializing_if = "is_bool_true")]
     pub is_enabled: bool,
     #[serde(flatten)]
@@ -47,6 +53,64 @@ pub struct MappingModelData {
     pub visible_in_projection: bool,
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdditionalTargetModelData {
+    pub target: TargetModelData,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub min_target_value: UnitValue,
+    #[serde(default = "unit_value_one", skip_serializing_if = "is_unit_value_one")]
+    pub max_target_value: UnitValue,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub reverse_is_enabled: bool,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub control_transformation: String,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub control_transformation_kind: TransformationKind,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub is_feedback_target: bool,
+}
+
+impl AdditionalTargetModelData {
+    pub fn from_model(
+        model: &AdditionalTargetModel,
+        conversion_context: &impl ModelToDataConversionContext,
+    ) -> Self {
+        Self {
+            target: TargetModelData::from_model(&model.target_model, conversion_context),
+            min_target_value: model.target_value_interval.min_val(),
+            max_target_value: model.target_value_interval.max_val(),
+            reverse_is_enabled: model.reverse,
+            control_transformation: model.control_transformation.clone(),
+            control_transformation_kind: model.control_transformation_kind,
+            is_feedback_target: model.is_feedback_target,
+        }
+    }
+
+    pub fn to_model(
+        &self,
+        compartment: Compartment,
+        preset_version: Option<&Version>,
+        conversion_context: impl DataToModelConversionContext,
+        processor_context: Option<ExtendedProcessorContext>,
+    ) -> AdditionalTargetModel {
+        let mut model = AdditionalTargetModel::new(compartment);
+        self.target.apply_to_model_flexible(
+            &mut model.target_model,
+            processor_context,
+            preset_version,
+            compartment,
+            conversion_context,
+        );
+        model.target_value_interval = Interval::new(self.min_target_value, self.max_target_value);
+        model.reverse = self.reverse_is_enabled;
+        model.control_transformation = self.control_transformation.clone();
+        model.control_transformation_kind = self.control_transformation_kind;
+        model.is_feedback_target = self.is_feedback_target;
+        model
+    }
+}
+
 impl MappingModelData {
     pub fn from_model(
         model: &MappingModel,
@@ -65,6 +129,11 @@ impl MappingModelData {
             source: SourceModelData::from_model(&model.source_model),
             mode: ModeModelData::from_model(&model.mode_model),
             target: TargetModelData::from_model(&model.target_model, conversion_context),
+            additional_targets: model
+                .additional_targets()
+                .iter()
+                .map(|t| AdditionalTargetModelData::from_model(t, conversion_context))
+                .collect(),
             is_enabled: model.is_enabled(),
             enabled_data: EnabledData {
                 control_is_enabled: model.control_is_enabled(),
@@ -186,6 +255,19 @@ impl MappingModelData {
             .apply_to_model_flexible(&mut model.source_model, compartment, preset_version);
         self.mode
             .apply_to_model_flexible(&mut model.mode_model, migration_descriptor, &self.name);
+        let additional_targets = self
+            .additional_targets
+            .iter()
+            .map(|t| {
+                t.to_model(
+                    compartment,
+                    preset_version,
+                    &conversion_context,
+                    processor_context,
+                )
+            })
+            .collect();
+        model.change(P::SetAdditionalTargets(additional_targets));
         self.target.apply_to_model_flexible(
             &mut model.target_model,
             processor_context,
@@ -1,5 +1,6 @@
-use crate::application::{Change, ModeCommand, ModeModel};
+use crate::application::{convert_curve, Change, ModeCommand, ModeModel};
 use crate::base::default_util::{is_default, is_unit_value_one, unit_value_one};
+use crate::domain::PressGesture;
 use crate::infrastructure::data::MigrationDescriptor;
 use crate::infrastructure::plugin::App;
 use helgoboss_learn::{
@@ -7,9 +8,11 @@ use helgoboss_learn::{
     OutOfRangeBehavior, SoftSymmetricUnitValue, TakeoverMode, UnitValue, ValueSequence,
     VirtualColor,
 };
-use realearn_api::persistence::FeedbackValueTable;
+use realearn_api::persistence::{
+    Acceleration, Curve, FeedbackAnimation, FeedbackValueTable, TransformationKind,
+};
 use serde::{Deserialize, Serialize};
-use slog::debug;
+use slog::{debug, warn};
 use std::time::Duration;
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -51,6 +54,11 @@ pub struct ModeModelData {
     #[serde(default, skip_serializing_if = "is_default")]
     pub eel_feedback_transformation: String,
     #[serde(default, skip_serializing_if = "is_default")]
+    pub control_transformation_kind: TransformationKind,
+    /// Not relevant for text expressions (they are always EEL)
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub feedback_transformation_kind: TransformationKind,
+    #[serde(default, skip_serializing_if = "is_default")]
     pub reverse_is_enabled: bool,
     #[serde(default, skip_serializing_if = "is_default")]
     pub feedback_color: Option<VirtualColor>,
@@ -88,6 +96,14 @@ pub struct ModeModelData {
     pub feedback_type: FeedbackType,
     #[serde(default, skip_serializing_if = "is_default")]
     pub feedback_value_table: Option<FeedbackValueTable>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub curve: Option<Curve>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub feedback_animation: Option<FeedbackAnimation>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub acceleration: Option<Acceleration>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub press_gesture: Option<PressGesture>,
 }
 
 fn default_step_size() -> SoftSymmetricUnitValue {
@@ -119,6 +135,8 @@ impl ModeModelData {
             } else {
                 model.eel_feedback_transformation().to_owned()
             },
+            control_transformation_kind: model.control_transformation_kind(),
+            feedback_transformation_kind: model.feedback_transformation_kind(),
             feedback_color: model.feedback_color().cloned(),
             feedback_background_color: model.feedback_background_color().cloned(),
             reverse_is_enabled: model.reverse(),
@@ -138,6 +156,10 @@ impl ModeModelData {
             target_value_sequence: model.target_value_sequence().clone(),
             feedback_type: model.feedback_type(),
             feedback_value_table: model.feedback_value_table().cloned(),
+            curve: model.curve().cloned(),
+            feedback_animation: model.feedback_animation().cloned(),
+            acceleration: model.acceleration().cloned(),
+            press_gesture: model.press_gesture(),
         }
     }
 
@@ -197,6 +219,12 @@ impl ModeModelData {
         };
         model.change(P::SetEelFeedbackTransformation(eel_fb_transformation));
         model.change(P::SetTextualFeedbackExpression(textual_fb_expression));
+        model.change(P::SetControlTransformationKind(
+            self.control_transformation_kind,
+        ));
+        model.change(P::SetFeedbackTransformationKind(
+            self.feedback_transformation_kind,
+        ));
         model.change(P::SetFeedbackColor(self.feedback_color.clone()));
         model.change(P::SetFeedbackBackgroundColor(
             self.feedback_background_color.clone(),
@@ -228,5 +256,15 @@ impl ModeModelData {
         ));
         model.change(P::SetFeedbackType(self.feedback_type));
         model.change(P::SetFeedbackValueTable(self.feedback_value_table.clone()));
+        if let Some(Err(e)) = self.curve.as_ref().map(convert_curve) {
+            warn!(
+                App::logger(),
+                "Curve of mapping {} is invalid and therefore ignored: {}", mapping_name, e
+            );
+        }
+        model.change(P::SetCurve(self.curve.clone()));
+        model.change(P::SetFeedbackAnimation(self.feedback_animation.clone()));
+        model.change(P::SetAcceleration(self.acceleration.clone()));
+        model.change(P::SetPressGesture(self.press_gesture));
     }
 }
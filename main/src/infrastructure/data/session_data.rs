@@ -581,7 +581,7 @@ fn get_parameter_data_map(
             let param = compartment_params.at(i);
             let value = param.raw_value();
             let setting = param.setting();
-            if value == 0.0 && setting.name.is_empty() {
+            if value == 0.0 && setting.is_default() {
                 return None;
             }
             let data = ParameterData {
@@ -639,3 +639,9 @@ pub trait DataToModelConversionContext {
 
     fn non_default_group_id_by_key(&self, key: &GroupKey) -> Option<GroupId>;
 }
+
+impl<T: DataToModelConversionContext> DataToModelConversionContext for &T {
+    fn non_default_group_id_by_key(&self, key: &GroupKey) -> Option<GroupId> {
+        (*self).non_default_group_id_by_key(key)
+    }
+}
@@ -10,7 +10,7 @@ use crate::infrastructure::data::common::OscValueRange;
 use crate::infrastructure::data::VirtualControlElementIdData;
 use helgoboss_learn::{DisplayType, MidiClockTransportMessage, OscTypeTag, SourceCharacter};
 use helgoboss_midi::{Channel, U14, U7};
-use realearn_api::persistence::MidiScriptKind;
+use realearn_api::persistence::{LfoSource, MidiScriptKind, StepSequencerSource};
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
@@ -84,6 +84,10 @@ pub struct SourceModelData {
     pub reaper_source_type: ReaperSourceType,
     #[serde(default, skip_serializing_if = "is_default")]
     pub timer_millis: u64,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub lfo: LfoSource,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub step_sequencer: StepSequencerSource,
 }
 
 impl SourceModelData {
@@ -120,6 +124,8 @@ impl SourceModelData {
             ),
             reaper_source_type: model.reaper_source_type(),
             timer_millis: model.timer_millis(),
+            lfo: model.lfo().clone(),
+            step_sequencer: model.step_sequencer().clone(),
         }
     }
 
@@ -199,6 +205,8 @@ impl SourceModelData {
         ));
         model.change(P::SetReaperSourceType(self.reaper_source_type));
         model.change(P::SetTimerMillis(self.timer_millis));
+        model.change(P::SetLfo(self.lfo.clone()));
+        model.change(P::SetStepSequencer(self.step_sequencer.clone()));
         model.change(P::SetKeystroke(self.keystroke));
     }
 }
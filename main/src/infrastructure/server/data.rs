@@ -30,6 +30,7 @@ pub enum DataError {
     OnlyCustomDataKeyIsSupportedAsPatchPath,
     ControllerUpdateFailed,
     ClipMatrixNotFound,
+    HistoryEntryNotFound,
 }
 
 pub enum DataErrorCategory {
@@ -52,6 +53,7 @@ impl DataError {
             }
             ControllerUpdateFailed => "couldn't update controller",
             ClipMatrixNotFound => "clip matrix not found",
+            HistoryEntryNotFound => "history entry not found",
         }
     }
 
@@ -61,7 +63,8 @@ impl DataError {
             SessionNotFound
             | SessionHasNoActiveController
             | ControllerNotFound
-            | ClipMatrixNotFound => DataErrorCategory::NotFound,
+            | ClipMatrixNotFound
+            | HistoryEntryNotFound => DataErrorCategory::NotFound,
             OnlyPatchReplaceIsSupported => DataErrorCategory::MethodNotAllowed,
             OnlyCustomDataKeyIsSupportedAsPatchPath => DataErrorCategory::BadRequest,
             ControllerUpdateFailed => DataErrorCategory::InternalServerError,
@@ -102,6 +105,14 @@ struct TargetDescriptor {
     label: String,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipMatrixHistoryEntryData {
+    index: usize,
+    label: String,
+    is_current: bool,
+}
+
 pub fn get_session_data(session_id: String) -> Result<SessionResponseData, DataError> {
     let _ = App::get()
         .find_session_by_id(&session_id)
@@ -121,6 +132,40 @@ pub fn get_clip_matrix_data(
         .map_err(|_| DataError::ClipMatrixNotFound)
 }
 
+pub fn get_clip_matrix_history_data(
+    session_id: &str,
+) -> Result<Vec<ClipMatrixHistoryEntryData>, DataError> {
+    let session = App::get()
+        .find_session_by_id(session_id)
+        .ok_or(DataError::SessionNotFound)?;
+    let session = session.borrow();
+    BackboneState::get()
+        .with_clip_matrix(session.instance_state(), |matrix| {
+            matrix
+                .history_entries()
+                .map(|e| ClipMatrixHistoryEntryData {
+                    index: e.index,
+                    label: e.label,
+                    is_current: e.is_current,
+                })
+                .collect()
+        })
+        .map_err(|_| DataError::ClipMatrixNotFound)
+}
+
+pub fn jump_to_clip_matrix_history_entry(session_id: &str, index: usize) -> Result<(), DataError> {
+    let session = App::get()
+        .find_session_by_id(session_id)
+        .ok_or(DataError::SessionNotFound)?;
+    let session = session.borrow();
+    BackboneState::get()
+        .with_clip_matrix_mut(session.instance_state(), |matrix| {
+            matrix.jump_to_history_entry(index)
+        })
+        .map_err(|_| DataError::ClipMatrixNotFound)?
+        .map_err(|_| DataError::HistoryEntryNotFound)
+}
+
 pub fn get_controller_routing_by_session_id(
     session_id: String,
 ) -> Result<ControllerRouting, DataError> {
@@ -1,4 +1,5 @@
 use crate::infrastructure::plugin::App;
+use crate::infrastructure::server::data::{jump_to_clip_matrix_history_entry, DataErrorCategory};
 use crate::infrastructure::server::grpc::WithSessionId;
 use futures::{Stream, StreamExt};
 use playtime_clip_engine::proto::{
@@ -9,7 +10,8 @@ use playtime_clip_engine::proto::{
     GetContinuousSlotUpdatesRequest, GetOccasionalMatrixUpdatesReply,
     GetOccasionalMatrixUpdatesRequest, GetOccasionalSlotUpdatesReply,
     GetOccasionalSlotUpdatesRequest, GetOccasionalTrackUpdatesReply,
-    GetOccasionalTrackUpdatesRequest, OccasionalMatrixUpdate, OccasionalTrackUpdate,
+    GetOccasionalTrackUpdatesRequest, HistoryState, JumpToHistoryEntryReply,
+    JumpToHistoryEntryRequest, OccasionalMatrixUpdate, OccasionalTrackUpdate,
     QualifiedOccasionalSlotUpdate, QualifiedOccasionalTrackUpdate, SlotCoordinates, SlotPlayState,
     TrackColor, TrackInput, TrackInputMonitoring,
 };
@@ -134,6 +136,7 @@ impl clip_engine_server::ClipEngine for RealearnClipEngine {
                     Update::ArrangementPlayState(
                         ArrangementPlayState::from_engine(project.play_state()).into(),
                     ),
+                    Update::History(HistoryState::from_engine(matrix.history_entries())),
                 ]
                 .into_iter()
                 .map(|u| OccasionalMatrixUpdate { update: Some(u) })
@@ -216,6 +219,20 @@ impl clip_engine_server::ClipEngine for RealearnClipEngine {
             Some(initial_reply).into_iter(),
         )
     }
+
+    async fn jump_to_history_entry(
+        &self,
+        request: Request<JumpToHistoryEntryRequest>,
+    ) -> Result<Response<JumpToHistoryEntryReply>, Status> {
+        let req = request.into_inner();
+        jump_to_clip_matrix_history_entry(&req.clip_matrix_id, req.index as usize).map_err(
+            |e| match e.category() {
+                DataErrorCategory::NotFound => Status::not_found(e.description()),
+                _ => Status::unknown(e.description()),
+            },
+        )?;
+        Ok(Response::new(JumpToHistoryEntryReply {}))
+    }
 }
 
 type SyncBoxStream<'a, T> = Pin<Box<dyn Stream<Item = T> + Send + Sync + 'a>>;
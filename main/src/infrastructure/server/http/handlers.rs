@@ -2,9 +2,10 @@ use crate::base::Global;
 use crate::infrastructure::data::ControllerPresetData;
 use crate::infrastructure::plugin::RealearnControlSurfaceServerTaskSender;
 use crate::infrastructure::server::data::{
-    get_clip_matrix_data, get_controller_preset_data, get_controller_routing_by_session_id,
-    obtain_control_surface_metrics_snapshot, patch_controller, ControllerRouting, DataError,
-    DataErrorCategory, PatchRequest, SessionResponseData, Topics,
+    get_clip_matrix_data, get_clip_matrix_history_data, get_controller_preset_data,
+    get_controller_routing_by_session_id, jump_to_clip_matrix_history_entry,
+    obtain_control_surface_metrics_snapshot, patch_controller, ClipMatrixHistoryEntryData,
+    ControllerRouting, DataError, DataErrorCategory, PatchRequest, SessionResponseData, Topics,
 };
 use crate::infrastructure::server::http::{send_initial_events, ServerClients, WebSocketClient};
 use axum::body::{boxed, Body, BoxBody};
@@ -41,6 +42,22 @@ pub async fn clip_matrix_handler(
     Ok(Json(clip_matrix_data))
 }
 
+/// Needs to be executed in the main thread!
+pub async fn clip_matrix_history_handler(
+    Path(session_id): Path<String>,
+) -> Result<Json<Vec<ClipMatrixHistoryEntryData>>, SimpleResponse> {
+    let history_data = get_clip_matrix_history_data(&session_id).map_err(translate_data_error)?;
+    Ok(Json(history_data))
+}
+
+/// Needs to be executed in the main thread!
+pub async fn jump_to_clip_matrix_history_entry_handler(
+    Path((session_id, index)): Path<(String, usize)>,
+) -> Result<StatusCode, SimpleResponse> {
+    jump_to_clip_matrix_history_entry(&session_id, index).map_err(translate_data_error)?;
+    Ok(StatusCode::OK)
+}
+
 /// Needs to be executed in the main thread!
 pub async fn session_controller_handler(
     Path(session_id): Path<String>,
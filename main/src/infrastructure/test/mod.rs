@@ -1,10 +1,13 @@
 use crate::base::{Global, SenderToNormalThread};
-use crate::domain::{SourceFeedbackValue, PLUGIN_PARAMETER_COUNT};
+use crate::domain::{
+    BackboneState, RealearnClipMatrix, SourceFeedbackValue, PLUGIN_PARAMETER_COUNT,
+};
 use crate::infrastructure::plugin::{App, SET_STATE_PARAM_NAME};
 use approx::assert_abs_diff_eq;
 use helgoboss_learn::{MidiSourceValue, BASE_EPSILON, FEEDBACK_EPSILON};
 use helgoboss_midi::test_util::*;
 use helgoboss_midi::{DataEntryByteOrder, ParameterNumberMessage, RawShortMessage, ShortMessage};
+use playtime_api::persistence as api;
 use reaper_high::{ActionKind, Fx, FxParameter, Reaper, Track};
 use reaper_medium::{Db, ReaperPanValue, StuffMidiMessageTarget};
 use std::ffi::CString;
@@ -95,6 +98,8 @@ impl Test {
         self.step("Conditional activation - EEL", conditional_activation_eel())
             .await;
         self.step("Virtual", virtual_mapping()).await;
+        self.step("Clip matrix - Scenes", clip_matrix_scenes())
+            .await;
         log("\nTests executed successfully!")
     }
 
@@ -1311,6 +1316,153 @@ async fn conditional_activation_eel() {
     );
 }
 
+async fn clip_matrix_scenes() {
+    // Given
+    let realearn = setup().await;
+    let session = App::get()
+        .find_session_by_containing_fx(&realearn.fx)
+        .expect("couldn't find session associated with ReaLearn FX instance");
+    let instance_state = session.borrow().instance_state().clone();
+    let mut instance_state = instance_state.borrow_mut();
+    let matrix = BackboneState::get()
+        .get_or_insert_owned_clip_matrix_from_instance_state(&mut instance_state);
+    matrix
+        .load(create_scene_test_matrix())
+        .expect("couldn't load clip matrix");
+    assert_scenes(matrix, &["A", "B", "C"], &["X", "Y", "Z"]);
+    // When
+    matrix.insert_scene(1).unwrap();
+    // Then
+    assert_scenes(matrix, &["A", "", "B", "C"], &["X", "Y", "Z", ""]);
+    // When
+    matrix.undo().unwrap();
+    // Then
+    assert_scenes(matrix, &["A", "B", "C"], &["X", "Y", "Z"]);
+    // When
+    matrix.delete_scene(0).unwrap();
+    // Then
+    assert_scenes(matrix, &["B", "C", ""], &["X", "Y", "Z"]);
+    // When
+    matrix.undo().unwrap();
+    // Then
+    assert_scenes(matrix, &["A", "B", "C"], &["X", "Y", "Z"]);
+    // When
+    matrix.duplicate_scene(1).unwrap();
+    // Then
+    assert_scenes(matrix, &["A", "B", "B", "C"], &["X", "Y", "Z", ""]);
+    // When
+    matrix.undo().unwrap();
+    // Then
+    assert_scenes(matrix, &["A", "B", "C"], &["X", "Y", "Z"]);
+    // When
+    matrix.move_scene(0, 2).unwrap();
+    // Then
+    assert_scenes(matrix, &["B", "C", "A"], &["X", "Y", "Z"]);
+    // When
+    matrix.undo().unwrap();
+    // Then
+    assert_scenes(matrix, &["A", "B", "C"], &["X", "Y", "Z"]);
+    // When
+    matrix.redo().unwrap();
+    // Then
+    assert_scenes(matrix, &["B", "C", "A"], &["X", "Y", "Z"]);
+}
+
+/// Creates a matrix with one column following scenes and one free column, 3 clips each.
+fn create_scene_test_matrix() -> api::Matrix {
+    let create_column = |mode, clip_names: [&str; 3]| api::Column {
+        clip_play_settings: api::ColumnClipPlaySettings {
+            mode: Some(mode),
+            ..Default::default()
+        },
+        clip_record_settings: Default::default(),
+        slots: Some(
+            clip_names
+                .iter()
+                .enumerate()
+                .map(|(row, name)| api::Slot {
+                    row,
+                    clip: Some(create_midi_clip(name)),
+                    takes: vec![],
+                })
+                .collect(),
+        ),
+    };
+    let row = api::Row {
+        name: None,
+        tempo: None,
+        time_signature: None,
+    };
+    api::Matrix {
+        columns: Some(vec![
+            create_column(
+                api::ColumnPlayMode::ExclusiveFollowingScene,
+                ["A", "B", "C"],
+            ),
+            create_column(api::ColumnPlayMode::Free, ["X", "Y", "Z"]),
+        ]),
+        rows: Some(vec![row.clone(), row.clone(), row]),
+        ..Default::default()
+    }
+}
+
+fn create_midi_clip(name: &str) -> api::Clip {
+    api::Clip {
+        name: Some(name.to_string()),
+        source: api::Source::MidiChunk(api::MidiChunkSource {
+            chunk: "HASDATA 1 960 QN\nE 3840 b0 7b 00\n".to_string(),
+        }),
+        frozen_source: None,
+        active_source: api::SourceOrigin::Normal,
+        time_base: api::ClipTimeBase::Time,
+        start_timing: None,
+        stop_timing: None,
+        looped: true,
+        reversed: false,
+        volume: api::Db::new(0.0).unwrap(),
+        pitch: Default::default(),
+        color: api::ClipColor::PlayTrackColor,
+        section: api::Section {
+            start_pos: api::PositiveSecond::new(0.0).unwrap(),
+            length: None,
+        },
+        audio_settings: Default::default(),
+        midi_settings: Default::default(),
+        canvas: None,
+        envelopes: Default::default(),
+    }
+}
+
+/// Checks the clip names row by row (empty string = empty slot) in both columns.
+fn assert_scenes(matrix: &RealearnClipMatrix, scene_column: &[&str], free_column: &[&str]) {
+    let api_columns = matrix.save().columns.unwrap();
+    let clip_names = |column_index: usize| -> Vec<String> {
+        let api_slots = api_columns[column_index]
+            .slots
+            .as_deref()
+            .unwrap_or_default();
+        (0..matrix.row_count())
+            .map(|row| {
+                api_slots
+                    .iter()
+                    .find(|s| s.row == row)
+                    .and_then(|s| s.clip.as_ref()?.name.clone())
+                    .unwrap_or_default()
+            })
+            .collect()
+    };
+    assert_eq!(
+        clip_names(0),
+        scene_column,
+        "unexpected clips in scene column"
+    );
+    assert_eq!(
+        clip_names(1),
+        free_column,
+        "unexpected clips in free column"
+    );
+}
+
 fn load_realearn_preset(realearn: &RealearnTestInstance, json: &str) {
     let preset_c_string = CString::new(json).expect("couldn't convert preset into c string");
     realearn
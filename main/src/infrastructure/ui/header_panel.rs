@@ -2776,6 +2776,7 @@ fn edit_compartment_parameter_internal(
             name: name.trim().to_owned(),
             value_count: { value_count.parse().ok() },
             value_labels: vec![],
+            ..old_setting.clone()
         })
         .collect();
     if out_settings.len() != settings.len() {
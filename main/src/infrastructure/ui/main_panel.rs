@@ -29,7 +29,7 @@ use playtime_clip_engine::main::ClipMatrixEvent;
 use playtime_clip_engine::proto::{
     occasional_matrix_update, occasional_track_update, qualified_occasional_slot_update,
     ArrangementPlayState, ContinuousClipUpdate, ContinuousColumnUpdate, ContinuousMatrixUpdate,
-    ContinuousSlotUpdate, OccasionalMatrixUpdate, OccasionalTrackUpdate,
+    ContinuousSlotUpdate, HistoryState, OccasionalMatrixUpdate, OccasionalTrackUpdate,
     QualifiedContinuousSlotUpdate, QualifiedOccasionalSlotUpdate, QualifiedOccasionalTrackUpdate,
     SlotCoordinates, SlotPlayState, TrackInput, TrackInputMonitoring,
 };
@@ -339,6 +339,7 @@ impl SessionUi for Weak<MainPanel> {
         events: &[ClipMatrixEvent],
     ) {
         send_occasional_slot_updates(session, events);
+        send_occasional_matrix_updates(session, matrix, events);
         send_continuous_slot_updates(session, events);
         send_continuous_matrix_updates(session);
         send_continuous_column_updates(session, matrix);
@@ -412,6 +413,33 @@ fn send_occasional_slot_updates(session: &Session, events: &[ClipMatrixEvent]) {
     }
 }
 
+fn send_occasional_matrix_updates(
+    session: &Session,
+    matrix: &RealearnClipMatrix,
+    events: &[ClipMatrixEvent],
+) {
+    let sender = App::get().occasional_matrix_update_sender();
+    if sender.receiver_count() == 0 {
+        return;
+    }
+    let history_changed = events
+        .iter()
+        .any(|event| matches!(event, ClipMatrixEvent::HistoryChanged));
+    if !history_changed {
+        return;
+    }
+    let update = occasional_matrix_update::Update::History(HistoryState::from_engine(
+        matrix.history_entries(),
+    ));
+    let batch_event = OccasionalMatrixUpdateBatch {
+        session_id: session.id().to_owned(),
+        value: vec![OccasionalMatrixUpdate {
+            update: Some(update),
+        }],
+    };
+    let _ = sender.send(batch_event);
+}
+
 fn send_occasional_track_updates(
     session: &Session,
     matrix: &RealearnClipMatrix,
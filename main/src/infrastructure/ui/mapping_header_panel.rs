@@ -12,7 +12,9 @@ use crate::application::{
     GroupCommand, GroupModel, MappingCommand, MappingModel, ModifierConditionModel, Session,
     SharedSession, WeakSession,
 };
-use crate::domain::{compartment_param_index_iter, Compartment, CompartmentParamIndex, Tag};
+use crate::domain::{
+    compartment_param_index_iter, Compartment, CompartmentParamIndex, MappingKey, Tag,
+};
 use std::fmt::Debug;
 use swell_ui::{DialogUnits, Point, SharedView, View, ViewContext, Window};
 
@@ -26,6 +28,8 @@ pub struct MappingHeaderPanel {
     item: RefCell<Option<WeakItem>>,
     is_invoked_programmatically: Cell<bool>,
     position: Point<DialogUnits>,
+    /// Keys of the mappings currently offered as lead mapping (in combo box order).
+    lead_mapping_candidates: RefCell<Vec<MappingKey>>,
 }
 
 pub trait Item: Debug {
@@ -50,6 +54,8 @@ pub trait Item: Debug {
     fn set_bank_condition(&mut self, session: WeakSession, value: BankConditionModel);
     fn script(&self) -> &str;
     fn set_script(&mut self, session: WeakSession, value: String, initiator: u32);
+    fn lead_mapping(&self) -> Option<&MappingKey>;
+    fn set_lead_mapping(&mut self, session: WeakSession, value: Option<MappingKey>);
 }
 
 pub enum ItemProp {
@@ -62,6 +68,7 @@ pub enum ItemProp {
     ModifierCondition2,
     BankCondition,
     Script,
+    LeadMapping,
 }
 
 impl ItemProp {
@@ -73,6 +80,7 @@ impl ItemProp {
             S::ModifierCondition2 => Self::ModifierCondition2,
             S::BankCondition => Self::BankCondition,
             S::Script => Self::Script,
+            S::LeadMapping => Self::LeadMapping,
         }
     }
 }
@@ -89,6 +97,7 @@ impl MappingHeaderPanel {
             item: RefCell::new(initial_item),
             is_invoked_programmatically: false.into(),
             position,
+            lead_mapping_candidates: Default::default(),
         }
     }
 
@@ -200,7 +209,8 @@ impl MappingHeaderPanel {
             Always => None,
             Modifiers => Some(("Modifier A", "Modifier B")),
             Bank => Some(("Parameter", "Bank")),
-            Eel | Expression => None,
+            TargetValue => Some(("Mapping", "")),
+            Eel | Expression | Lua => None,
         };
         if let Some((first, second)) = label {
             self.view
@@ -235,10 +245,36 @@ impl MappingHeaderPanel {
                     compartment,
                 );
             }
+            TargetValue => {
+                self.fill_activation_setting_1_combo_box_with_mappings(compartment);
+            }
             _ => {}
         };
     }
 
+    fn fill_activation_setting_1_combo_box_with_mappings(&self, compartment: Compartment) {
+        let session = self.session();
+        let session = session.borrow();
+        let candidates: Vec<(MappingKey, String)> = session
+            .mappings(compartment)
+            .map(|m| {
+                let m = m.borrow();
+                (m.key().clone(), m.effective_name())
+            })
+            .collect();
+        let data = std::iter::once((-1isize, "<None>".to_string())).chain(
+            candidates
+                .iter()
+                .enumerate()
+                .map(|(i, (_, name))| (i as isize, format!("{}. {}", i + 1, name))),
+        );
+        self.view
+            .require_control(root::ID_MAPPING_ACTIVATION_SETTING_1_COMBO_BOX)
+            .fill_combo_box_with_data_vec(data.collect());
+        self.lead_mapping_candidates
+            .replace(candidates.into_iter().map(|(key, _)| key).collect());
+    }
+
     fn fill_activation_setting_2_combo_box_with_banks(&self, item: &dyn Item) {
         let bank_param_index = item.bank_condition().param_index();
         let session = self.session();
@@ -279,10 +315,17 @@ impl MappingHeaderPanel {
         );
         self.show_if(
             show && (activation_type == ActivationType::Modifiers
-                || activation_type == ActivationType::Bank),
+                || activation_type == ActivationType::Bank
+                || activation_type == ActivationType::TargetValue),
             &[
                 root::ID_MAPPING_ACTIVATION_SETTING_1_LABEL_TEXT,
                 root::ID_MAPPING_ACTIVATION_SETTING_1_COMBO_BOX,
+            ],
+        );
+        self.show_if(
+            show && (activation_type == ActivationType::Modifiers
+                || activation_type == ActivationType::Bank),
+            &[
                 root::ID_MAPPING_ACTIVATION_SETTING_2_LABEL_TEXT,
                 root::ID_MAPPING_ACTIVATION_SETTING_2_COMBO_BOX,
             ],
@@ -298,6 +341,8 @@ impl MappingHeaderPanel {
             match activation_type {
                 ActivationType::Eel => Some("EEL (e.g. y = p1 > 0)"),
                 ActivationType::Expression => Some("e.g. p[0] == 2"),
+                ActivationType::Lua => Some("Lua (e.g. return p1 > 0)"),
+                ActivationType::TargetValue => Some("e.g. y > 0"),
                 _ => None,
             }
         } else {
@@ -342,6 +387,32 @@ impl MappingHeaderPanel {
                     .select_combo_box_item_by_index(param_index.get() as _)
                     .unwrap();
             }
+            TargetValue => {
+                let combo_box = self
+                    .view
+                    .require_control(root::ID_MAPPING_ACTIVATION_SETTING_1_COMBO_BOX);
+                match item.lead_mapping() {
+                    None => {
+                        combo_box.select_combo_box_item_by_data(-1).unwrap();
+                    }
+                    Some(key) => {
+                        let position = self
+                            .lead_mapping_candidates
+                            .borrow()
+                            .iter()
+                            .position(|k| k == key);
+                        match position {
+                            None => {
+                                combo_box
+                                    .select_new_combo_box_item(format!("<Not present> ({})", key));
+                            }
+                            Some(i) => {
+                                combo_box.select_combo_box_item_by_data(i as _).unwrap();
+                            }
+                        }
+                    }
+                }
+            }
             _ => {}
         };
     }
@@ -495,6 +566,20 @@ impl MappingHeaderPanel {
                     .unwrap();
                 item.set_bank_condition(session, item.bank_condition().with_param_index(index));
             }
+            TargetValue => {
+                let b = self
+                    .view
+                    .require_control(root::ID_MAPPING_ACTIVATION_SETTING_1_COMBO_BOX);
+                let key = match b.selected_combo_box_item_data() {
+                    -1 => None,
+                    i => self
+                        .lead_mapping_candidates
+                        .borrow()
+                        .get(i as usize)
+                        .cloned(),
+                };
+                item.set_lead_mapping(session, key);
+            }
             _ => {}
         };
     }
@@ -589,6 +674,7 @@ impl MappingHeaderPanel {
                         self.invalidate_activation_setting_2_controls(item);
                     }
                     Script => self.invalidate_activation_script_edit_control(item, initiator),
+                    LeadMapping => self.invalidate_activation_setting_1_controls(item),
                 };
             });
         });
@@ -859,6 +945,21 @@ impl Item for MappingModel {
             Some(initiator),
         );
     }
+
+    fn lead_mapping(&self) -> Option<&MappingKey> {
+        self.activation_condition_model().lead_mapping()
+    }
+
+    fn set_lead_mapping(&mut self, session: WeakSession, value: Option<MappingKey>) {
+        Session::change_mapping_from_ui_simple(
+            session,
+            self,
+            MappingCommand::ChangeActivationCondition(ActivationConditionCommand::SetLeadMapping(
+                value,
+            )),
+            None,
+        );
+    }
 }
 
 impl Item for GroupModel {
@@ -998,4 +1099,19 @@ impl Item for GroupModel {
             Some(initiator),
         );
     }
+
+    fn lead_mapping(&self) -> Option<&MappingKey> {
+        self.activation_condition_model().lead_mapping()
+    }
+
+    fn set_lead_mapping(&mut self, session: WeakSession, value: Option<MappingKey>) {
+        Session::change_group_from_ui_simple(
+            session,
+            self,
+            GroupCommand::ChangeActivationCondition(ActivationConditionCommand::SetLeadMapping(
+                value,
+            )),
+            None,
+        );
+    }
 }
@@ -218,6 +218,9 @@ impl MappingPanel {
                                     view.invalidate_mapping_feedback_send_behavior_combo_box();
                                 }
                                 P::GroupId => {}
+                                P::AdditionalTargets => {
+                                    view.invalidate_window_title();
+                                }
                                 P::InActivationCondition(p) => match p {
                                     Multiple => {
                                         view.panel.mapping_header_panel.invalidate_controls();
@@ -291,6 +294,9 @@ impl MappingPanel {
                                             P::RawMidiPattern | P::TimerMillis => {
                                                 view.invalidate_source_line_3_edit_control(initiator);
                                             }
+                                            P::Lfo | P::StepSequencer => {
+                                                // No representation in GUI at the moment.
+                                            }
                                             P::MidiScriptKind => {
                                                 view.invalidate_source_line_3(initiator);
                                             }
@@ -388,7 +394,13 @@ impl MappingPanel {
                                             P::FeedbackColor | P::FeedbackBackgroundColor => {
                                                 view.invalidate_mode_feedback_type_button();
                                             }
-                                            P::FeedbackValueTable => {
+                                            P::FeedbackValueTable
+                                            | P::ControlTransformationKind
+                                            | P::FeedbackTransformationKind
+                                            | P::Curve
+                                            | P::FeedbackAnimation
+                                            | P::Acceleration
+                                            | P::PressGesture => {
                                                 // No representation in GUI at the moment.
                                             }
                                         }
@@ -3038,9 +3050,22 @@ impl<'a> ImmutableMappingPanel<'a> {
     }
 
     fn invalidate_window_title(&self) {
-        self.view
-            .require_window()
-            .set_text(format!("Mapping \"{}\"", self.mapping.effective_name()));
+        let name = self.mapping.effective_name();
+        let additional_targets = self.mapping.additional_targets();
+        let feedback_target_count = additional_targets
+            .iter()
+            .filter(|t| t.is_feedback_target)
+            .count();
+        // Additional targets can't be edited in the GUI yet, so at least show that they exist.
+        let title = match additional_targets.len() {
+            0 => format!("Mapping \"{}\"", name),
+            count if feedback_target_count > 1 => format!(
+                "Mapping \"{}\" (+ {} additional targets, WARNING: only the first of {} feedback targets is used)",
+                name, count, feedback_target_count
+            ),
+            count => format!("Mapping \"{}\" (+ {} additional targets)", name, count),
+        };
+        self.view.require_window().set_text(title);
     }
 
     fn invalidate_mapping_feedback_send_behavior_combo_box(&self) {
@@ -17,7 +17,6 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
-// TODO-medium Add start time detection
 // TODO-medium Add legato
 
 /// Only used for JSON schema generation.
@@ -242,6 +241,11 @@ pub struct MatrixClipRecordAudioSettings {
     /// Makes the global record button work for audio by allowing global input detection.
     // TODO-clip-implement
     pub detect_input: bool,
+    #[serde(default)]
+    pub record_mode: AudioClipRecordMode,
+    /// How much of the existing material is kept when overdubbing an audio clip.
+    #[serde(default)]
+    pub overdub_feedback: OverdubFeedback,
 }
 
 #[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
@@ -383,6 +387,25 @@ impl Default for MidiClipRecordMode {
     }
 }
 
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind")]
+pub enum AudioClipRecordMode {
+    /// Records new audio material, replacing the existing clip (which is kept as take).
+    Normal,
+    /// Mixes the incoming audio into the existing clip while it's playing, just like a looper
+    /// pedal. The result is written to a new file when overdubbing stops.
+    ///
+    /// Only works if the audio material is cached in memory. Falls back to Normal when used on an
+    /// empty slot.
+    Overdub,
+}
+
+impl Default for AudioClipRecordMode {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "kind")]
 pub enum ClipPlayStartTiming {
@@ -755,9 +778,12 @@ impl Default for RecordOrigin {
 pub struct Slot {
     /// Slot index within the column (= row), starting at zero.
     pub row: usize,
-    /// Clip which currently lives in this slot.
+    /// Clip which currently lives in this slot (the active take).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub clip: Option<Clip>,
+    /// Previously recorded takes which are currently not active, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub takes: Vec<Clip>,
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
@@ -787,8 +813,17 @@ pub struct Clip {
     pub stop_timing: Option<ClipPlayStopTiming>,
     /// Whether the clip should be played repeatedly or as a single shot.
     pub looped: bool,
+    /// Whether the clip should be played backwards.
+    #[serde(default)]
+    pub reversed: bool,
     /// Relative volume adjustment of clip.
     pub volume: Db,
+    /// Transposition of clip, independent of its tempo.
+    ///
+    /// MIDI clips are transposed by shifting their notes (rounded to whole semitones), audio clips
+    /// by pitch shifting.
+    #[serde(default)]
+    pub pitch: ClipPitch,
     /// Color of the clip.
     // TODO-clip-implement
     pub color: ClipColor,
@@ -796,16 +831,20 @@ pub struct Clip {
     pub section: Section,
     pub audio_settings: ClipAudioSettings,
     pub midi_settings: ClipMidiSettings,
-    // /// Defines the total amount of time this clip should consume and where within that range the
-    // /// portion of the original source is located.
-    // ///
-    // /// This allows one to insert silence at the beginning and the end
-    // /// as well as changing the source section without affecting beat
-    // /// alignment.
-    // ///
-    // /// `None` means the canvas will have the same size as the source
-    // /// section.
-    // canvas: Option<Canvas>,
+    /// Defines the total amount of time this clip should consume and where within that range the
+    /// portion of the original source is located.
+    ///
+    /// This allows one to insert silence at the beginning and the end
+    /// as well as changing the source section without affecting beat
+    /// alignment.
+    ///
+    /// `None` means the canvas will have the same size as the source
+    /// section.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub canvas: Option<Canvas>,
+    /// Volume and pan changes over the course of one loop cycle.
+    #[serde(default)]
+    pub envelopes: ClipEnvelopes,
 }
 
 #[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
@@ -850,6 +889,9 @@ pub struct ClipAudioSettings {
     /// `None` means it uses the column cache behavior.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cache_behavior: Option<AudioCacheBehavior>,
+    /// Lengths and shape of the fades applied to this clip.
+    #[serde(default)]
+    pub fades: ClipAudioFades,
 }
 
 impl Default for ClipAudioSettings {
@@ -859,10 +901,61 @@ impl Default for ClipAudioSettings {
             apply_source_fades: true,
             time_stretch_mode: None,
             resample_mode: None,
+            fades: Default::default(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ClipAudioFades {
+    /// Length of the fade-in at the start of the section or source.
+    ///
+    /// Applied to the source only if `apply_source_fades` is enabled.
+    pub fade_in_length: PositiveSecond,
+    /// Length of the fade-out at the end of the section or source.
+    ///
+    /// Applied to the source only if `apply_source_fades` is enabled.
+    pub fade_out_length: PositiveSecond,
+    /// Length of the fades applied when starting or stopping the clip in the middle of the
+    /// material (e.g. immediate stop or resume after pause).
+    pub interaction_fade_length: PositiveSecond,
+    /// Curve shape of all fades.
+    pub shape: AudioFadeShape,
+}
+
+impl Default for ClipAudioFades {
+    fn default() -> Self {
+        Self {
+            fade_in_length: ClipAudioFades::DEFAULT_LENGTH,
+            fade_out_length: ClipAudioFades::DEFAULT_LENGTH,
+            interaction_fade_length: ClipAudioFades::DEFAULT_LENGTH,
+            shape: Default::default(),
         }
     }
 }
 
+impl ClipAudioFades {
+    /// 5 milliseconds, just enough to prevent clicks.
+    const DEFAULT_LENGTH: PositiveSecond = PositiveSecond(0.005);
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+pub enum AudioFadeShape {
+    /// Volume changes linearly.
+    Linear,
+    /// Keeps the perceived loudness constant when crossfading uncorrelated material.
+    EqualPower,
+    /// Starts and ends slowly, changes fastest in the middle.
+    SCurve,
+}
+
+impl Default for AudioFadeShape {
+    fn default() -> Self {
+        Self::Linear
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Debug, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct ClipMidiSettings {
@@ -874,6 +967,90 @@ pub struct ClipMidiSettings {
     pub loop_reset_settings: MidiResetMessageRange,
     /// For fine-tuning instant start/stop of a MIDI clip when in the middle of a source or section.
     pub interaction_reset_settings: MidiResetMessageRange,
+    /// Changes the MIDI material on playback.
+    #[serde(default)]
+    pub transformation: MidiTransformation,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MidiTransformation {
+    /// Bends note-on velocities before they are scaled.
+    ///
+    /// 0.0 is linear. Positive values (up to 1.0) make soft notes softer, negative values (down
+    /// to -1.0) make them louder.
+    #[serde(default)]
+    pub velocity_curve: f64,
+    /// Factor by which note-on velocities are multiplied.
+    #[serde(default = "default_velocity_scale")]
+    pub velocity_scale: f64,
+    /// Value which is added to note-on velocities after scaling.
+    #[serde(default)]
+    pub velocity_offset: i32,
+    /// If set, all channel messages are sent on this channel (0 - 15).
+    #[serde(default)]
+    pub output_channel: Option<u8>,
+    /// If set, notes outside of this range are filtered out.
+    #[serde(default)]
+    pub note_range: Option<MidiNoteRange>,
+    /// If set, each note ends after this length, no matter how long it is in the source.
+    #[serde(default)]
+    pub fixed_note_length: Option<PositiveBeat>,
+}
+
+impl Default for MidiTransformation {
+    fn default() -> Self {
+        Self {
+            velocity_curve: 0.0,
+            velocity_scale: 1.0,
+            velocity_offset: 0,
+            output_channel: None,
+            note_range: None,
+            fixed_note_length: None,
+        }
+    }
+}
+
+impl MidiTransformation {
+    /// Maximum velocity scale factor.
+    pub const MAX_VELOCITY_SCALE: f64 = 2.0;
+
+    /// Returns `true` if this transformation doesn't change anything.
+    pub fn is_neutral(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Applies curve, scale and offset to the given note-on velocity (1 - 127).
+    ///
+    /// The result is at least 1 because a velocity of 0 would turn the note-on into a note-off.
+    pub fn transform_velocity(&self, velocity: u8) -> u8 {
+        let normalized = velocity as f64 / 127.0;
+        let exponent = 4.0f64.powf(self.velocity_curve.clamp(-1.0, 1.0));
+        let curved = normalized.powf(exponent) * 127.0;
+        let transformed = (curved * self.velocity_scale).round() + self.velocity_offset as f64;
+        transformed.clamp(1.0, 127.0) as u8
+    }
+
+    /// Returns whether notes with the given key number pass the note range filter.
+    pub fn lets_note_pass(&self, key_number: u8) -> bool {
+        match self.note_range {
+            None => true,
+            Some(r) => (r.min..=r.max).contains(&key_number),
+        }
+    }
+}
+
+fn default_velocity_scale() -> f64 {
+    1.0
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MidiNoteRange {
+    /// Lowest key number which passes (0 - 127).
+    pub min: u8,
+    /// Highest key number which passes (0 - 127).
+    pub max: u8,
 }
 
 #[derive(Copy, Clone, PartialEq, Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -924,6 +1101,23 @@ pub struct Section {
     pub length: Option<PositiveSecond>,
 }
 
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Canvas {
+    /// Position within the canvas at which the source section starts.
+    ///
+    /// Everything before is silence.
+    pub section_pos: PositiveSecond,
+    /// Total length of the canvas, starting from the canvas start.
+    ///
+    /// - `None` means until the end of the source section.
+    /// - If this exceeds the end of the source section, the rest is filled with silence.
+    /// - If this makes the canvas end be located before the end of the source section, the
+    ///   section will be cut off.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<PositiveSecond>,
+}
+
 #[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "kind")]
 pub enum AudioCacheBehavior {
@@ -1087,6 +1281,34 @@ impl PositiveBeat {
     }
 }
 
+/// Factor by which the existing material is attenuated whenever new material is overdubbed onto
+/// it.
+///
+/// 1.0 keeps the existing material as it is, 0.0 replaces it with the new material.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct OverdubFeedback(f64);
+
+impl OverdubFeedback {
+    pub const MAX: OverdubFeedback = OverdubFeedback(1.0);
+
+    pub fn new(value: f64) -> PlaytimeApiResult<Self> {
+        if !(0.0..=1.0).contains(&value) {
+            return Err("overdub feedback must be between 0.0 and 1.0");
+        }
+        Ok(Self(value))
+    }
+
+    pub const fn get(&self) -> f64 {
+        self.0
+    }
+}
+
+impl Default for OverdubFeedback {
+    fn default() -> Self {
+        Self::MAX
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Db(f64);
 
@@ -1105,7 +1327,335 @@ impl Db {
     }
 }
 
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ClipPitch {
+    /// Transposition in semitones.
+    pub semitones: i32,
+    /// Fine adjustment in cents (hundredths of a semitone).
+    pub cents: i32,
+}
+
+impl ClipPitch {
+    pub const ZERO: ClipPitch = ClipPitch {
+        semitones: 0,
+        cents: 0,
+    };
+
+    /// Maximum transposition in each direction, in semitones.
+    pub const MAX_SEMITONES: f64 = 24.0;
+
+    /// Creates a pitch from a fractional number of semitones, rounded to whole cents.
+    pub fn from_semitones(value: f64) -> PlaytimeApiResult<Self> {
+        if value.is_nan() {
+            return Err("pitch must not be NaN");
+        }
+        let value = value.clamp(-Self::MAX_SEMITONES, Self::MAX_SEMITONES);
+        let total_cents = (value * 100.0).round() as i32;
+        let pitch = Self {
+            semitones: total_cents / 100,
+            cents: total_cents % 100,
+        };
+        Ok(pitch)
+    }
+
+    /// Returns the complete transposition as fractional number of semitones.
+    pub fn in_semitones(&self) -> f64 {
+        self.semitones as f64 + self.cents as f64 / 100.0
+    }
+
+    /// Returns the frequency factor which corresponds to this transposition.
+    pub fn frequency_factor(&self) -> f64 {
+        2.0f64.powf(self.in_semitones() / 12.0)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.in_semitones() == 0.0
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ClipEnvelopes {
+    /// Volume in dB.
+    ///
+    /// As soon as this envelope has points, it overrides the clip volume.
+    #[serde(default)]
+    pub volume: ClipEnvelope,
+    /// Pan from -1.0 (left) to 1.0 (right).
+    #[serde(default)]
+    pub pan: ClipEnvelope,
+    /// Whether envelopes are just played back or also written.
+    #[serde(default)]
+    pub mode: ClipEnvelopeMode,
+}
+
+impl ClipEnvelopes {
+    pub fn is_empty(&self) -> bool {
+        self.volume.is_empty() && self.pan.is_empty()
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+pub enum ClipEnvelopeMode {
+    /// Envelopes are played back.
+    Read,
+    /// Envelopes are played back and changes of the clip volume during playback are written into
+    /// the volume envelope.
+    Write,
+}
+
+impl Default for ClipEnvelopeMode {
+    fn default() -> Self {
+        Self::Read
+    }
+}
+
+/// Breakpoint envelope whose positions are relative to the start of the loop cycle.
+///
+/// Values between points are interpolated linearly. Before the first point, the envelope has the
+/// value of the first point, after the last point the one of the last point.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ClipEnvelope {
+    /// Points sorted by position.
+    pub points: Vec<ClipEnvelopePoint>,
+}
+
+impl ClipEnvelope {
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Returns the value at the given position in beats or `None` if the envelope has no points.
+    pub fn value_at(&self, pos: f64) -> Option<f64> {
+        let first = self.points.first()?;
+        let next_index = self.points.partition_point(|p| p.pos.get() <= pos);
+        if next_index == 0 {
+            return Some(first.value);
+        }
+        let prev = &self.points[next_index - 1];
+        let next = match self.points.get(next_index) {
+            None => return Some(prev.value),
+            Some(p) => p,
+        };
+        let distance = next.pos.get() - prev.pos.get();
+        let ratio = (pos - prev.pos.get()) / distance;
+        Some(prev.value + (next.value - prev.value) * ratio)
+    }
+
+    /// Writes a point at the given position.
+    ///
+    /// Points between the given previous write position (exclusive) and the new position
+    /// (inclusive) are replaced. This overwrites the existing curve while moving forward.
+    pub fn write_point(&mut self, pos: PositiveBeat, value: f64, prev_write_pos: Option<f64>) {
+        let pos_value = pos.get();
+        let replaced_from = prev_write_pos.filter(|p| *p <= pos_value);
+        self.points.retain(|p| {
+            let p = p.pos.get();
+            match replaced_from {
+                None => p != pos_value,
+                Some(from) => p <= from || p > pos_value,
+            }
+        });
+        let index = self.points.partition_point(|p| p.pos.get() < pos_value);
+        self.points.insert(index, ClipEnvelopePoint { pos, value });
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ClipEnvelopePoint {
+    /// Position in beats.
+    pub pos: PositiveBeat,
+    pub value: f64,
+}
+
 #[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct RgbColor(pub u8, pub u8, pub u8);
 
 type PlaytimeApiResult<T> = Result<T, &'static str>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neutral_velocity_transformation() {
+        // Given
+        let transformation = MidiTransformation::default();
+        // When
+        let velocities: Vec<_> = (1..=127)
+            .map(|v| transformation.transform_velocity(v))
+            .collect();
+        // Then
+        assert_eq!(velocities, (1..=127).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn scale_and_offset_velocity() {
+        // Given
+        let transformation = MidiTransformation {
+            velocity_scale: 0.5,
+            velocity_offset: 10,
+            ..Default::default()
+        };
+        // When
+        let velocity = transformation.transform_velocity(100);
+        // Then
+        assert_eq!(velocity, 60);
+    }
+
+    #[test]
+    fn curve_velocity() {
+        // Given
+        let softer = MidiTransformation {
+            velocity_curve: 1.0,
+            ..Default::default()
+        };
+        let louder = MidiTransformation {
+            velocity_curve: -1.0,
+            ..Default::default()
+        };
+        // When
+        let soft_velocity = softer.transform_velocity(64);
+        let loud_velocity = louder.transform_velocity(64);
+        // Then
+        assert!(soft_velocity < 64);
+        assert!(loud_velocity > 64);
+        // Extremes stay where they are.
+        assert_eq!(softer.transform_velocity(127), 127);
+        assert_eq!(louder.transform_velocity(127), 127);
+    }
+
+    #[test]
+    fn keep_velocity_within_valid_range() {
+        // Given
+        let silent = MidiTransformation {
+            velocity_scale: 0.0,
+            ..Default::default()
+        };
+        let loud = MidiTransformation {
+            velocity_scale: MidiTransformation::MAX_VELOCITY_SCALE,
+            velocity_offset: 100,
+            ..Default::default()
+        };
+        // When
+        let silent_velocity = silent.transform_velocity(100);
+        let loud_velocity = loud.transform_velocity(100);
+        // Then
+        // A velocity of 0 would turn the note-on into a note-off.
+        assert_eq!(silent_velocity, 1);
+        assert_eq!(loud_velocity, 127);
+    }
+
+    #[test]
+    fn let_all_notes_pass_without_note_range() {
+        // Given
+        let transformation = MidiTransformation::default();
+        // When
+        let all_notes_pass = (0..=127).all(|key| transformation.lets_note_pass(key));
+        // Then
+        assert!(all_notes_pass);
+    }
+
+    #[test]
+    fn filter_notes_outside_note_range() {
+        // Given
+        let transformation = MidiTransformation {
+            note_range: Some(MidiNoteRange { min: 36, max: 48 }),
+            ..Default::default()
+        };
+        // When
+        let passing_keys: Vec<_> = (0..=127)
+            .filter(|key| transformation.lets_note_pass(*key))
+            .collect();
+        // Then
+        assert_eq!(passing_keys, (36..=48).collect::<Vec<_>>());
+    }
+
+    fn envelope(points: &[(f64, f64)]) -> ClipEnvelope {
+        ClipEnvelope {
+            points: points
+                .iter()
+                .map(|(pos, value)| ClipEnvelopePoint {
+                    pos: PositiveBeat::new(*pos).unwrap(),
+                    value: *value,
+                })
+                .collect(),
+        }
+    }
+
+    fn positions(envelope: &ClipEnvelope) -> Vec<f64> {
+        envelope.points.iter().map(|p| p.pos.get()).collect()
+    }
+
+    #[test]
+    fn empty_envelope_has_no_value() {
+        // Given
+        let envelope = ClipEnvelope::default();
+        // When
+        let value = envelope.value_at(1.0);
+        // Then
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    fn interpolate_envelope_values() {
+        // Given
+        let envelope = envelope(&[(1.0, -10.0), (3.0, 0.0)]);
+        // When
+        let values: Vec<_> = [0.0, 1.0, 2.0, 3.0, 5.0]
+            .iter()
+            .map(|pos| envelope.value_at(*pos).unwrap())
+            .collect();
+        // Then
+        // Before the first and after the last point, the value stays constant.
+        assert_eq!(values, vec![-10.0, -10.0, -5.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn write_points_in_order() {
+        // Given
+        let mut envelope = envelope(&[(1.0, 0.0), (3.0, 0.0)]);
+        // When
+        envelope.write_point(PositiveBeat::new(2.0).unwrap(), -6.0, None);
+        envelope.write_point(PositiveBeat::new(0.5).unwrap(), -3.0, None);
+        // Then
+        assert_eq!(positions(&envelope), vec![0.5, 1.0, 2.0, 3.0]);
+        assert_eq!(envelope.value_at(2.0), Some(-6.0));
+    }
+
+    #[test]
+    fn overwrite_point_at_same_position() {
+        // Given
+        let mut envelope = envelope(&[(1.0, 0.0)]);
+        // When
+        envelope.write_point(PositiveBeat::new(1.0).unwrap(), -6.0, None);
+        // Then
+        assert_eq!(positions(&envelope), vec![1.0]);
+        assert_eq!(envelope.value_at(1.0), Some(-6.0));
+    }
+
+    #[test]
+    fn replace_points_passed_while_writing() {
+        // Given
+        let mut envelope = envelope(&[(1.0, 0.0), (1.5, 0.0), (2.0, 0.0), (2.5, 0.0)]);
+        // When
+        envelope.write_point(PositiveBeat::new(2.0).unwrap(), -6.0, Some(1.0));
+        // Then
+        // The point at the previous write position stays, the ones up to the new one are gone.
+        assert_eq!(positions(&envelope), vec![1.0, 2.0, 2.5]);
+        assert_eq!(envelope.value_at(2.0), Some(-6.0));
+    }
+
+    #[test]
+    fn dont_replace_points_after_jumping_back() {
+        // Given
+        let mut envelope = envelope(&[(1.0, 0.0), (2.0, 0.0), (3.0, 0.0)]);
+        // When
+        envelope.write_point(PositiveBeat::new(1.5).unwrap(), -6.0, Some(3.0));
+        // Then
+        assert_eq!(positions(&envelope), vec![1.0, 1.5, 2.0, 3.0]);
+    }
+}
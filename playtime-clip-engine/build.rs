@@ -0,0 +1,23 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/proto/clip_engine.proto");
+
+    // Optionally generate gRPC code
+    #[cfg(feature = "generate")]
+    codegen::generate_proto();
+}
+
+#[cfg(feature = "generate")]
+mod codegen {
+    /// Generates the gRPC server code from the protocol buffer definition.
+    pub fn generate_proto() {
+        tonic_build::configure()
+            .build_client(false)
+            .out_dir("src/proto")
+            .compile(&["src/proto/clip_engine.proto"], &["src/proto"])
+            .expect("couldn't generate gRPC code");
+        // tonic-build names the file after the package.
+        std::fs::rename("src/proto/playtime.clip_engine.rs", "src/proto/clip_engine.rs")
+            .expect("couldn't rename generated gRPC code");
+    }
+}
@@ -0,0 +1,280 @@
+use crate::rt::buffer::{AudioBuf, OwnedAudioBuffer};
+use crate::rt::supplier::{
+    AudioSupplier, ClipSource, MaterialInfo, SupplyAudioRequest, SupplyRequestInfo,
+    WithMaterialInfo,
+};
+use crate::ClipEngineResult;
+use playtime_api::persistence::TempoRange;
+use reaper_medium::{Bpm, DurationInSeconds, Hz};
+
+/// Result of analyzing audio material.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AudioAnalysis {
+    /// Position of the first transient, relative to the start of the source.
+    pub start_pos: DurationInSeconds,
+    /// Estimated tempo, if the material is rhythmic enough to make a guess.
+    pub tempo: Option<Bpm>,
+}
+
+/// Length of one analysis window.
+const HOP_DURATION: f64 = 0.01;
+/// Number of analysis windows which are read from the source at once.
+const HOPS_PER_CHUNK: usize = 100;
+/// A window counts as transient if its level exceeds the peak level multiplied by this factor
+/// (-20 dB).
+const TRANSIENT_THRESHOLD: f64 = 0.1;
+/// Windows below this fraction of the peak level are considered silence (-40 dB).
+const NOISE_FLOOR: f64 = 0.01;
+const MIN_TEMPO: f64 = 60.0;
+const MAX_TEMPO: f64 = 200.0;
+/// Tempo candidates are weighted by their distance (in octaves) from this tempo. Without that,
+/// accented beats often make us detect half the tempo.
+const MOST_LIKELY_TEMPO: f64 = 120.0;
+/// How far the material length may deviate from a whole number of bars (in beats) for the tempo
+/// to be snapped to that bar grid.
+const BAR_SNAP_TOLERANCE_IN_BEATS: f64 = 0.5;
+
+/// Reads the complete audio material of the given source and detects the first transient and the
+/// tempo. The tempo is folded into the given tempo range.
+///
+/// Reads the source chunk by chunk, so only the levels of the material are kept in memory. Still
+/// reads the whole source, so don't call this in a real-time thread.
+pub fn analyze_audio_source(
+    source: &mut ClipSource,
+    tempo_range: TempoRange,
+) -> ClipEngineResult<AudioAnalysis> {
+    let info = match source.material_info()? {
+        MaterialInfo::Audio(i) => i,
+        MaterialInfo::Midi(_) => return Err("start time detection only supported for audio"),
+    };
+    if info.frame_count == 0 || info.channel_count == 0 {
+        return Err("audio source is empty");
+    }
+    let hop_size = calculate_hop_size(info.frame_rate);
+    // A chunk contains whole windows only, so no window spans two chunks.
+    let mut chunk = OwnedAudioBuffer::new(info.channel_count, hop_size * HOPS_PER_CHUNK);
+    let mut levels = Vec::with_capacity(info.frame_count / hop_size + 1);
+    let mut start_frame = 0;
+    while start_frame < info.frame_count {
+        let frame_count = (info.frame_count - start_frame).min(chunk.to_buf().frame_count());
+        let mut dest_buffer = chunk.to_buf_mut();
+        let mut dest_buffer = dest_buffer.slice_mut(..frame_count);
+        let request = SupplyAudioRequest {
+            start_frame: start_frame as isize,
+            dest_sample_rate: None,
+            info: SupplyRequestInfo {
+                audio_block_frame_offset: 0,
+                requester: "analysis",
+                note: "",
+                is_realtime: false,
+            },
+            parent_request: None,
+            general_info: &Default::default(),
+        };
+        source.supply_audio(&request, &mut dest_buffer);
+        levels.extend(calculate_levels(
+            &chunk.to_buf().slice(..frame_count),
+            hop_size,
+        ));
+        start_frame += frame_count;
+    }
+    Ok(analyze_levels(
+        &levels,
+        hop_size,
+        info.frame_count,
+        info.frame_rate,
+        tempo_range,
+    ))
+}
+
+/// Detects the first transient and the tempo of the given audio material.
+pub fn analyze_audio_buffer(
+    buffer: AudioBuf,
+    frame_rate: Hz,
+    tempo_range: TempoRange,
+) -> AudioAnalysis {
+    let hop_size = calculate_hop_size(frame_rate);
+    let levels: Vec<f64> = calculate_levels(&buffer, hop_size).collect();
+    analyze_levels(
+        &levels,
+        hop_size,
+        buffer.frame_count(),
+        frame_rate,
+        tempo_range,
+    )
+}
+
+fn calculate_hop_size(frame_rate: Hz) -> usize {
+    ((frame_rate.get() * HOP_DURATION).round() as usize).max(1)
+}
+
+/// Detects the first transient and the tempo from the levels of consecutive windows.
+fn analyze_levels(
+    levels: &[f64],
+    hop_size: usize,
+    frame_count: usize,
+    frame_rate: Hz,
+    tempo_range: TempoRange,
+) -> AudioAnalysis {
+    let start_hop = detect_first_transient(levels).unwrap_or(0);
+    let hop_duration = hop_size as f64 / frame_rate.get();
+    let start_pos = start_hop as f64 * hop_duration;
+    let remaining_duration = frame_count as f64 / frame_rate.get() - start_pos;
+    let tempo = estimate_tempo(&levels[start_hop..], hop_duration, tempo_range)
+        .map(|t| Bpm::new(snap_tempo_to_bar_grid(t, remaining_duration)));
+    AudioAnalysis {
+        start_pos: DurationInSeconds::new(start_pos),
+        tempo,
+    }
+}
+
+/// Calculates the RMS level (of all channels mixed) for each window.
+fn calculate_levels<'a>(buffer: &'a AudioBuf, hop_size: usize) -> impl Iterator<Item = f64> + 'a {
+    let channel_count = buffer.channel_count();
+    buffer
+        .data_as_slice()
+        .chunks(hop_size * channel_count)
+        .map(|window| {
+            let frame_count = window.len() / channel_count;
+            let sum: f64 = window
+                .chunks(channel_count)
+                .map(|frame| {
+                    let mono = frame.iter().sum::<f64>() / channel_count as f64;
+                    mono * mono
+                })
+                .sum();
+            (sum / frame_count as f64).sqrt()
+        })
+}
+
+/// Returns the index of the window in which the first transient starts.
+///
+/// Doesn't just return the first window exceeding the threshold but walks back to where the
+/// level started rising, so we don't cut off the attack.
+fn detect_first_transient(levels: &[f64]) -> Option<usize> {
+    let peak = levels.iter().copied().fold(0.0, f64::max);
+    if peak == 0.0 {
+        return None;
+    }
+    let threshold_index = levels
+        .iter()
+        .position(|l| *l >= peak * TRANSIENT_THRESHOLD)?;
+    let mut index = threshold_index;
+    while index > 0 {
+        let previous = levels[index - 1];
+        if previous < peak * NOISE_FLOOR || previous >= levels[index] {
+            break;
+        }
+        index -= 1;
+    }
+    Some(index)
+}
+
+/// Estimates the tempo by auto-correlating the onset strength (positive level changes).
+fn estimate_tempo(levels: &[f64], hop_duration: f64, tempo_range: TempoRange) -> Option<f64> {
+    let onsets: Vec<f64> = levels.windows(2).map(|w| (w[1] - w[0]).max(0.0)).collect();
+    let min_lag = (60.0 / MAX_TEMPO / hop_duration).floor().max(1.0) as usize;
+    let max_lag = (60.0 / MIN_TEMPO / hop_duration).ceil() as usize;
+    if onsets.len() < max_lag * 2 {
+        return None;
+    }
+    let (best_lag, best_correlation) = (min_lag..=max_lag)
+        .map(|lag| {
+            let correlation: f64 = onsets.iter().zip(&onsets[lag..]).map(|(a, b)| a * b).sum();
+            let octaves = (60.0 / (lag as f64 * hop_duration) / MOST_LIKELY_TEMPO).log2();
+            (lag, correlation * (-0.5 * octaves * octaves).exp())
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+    if best_correlation <= 0.0 {
+        return None;
+    }
+    let mut tempo = 60.0 / (best_lag as f64 * hop_duration);
+    while tempo < tempo_range.min().get() {
+        tempo *= 2.0;
+    }
+    while tempo > tempo_range.max().get() {
+        tempo /= 2.0;
+    }
+    Some(tempo)
+}
+
+/// Loops from sample packs usually span a whole number of 4/4 bars. If the estimated tempo
+/// almost yields such a length, we take the exact tempo that makes it fit.
+fn snap_tempo_to_bar_grid(tempo: f64, duration: f64) -> f64 {
+    if duration <= 0.0 {
+        return tempo;
+    }
+    let beat_count = duration * tempo / 60.0;
+    let bar_count = (beat_count / 4.0).round();
+    if bar_count < 1.0 {
+        return tempo;
+    }
+    let snapped_beat_count = bar_count * 4.0;
+    if (beat_count - snapped_beat_count).abs() > BAR_SNAP_TOLERANCE_IN_BEATS {
+        return tempo;
+    }
+    snapped_beat_count * 60.0 / duration
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rt::supplier::InMemoryAudioSource;
+
+    /// Creates 8 decaying clicks at the given tempo, preceded by silence.
+    fn click_loop(frame_rate: f64, tempo: f64, lead_in_secs: f64) -> Vec<f64> {
+        let frames_per_beat = (frame_rate * 60.0 / tempo) as usize;
+        let lead_in_frames = (frame_rate * lead_in_secs) as usize;
+        let mut data = vec![0.0; lead_in_frames + frames_per_beat * 8];
+        for beat in 0..8 {
+            let start = lead_in_frames + beat * frames_per_beat;
+            for frame in 0..1000 {
+                data[start + frame] = 1.0 - frame as f64 / 1000.0;
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn detect_start_and_tempo_of_click_loop() {
+        // Given
+        let frame_rate = 48_000.0;
+        let tempo = 120.0;
+        let lead_in_secs = 0.25;
+        let data = click_loop(frame_rate, tempo, lead_in_secs);
+        let buffer = AudioBuf::from_slice(&data, 1, data.len()).unwrap();
+        // When
+        let analysis = analyze_audio_buffer(buffer, Hz::new(frame_rate), TempoRange::default());
+        // Then
+        assert!((analysis.start_pos.get() - lead_in_secs).abs() < HOP_DURATION);
+        assert_eq!(analysis.tempo.map(|t| t.get().round()), Some(tempo));
+    }
+
+    #[test]
+    fn analyze_source_chunk_by_chunk() {
+        // Given
+        let frame_rate = 48_000.0;
+        let data = click_loop(frame_rate, 120.0, 0.25);
+        let buffer = AudioBuf::from_slice(&data, 1, data.len()).unwrap();
+        let expected = analyze_audio_buffer(buffer, Hz::new(frame_rate), TempoRange::default());
+        let mut source = ClipSource::in_memory_audio(
+            InMemoryAudioSource::from_samples(data, 1, Hz::new(frame_rate)).unwrap(),
+        );
+        // When
+        let analysis = analyze_audio_source(&mut source, TempoRange::default()).unwrap();
+        // Then
+        assert_eq!(analysis, expected);
+    }
+
+    #[test]
+    fn silence_has_no_tempo() {
+        // Given
+        let data = vec![0.0; 48_000 * 4];
+        let buffer = AudioBuf::from_slice(&data, 1, data.len()).unwrap();
+        // When
+        let analysis = analyze_audio_buffer(buffer, Hz::new(48_000.0), TempoRange::default());
+        // Then
+        assert_eq!(analysis.start_pos.get(), 0.0);
+        assert_eq!(analysis.tempo, None);
+    }
+}
@@ -12,6 +12,8 @@ pub use timeline::*;
 
 mod source_util;
 
+mod analysis_util;
+
 mod file_util;
 
 mod conversion_util;
@@ -1,3 +1,4 @@
+use crate::analysis_util::{analyze_audio_source, AudioAnalysis};
 use crate::rt::supplier::{
     ChainEquipment, ClipSource, KindSpecificRecordingOutcome, RecorderRequest,
 };
@@ -9,9 +10,13 @@ use crate::source_util::{
 use crate::{rt, source_util, ClipEngineResult};
 use crossbeam_channel::Sender;
 use playtime_api::persistence as api;
-use playtime_api::persistence::{ClipColor, ClipTimeBase, Db, Section, SourceOrigin};
+use playtime_api::persistence::{
+    BeatTimeBase, Canvas, ClipColor, ClipTimeBase, Db, PositiveBeat, PositiveSecond, Section,
+    SourceOrigin, TempoRange, TimeSignature,
+};
 use reaper_high::{Project, Reaper, Track};
 use reaper_medium::Bpm;
+use std::path::Path;
 
 /// Describes a clip.
 ///
@@ -96,11 +101,15 @@ impl Clip {
             start_timing: self.processing_relevant_settings.start_timing,
             stop_timing: self.processing_relevant_settings.stop_timing,
             looped: self.processing_relevant_settings.looped,
+            reversed: self.processing_relevant_settings.reversed,
             volume: self.processing_relevant_settings.volume,
+            pitch: self.processing_relevant_settings.pitch,
             color: ClipColor::PlayTrackColor,
             section: self.processing_relevant_settings.section,
             audio_settings: self.processing_relevant_settings.audio_settings,
             midi_settings: self.processing_relevant_settings.midi_settings,
+            canvas: self.processing_relevant_settings.canvas,
+            envelopes: self.processing_relevant_settings.envelopes.clone(),
         };
         Ok(clip)
     }
@@ -134,6 +143,14 @@ impl Clip {
         Ok(())
     }
 
+    pub fn notify_audio_overdub_finished(
+        &mut self,
+        file: &Path,
+        temporary_project: Option<Project>,
+    ) {
+        self.source = create_file_api_source(temporary_project, file);
+    }
+
     pub fn api_source(&self) -> &api::Source {
         &self.source
     }
@@ -191,6 +208,16 @@ impl Clip {
         looped_new
     }
 
+    pub fn reversed(&self) -> bool {
+        self.processing_relevant_settings.reversed
+    }
+
+    pub fn toggle_reversed(&mut self) -> bool {
+        let reversed_new = !self.processing_relevant_settings.reversed;
+        self.processing_relevant_settings.reversed = reversed_new;
+        reversed_new
+    }
+
     pub fn set_volume(&mut self, volume: Db) {
         self.processing_relevant_settings.volume = volume;
     }
@@ -199,6 +226,47 @@ impl Clip {
         self.processing_relevant_settings.volume
     }
 
+    pub fn set_pitch(&mut self, pitch: api::ClipPitch) {
+        self.processing_relevant_settings.pitch = pitch;
+    }
+
+    pub fn pitch(&self) -> api::ClipPitch {
+        self.processing_relevant_settings.pitch
+    }
+
+    pub fn set_midi_transformation(&mut self, transformation: api::MidiTransformation) {
+        self.processing_relevant_settings
+            .midi_settings
+            .transformation = transformation;
+    }
+
+    pub fn midi_transformation(&self) -> api::MidiTransformation {
+        self.processing_relevant_settings
+            .midi_settings
+            .transformation
+    }
+
+    pub fn envelopes(&self) -> &api::ClipEnvelopes {
+        &self.processing_relevant_settings.envelopes
+    }
+
+    pub fn set_envelopes(&mut self, envelopes: api::ClipEnvelopes) {
+        self.processing_relevant_settings.envelopes = envelopes;
+    }
+
+    /// Writes the given volume into the volume envelope at the given position.
+    pub fn write_volume_envelope_point(
+        &mut self,
+        pos: PositiveBeat,
+        volume: Db,
+        prev_write_pos: Option<f64>,
+    ) {
+        self.processing_relevant_settings
+            .envelopes
+            .volume
+            .write_point(pos, volume.get(), prev_write_pos);
+    }
+
     pub fn tempo_factor(&self, timeline_tempo: Bpm, is_midi: bool) -> f64 {
         if let Some(tempo) = self.tempo(is_midi) {
             calc_tempo_factor(tempo, timeline_tempo)
@@ -219,6 +287,70 @@ impl Clip {
         self.processing_relevant_settings.section = section;
     }
 
+    pub fn canvas(&self) -> Option<Canvas> {
+        self.processing_relevant_settings.canvas
+    }
+
+    pub fn set_canvas(&mut self, canvas: Option<Canvas>) {
+        self.processing_relevant_settings.canvas = canvas;
+    }
+
+    /// Returns whether the material is taken as-is, that is, without section and with time base
+    /// "Time".
+    ///
+    /// That's usually the case for files which have just been imported.
+    pub fn takes_material_as_is(&self) -> bool {
+        let settings = &self.processing_relevant_settings;
+        settings.section.start_pos.get() == 0.0
+            && settings.section.length.is_none()
+            && matches!(settings.time_base, ClipTimeBase::Time)
+    }
+
+    /// Detects the first transient and the tempo of the (normal) source material.
+    ///
+    /// Only works for audio. Reads the complete source, so this can take a while.
+    pub fn analyze_audio(
+        &self,
+        temporary_project: Option<Project>,
+        tempo_range: TempoRange,
+    ) -> ClipEngineResult<AudioAnalysis> {
+        let mut source = self.create_pcm_source(temporary_project)?;
+        analyze_audio_source(&mut source, tempo_range)
+    }
+
+    /// Makes the section start at the detected first transient and, if a tempo has been detected,
+    /// switches to beat time base with the downbeat at the section start.
+    ///
+    /// If the section has a length, it's adjusted so that the section still ends at the same
+    /// position.
+    pub fn apply_audio_analysis(&mut self, analysis: AudioAnalysis) -> ClipEngineResult<()> {
+        let settings = &mut self.processing_relevant_settings;
+        let new_start_pos = analysis.start_pos.get();
+        if let Some(length) = settings.section.length {
+            let end_pos = settings.section.start_pos.get() + length.get();
+            if new_start_pos >= end_pos {
+                return Err("detected start lies behind the end of the section");
+            }
+            settings.section.length = Some(PositiveSecond::new(end_pos - new_start_pos)?);
+        }
+        settings.section.start_pos = PositiveSecond::new(new_start_pos)?;
+        if let Some(tempo) = analysis.tempo {
+            let time_signature = match settings.time_base {
+                ClipTimeBase::Beat(tb) => tb.time_signature,
+                ClipTimeBase::Time => TimeSignature {
+                    numerator: 4,
+                    denominator: 4,
+                },
+            };
+            settings.time_base = ClipTimeBase::Beat(BeatTimeBase {
+                audio_tempo: Some(api::Bpm::new(tempo.get())?),
+                time_signature,
+                downbeat: PositiveBeat::new(0.0)?,
+            });
+        }
+        Ok(())
+    }
+
     /// Returns `None` if time base is not "Beat".
     fn tempo(&self, is_midi: bool) -> Option<Bpm> {
         determine_tempo_from_time_base(&self.processing_relevant_settings.time_base, is_midi)
@@ -230,9 +362,92 @@ pub fn create_api_source_from_recorded_midi_source(
     temporary_project: Option<Project>,
 ) -> ClipEngineResult<api::Source> {
     let api_source = source_util::create_api_source_from_pcm_source(
-        midi_source.reaper_source(),
+        midi_source.reaper_source()?,
         CreateApiSourceMode::AllowEmbeddedData,
         temporary_project,
     );
     api_source.map_err(|_| "failed creating API source from mirror source")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reaper_medium::DurationInSeconds;
+
+    fn audio_clip(section: Section) -> Clip {
+        let api_clip = api::Clip {
+            name: None,
+            source: api::Source::File(api::FileSource {
+                path: "loop.wav".into(),
+            }),
+            frozen_source: None,
+            active_source: Default::default(),
+            time_base: ClipTimeBase::Time,
+            start_timing: None,
+            stop_timing: None,
+            looped: true,
+            reversed: false,
+            volume: Db::ZERO,
+            pitch: Default::default(),
+            color: ClipColor::PlayTrackColor,
+            section,
+            audio_settings: Default::default(),
+            midi_settings: Default::default(),
+            canvas: None,
+            envelopes: Default::default(),
+        };
+        Clip::load(api_clip)
+    }
+
+    fn analysis(start_pos: f64) -> AudioAnalysis {
+        AudioAnalysis {
+            start_pos: DurationInSeconds::new(start_pos),
+            tempo: Some(Bpm::new(120.0)),
+        }
+    }
+
+    #[test]
+    fn applying_analysis_keeps_section_end() {
+        // Given
+        let mut clip = audio_clip(Section {
+            start_pos: PositiveSecond::new(1.0).unwrap(),
+            length: Some(PositiveSecond::new(4.0).unwrap()),
+        });
+        // When
+        clip.apply_audio_analysis(analysis(1.5)).unwrap();
+        // Then
+        let section = clip.section();
+        assert_eq!(section.start_pos.get(), 1.5);
+        assert_eq!(section.length.map(|l| l.get()), Some(3.5));
+        assert!(!clip.takes_material_as_is());
+    }
+
+    #[test]
+    fn applying_analysis_keeps_open_section_open() {
+        // Given
+        let mut clip = audio_clip(Section {
+            start_pos: PositiveSecond::new(0.0).unwrap(),
+            length: None,
+        });
+        assert!(clip.takes_material_as_is());
+        // When
+        clip.apply_audio_analysis(analysis(0.25)).unwrap();
+        // Then
+        let section = clip.section();
+        assert_eq!(section.start_pos.get(), 0.25);
+        assert_eq!(section.length, None);
+    }
+
+    #[test]
+    fn applying_analysis_rejects_start_behind_section_end() {
+        // Given
+        let mut clip = audio_clip(Section {
+            start_pos: PositiveSecond::new(0.0).unwrap(),
+            length: Some(PositiveSecond::new(1.0).unwrap()),
+        });
+        // When
+        let result = clip.apply_audio_analysis(analysis(2.0));
+        // Then
+        assert!(result.is_err());
+    }
+}
@@ -1,11 +1,13 @@
+use crate::analysis_util::analyze_audio_source;
 use crate::main::{Clip, ClipMatrixHandler, MatrixSettings, Slot};
-use crate::rt::supplier::{ChainEquipment, RecorderRequest};
+use crate::rt::supplier::{AudioOverdubOutcome, ChainEquipment, RecorderRequest, WithMaterialInfo};
 use crate::rt::{
     ClipChangeEvent, ColumnCommandSender, ColumnEvent, ColumnFillSlotArgs, ColumnPlayClipArgs,
     ColumnPlayRowArgs, ColumnStopArgs, ColumnStopClipArgs, InternalClipPlayState,
     OverridableMatrixSettings, SharedColumn, WeakColumn,
 };
-use crate::{clip_timeline, rt, ClipEngineResult};
+use crate::source_util::create_pcm_source_from_api_source;
+use crate::{clip_timeline, rt, source_util, ClipEngineResult};
 use crossbeam_channel::{Receiver, Sender};
 use enumflags2::BitFlags;
 use helgoboss_learn::UnitValue;
@@ -21,6 +23,7 @@ use reaper_medium::{
     create_custom_owned_pcm_source, Bpm, CustomPcmSource, FlexibleOwnedPcmSource, HelpMode,
     MeasureAlignment, OwnedPreviewRegister, PositionInSeconds, ReaperMutex, ReaperVolumeValue,
 };
+use std::mem;
 use std::ptr::NonNull;
 use std::sync::Arc;
 
@@ -36,6 +39,7 @@ pub struct Column {
     slots: Vec<Slot>,
     event_receiver: Receiver<ColumnEvent>,
     project: Option<Project>,
+    finished_audio_overdubs: Vec<(usize, AudioOverdubOutcome)>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -68,6 +72,7 @@ impl Column {
             slots: vec![],
             event_receiver,
             project: permanent_project,
+            finished_audio_overdubs: vec![],
         }
     }
 
@@ -133,11 +138,27 @@ impl Column {
                     &self.rt_command_sender,
                     self.project,
                 )?;
+                slot.set_takes(api_slot.takes)?;
             }
         }
         Ok(())
     }
 
+    /// Makes the column play its clips on the given track.
+    pub fn set_playback_track(&mut self, track: Track) {
+        // Stop playing on the old track first. Otherwise the real-time column would be played by
+        // two preview registers for a short moment.
+        self.preview_register = None;
+        self.init_preview_register(Some(track));
+    }
+
+    /// Makes the column record from the given track. If `None`, it records from the playback
+    /// track.
+    pub fn set_recording_track(&mut self, track: Option<&Track>) {
+        self.settings.clip_record_settings.track =
+            track.map(|t| api::TrackId::new(t.guid().to_string_without_braces()));
+    }
+
     fn init_preview_register(&mut self, track: Option<Track>) {
         self.preview_register = Some(PlayingPreviewRegister::new(self.rt_column.clone(), track));
     }
@@ -264,6 +285,17 @@ impl Column {
                         None
                     }
                 }
+                AudioOverdubFinished {
+                    slot_index,
+                    outcome,
+                } => {
+                    if let Some(slot) = self.slots.get_mut(slot_index) {
+                        slot.notify_audio_overdub_finished();
+                        // Committed by the matrix because it changes the persistent state.
+                        self.finished_audio_overdubs.push((slot_index, outcome));
+                    }
+                    None
+                }
                 NormalRecordingFinished {
                     slot_index,
                     outcome,
@@ -315,10 +347,169 @@ impl Column {
         change_events
     }
 
+    /// Returns the audio overdubs which have finished since the last call, together with the
+    /// indexes of their slots.
+    pub fn take_finished_audio_overdubs(&mut self) -> Vec<(usize, AudioOverdubOutcome)> {
+        mem::take(&mut self.finished_audio_overdubs)
+    }
+
+    /// Writes the material of a finished audio overdub to a file and lets the clip use it.
+    pub fn commit_audio_overdub(
+        &mut self,
+        slot_index: usize,
+        outcome: AudioOverdubOutcome,
+    ) -> ClipEngineResult<ClipChangeEvent> {
+        get_slot_mut(&mut self.slots, slot_index)?.commit_audio_overdub(
+            outcome,
+            self.project,
+            &self.rt_command_sender,
+        )
+    }
+
     pub fn clear_slot(&self, slot_index: usize) {
         self.rt_command_sender.clear_slot(slot_index);
     }
 
+    /// Inserts an empty slot at the given index, shifting all following slots down by one.
+    pub fn insert_slot(&mut self, slot_index: usize) -> ClipEngineResult<()> {
+        self.ensure_not_recording()?;
+        if slot_index > self.slots.len() {
+            // Slot doesn't exist physically yet, so there's nothing to shift.
+            return Ok(());
+        }
+        self.slots.insert(slot_index, Slot::new(slot_index));
+        self.update_slot_indexes();
+        self.rt_command_sender.insert_slot(slot_index);
+        Ok(())
+    }
+
+    /// Removes the slot at the given index, shifting all following slots up by one.
+    pub fn remove_slot(&mut self, slot_index: usize) -> ClipEngineResult<()> {
+        self.ensure_not_recording()?;
+        if slot_index >= self.slots.len() {
+            return Ok(());
+        }
+        self.slots.remove(slot_index);
+        self.update_slot_indexes();
+        self.rt_command_sender.remove_slot(slot_index);
+        Ok(())
+    }
+
+    /// Moves the slot at the given index to another index, keeping its clip playing.
+    pub fn move_slot(&mut self, from_index: usize, to_index: usize) -> ClipEngineResult<()> {
+        self.ensure_not_recording()?;
+        if from_index == to_index {
+            return Ok(());
+        }
+        upsize_if_necessary(&mut self.slots, from_index.max(to_index) + 1);
+        let slot = self.slots.remove(from_index);
+        self.slots.insert(to_index, slot);
+        self.update_slot_indexes();
+        self.rt_command_sender.move_slot(from_index, to_index);
+        Ok(())
+    }
+
+    /// Inserts a new slot right after the given one, containing an independent copy of its clip
+    /// and takes.
+    ///
+    /// In-project MIDI is copied as well, including unsaved changes made in the MIDI editor.
+    pub fn duplicate_slot(
+        &mut self,
+        slot_index: usize,
+        chain_equipment: &ChainEquipment,
+        recorder_request_sender: &Sender<RecorderRequest>,
+        matrix_settings: &MatrixSettings,
+    ) -> ClipEngineResult<()> {
+        let api_clip = self.save_clip(slot_index).ok();
+        let takes = self.save_takes(slot_index);
+        let duplicate_index = slot_index + 1;
+        self.insert_slot(duplicate_index)?;
+        if let Some(api_clip) = api_clip {
+            self.fill_slot_with_clip(
+                duplicate_index,
+                api_clip,
+                chain_equipment,
+                recorder_request_sender,
+                matrix_settings,
+            )?;
+            self.set_takes(duplicate_index, takes)?;
+        }
+        Ok(())
+    }
+
+    /// Swaps the contents of the given slots. Playing clips keep playing.
+    pub fn swap_slots(&mut self, index_a: usize, index_b: usize) -> ClipEngineResult<()> {
+        self.ensure_not_recording()?;
+        if index_a == index_b {
+            return Ok(());
+        }
+        upsize_if_necessary(&mut self.slots, index_a.max(index_b) + 1);
+        self.slots.swap(index_a, index_b);
+        self.update_slot_indexes();
+        self.rt_command_sender.swap_slots(index_a, index_b);
+        Ok(())
+    }
+
+    /// Moves the clip in the given slot to a slot of another column. A playing clip keeps
+    /// playing.
+    ///
+    /// If `swap` is `true`, the clip in the destination slot is moved to the source slot.
+    /// Otherwise the destination slot must be empty.
+    pub fn hand_over_clip(
+        &mut self,
+        slot_index: usize,
+        dest_column: &mut Column,
+        dest_slot_index: usize,
+        swap: bool,
+    ) -> ClipEngineResult<()> {
+        self.ensure_not_recording()?;
+        dest_column.ensure_not_recording()?;
+        if !swap && !dest_column.slot_is_empty(dest_slot_index) {
+            return Err("destination slot is not empty");
+        }
+        let content = get_slot_mut_insert(&mut self.slots, slot_index).take_content();
+        let dest_slot = get_slot_mut_insert(&mut dest_column.slots, dest_slot_index);
+        let dest_content = dest_slot.take_content();
+        dest_slot.set_content(content);
+        get_slot_mut_insert(&mut self.slots, slot_index).set_content(dest_content);
+        let args = rt::ColumnHandOverClipArgs {
+            slot_index,
+            dest_column: dest_column.rt_command_sender.clone(),
+            dest_slot_index,
+            swap_column: if swap {
+                Some(self.rt_command_sender.clone())
+            } else {
+                None
+            },
+            clip: None,
+        };
+        self.rt_command_sender.hand_over_clip(Box::new(Some(args)));
+        Ok(())
+    }
+
+    /// Creates an API clip from the clip in the given slot.
+    ///
+    /// In-project MIDI includes unsaved changes made in the MIDI editor.
+    pub fn save_clip(&self, slot_index: usize) -> ClipEngineResult<api::Clip> {
+        self.get_slot(slot_index)?
+            .save(self.project)
+            .and_then(|api_slot| api_slot.clip)
+            .ok_or("slot is empty")
+    }
+
+    fn ensure_not_recording(&self) -> ClipEngineResult<()> {
+        if self.is_recording() {
+            return Err("can't rearrange slots while recording");
+        }
+        Ok(())
+    }
+
+    fn update_slot_indexes(&mut self) {
+        for (i, slot) in self.slots.iter_mut().enumerate() {
+            slot.set_index(i);
+        }
+    }
+
     pub fn adjust_clip_section_length(
         &mut self,
         slot_index: usize,
@@ -328,6 +519,24 @@ impl Column {
         slot.adjust_clip_section_length(factor, &self.rt_command_sender)
     }
 
+    pub fn adjust_clip_canvas_length(
+        &mut self,
+        slot_index: usize,
+        factor: f64,
+    ) -> ClipEngineResult<()> {
+        let slot = get_slot_mut(&mut self.slots, slot_index)?;
+        slot.adjust_clip_canvas_length(factor, &self.rt_command_sender)
+    }
+
+    pub fn move_clip_section_in_canvas(
+        &mut self,
+        slot_index: usize,
+        delta: f64,
+    ) -> ClipEngineResult<()> {
+        let slot = get_slot_mut(&mut self.slots, slot_index)?;
+        slot.move_clip_section_in_canvas(delta, &self.rt_command_sender)
+    }
+
     /// Freezes the complete column.
     pub async fn freeze(&mut self, _column_index: usize) -> ClipEngineResult<()> {
         let playback_track = self.playback_track()?.clone();
@@ -381,34 +590,254 @@ impl Column {
         )
     }
 
-    // TODO-high Implement
+    /// Like [`Self::fill_slot_with_clip`] but meant for clips coming from outside, e.g. pasted or
+    /// imported ones.
+    ///
+    /// If the clip takes audio material as-is, the section start and tempo are detected
+    /// automatically.
+    pub fn fill_slot_with_imported_clip(
+        &mut self,
+        slot_index: usize,
+        api_clip: api::Clip,
+        chain_equipment: &ChainEquipment,
+        recorder_request_sender: &Sender<RecorderRequest>,
+        matrix_settings: &MatrixSettings,
+    ) -> ClipEngineResult<ClipChangeEvent> {
+        let slot = get_slot_mut_insert(&mut self.slots, slot_index);
+        if !slot.is_empty() {
+            return Err("slot is not empty");
+        }
+        let mut clip = Clip::load(api_clip);
+        if clip.takes_material_as_is() {
+            // Analysis fails for MIDI. Then we simply take the material as-is.
+            if let Ok(analysis) =
+                clip.analyze_audio(self.project, matrix_settings.common_tempo_range)
+            {
+                clip.apply_audio_analysis(analysis)?;
+            }
+        }
+        fill_slot_internal(
+            slot,
+            clip,
+            chain_equipment,
+            recorder_request_sender,
+            matrix_settings,
+            &self.rt_settings,
+            &self.rt_command_sender,
+            self.project,
+        )
+    }
+
+    /// Returns the number of takes in the given slot, including the active one.
+    pub fn take_count(&self, slot_index: usize) -> usize {
+        self.slots
+            .get(slot_index)
+            .map(|s| s.take_count())
+            .unwrap_or(0)
+    }
+
+    /// Makes the oldest inactive take of the given slot the active one.
+    pub fn cycle_takes(
+        &mut self,
+        slot_index: usize,
+        chain_equipment: &ChainEquipment,
+        recorder_request_sender: &Sender<RecorderRequest>,
+        matrix_settings: &MatrixSettings,
+    ) -> ClipEngineResult<ClipChangeEvent> {
+        let (next_take, takes) = self.get_slot(slot_index)?.cycle_takes(self.project)?;
+        self.activate_take(
+            slot_index,
+            next_take,
+            takes,
+            chain_equipment,
+            recorder_request_sender,
+            matrix_settings,
+        )
+    }
+
+    /// Discards the active take of the given slot and makes the most recent inactive take the
+    /// active one.
+    pub fn delete_active_take(
+        &mut self,
+        slot_index: usize,
+        chain_equipment: &ChainEquipment,
+        recorder_request_sender: &Sender<RecorderRequest>,
+        matrix_settings: &MatrixSettings,
+    ) -> ClipEngineResult<ClipChangeEvent> {
+        let (next_take, takes) = self.get_slot(slot_index)?.delete_active_take()?;
+        self.activate_take(
+            slot_index,
+            next_take,
+            takes,
+            chain_equipment,
+            recorder_request_sender,
+            matrix_settings,
+        )
+    }
+
+    /// Makes the inactive take at the given index (oldest first) the active one.
+    pub fn select_take(
+        &mut self,
+        slot_index: usize,
+        take_index: usize,
+        chain_equipment: &ChainEquipment,
+        recorder_request_sender: &Sender<RecorderRequest>,
+        matrix_settings: &MatrixSettings,
+    ) -> ClipEngineResult<ClipChangeEvent> {
+        let (next_take, takes) = self
+            .get_slot(slot_index)?
+            .select_take(take_index, self.project)?;
+        self.activate_take(
+            slot_index,
+            next_take,
+            takes,
+            chain_equipment,
+            recorder_request_sender,
+            matrix_settings,
+        )
+    }
+
+    /// Keeps the active take of the given slot and discards all other ones.
+    pub fn keep_active_take_only(&mut self, slot_index: usize) -> ClipEngineResult<()> {
+        get_slot_mut(&mut self.slots, slot_index)?.keep_active_take_only()
+    }
+
+    /// Returns copies of the inactive takes of the given slot, oldest first.
+    pub fn save_takes(&self, slot_index: usize) -> Vec<api::Clip> {
+        self.slots
+            .get(slot_index)
+            .map(|s| s.takes().to_vec())
+            .unwrap_or_default()
+    }
+
+    /// Replaces the inactive takes of the given (filled) slot.
+    pub fn set_takes(&mut self, slot_index: usize, takes: Vec<api::Clip>) -> ClipEngineResult<()> {
+        get_slot_mut(&mut self.slots, slot_index)?.set_takes(takes)
+    }
+
+    fn activate_take(
+        &mut self,
+        slot_index: usize,
+        take: api::Clip,
+        inactive_takes: Vec<api::Clip>,
+        chain_equipment: &ChainEquipment,
+        recorder_request_sender: &Sender<RecorderRequest>,
+        matrix_settings: &MatrixSettings,
+    ) -> ClipEngineResult<ClipChangeEvent> {
+        let slot = get_slot_mut(&mut self.slots, slot_index)?;
+        let event = fill_slot_internal(
+            slot,
+            Clip::load(take),
+            chain_equipment,
+            recorder_request_sender,
+            matrix_settings,
+            &self.rt_settings,
+            &self.rt_command_sender,
+            self.project,
+        )?;
+        slot.set_takes(inactive_takes)?;
+        Ok(event)
+    }
+
+    /// Fills the slot with the source of the first selected item.
+    ///
+    /// If it's audio, the section start and tempo are detected automatically.
     pub fn fill_slot_with_selected_item(
         &mut self,
-        _slot_index: usize,
-        _chain_equipment: &ChainEquipment,
-        _recorder_request_sender: &Sender<RecorderRequest>,
-        _matrix_settings: &MatrixSettings,
-    ) -> ClipEngineResult<()> {
-        // let item = self
-        //     .project
-        //     .or_current_project()
-        //     .first_selected_item()
-        //     .ok_or("no item selected")?;
-        // let clip = api::Clip {
-        //     source: source_util::create_api_source_from_item(item, false)
-        //         .map_err(|_| "couldn't create source from item")?,
-        //     time_base: todo!(),
-        //     start_timing: todo!(),
-        //     stop_timing: todo!(),
-        //     looped: todo!(),
-        //     volume: api::Db::ZERO,
-        //     color: ClipColor::PlayTrackColor,
-        //     section: todo!(),
-        //     audio_settings: todo!(),
-        //     midi_settings: todo!(),
-        // };
-        // self.fill_slot_with_clip(_slot_index, clip)
-        Err("not yet implemented")
+        slot_index: usize,
+        chain_equipment: &ChainEquipment,
+        recorder_request_sender: &Sender<RecorderRequest>,
+        matrix_settings: &MatrixSettings,
+    ) -> ClipEngineResult<ClipChangeEvent> {
+        let slot = get_slot_mut_insert(&mut self.slots, slot_index);
+        if !slot.is_empty() {
+            return Err("slot is not empty");
+        }
+        let item = self
+            .project
+            .or_current_project()
+            .first_selected_item()
+            .ok_or("no item selected")?;
+        let name = item.active_take().map(|take| take.name());
+        let api_source = source_util::create_api_source_from_item(item, false)
+            .map_err(|_| "couldn't create source from item")?;
+        let mut pcm_source = create_pcm_source_from_api_source(&api_source, self.project)?;
+        let is_midi = pcm_source.material_info()?.is_midi();
+        let time_base = if is_midi {
+            api::ClipTimeBase::Beat(api::BeatTimeBase {
+                audio_tempo: None,
+                time_signature: api::TimeSignature {
+                    numerator: 4,
+                    denominator: 4,
+                },
+                downbeat: api::PositiveBeat::new(0.0)?,
+            })
+        } else {
+            api::ClipTimeBase::Time
+        };
+        let api_clip = api::Clip {
+            name,
+            source: api_source,
+            frozen_source: None,
+            active_source: Default::default(),
+            time_base,
+            start_timing: None,
+            stop_timing: None,
+            looped: true,
+            reversed: false,
+            volume: Db::ZERO,
+            pitch: Default::default(),
+            color: api::ClipColor::PlayTrackColor,
+            section: api::Section {
+                start_pos: api::PositiveSecond::new(0.0)?,
+                length: None,
+            },
+            audio_settings: Default::default(),
+            midi_settings: Default::default(),
+            canvas: None,
+            envelopes: Default::default(),
+        };
+        let mut clip = Clip::load(api_clip);
+        if !is_midi {
+            let analysis =
+                analyze_audio_source(&mut pcm_source, matrix_settings.common_tempo_range)?;
+            clip.apply_audio_analysis(analysis)?;
+        }
+        fill_slot_internal(
+            slot,
+            clip,
+            chain_equipment,
+            recorder_request_sender,
+            matrix_settings,
+            &self.rt_settings,
+            &self.rt_command_sender,
+            self.project,
+        )
+    }
+
+    /// Runs start time and tempo detection again on the audio clip in the given slot and reloads
+    /// the clip with the results.
+    pub fn detect_clip_start_time_and_tempo(
+        &mut self,
+        slot_index: usize,
+        chain_equipment: &ChainEquipment,
+        recorder_request_sender: &Sender<RecorderRequest>,
+        matrix_settings: &MatrixSettings,
+    ) -> ClipEngineResult<ClipChangeEvent> {
+        let slot = get_slot_mut(&mut self.slots, slot_index)?;
+        let mut clip = slot.clip().ok_or("slot is empty")?.clone();
+        let analysis = clip.analyze_audio(self.project, matrix_settings.common_tempo_range)?;
+        clip.apply_audio_analysis(analysis)?;
+        fill_slot_internal(
+            slot,
+            clip,
+            chain_equipment,
+            recorder_request_sender,
+            matrix_settings,
+            &self.rt_settings,
+            &self.rt_command_sender,
+            self.project,
+        )
     }
 
     pub fn play_row(&self, args: ColumnPlayRowArgs) {
@@ -444,11 +873,43 @@ impl Column {
         slot.set_clip_volume(volume, &self.rt_command_sender)
     }
 
+    pub fn set_clip_pitch(
+        &mut self,
+        slot_index: usize,
+        pitch: api::ClipPitch,
+    ) -> ClipEngineResult<ClipChangeEvent> {
+        let slot = get_slot_mut(&mut self.slots, slot_index)?;
+        slot.set_clip_pitch(pitch, &self.rt_command_sender)
+    }
+
+    pub fn set_clip_envelopes(
+        &mut self,
+        slot_index: usize,
+        envelopes: api::ClipEnvelopes,
+    ) -> ClipEngineResult<()> {
+        let slot = get_slot_mut(&mut self.slots, slot_index)?;
+        slot.set_clip_envelopes(envelopes, &self.rt_command_sender)
+    }
+
+    pub fn set_clip_midi_transformation(
+        &mut self,
+        slot_index: usize,
+        transformation: api::MidiTransformation,
+    ) -> ClipEngineResult<ClipChangeEvent> {
+        let slot = get_slot_mut(&mut self.slots, slot_index)?;
+        slot.set_clip_midi_transformation(transformation, &self.rt_command_sender)
+    }
+
     pub fn toggle_clip_looped(&mut self, slot_index: usize) -> ClipEngineResult<ClipChangeEvent> {
         let slot = get_slot_mut(&mut self.slots, slot_index)?;
         slot.toggle_clip_looped(&self.rt_command_sender)
     }
 
+    pub fn toggle_clip_reversed(&mut self, slot_index: usize) -> ClipEngineResult<ClipChangeEvent> {
+        let slot = get_slot_mut(&mut self.slots, slot_index)?;
+        slot.toggle_clip_reversed(&self.rt_command_sender)
+    }
+
     pub fn slot_position_in_seconds(
         &self,
         slot_index: usize,
@@ -470,6 +931,21 @@ impl Column {
         self.get_slot(slot_index)?.clip_volume()
     }
 
+    pub fn clip_pitch(&self, slot_index: usize) -> ClipEngineResult<api::ClipPitch> {
+        self.get_slot(slot_index)?.clip_pitch()
+    }
+
+    pub fn clip_envelopes(&self, slot_index: usize) -> ClipEngineResult<&api::ClipEnvelopes> {
+        self.get_slot(slot_index)?.clip_envelopes()
+    }
+
+    pub fn clip_midi_transformation(
+        &self,
+        slot_index: usize,
+    ) -> ClipEngineResult<api::MidiTransformation> {
+        self.get_slot(slot_index)?.clip_midi_transformation()
+    }
+
     pub fn is_stoppable(&self) -> bool {
         self.slots.iter().any(|slot| slot.is_stoppable())
     }
@@ -502,6 +978,10 @@ impl Column {
         self.get_slot(slot_index)?.clip_looped()
     }
 
+    pub fn clip_reversed(&self, slot_index: usize) -> ClipEngineResult<bool> {
+        self.get_slot(slot_index)?.clip_reversed()
+    }
+
     pub fn proportional_slot_position(&self, slot_index: usize) -> ClipEngineResult<UnitValue> {
         self.get_slot(slot_index)?.proportional_position()
     }
@@ -1,11 +1,28 @@
 use crate::ClipEngineResult;
 use playtime_api::persistence as api;
+use std::collections::VecDeque;
+
+/// Maximum number of undo points. If exceeded, the oldest ones are discarded.
+const MAX_UNDO_POINT_COUNT: usize = 100;
 
 /// Data structure holding the undo history.
 #[derive(Debug, Default)]
 pub struct History {
-    undo_stack: Vec<State>,
+    /// A deque because the oldest undo points are discarded when exceeding the maximum count.
+    undo_stack: VecDeque<State>,
     redo_stack: Vec<State>,
+    changed: bool,
+}
+
+/// Describes one entry in the history.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct HistoryEntry {
+    /// Index within the list of all entries, from oldest to newest.
+    pub index: usize,
+    /// Describes the action which led to this state.
+    pub label: String,
+    /// Whether this is the state which is currently loaded.
+    pub is_current: bool,
 }
 
 impl History {
@@ -13,6 +30,7 @@ impl History {
     pub fn clear(&mut self) {
         self.redo_stack.clear();
         self.undo_stack.clear();
+        self.changed = true;
     }
 
     /// Returns if undo is possible.
@@ -25,17 +43,64 @@ impl History {
         !self.redo_stack.is_empty()
     }
 
+    /// Returns the label of the action which would be undone next.
+    pub fn undo_label(&self) -> Option<&str> {
+        if !self.can_undo() {
+            return None;
+        }
+        Some(&self.undo_stack.back()?.label)
+    }
+
+    /// Returns the label of the action which would be redone next.
+    pub fn redo_label(&self) -> Option<&str> {
+        Some(&self.redo_stack.last()?.label)
+    }
+
+    /// Returns all entries, from oldest to newest (including the ones that can be redone).
+    pub fn entries(&self) -> impl Iterator<Item = HistoryEntry> + '_ {
+        let current_index = self.current_index();
+        self.undo_stack
+            .iter()
+            .chain(self.redo_stack.iter().rev())
+            .enumerate()
+            .map(move |(index, state)| HistoryEntry {
+                index,
+                label: state.label.clone(),
+                is_current: Some(index) == current_index,
+            })
+    }
+
+    /// Returns the total number of entries (including the ones that can be redone).
+    pub fn entry_count(&self) -> usize {
+        self.undo_stack.len() + self.redo_stack.len()
+    }
+
+    /// Returns the index of the entry which is currently loaded.
+    pub fn current_index(&self) -> Option<usize> {
+        self.undo_stack.len().checked_sub(1)
+    }
+
+    /// Returns whether the history changed since the last call of this method and resets that
+    /// flag.
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::replace(&mut self.changed, false)
+    }
+
     /// Adds the given history entry if the matrix is different from the one in the previous
     /// undo point.
     pub fn add(&mut self, label: String, new_matrix: api::Matrix) {
-        if let Some(prev_state) = self.undo_stack.last() {
+        if let Some(prev_state) = self.undo_stack.back() {
             if new_matrix == prev_state.matrix {
                 return;
             }
         };
         self.redo_stack.clear();
         let new_state = State::new(label, new_matrix);
-        self.undo_stack.push(new_state);
+        self.undo_stack.push_back(new_state);
+        if self.undo_stack.len() > MAX_UNDO_POINT_COUNT {
+            self.undo_stack.pop_front();
+        }
+        self.changed = true;
     }
 
     /// Marks the last action as undone and returns the matrix state to be loaded.
@@ -43,21 +108,39 @@ impl History {
         if self.undo_stack.len() <= 1 {
             return Err("nothing to undo");
         }
-        let state = self.undo_stack.pop().unwrap();
+        let state = self.undo_stack.pop_back().unwrap();
         self.redo_stack.push(state);
-        Ok(&self.undo_stack.last().unwrap().matrix)
+        self.changed = true;
+        Ok(&self.undo_stack.back().unwrap().matrix)
     }
 
     /// Marks the last undone action as redone and returns the matrix state to be loaded.
     pub fn redo(&mut self) -> ClipEngineResult<&api::Matrix> {
         let state = self.redo_stack.pop().ok_or("nothing to redo")?;
-        self.undo_stack.push(state);
-        Ok(&self.undo_stack.last().unwrap().matrix)
+        self.undo_stack.push_back(state);
+        self.changed = true;
+        Ok(&self.undo_stack.back().unwrap().matrix)
+    }
+
+    /// Undoes or redoes as many actions as necessary to arrive at the entry with the given index
+    /// and returns the matrix state to be loaded.
+    pub fn jump_to(&mut self, index: usize) -> ClipEngineResult<&api::Matrix> {
+        if index >= self.entry_count() {
+            return Err("history entry doesn't exist");
+        }
+        while self.undo_stack.len() > index + 1 {
+            let state = self.undo_stack.pop_back().unwrap();
+            self.redo_stack.push(state);
+        }
+        while self.undo_stack.len() < index + 1 {
+            let state = self.redo_stack.pop().unwrap();
+            self.undo_stack.push_back(state);
+        }
+        self.changed = true;
+        Ok(&self.undo_stack.back().unwrap().matrix)
     }
 }
 
-// TODO-medium Make use of label
-#[allow(dead_code)]
 #[derive(Debug)]
 struct State {
     label: String,
@@ -1,4 +1,4 @@
-use crate::main::history::History;
+use crate::main::history::{History, HistoryEntry};
 use crate::main::row::Row;
 use crate::main::{Clip, Column, Slot};
 use crate::rt::supplier::{
@@ -41,6 +41,8 @@ pub struct Matrix<H> {
     command_receiver: Receiver<MatrixCommand>,
     rt_command_sender: Sender<rt::MatrixCommand>,
     history: History,
+    /// Slot which has been picked up as source for moving, copying or swapping its clip.
+    picked_up_clip_slot: Option<ClipSlotCoordinates>,
     // We use this just for RAII (joining worker threads when dropped)
     _worker_pool: WorkerPool,
 }
@@ -143,6 +145,7 @@ impl<H: ClipMatrixHandler> Matrix<H> {
             command_receiver: main_command_receiver,
             rt_command_sender,
             history: History::default(),
+            picked_up_clip_slot: None,
             _worker_pool: worker_pool,
         }
     }
@@ -161,6 +164,7 @@ impl<H: ClipMatrixHandler> Matrix<H> {
     //  slightly benefit undo/redo performance.
     fn load_internal(&mut self, api_matrix: api::Matrix) -> ClipEngineResult<()> {
         self.clear_columns();
+        self.picked_up_clip_slot = None;
         let permanent_project = self.permanent_project();
         // Main settings
         self.settings.common_tempo_range = api_matrix.common_tempo_range;
@@ -254,6 +258,37 @@ impl<H: ClipMatrixHandler> Matrix<H> {
         Ok(())
     }
 
+    /// Returns the label of the action which would be undone next.
+    pub fn undo_label(&self) -> Option<&str> {
+        self.history.undo_label()
+    }
+
+    /// Returns the label of the action which would be redone next.
+    pub fn redo_label(&self) -> Option<&str> {
+        self.history.redo_label()
+    }
+
+    /// Returns all undo history entries, from oldest to newest.
+    pub fn history_entries(&self) -> impl Iterator<Item = HistoryEntry> + '_ {
+        self.history.entries()
+    }
+
+    pub fn history_entry_count(&self) -> usize {
+        self.history.entry_count()
+    }
+
+    pub fn current_history_index(&self) -> Option<usize> {
+        self.history.current_index()
+    }
+
+    /// Undoes or redoes as many actions as necessary to restore the state of the given history
+    /// entry.
+    pub fn jump_to_history_entry(&mut self, index: usize) -> ClipEngineResult<()> {
+        let api_matrix = self.history.jump_to(index)?.clone();
+        self.load_internal(api_matrix)?;
+        Ok(())
+    }
+
     fn undoable<R>(&mut self, label: impl Into<String>, f: impl FnOnce(&mut Self) -> R) -> R {
         let owned_label = label.into();
         self.history
@@ -328,6 +363,107 @@ impl<H: ClipMatrixHandler> Matrix<H> {
         self.columns.iter().filter(|c| c.follows_scene())
     }
 
+    fn scene_columns_mut(&mut self) -> impl Iterator<Item = &mut Column> {
+        self.columns.iter_mut().filter(|c| c.follows_scene())
+    }
+
+    /// Inserts an empty scene at the given row index, shifting all following scenes down by one.
+    pub fn insert_scene(&mut self, row_index: usize) -> ClipEngineResult<()> {
+        if row_index > self.row_count() {
+            return Err("row doesn't exist");
+        }
+        self.undoable("Insert scene", |matrix| {
+            matrix.rearrange_scenes(|matrix| {
+                for column in matrix.scene_columns_mut() {
+                    column.insert_slot(row_index)?;
+                }
+                matrix.rows.insert(row_index, Row {});
+                Ok(())
+            })
+        })
+    }
+
+    /// Deletes the scene at the given row index, shifting all following scenes up by one.
+    ///
+    /// Clips in that scene stop immediately.
+    pub fn delete_scene(&mut self, row_index: usize) -> ClipEngineResult<()> {
+        if row_index >= self.row_count() {
+            return Err("row doesn't exist");
+        }
+        self.undoable("Delete scene", |matrix| {
+            matrix.rearrange_scenes(|matrix| {
+                for column in matrix.scene_columns_mut() {
+                    column.remove_slot(row_index)?;
+                }
+                matrix.rows.remove(row_index);
+                Ok(())
+            })
+        })
+    }
+
+    /// Inserts a copy of the scene at the given row index right below it.
+    pub fn duplicate_scene(&mut self, row_index: usize) -> ClipEngineResult<()> {
+        if row_index >= self.row_count() {
+            return Err("row doesn't exist");
+        }
+        self.undoable("Duplicate scene", |matrix| {
+            matrix.rearrange_scenes(|matrix| {
+                for column in matrix.columns.iter_mut().filter(|c| c.follows_scene()) {
+                    column.duplicate_slot(
+                        row_index,
+                        &matrix.chain_equipment,
+                        &matrix.recorder_request_sender,
+                        &matrix.settings,
+                    )?;
+                }
+                let row = matrix.rows[row_index].clone();
+                matrix.rows.insert(row_index + 1, row);
+                Ok(())
+            })
+        })
+    }
+
+    /// Moves the scene at the given row index to another row index. Clips keep playing.
+    pub fn move_scene(
+        &mut self,
+        from_row_index: usize,
+        to_row_index: usize,
+    ) -> ClipEngineResult<()> {
+        let row_count = self.row_count();
+        if from_row_index >= row_count || to_row_index >= row_count {
+            return Err("row doesn't exist");
+        }
+        self.undoable("Move scene", |matrix| {
+            matrix.rearrange_scenes(|matrix| {
+                for column in matrix.scene_columns_mut() {
+                    column.move_slot(from_row_index, to_row_index)?;
+                }
+                let row = matrix.rows.remove(from_row_index);
+                matrix.rows.insert(to_row_index, row);
+                Ok(())
+            })
+        })
+    }
+
+    fn rearrange_scenes(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> ClipEngineResult<()>,
+    ) -> ClipEngineResult<()> {
+        if self.is_recording() {
+            return Err("can't rearrange scenes while recording");
+        }
+        // Make sure that each row which exists virtually is also represented by a row object.
+        let row_count = self.row_count();
+        if self.rows.len() < row_count {
+            self.rows.resize_with(row_count, || Row {});
+        }
+        f(self)?;
+        // Slot coordinates have shifted.
+        self.picked_up_clip_slot = None;
+        self.handler.emit_event(ClipMatrixEvent::AllClipsChanged);
+        Ok(())
+    }
+
     pub fn clear_slot(&mut self, coordinates: ClipSlotCoordinates) -> ClipEngineResult<()> {
         // The undo point after clip removal is created later, in response to the upcoming event
         // that indicates that the slot has actually been cleared.
@@ -338,6 +474,121 @@ impl<H: ClipMatrixHandler> Matrix<H> {
         Ok(())
     }
 
+    /// Moves the clip from the source slot to the (empty) destination slot, even across columns.
+    /// A playing clip keeps playing.
+    pub fn move_clip(
+        &mut self,
+        source: ClipSlotCoordinates,
+        dest: ClipSlotCoordinates,
+    ) -> ClipEngineResult<()> {
+        get_column(&self.columns, source.column)?;
+        get_column(&self.columns, dest.column)?;
+        if self.slot_is_empty(source) {
+            return Err("source slot is empty");
+        }
+        if source == dest {
+            self.forget_picked_up_clip_slot_if_involved(&[source]);
+            return Ok(());
+        }
+        if !self.slot_is_empty(dest) {
+            return Err("destination slot is not empty");
+        }
+        self.undoable("Move clip", |matrix| {
+            matrix.exchange_clips(source, dest, false)?;
+            matrix.forget_picked_up_clip_slot_if_involved(&[source, dest]);
+            Ok(())
+        })
+    }
+
+    /// Swaps the clips of the given slots, even across columns. Playing clips keep playing.
+    pub fn swap_clips(
+        &mut self,
+        a: ClipSlotCoordinates,
+        b: ClipSlotCoordinates,
+    ) -> ClipEngineResult<()> {
+        get_column(&self.columns, a.column)?;
+        get_column(&self.columns, b.column)?;
+        if a == b {
+            self.forget_picked_up_clip_slot_if_involved(&[a]);
+            return Ok(());
+        }
+        self.undoable("Swap clips", |matrix| {
+            matrix.exchange_clips(a, b, true)?;
+            matrix.forget_picked_up_clip_slot_if_involved(&[a, b]);
+            Ok(())
+        })
+    }
+
+    /// Fills the (empty) destination slot with an independent copy of the clip and takes in the
+    /// source slot.
+    pub fn copy_clip(
+        &mut self,
+        source: ClipSlotCoordinates,
+        dest: ClipSlotCoordinates,
+    ) -> ClipEngineResult<()> {
+        let source_column = get_column(&self.columns, source.column)?;
+        let api_clip = source_column.save_clip(source.row)?;
+        let takes = source_column.save_takes(source.row);
+        self.undoable("Copy clip", |matrix| {
+            let column = get_column_mut(&mut matrix.columns, dest.column)?;
+            let event = column.fill_slot_with_clip(
+                dest.row,
+                api_clip,
+                &matrix.chain_equipment,
+                &matrix.recorder_request_sender,
+                &matrix.settings,
+            )?;
+            column.set_takes(dest.row, takes)?;
+            matrix
+                .handler
+                .emit_event(ClipMatrixEvent::clip_changed(dest, event));
+            Ok(())
+        })
+    }
+
+    fn exchange_clips(
+        &mut self,
+        a: ClipSlotCoordinates,
+        b: ClipSlotCoordinates,
+        swap: bool,
+    ) -> ClipEngineResult<()> {
+        if a.column == b.column {
+            // Within one column, a move is just a swap with an empty slot.
+            get_column_mut(&mut self.columns, a.column)?.swap_slots(a.row, b.row)?;
+        } else {
+            let (column_a, column_b) = get_two_columns_mut(&mut self.columns, a.column, b.column)?;
+            column_a.hand_over_clip(a.row, column_b, b.row, swap)?;
+        }
+        self.handler.emit_event(ClipMatrixEvent::AllClipsChanged);
+        Ok(())
+    }
+
+    /// Remembers the given slot as source for a subsequent move, copy or swap.
+    ///
+    /// Because this is stored in the matrix, it works across all instances sharing the matrix.
+    pub fn set_picked_up_clip_slot(&mut self, coordinates: Option<ClipSlotCoordinates>) {
+        self.picked_up_clip_slot = coordinates;
+    }
+
+    pub fn picked_up_clip_slot(&self) -> Option<ClipSlotCoordinates> {
+        self.picked_up_clip_slot
+    }
+
+    /// Forgets the picked-up slot if it's one of the given slots, e.g. because its clip has been
+    /// dropped somewhere else.
+    fn forget_picked_up_clip_slot_if_involved(&mut self, slots: &[ClipSlotCoordinates]) {
+        if matches!(self.picked_up_clip_slot, Some(c) if slots.contains(&c)) {
+            self.picked_up_clip_slot = None;
+        }
+    }
+
+    /// Forgets the picked-up slot if its clip is gone.
+    fn forget_picked_up_clip_slot_if_empty(&mut self) {
+        if matches!(self.picked_up_clip_slot, Some(c) if self.slot_is_empty(c)) {
+            self.picked_up_clip_slot = None;
+        }
+    }
+
     pub fn adjust_clip_section_length(
         &mut self,
         coordinates: ClipSlotCoordinates,
@@ -347,6 +598,24 @@ impl<H: ClipMatrixHandler> Matrix<H> {
         column.adjust_clip_section_length(coordinates.row, factor)
     }
 
+    pub fn adjust_clip_canvas_length(
+        &mut self,
+        coordinates: ClipSlotCoordinates,
+        factor: f64,
+    ) -> ClipEngineResult<()> {
+        let column = get_column_mut(&mut self.columns, coordinates.column)?;
+        column.adjust_clip_canvas_length(coordinates.row, factor)
+    }
+
+    pub fn move_clip_section_in_canvas(
+        &mut self,
+        coordinates: ClipSlotCoordinates,
+        delta: f64,
+    ) -> ClipEngineResult<()> {
+        let column = get_column_mut(&mut self.columns, coordinates.column)?;
+        column.move_clip_section_in_canvas(coordinates.row, delta)
+    }
+
     pub fn start_editing_clip(&self, coordinates: ClipSlotCoordinates) -> ClipEngineResult<()> {
         let column = get_column(&self.columns, coordinates.column)?;
         column.start_editing_clip(coordinates.row)
@@ -389,6 +658,8 @@ impl<H: ClipMatrixHandler> Matrix<H> {
         })
     }
 
+    /// If the clip takes audio material as-is, the section start and tempo are detected
+    /// automatically.
     pub fn fill_slot_with_clip(
         &mut self,
         coordinates: ClipSlotCoordinates,
@@ -396,7 +667,7 @@ impl<H: ClipMatrixHandler> Matrix<H> {
     ) -> ClipEngineResult<()> {
         self.undoable("Fill slot with clip", |matrix| {
             let column = get_column_mut(&mut matrix.columns, coordinates.column)?;
-            let event = column.fill_slot_with_clip(
+            let event = column.fill_slot_with_imported_clip(
                 coordinates.row,
                 api_clip,
                 &matrix.chain_equipment,
@@ -416,12 +687,109 @@ impl<H: ClipMatrixHandler> Matrix<H> {
     ) -> ClipEngineResult<()> {
         self.undoable("Fill slot with selected item", |matrix| {
             let column = get_column_mut(&mut matrix.columns, coordinates.column)?;
-            column.fill_slot_with_selected_item(
+            let event = column.fill_slot_with_selected_item(
+                coordinates.row,
+                &matrix.chain_equipment,
+                &matrix.recorder_request_sender,
+                &matrix.settings,
+            )?;
+            matrix
+                .handler
+                .emit_event(ClipMatrixEvent::clip_changed(coordinates, event));
+            Ok(())
+        })
+    }
+
+    /// Returns the number of takes in the given slot, including the active one.
+    pub fn clip_take_count(&self, coordinates: ClipSlotCoordinates) -> usize {
+        self.columns
+            .get(coordinates.column)
+            .map(|c| c.take_count(coordinates.row))
+            .unwrap_or(0)
+    }
+
+    /// Makes the next take of the given slot the active one. The clip must be stopped.
+    pub fn cycle_clip_takes(&mut self, coordinates: ClipSlotCoordinates) -> ClipEngineResult<()> {
+        self.undoable("Cycle clip takes", |matrix| {
+            let column = get_column_mut(&mut matrix.columns, coordinates.column)?;
+            column.cycle_takes(
+                coordinates.row,
+                &matrix.chain_equipment,
+                &matrix.recorder_request_sender,
+                &matrix.settings,
+            )?;
+            matrix.handler.emit_event(ClipMatrixEvent::AllClipsChanged);
+            Ok(())
+        })
+    }
+
+    /// Discards the active take of the given slot and activates the most recent remaining one.
+    /// The clip must be stopped.
+    pub fn delete_active_clip_take(
+        &mut self,
+        coordinates: ClipSlotCoordinates,
+    ) -> ClipEngineResult<()> {
+        self.undoable("Delete clip take", |matrix| {
+            let column = get_column_mut(&mut matrix.columns, coordinates.column)?;
+            column.delete_active_take(
+                coordinates.row,
+                &matrix.chain_equipment,
+                &matrix.recorder_request_sender,
+                &matrix.settings,
+            )?;
+            matrix.handler.emit_event(ClipMatrixEvent::AllClipsChanged);
+            Ok(())
+        })
+    }
+
+    /// Makes the inactive take at the given index (oldest first) the active one. The clip must be
+    /// stopped.
+    pub fn select_clip_take(
+        &mut self,
+        coordinates: ClipSlotCoordinates,
+        take_index: usize,
+    ) -> ClipEngineResult<()> {
+        self.undoable("Select clip take", |matrix| {
+            let column = get_column_mut(&mut matrix.columns, coordinates.column)?;
+            column.select_take(
                 coordinates.row,
+                take_index,
                 &matrix.chain_equipment,
                 &matrix.recorder_request_sender,
                 &matrix.settings,
-            )
+            )?;
+            matrix.handler.emit_event(ClipMatrixEvent::AllClipsChanged);
+            Ok(())
+        })
+    }
+
+    /// Keeps the active take of the given slot and discards all other takes.
+    pub fn keep_active_clip_take_only(
+        &mut self,
+        coordinates: ClipSlotCoordinates,
+    ) -> ClipEngineResult<()> {
+        self.undoable("Keep active clip take only", |matrix| {
+            get_column_mut(&mut matrix.columns, coordinates.column)?
+                .keep_active_take_only(coordinates.row)
+        })
+    }
+
+    pub fn detect_clip_start_time_and_tempo(
+        &mut self,
+        coordinates: ClipSlotCoordinates,
+    ) -> ClipEngineResult<()> {
+        self.undoable("Detect clip start time and tempo", |matrix| {
+            let column = get_column_mut(&mut matrix.columns, coordinates.column)?;
+            let event = column.detect_clip_start_time_and_tempo(
+                coordinates.row,
+                &matrix.chain_equipment,
+                &matrix.recorder_request_sender,
+                &matrix.settings,
+            )?;
+            matrix
+                .handler
+                .emit_event(ClipMatrixEvent::clip_changed(coordinates, event));
+            Ok(())
         })
     }
 
@@ -606,9 +974,41 @@ impl<H: ClipMatrixHandler> Matrix<H> {
         }
     }
 
+    /// Writes the material of finished audio overdubs to files and lets the clips use them.
+    fn commit_finished_audio_overdubs(&mut self) -> Vec<ClipMatrixEvent> {
+        let finished_audio_overdubs: Vec<_> = self
+            .columns
+            .iter_mut()
+            .enumerate()
+            .flat_map(|(column_index, column)| {
+                column.take_finished_audio_overdubs().into_iter().map(
+                    move |(row_index, outcome)| {
+                        (ClipSlotCoordinates::new(column_index, row_index), outcome)
+                    },
+                )
+            })
+            .collect();
+        finished_audio_overdubs
+            .into_iter()
+            .filter_map(|(coordinates, outcome)| {
+                let result = self.undoable("Overdub audio", |matrix| {
+                    get_column_mut(&mut matrix.columns, coordinates.column)?
+                        .commit_audio_overdub(coordinates.row, outcome)
+                });
+                match result {
+                    Ok(event) => Some(ClipMatrixEvent::clip_changed(coordinates, event)),
+                    Err(e) => {
+                        debug!("Couldn't commit audio overdub: {}", e);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
     pub fn poll(&mut self, timeline_tempo: Bpm) -> Vec<ClipMatrixEvent> {
         self.process_commands();
-        let events: Vec<_> = self
+        let mut events: Vec<_> = self
             .columns
             .iter_mut()
             .enumerate()
@@ -634,6 +1034,13 @@ impl<H: ClipMatrixHandler> Matrix<H> {
         if let Some(l) = undo_point_label {
             self.history.add(l.into(), self.save());
         }
+        if events.iter().any(|evt| evt.is_clip_removal()) {
+            self.forget_picked_up_clip_slot_if_empty();
+        }
+        events.extend(self.commit_finished_audio_overdubs());
+        if self.history.take_changed() {
+            events.push(ClipMatrixEvent::HistoryChanged);
+        }
         events
     }
 
@@ -648,6 +1055,17 @@ impl<H: ClipMatrixHandler> Matrix<H> {
         })
     }
 
+    pub fn toggle_reversed(&mut self, coordinates: ClipSlotCoordinates) -> ClipEngineResult<()> {
+        self.undoable("Toggle reversed", |matrix| {
+            let event = get_column_mut(&mut matrix.columns, coordinates.column())?
+                .toggle_clip_reversed(coordinates.row())?;
+            matrix
+                .handler
+                .emit_event(ClipMatrixEvent::clip_changed(coordinates, event));
+            Ok(())
+        })
+    }
+
     pub fn clip_position_in_seconds(
         &self,
         coordinates: ClipSlotCoordinates,
@@ -684,6 +1102,10 @@ impl<H: ClipMatrixHandler> Matrix<H> {
         get_column(&self.columns, coordinates.column())?.clip_looped(coordinates.row())
     }
 
+    pub fn clip_reversed(&self, coordinates: ClipSlotCoordinates) -> ClipEngineResult<bool> {
+        get_column(&self.columns, coordinates.column())?.clip_reversed(coordinates.row())
+    }
+
     pub fn uses_playback_track(&self, track: &Track) -> bool {
         self.columns.iter().any(|c| c.playback_track() == Ok(track))
     }
@@ -696,6 +1118,116 @@ impl<H: ClipMatrixHandler> Matrix<H> {
         get_column(&self.columns, index)
     }
 
+    /// Inserts an empty column at the given index.
+    ///
+    /// The new column takes over the settings of its left neighbor, including the playback track.
+    /// If it's going to be the first column, it plays on the track containing the matrix. If
+    /// `create_track` is `true`, a new REAPER track is created and used as playback track instead.
+    pub fn insert_column(
+        &mut self,
+        column_index: usize,
+        create_track: bool,
+    ) -> ClipEngineResult<()> {
+        if column_index > self.columns.len() {
+            return Err("column index out of range");
+        }
+        let left_neighbor = column_index
+            .checked_sub(1)
+            .and_then(|i| self.columns.get(i));
+        let mut column = match left_neighbor {
+            // Take over the settings of the left neighbor (including the playback track).
+            Some(c) => c.duplicate_without_contents(),
+            None => Column::new(self.permanent_project()),
+        };
+        if !create_track && left_neighbor.is_none() {
+            let track = self
+                .containing_track
+                .clone()
+                .ok_or("no track available for new column")?;
+            column.set_playback_track(track);
+        }
+        self.undoable("Insert column", |matrix| {
+            // The track is created as part of the undoable action, not as a side effect before.
+            if create_track {
+                let track = matrix.permanent_project().or_current_project().add_track();
+                column.set_playback_track(track);
+            }
+            column.sync_settings_to_rt(&matrix.settings);
+            initialize_new_column(
+                column_index,
+                column,
+                &matrix.rt_command_sender,
+                &mut matrix.columns,
+            );
+            // Slot coordinates have shifted.
+            matrix.picked_up_clip_slot = None;
+            matrix.handler.emit_event(ClipMatrixEvent::AllClipsChanged);
+            Ok(())
+        })
+    }
+
+    /// Removes the column at the given index including all of its clips.
+    pub fn remove_column(&mut self, column_index: usize) -> ClipEngineResult<()> {
+        let column = get_column(&self.columns, column_index)?;
+        if column.is_recording() {
+            return Err("can't remove column while recording");
+        }
+        self.undoable("Remove column", |matrix| {
+            matrix.columns.remove(column_index);
+            matrix.rt_command_sender.remove_column(column_index);
+            matrix.picked_up_clip_slot = None;
+            matrix.handler.emit_event(ClipMatrixEvent::AllClipsChanged);
+            Ok(())
+        })
+    }
+
+    /// Moves the column at the given index to another index. Clips keep playing.
+    pub fn move_column(&mut self, from_index: usize, to_index: usize) -> ClipEngineResult<()> {
+        if from_index >= self.columns.len() || to_index >= self.columns.len() {
+            return Err("column doesn't exist");
+        }
+        if self.is_recording() {
+            return Err("can't move column while recording");
+        }
+        if from_index == to_index {
+            return Ok(());
+        }
+        self.undoable("Move column", |matrix| {
+            let column = matrix.columns.remove(from_index);
+            matrix.columns.insert(to_index, column);
+            matrix.rt_command_sender.move_column(from_index, to_index);
+            matrix.picked_up_clip_slot = None;
+            matrix.handler.emit_event(ClipMatrixEvent::AllClipsChanged);
+            Ok(())
+        })
+    }
+
+    /// Makes the column at the given index play its clips on the given track.
+    pub fn set_column_playback_track(
+        &mut self,
+        column_index: usize,
+        track: Track,
+    ) -> ClipEngineResult<()> {
+        self.undoable("Set column playback track", |matrix| {
+            get_column_mut(&mut matrix.columns, column_index)?.set_playback_track(track);
+            matrix.handler.emit_event(ClipMatrixEvent::AllClipsChanged);
+            Ok(())
+        })
+    }
+
+    /// Makes the column at the given index record from the given track. If `None`, it records
+    /// from its playback track.
+    pub fn set_column_recording_track(
+        &mut self,
+        column_index: usize,
+        track: Option<&Track>,
+    ) -> ClipEngineResult<()> {
+        self.undoable("Set column recording track", |matrix| {
+            get_column_mut(&mut matrix.columns, column_index)?.set_recording_track(track);
+            Ok(())
+        })
+    }
+
     pub fn row_count(&self) -> usize {
         let max_slot_count_per_col = self
             .columns
@@ -710,6 +1242,24 @@ impl<H: ClipMatrixHandler> Matrix<H> {
         get_column(&self.columns, coordinates.column())?.clip_volume(coordinates.row())
     }
 
+    pub fn clip_pitch(&self, coordinates: ClipSlotCoordinates) -> ClipEngineResult<api::ClipPitch> {
+        get_column(&self.columns, coordinates.column())?.clip_pitch(coordinates.row())
+    }
+
+    pub fn clip_envelopes(
+        &self,
+        coordinates: ClipSlotCoordinates,
+    ) -> ClipEngineResult<&api::ClipEnvelopes> {
+        get_column(&self.columns, coordinates.column())?.clip_envelopes(coordinates.row())
+    }
+
+    pub fn clip_midi_transformation(
+        &self,
+        coordinates: ClipSlotCoordinates,
+    ) -> ClipEngineResult<api::MidiTransformation> {
+        get_column(&self.columns, coordinates.column())?.clip_midi_transformation(coordinates.row())
+    }
+
     pub fn record_clip(&mut self, coordinates: ClipSlotCoordinates) -> ClipEngineResult<()> {
         if self.is_recording() {
             return Err("recording already");
@@ -757,6 +1307,54 @@ impl<H: ClipMatrixHandler> Matrix<H> {
         Ok(())
     }
 
+    pub fn set_clip_pitch(
+        &mut self,
+        coordinates: ClipSlotCoordinates,
+        pitch: api::ClipPitch,
+    ) -> ClipEngineResult<()> {
+        let event = get_column_mut(&mut self.columns, coordinates.column())?
+            .set_clip_pitch(coordinates.row(), pitch)?;
+        self.handler
+            .emit_event(ClipMatrixEvent::clip_changed(coordinates, event));
+        Ok(())
+    }
+
+    /// Replaces the volume and pan envelopes of the given clip.
+    pub fn set_clip_envelopes(
+        &mut self,
+        coordinates: ClipSlotCoordinates,
+        envelopes: api::ClipEnvelopes,
+    ) -> ClipEngineResult<()> {
+        self.undoable("Set clip envelopes", |matrix| {
+            get_column_mut(&mut matrix.columns, coordinates.column())?
+                .set_clip_envelopes(coordinates.row(), envelopes)
+        })
+    }
+
+    pub fn set_clip_envelope_mode(
+        &mut self,
+        coordinates: ClipSlotCoordinates,
+        mode: api::ClipEnvelopeMode,
+    ) -> ClipEngineResult<()> {
+        let envelopes = api::ClipEnvelopes {
+            mode,
+            ..self.clip_envelopes(coordinates)?.clone()
+        };
+        self.set_clip_envelopes(coordinates, envelopes)
+    }
+
+    pub fn set_clip_midi_transformation(
+        &mut self,
+        coordinates: ClipSlotCoordinates,
+        transformation: api::MidiTransformation,
+    ) -> ClipEngineResult<()> {
+        let event = get_column_mut(&mut self.columns, coordinates.column())?
+            .set_clip_midi_transformation(coordinates.row(), transformation)?;
+        self.handler
+            .emit_event(ClipMatrixEvent::clip_changed(coordinates, event));
+        Ok(())
+    }
+
     pub fn proportional_clip_position(
         &self,
         coordinates: ClipSlotCoordinates,
@@ -774,6 +1372,26 @@ fn get_column_mut(columns: &mut [Column], index: usize) -> ClipEngineResult<&mut
     columns.get_mut(index).ok_or(NO_SUCH_COLUMN)
 }
 
+fn get_two_columns_mut(
+    columns: &mut [Column],
+    index_a: usize,
+    index_b: usize,
+) -> ClipEngineResult<(&mut Column, &mut Column)> {
+    if index_a == index_b {
+        return Err("columns must be different");
+    }
+    if index_a >= columns.len() || index_b >= columns.len() {
+        return Err(NO_SUCH_COLUMN);
+    }
+    if index_a < index_b {
+        let (left, right) = columns.split_at_mut(index_b);
+        Ok((&mut left[index_a], &mut right[0]))
+    } else {
+        let (left, right) = columns.split_at_mut(index_a);
+        Ok((&mut right[0], &mut left[index_b]))
+    }
+}
+
 const NO_SUCH_COLUMN: &str = "no such column";
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
@@ -806,13 +1424,14 @@ pub struct ClipRecordTask {
 pub struct ClipRecordDestination {
     pub column_source: WeakColumn,
     pub slot_index: usize,
-    /// If this is set, it's important to write the MIDI events during the *post* phase of the audio
-    /// callback, otherwise the written MIDI events would be played back a moment later, which
-    /// would result in duplicated note playback during recording.
+    /// If this is set, it's important to write the material during the *post* phase of the audio
+    /// callback. With MIDI, the written events would otherwise be played back a moment later,
+    /// which would result in duplicated note playback during recording. With audio, the overdub
+    /// material must be mixed into the part of the clip which has just been played.
     ///
     /// If this is not set, it's important to write it in the *pre* phase because we don't want
     /// to miss playing back any material when we change back from recording to ready.
-    pub is_midi_overdub: bool,
+    pub is_overdub: bool,
 }
 
 #[derive(Debug)]
@@ -898,6 +1517,8 @@ pub trait ClipMatrixHandler: Sized {
 pub enum ClipMatrixEvent {
     AllClipsChanged,
     ClipChanged(QualifiedClipChangeEvent),
+    /// Undo points have been added or the current position within the undo history changed.
+    HistoryChanged,
 }
 
 impl ClipMatrixEvent {
@@ -999,5 +1620,5 @@ fn initialize_new_column(
         command_sender: column.rt_command_sender(),
     };
     rt_command_sender.insert_column(column_index, handle);
-    columns.push(column);
+    columns.insert(column_index, column);
 }
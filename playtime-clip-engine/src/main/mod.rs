@@ -7,5 +7,6 @@ mod slot;
 
 pub use clip::*;
 pub use column::*;
+pub use history::HistoryEntry;
 pub use matrix::*;
 pub use slot::*;
@@ -2,29 +2,36 @@ use crate::conversion_util::{
     adjust_duration_in_secs_anti_proportionally, adjust_pos_in_secs_anti_proportionally,
     convert_position_in_frames_to_seconds,
 };
+use crate::file_util::get_path_for_new_media_file;
 use crate::main::{
     create_api_source_from_recorded_midi_source, Clip, ClipMatrixHandler, ClipRecordDestination,
     ClipRecordHardwareInput, ClipRecordHardwareMidiInput, ClipRecordInput, ClipRecordTask,
     VirtualClipRecordAudioInput, VirtualClipRecordHardwareMidiInput,
 };
+use crate::rt::supplier::AudioOverdubOutcome;
 use crate::rt::supplier::{
     ChainEquipment, ClipSource, MaterialInfo, MidiOverdubSettings, QuantizationSettings, Recorder,
     RecorderRequest, RecordingArgs, RecordingEquipment, SupplierChain,
+    MAX_ENVELOPE_POINT_COUNT_WITHOUT_REALLOCATION,
 };
+use crate::rt::tempo_util::determine_envelope_frames_per_beat;
 use crate::rt::{
-    ClipChangeEvent, ClipRecordArgs, ColumnCommandSender, ColumnSetClipLoopedArgs,
-    InternalClipPlayState, MidiOverdubInstruction, NormalRecordingOutcome,
-    OverridableMatrixSettings, RecordNewClipInstruction, SharedColumn, SlotRecordInstruction,
-    SlotRuntimeData,
+    AudioOverdubInstruction, ClipChangeEvent, ClipRecordArgs, ColumnCommandSender,
+    ColumnSetClipLoopedArgs, ColumnSetClipReversedArgs, InternalClipPlayState,
+    MidiOverdubInstruction, NormalRecordingOutcome, OverridableMatrixSettings, OwnedAudioBuffer,
+    RecordNewClipInstruction, SharedColumn, SlotRecordInstruction, SlotRuntimeData,
+};
+use crate::source_util::{
+    create_file_api_source, create_pcm_source_from_file_based_api_source, write_audio_file,
 };
-use crate::source_util::{create_file_api_source, create_pcm_source_from_file_based_api_source};
 use crate::{clip_timeline, rt, ClipEngineResult, HybridTimeline, QuantizedPosition, Timeline};
 use crossbeam_channel::Sender;
 use helgoboss_learn::UnitValue;
 use playtime_api::persistence as api;
 use playtime_api::persistence::{
-    ChannelRange, ClipTimeBase, ColumnClipRecordSettings, Db, MatrixClipRecordSettings,
-    MidiClipRecordMode, PositiveSecond, RecordOrigin,
+    AudioClipRecordMode, ChannelRange, ClipTimeBase, ColumnClipRecordSettings, Db,
+    MatrixClipRecordSettings, MidiClipRecordMode, OverdubFeedback, PositiveBeat, PositiveSecond,
+    RecordOrigin,
 };
 use playtime_api::runtime::ClipPlayState;
 use reaper_high::{BorrowedSource, Item, OwnedSource, Project, Reaper, Take, Track, TrackRoute};
@@ -64,6 +71,15 @@ pub struct Content {
     /// Now that we have pooled MIDI anyway, we don't need to send a finished MIDI recording back
     /// to the main thread using the "mirror source" method (which we did before).
     pooled_midi_source: Option<ClipSource>,
+    /// Position in beats at which the volume was last written into the envelope.
+    envelope_write_pos: Option<f64>,
+    /// Number of envelope points sent to the real-time clip since it received the complete
+    /// envelopes.
+    ///
+    /// The real-time clip only has room for a limited number of additional points.
+    written_envelope_point_count: usize,
+    /// Previously recorded takes which are currently not active, oldest first.
+    takes: Vec<api::Clip>,
 }
 
 impl Content {
@@ -85,6 +101,70 @@ impl Content {
         let is_midi = self.runtime_data.material_info.is_midi();
         self.clip.tempo_factor(timeline_tempo, is_midi)
     }
+
+    /// Returns the current position within the loop cycle in beats or `None` if not playing.
+    fn envelope_pos_in_beats(&self) -> Option<f64> {
+        let play_state = self.runtime_data.play_state.get();
+        if !matches!(
+            play_state,
+            ClipPlayState::Playing | ClipPlayState::ScheduledForPlayStop
+        ) {
+            return None;
+        }
+        let frame = self.runtime_data.mod_frame();
+        if frame < 0 {
+            return None;
+        }
+        let material_info = &self.runtime_data.material_info;
+        let frames_per_beat = determine_envelope_frames_per_beat(
+            self.clip.time_base(),
+            material_info.is_midi(),
+            material_info.frame_rate(),
+        );
+        Some(frame as f64 / frames_per_beat)
+    }
+
+    fn save_active_take(&self, temporary_project: Option<Project>) -> ClipEngineResult<api::Clip> {
+        self.clip
+            .save_flexible(self.pooled_midi_source.as_ref(), temporary_project)
+    }
+
+    /// Captures the given volume in the volume envelope (if the clip is playing).
+    fn write_volume_envelope_point(
+        &mut self,
+        volume: Db,
+        slot_index: usize,
+        column_command_sender: &ColumnCommandSender,
+    ) {
+        let pos = match self.envelope_pos_in_beats() {
+            None => {
+                self.envelope_write_pos = None;
+                return;
+            }
+            Some(p) => p,
+        };
+        let pos_in_beats = match PositiveBeat::new(pos) {
+            Ok(p) => p,
+            Err(_) => return,
+        };
+        self.clip
+            .write_volume_envelope_point(pos_in_beats, volume, self.envelope_write_pos);
+        if self.written_envelope_point_count < MAX_ENVELOPE_POINT_COUNT_WITHOUT_REALLOCATION {
+            // Just send the new point. Cheap.
+            column_command_sender.write_clip_volume_envelope_point(
+                slot_index,
+                pos_in_beats,
+                volume,
+                self.envelope_write_pos,
+            );
+            self.written_envelope_point_count += 1;
+        } else {
+            // The real-time clip is out of space. Send everything, which makes room again.
+            column_command_sender.set_clip_envelopes(slot_index, self.clip.envelopes().clone());
+            self.written_envelope_point_count = 0;
+        }
+        self.envelope_write_pos = Some(pos);
+    }
 }
 
 impl Slot {
@@ -105,6 +185,20 @@ impl Slot {
         self.index
     }
 
+    /// Takes the content out of this slot, e.g. in order to move it to another slot.
+    pub(super) fn take_content(&mut self) -> Option<Content> {
+        self.content.take()
+    }
+
+    pub(super) fn set_content(&mut self, content: Option<Content>) {
+        self.content = content;
+    }
+
+    /// Must be called whenever the slot changes its position within the column.
+    pub(super) fn set_index(&mut self, index: usize) {
+        self.index = index;
+    }
+
     /// Returns `None` if this slot doesn't need to be saved (because it's empty).
     pub fn save(&self, temporary_project: Option<Project>) -> Option<api::Slot> {
         let content = self.content.as_ref()?;
@@ -131,6 +225,7 @@ impl Slot {
         let api_slot = api::Slot {
             row: self.index,
             clip: Some(clip),
+            takes: content.takes.clone(),
         };
         Some(api_slot)
     }
@@ -185,6 +280,22 @@ impl Slot {
         } else {
             None
         };
+        let audio_settings = &matrix_record_settings.audio_settings;
+        let desired_audio_overdub_instruction = match &self.content {
+            // Only allow audio overdub if existing clip is an audio clip already.
+            Some(content)
+                if audio_settings.record_mode == AudioClipRecordMode::Overdub
+                    && !content.runtime_data.material_info.is_midi() =>
+            {
+                let instruction = create_audio_overdub_instruction(
+                    &content.runtime_data.material_info,
+                    audio_settings.overdub_feedback,
+                    Some(project),
+                );
+                Some(instruction)
+            }
+            _ => None,
+        };
         let (common_stuff, mode_specific_stuff) = create_record_stuff(
             self.index,
             containing_track,
@@ -193,6 +304,7 @@ impl Slot {
             recording_track,
             rt_column,
             desired_midi_overdub_instruction,
+            desired_audio_overdub_instruction,
         )?;
         match mode_specific_stuff {
             ModeSpecificRecordStuff::FromScratch(from_scratch_stuff) => self.record_from_scratch(
@@ -215,6 +327,17 @@ impl Slot {
                     common_stuff,
                     midi_overdub_stuff,
                 ),
+            ModeSpecificRecordStuff::AudioOverdub(audio_overdub_stuff) => {
+                self.initiate_recording(
+                    column_command_sender,
+                    handler,
+                    SlotState::RequestedOverdubbing,
+                    SlotRecordInstruction::AudioOverdub(audio_overdub_stuff.instruction),
+                    common_stuff.temporary_route,
+                    common_stuff.task,
+                );
+                Ok(())
+            }
         }
     }
 
@@ -383,6 +506,62 @@ impl Slot {
         Ok(())
     }
 
+    /// Multiplies the canvas length with the given factor.
+    ///
+    /// If the clip doesn't have a canvas yet, the current canvas length is the end of the section.
+    pub fn adjust_clip_canvas_length(
+        &mut self,
+        factor: f64,
+        column_command_sender: &ColumnCommandSender,
+    ) -> ClipEngineResult<()> {
+        let content = get_content_mut(&mut self.content)?;
+        let current_canvas = content.clip.canvas();
+        let section_pos = current_canvas
+            .map(|c| c.section_pos)
+            .unwrap_or(PositiveSecond::new(0.0)?);
+        let current_length = if let Some(current_length) = current_canvas.and_then(|c| c.length) {
+            current_length.get()
+        } else {
+            let section = content.clip.section();
+            let section_length = if let Some(l) = section.length {
+                l.get()
+            } else {
+                (content.runtime_data.material_info.duration().get() - section.start_pos.get())
+                    .max(0.0)
+            };
+            section_pos.get() + section_length
+        };
+        let new_canvas = api::Canvas {
+            section_pos,
+            length: Some(PositiveSecond::new(current_length * factor)?),
+        };
+        content.clip.set_canvas(Some(new_canvas));
+        column_command_sender.set_clip_canvas(self.index, Some(new_canvas));
+        Ok(())
+    }
+
+    /// Moves the section within the canvas by the given amount of seconds (negative means to the
+    /// left).
+    ///
+    /// A fixed canvas length stays as it is. If the canvas has no fixed length, it ends with the
+    /// section, so it grows or shrinks by the same amount.
+    pub fn move_clip_section_in_canvas(
+        &mut self,
+        delta: f64,
+        column_command_sender: &ColumnCommandSender,
+    ) -> ClipEngineResult<()> {
+        let content = get_content_mut(&mut self.content)?;
+        let current_canvas = content.clip.canvas();
+        let current_pos = current_canvas.map(|c| c.section_pos.get()).unwrap_or(0.0);
+        let new_canvas = api::Canvas {
+            section_pos: PositiveSecond::new((current_pos + delta).max(0.0))?,
+            length: current_canvas.and_then(|c| c.length),
+        };
+        content.clip.set_canvas(Some(new_canvas));
+        column_command_sender.set_clip_canvas(self.index, Some(new_canvas));
+        Ok(())
+    }
+
     pub fn is_freezeable(&self) -> bool {
         let content = match self.content.as_ref() {
             None => return false,
@@ -488,10 +667,22 @@ impl Slot {
         Ok(self.get_content()?.clip.volume())
     }
 
+    pub fn clip_pitch(&self) -> ClipEngineResult<api::ClipPitch> {
+        Ok(self.get_content()?.clip.pitch())
+    }
+
+    pub fn clip_midi_transformation(&self) -> ClipEngineResult<api::MidiTransformation> {
+        Ok(self.get_content()?.clip.midi_transformation())
+    }
+
     pub fn clip_looped(&self) -> ClipEngineResult<bool> {
         Ok(self.get_content()?.clip.looped())
     }
 
+    pub fn clip_reversed(&self) -> ClipEngineResult<bool> {
+        Ok(self.get_content()?.clip.reversed())
+    }
+
     pub fn set_clip_volume(
         &mut self,
         volume: Db,
@@ -500,9 +691,51 @@ impl Slot {
         let content = get_content_mut(&mut self.content)?;
         content.clip.set_volume(volume);
         column_command_sender.set_clip_volume(self.index, volume);
+        if content.clip.envelopes().mode == api::ClipEnvelopeMode::Write {
+            content.write_volume_envelope_point(volume, self.index, column_command_sender);
+        }
         Ok(ClipChangeEvent::ClipVolume(volume))
     }
 
+    pub fn clip_envelopes(&self) -> ClipEngineResult<&api::ClipEnvelopes> {
+        Ok(self.get_content()?.clip.envelopes())
+    }
+
+    pub fn set_clip_envelopes(
+        &mut self,
+        envelopes: api::ClipEnvelopes,
+        column_command_sender: &ColumnCommandSender,
+    ) -> ClipEngineResult<()> {
+        let content = get_content_mut(&mut self.content)?;
+        content.envelope_write_pos = None;
+        content.written_envelope_point_count = 0;
+        content.clip.set_envelopes(envelopes.clone());
+        column_command_sender.set_clip_envelopes(self.index, envelopes);
+        Ok(())
+    }
+
+    pub fn set_clip_pitch(
+        &mut self,
+        pitch: api::ClipPitch,
+        column_command_sender: &ColumnCommandSender,
+    ) -> ClipEngineResult<ClipChangeEvent> {
+        let content = get_content_mut(&mut self.content)?;
+        content.clip.set_pitch(pitch);
+        column_command_sender.set_clip_pitch(self.index, pitch);
+        Ok(ClipChangeEvent::ClipPitch(pitch))
+    }
+
+    pub fn set_clip_midi_transformation(
+        &mut self,
+        transformation: api::MidiTransformation,
+        column_command_sender: &ColumnCommandSender,
+    ) -> ClipEngineResult<ClipChangeEvent> {
+        let content = get_content_mut(&mut self.content)?;
+        content.clip.set_midi_transformation(transformation);
+        column_command_sender.set_clip_midi_transformation(self.index, transformation);
+        Ok(ClipChangeEvent::ClipMidiTransformation(transformation))
+    }
+
     pub fn toggle_clip_looped(
         &mut self,
         column_command_sender: &ColumnCommandSender,
@@ -517,6 +750,20 @@ impl Slot {
         Ok(ClipChangeEvent::ClipLooped(looped))
     }
 
+    pub fn toggle_clip_reversed(
+        &mut self,
+        column_command_sender: &ColumnCommandSender,
+    ) -> ClipEngineResult<ClipChangeEvent> {
+        let content = get_content_mut(&mut self.content)?;
+        let reversed = content.clip.toggle_reversed();
+        let args = ColumnSetClipReversedArgs {
+            slot_index: self.index,
+            reversed,
+        };
+        column_command_sender.set_clip_reversed(args);
+        Ok(ClipChangeEvent::ClipReversed(reversed))
+    }
+
     pub fn clip_play_state(&self) -> ClipEngineResult<InternalClipPlayState> {
         use SlotState::*;
         match &self.state {
@@ -605,6 +852,9 @@ impl Slot {
         rt_clip: &rt::Clip,
         pooled_midi_source: Option<ClipSource>,
     ) {
+        // Refilling an occupied slot (e.g. after detecting the tempo again) keeps its inactive
+        // takes.
+        let takes = self.content.take().map(|c| c.takes).unwrap_or_default();
         let content = Content {
             clip,
             runtime_data: SlotRuntimeData {
@@ -613,10 +863,105 @@ impl Slot {
                 material_info: rt_clip.material_info().unwrap(),
             },
             pooled_midi_source,
+            envelope_write_pos: None,
+            written_envelope_point_count: 0,
+            takes,
         };
         self.content = Some(content);
     }
 
+    /// Returns the number of takes in this slot, including the active one.
+    pub fn take_count(&self) -> usize {
+        match &self.content {
+            None => 0,
+            Some(c) => c.takes.len() + 1,
+        }
+    }
+
+    pub(super) fn set_takes(&mut self, mut takes: Vec<api::Clip>) -> ClipEngineResult<()> {
+        discard_excess_takes(&mut takes);
+        get_content_mut(&mut self.content)?.takes = takes;
+        Ok(())
+    }
+
+    /// Returns the inactive takes of this slot, oldest first.
+    pub fn takes(&self) -> &[api::Clip] {
+        match &self.content {
+            None => &[],
+            Some(c) => &c.takes,
+        }
+    }
+
+    /// Returns the oldest inactive take (which should become the active one) and the remaining
+    /// takes, with the currently active take moved to the end.
+    ///
+    /// Doesn't change anything yet.
+    pub(super) fn cycle_takes(
+        &self,
+        temporary_project: Option<Project>,
+    ) -> ClipEngineResult<(api::Clip, Vec<api::Clip>)> {
+        if self.get_content()?.takes.is_empty() {
+            return Err("slot has only one take");
+        }
+        self.select_take(0, temporary_project)
+    }
+
+    /// Returns the inactive take at the given index (which should become the active one) and the
+    /// remaining takes, with the currently active take moved to the end.
+    ///
+    /// Doesn't change anything yet.
+    pub(super) fn select_take(
+        &self,
+        take_index: usize,
+        temporary_project: Option<Project>,
+    ) -> ClipEngineResult<(api::Clip, Vec<api::Clip>)> {
+        self.ensure_takes_switchable()?;
+        let content = self.get_content()?;
+        if take_index >= content.takes.len() {
+            return Err("take doesn't exist");
+        }
+        let active_take = content.save_active_take(temporary_project)?;
+        Ok(exchange_take(
+            content.takes.clone(),
+            active_take,
+            take_index,
+        ))
+    }
+
+    /// Returns the most recent inactive take (which should replace the active one) and the
+    /// remaining takes.
+    ///
+    /// Doesn't change anything yet.
+    pub(super) fn delete_active_take(&self) -> ClipEngineResult<(api::Clip, Vec<api::Clip>)> {
+        self.ensure_takes_switchable()?;
+        let mut takes = self.get_content()?.takes.clone();
+        let next_take = takes.pop().ok_or("slot has only one take")?;
+        Ok((next_take, takes))
+    }
+
+    /// Keeps the active take and discards all other ones.
+    ///
+    /// This is not comping in the sense of combining the best parts of several takes into one.
+    /// Users who want that need to do it in the arrange view for now.
+    // TODO-medium Support real comping, e.g. by choosing a take per section.
+    pub(super) fn keep_active_take_only(&mut self) -> ClipEngineResult<()> {
+        if self.is_recording() {
+            return Err("can't discard takes while recording");
+        }
+        get_content_mut(&mut self.content)?.takes.clear();
+        Ok(())
+    }
+
+    fn ensure_takes_switchable(&self) -> ClipEngineResult<()> {
+        if self.is_recording() {
+            return Err("can't switch takes while recording");
+        }
+        if self.is_stoppable() {
+            return Err("can't switch takes while clip is playing");
+        }
+        Ok(())
+    }
+
     pub fn notify_recording_request_acknowledged(
         &mut self,
         result: Result<Option<SlotRuntimeData>, SlotRecordInstruction>,
@@ -666,6 +1011,38 @@ impl Slot {
         Ok(ClipChangeEvent::RecordingFinished)
     }
 
+    /// The overdubbed material still needs to be committed via [`Self::commit_audio_overdub`].
+    pub fn notify_audio_overdub_finished(&mut self) {
+        self.remove_temporary_route();
+    }
+
+    /// Writes the overdubbed material to a new file and lets the clip use it.
+    pub fn commit_audio_overdub(
+        &mut self,
+        outcome: AudioOverdubOutcome,
+        temporary_project: Option<Project>,
+        column_command_sender: &ColumnCommandSender,
+    ) -> ClipEngineResult<ClipChangeEvent> {
+        let content = get_content_mut(&mut self.content)?;
+        write_audio_file(&outcome.file, outcome.content.to_buf(), outcome.frame_rate)?;
+        let file_source = api::FileSource {
+            path: outcome.file.clone(),
+        };
+        let pcm_source = create_pcm_source_from_file_based_api_source(None, &file_source, false)?;
+        content
+            .clip
+            .notify_audio_overdub_finished(&outcome.file, temporary_project);
+        // The real-time clip still plays the overdubbed material from its cache. From now on, it
+        // should be associated with the new file, otherwise it would get lost when the cache is
+        // rebuilt.
+        column_command_sender.replace_clip_source(
+            self.index,
+            ClipSource::new(pcm_source.into_raw()),
+            outcome.file,
+        );
+        Ok(ClipChangeEvent::RecordingFinished)
+    }
+
     pub fn slot_cleared(&mut self) -> Option<ClipChangeEvent> {
         self.content.take().map(|_| ClipChangeEvent::Removed)
     }
@@ -690,12 +1067,28 @@ impl Slot {
                         s.pooled_midi_source.as_ref(),
                         recording_track,
                     )?;
+                    // The previous clip stays available as inactive take.
+                    let takes = match self.content.take() {
+                        None => vec![],
+                        Some(previous_content) => {
+                            let mut takes = previous_content.takes;
+                            match previous_content.save_active_take(temporary_project) {
+                                Ok(previous_take) => takes.push(previous_take),
+                                Err(e) => debug!("Couldn't keep previous take: {}", e),
+                            }
+                            discard_excess_takes(&mut takes);
+                            takes
+                        }
+                    };
                     s.runtime_data.material_info = recording.material_info;
                     debug!("Fill slot with clip: {:#?}", &clip);
                     let content = Content {
                         clip,
                         runtime_data: s.runtime_data,
                         pooled_midi_source: s.pooled_midi_source,
+                        envelope_write_pos: None,
+                        written_envelope_point_count: 0,
+                        takes,
                     };
                     self.content = Some(content);
                     self.state = SlotState::Normal;
@@ -763,6 +1156,7 @@ struct CommonRecordStuff {
 enum ModeSpecificRecordStuff {
     FromScratch(FromScratchRecordStuff),
     MidiOverdub(MidiOverdubRecordStuff),
+    AudioOverdub(AudioOverdubRecordStuff),
 }
 
 struct FromScratchRecordStuff {
@@ -774,6 +1168,11 @@ struct MidiOverdubRecordStuff {
     instruction: MidiOverdubInstruction,
 }
 
+struct AudioOverdubRecordStuff {
+    instruction: AudioOverdubInstruction,
+}
+
+#[allow(clippy::too_many_arguments)]
 fn create_record_stuff(
     slot_index: usize,
     containing_track: Option<&Track>,
@@ -782,6 +1181,7 @@ fn create_record_stuff(
     recording_track: &Track,
     column_source: &SharedColumn,
     desired_midi_overdub_instruction: Option<MidiOverdubInstruction>,
+    desired_audio_overdub_instruction: Option<AudioOverdubInstruction>,
 ) -> ClipEngineResult<(CommonRecordStuff, ModeSpecificRecordStuff)> {
     let (input, temporary_route) = {
         use RecordOrigin::*;
@@ -834,22 +1234,27 @@ fn create_record_stuff(
         Some(recording_track.project()),
         matrix_record_settings.midi_settings.auto_quantize,
     )?;
-    let final_midi_overdub_instruction = if recording_equipment.is_midi() {
-        desired_midi_overdub_instruction
+    // If we want overdub but the input doesn't match the existing clip, don't use overdub mode
+    // after all.
+    let final_overdub_stuff = if recording_equipment.is_midi() {
+        desired_midi_overdub_instruction.map(|instruction| {
+            ModeSpecificRecordStuff::MidiOverdub(MidiOverdubRecordStuff { instruction })
+        })
     } else {
-        // Want overdub but we have a audio input, so don't use overdub mode after all.
-        None
+        desired_audio_overdub_instruction.map(|instruction| {
+            ModeSpecificRecordStuff::AudioOverdub(AudioOverdubRecordStuff { instruction })
+        })
     };
     let task = ClipRecordTask {
         input,
         destination: ClipRecordDestination {
             column_source: column_source.downgrade(),
             slot_index,
-            is_midi_overdub: final_midi_overdub_instruction.is_some(),
+            is_overdub: final_overdub_stuff.is_some(),
         },
     };
-    let mode_specific_stuff = if let Some(instruction) = final_midi_overdub_instruction {
-        ModeSpecificRecordStuff::MidiOverdub(MidiOverdubRecordStuff { instruction })
+    let mode_specific_stuff = if let Some(overdub_stuff) = final_overdub_stuff {
+        overdub_stuff
     } else {
         let pooled_midi_source = match &recording_equipment {
             RecordingEquipment::Midi(e) => Some(e.create_pooled_copy_of_midi_source()),
@@ -869,6 +1274,10 @@ fn create_record_stuff(
 
 const SLOT_NOT_FILLED: &str = "slot not filled";
 
+/// Maximum number of takes per slot, including the active one. If exceeded, the oldest ones are
+/// discarded.
+const MAX_TAKE_COUNT: usize = 10;
+
 fn translate_track_input_to_hw_input(
     track_input: RecordingInput,
 ) -> ClipEngineResult<ClipRecordHardwareInput> {
@@ -934,6 +1343,23 @@ pub fn create_midi_overdub_instruction(
     Ok(instruction)
 }
 
+pub fn create_audio_overdub_instruction(
+    material_info: &MaterialInfo,
+    feedback: OverdubFeedback,
+    temporary_project: Option<Project>,
+) -> AudioOverdubInstruction {
+    AudioOverdubInstruction {
+        file: get_path_for_new_media_file("clip-audio", "wav", temporary_project),
+        // The buffer for committing the overdub must be allocated here because we must not
+        // allocate in the real-time thread.
+        commit_buffer: OwnedAudioBuffer::new(
+            material_info.channel_count(),
+            material_info.frame_count(),
+        ),
+        feedback: feedback.get(),
+    }
+}
+
 fn find_or_create_editor_track(project: Project, show_track: bool) -> Track {
     let track = find_editor_track(project).unwrap_or_else(|| {
         let track = project.add_track();
@@ -968,7 +1394,10 @@ fn item_refers_to_clip_content(item: Item, content: &Content) -> bool {
     };
     if let Some(clip_source) = &content.pooled_midi_source {
         // TODO-medium Checks can be optimized (in terms of performance)
-        let clip_source = BorrowedSource::from_raw(clip_source.reaper_source());
+        let clip_source = match clip_source.reaper_source() {
+            Ok(s) => BorrowedSource::from_raw(s),
+            Err(_) => return false,
+        };
         clip_source.pooled_midi_id().map(|res| res.id) == source.pooled_midi_id().map(|res| res.id)
     } else if let api::Source::File(s) = &content.clip.api_source() {
         source
@@ -1103,10 +1532,10 @@ fn manifest_clip_on_track(
         // TODO-low This problem might disappear though as soon as we can use
         //  "Source beats" MIDI editor time base (which we can't use at the moment because we rely
         //  on sections).
-        let _ = source.reaper_source().ext_set_preview_tempo(None);
+        let _ = source.reaper_source()?.ext_set_preview_tempo(None);
     }
     let take = item.add_take().map_err(|e| e.message())?;
-    let source = OwnedSource::new(source.into_reaper_source());
+    let source = OwnedSource::new(source.into_reaper_source()?);
     take.set_source(source);
     take.set_start_offset(take_offset).unwrap();
     item.set_position(item_pos, UiRefreshBehavior::NoRefresh)
@@ -1123,3 +1552,208 @@ pub struct ClipOnTrackManifestation {
     /// Always set if beat-based.
     tempo: Option<Bpm>,
 }
+
+/// Takes the inactive take at the given index out of the list and appends the active take
+/// instead.
+///
+/// Returns the take which should become the active one and the new list of inactive takes.
+fn exchange_take<T>(mut inactive_takes: Vec<T>, active_take: T, take_index: usize) -> (T, Vec<T>) {
+    let next_take = inactive_takes.remove(take_index);
+    inactive_takes.push(active_take);
+    (next_take, inactive_takes)
+}
+
+/// Discards the oldest inactive takes if the slot would exceed [`MAX_TAKE_COUNT`].
+fn discard_excess_takes<T>(inactive_takes: &mut Vec<T>) {
+    let max_inactive_take_count = MAX_TAKE_COUNT - 1;
+    if inactive_takes.len() > max_inactive_take_count {
+        let excess_count = inactive_takes.len() - max_inactive_take_count;
+        inactive_takes.drain(..excess_count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rt::supplier::MidiMaterialInfo;
+    use playtime_api::persistence::{ClipColor, Section};
+
+    fn take(name: &str) -> api::Clip {
+        api::Clip {
+            name: Some(name.to_string()),
+            source: api::Source::File(api::FileSource {
+                path: format!("{}.mid", name).into(),
+            }),
+            frozen_source: None,
+            active_source: Default::default(),
+            time_base: ClipTimeBase::Time,
+            start_timing: None,
+            stop_timing: None,
+            looped: true,
+            reversed: false,
+            volume: Db::ZERO,
+            pitch: Default::default(),
+            color: ClipColor::PlayTrackColor,
+            section: Section {
+                start_pos: PositiveSecond::new(0.0).unwrap(),
+                length: None,
+            },
+            audio_settings: Default::default(),
+            midi_settings: Default::default(),
+            canvas: None,
+            envelopes: Default::default(),
+        }
+    }
+
+    fn slot_with_takes(
+        active_take: &str,
+        inactive_takes: &[&str],
+        play_state: ClipPlayState,
+    ) -> Slot {
+        let content = Content {
+            clip: Clip::load(take(active_take)),
+            runtime_data: SlotRuntimeData {
+                play_state: play_state.into(),
+                pos: Default::default(),
+                material_info: MaterialInfo::Midi(MidiMaterialInfo { frame_count: 1024 }),
+            },
+            pooled_midi_source: None,
+            envelope_write_pos: None,
+            written_envelope_point_count: 0,
+            takes: inactive_takes.iter().map(|name| take(name)).collect(),
+        };
+        let mut slot = Slot::new(0);
+        slot.content = Some(content);
+        slot
+    }
+
+    fn names(takes: &[api::Clip]) -> Vec<&str> {
+        takes.iter().map(|t| t.name.as_deref().unwrap()).collect()
+    }
+
+    #[test]
+    fn cycle_takes_in_slot() {
+        // Given
+        let slot = slot_with_takes("c", &["a", "b"], ClipPlayState::Stopped);
+        // When
+        let (next_take, takes) = slot.cycle_takes(None).unwrap();
+        // Then
+        assert_eq!(next_take.name.as_deref(), Some("a"));
+        assert_eq!(names(&takes), vec!["b", "c"]);
+    }
+
+    #[test]
+    fn select_take_in_slot() {
+        // Given
+        let slot = slot_with_takes("c", &["a", "b"], ClipPlayState::Stopped);
+        // When
+        let (next_take, takes) = slot.select_take(1, None).unwrap();
+        // Then
+        assert_eq!(next_take.name.as_deref(), Some("b"));
+        assert_eq!(names(&takes), vec!["a", "c"]);
+        assert!(slot.select_take(2, None).is_err());
+    }
+
+    #[test]
+    fn delete_active_take_in_slot() {
+        // Given
+        let slot = slot_with_takes("c", &["a", "b"], ClipPlayState::Stopped);
+        // When
+        let (next_take, takes) = slot.delete_active_take().unwrap();
+        // Then
+        assert_eq!(next_take.name.as_deref(), Some("b"));
+        assert_eq!(names(&takes), vec!["a"]);
+    }
+
+    #[test]
+    fn refuse_deleting_only_take() {
+        // Given
+        let slot = slot_with_takes("a", &[], ClipPlayState::Stopped);
+        // When
+        let result = slot.delete_active_take();
+        // Then
+        assert!(result.is_err());
+        assert!(slot.cycle_takes(None).is_err());
+    }
+
+    #[test]
+    fn refuse_switching_takes_while_playing() {
+        // Given
+        let slot = slot_with_takes("c", &["a", "b"], ClipPlayState::Playing);
+        // When
+        let result = slot.cycle_takes(None);
+        // Then
+        assert!(result.is_err());
+        assert!(slot.select_take(0, None).is_err());
+        assert!(slot.delete_active_take().is_err());
+    }
+
+    #[test]
+    fn keep_active_take_only_in_slot() {
+        // Given
+        let mut slot = slot_with_takes("c", &["a", "b"], ClipPlayState::Playing);
+        assert_eq!(slot.take_count(), 3);
+        // When
+        slot.keep_active_take_only().unwrap();
+        // Then
+        assert_eq!(slot.take_count(), 1);
+        assert!(slot.takes().is_empty());
+    }
+
+    #[test]
+    fn cap_take_count_in_slot() {
+        // Given
+        let mut slot = slot_with_takes("active", &[], ClipPlayState::Stopped);
+        let takes: Vec<_> = (0..MAX_TAKE_COUNT + 2)
+            .map(|i| take(&i.to_string()))
+            .collect();
+        // When
+        slot.set_takes(takes).unwrap();
+        // Then
+        assert_eq!(slot.take_count(), MAX_TAKE_COUNT);
+        assert_eq!(slot.takes()[0].name.as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn discard_oldest_takes_when_exceeding_max_take_count() {
+        // Given
+        let mut inactive_takes: Vec<_> = (0..MAX_TAKE_COUNT - 1).collect();
+        // When
+        inactive_takes.push(MAX_TAKE_COUNT - 1);
+        discard_excess_takes(&mut inactive_takes);
+        // Then
+        assert_eq!(inactive_takes.len(), MAX_TAKE_COUNT - 1);
+        assert_eq!(inactive_takes.first(), Some(&1));
+        assert_eq!(inactive_takes.last(), Some(&(MAX_TAKE_COUNT - 1)));
+    }
+
+    #[test]
+    fn cycling_takes_visits_all_takes_in_order() {
+        // Given
+        let mut active_take = 3;
+        let mut inactive_takes = vec![0, 1, 2];
+        // When
+        let mut visited = vec![];
+        for _ in 0..4 {
+            let (next_take, remaining) = exchange_take(inactive_takes, active_take, 0);
+            active_take = next_take;
+            inactive_takes = remaining;
+            visited.push(active_take);
+        }
+        // Then
+        assert_eq!(visited, vec![0, 1, 2, 3]);
+        assert_eq!(inactive_takes, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn selecting_take_moves_active_take_to_end() {
+        // Given
+        let active_take = 3;
+        let inactive_takes = vec![0, 1, 2];
+        // When
+        let (next_take, remaining) = exchange_take(inactive_takes, active_take, 1);
+        // Then
+        assert_eq!(next_take, 1);
+        assert_eq!(remaining, vec![0, 2, 3]);
+    }
+}
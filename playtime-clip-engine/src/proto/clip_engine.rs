@@ -29,6 +29,14 @@ pub struct GetContinuousSlotUpdatesRequest {
     pub clip_matrix_id: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JumpToHistoryEntryRequest {
+    #[prost(string, tag = "1")]
+    pub clip_matrix_id: ::prost::alloc::string::String,
+    /// Index within the list of all history entries, from oldest to newest.
+    #[prost(uint32, tag = "2")]
+    pub index: u32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetOccasionalMatrixUpdatesReply {
     #[prost(message, repeated, tag = "1")]
     pub matrix_updates: ::prost::alloc::vec::Vec<OccasionalMatrixUpdate>,
@@ -59,6 +67,8 @@ pub struct GetContinuousSlotUpdatesReply {
     pub slot_updates: ::prost::alloc::vec::Vec<QualifiedContinuousSlotUpdate>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JumpToHistoryEntryReply {}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ContinuousMatrixUpdate {
     #[prost(double, tag = "1")]
     pub second: f64,
@@ -90,7 +100,10 @@ pub struct QualifiedOccasionalTrackUpdate {
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OccasionalMatrixUpdate {
-    #[prost(oneof = "occasional_matrix_update::Update", tags = "1, 2, 3, 4, 5, 6")]
+    #[prost(
+        oneof = "occasional_matrix_update::Update",
+        tags = "1, 2, 3, 4, 5, 6, 7"
+    )]
     pub update: ::core::option::Option<occasional_matrix_update::Update>,
 }
 /// Nested message and enum types in `OccasionalMatrixUpdate`.
@@ -109,6 +122,8 @@ pub mod occasional_matrix_update {
         MidiInputDevices(super::MidiInputDevices),
         #[prost(message, tag = "6")]
         AudioInputChannels(super::AudioInputChannels),
+        #[prost(message, tag = "7")]
+        History(super::HistoryState),
     }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -199,6 +214,13 @@ pub struct AudioInputChannel {
     pub name: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HistoryState {
+    #[prost(string, repeated, tag = "1")]
+    pub entry_labels: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(uint32, optional, tag = "2")]
+    pub current_index: ::core::option::Option<u32>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct QualifiedOccasionalSlotUpdate {
     #[prost(message, optional, tag = "1")]
     pub slot_coordinates: ::core::option::Option<SlotCoordinates>,
@@ -330,6 +352,11 @@ pub mod clip_engine_server {
             &self,
             request: tonic::Request<super::GetContinuousSlotUpdatesRequest>,
         ) -> Result<tonic::Response<Self::GetContinuousSlotUpdatesStream>, tonic::Status>;
+        #[doc = " Commands"]
+        async fn jump_to_history_entry(
+            &self,
+            request: tonic::Request<super::JumpToHistoryEntryRequest>,
+        ) -> Result<tonic::Response<super::JumpToHistoryEntryReply>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct ClipEngineServer<T: ClipEngine> {
@@ -601,6 +628,40 @@ pub mod clip_engine_server {
                     };
                     Box::pin(fut)
                 }
+                "/playtime.clip_engine.ClipEngine/JumpToHistoryEntry" => {
+                    #[allow(non_camel_case_types)]
+                    struct JumpToHistoryEntrySvc<T: ClipEngine>(pub Arc<T>);
+                    impl<T: ClipEngine>
+                        tonic::server::UnaryService<super::JumpToHistoryEntryRequest>
+                        for JumpToHistoryEntrySvc<T>
+                    {
+                        type Response = super::JumpToHistoryEntryReply;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::JumpToHistoryEntryRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).jump_to_history_entry(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = JumpToHistoryEntrySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => Box::pin(async move {
                     Ok(http::Response::builder()
                         .status(200)
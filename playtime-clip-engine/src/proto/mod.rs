@@ -1,6 +1,6 @@
 mod clip_engine;
 
-use crate::main::ClipSlotCoordinates;
+use crate::main::{ClipSlotCoordinates, HistoryEntry};
 pub use clip_engine::*;
 use playtime_api::runtime::ClipPlayState;
 use reaper_medium::{InputMonitoringMode, RecordingInput, RgbColor};
@@ -14,6 +14,24 @@ impl SlotCoordinates {
     }
 }
 
+impl HistoryState {
+    pub fn from_engine(entries: impl Iterator<Item = HistoryEntry>) -> Self {
+        let mut current_index = None;
+        let entry_labels = entries
+            .map(|e| {
+                if e.is_current {
+                    current_index = Some(e.index as _);
+                }
+                e.label
+            })
+            .collect();
+        Self {
+            entry_labels,
+            current_index,
+        }
+    }
+}
+
 impl SlotPlayState {
     pub fn from_engine(play_state: ClipPlayState) -> Self {
         use ClipPlayState::*;
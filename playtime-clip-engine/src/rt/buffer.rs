@@ -4,7 +4,7 @@ use std::collections::Bound;
 use std::fmt::Debug;
 use std::ops::RangeBounds;
 
-#[derive(Derivative)]
+#[derive(Clone, Derivative)]
 #[derivative(Debug)]
 pub struct OwnedAudioBuffer {
     #[derivative(Debug = "ignore")]
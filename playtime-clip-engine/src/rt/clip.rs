@@ -3,16 +3,16 @@ use crate::conversion_util::{
     convert_duration_in_frames_to_seconds, convert_duration_in_seconds_to_frames,
 };
 use crate::main::ClipSlotCoordinates;
-use crate::rt::buffer::AudioBufMut;
+use crate::rt::buffer::{AudioBufMut, OwnedAudioBuffer};
 use crate::rt::schedule_util::calc_distance_from_quantized_pos;
 use crate::rt::supplier::{
-    AudioSupplier, ChainEquipment, ChainSettings, ClipSource, CompleteRecordingData,
-    KindSpecificRecordingOutcome, MaterialInfo, MidiOverdubSettings, MidiSupplier,
-    PollRecordingOutcome, RecordState, Recorder, RecorderRequest, RecordingArgs,
-    RecordingEquipment, RecordingOutcome, StopRecordingOutcome, SupplierChain, SupplyAudioRequest,
-    SupplyMidiRequest, SupplyRequestGeneralInfo, SupplyRequestInfo, SupplyResponse,
-    SupplyResponseStatus, WithMaterialInfo, WriteAudioRequest, WriteMidiRequest, MIDI_BASE_BPM,
-    MIDI_FRAME_RATE,
+    reserve_envelope_points_for_writing, AudioOverdubOutcome, AudioSupplier, ChainEquipment,
+    ChainSettings, ClipSource, CompleteRecordingData, KindSpecificRecordingOutcome, MaterialInfo,
+    MidiOverdubSettings, MidiSupplier, PollRecordingOutcome, RecordState, Recorder,
+    RecorderRequest, RecordingArgs, RecordingEquipment, RecordingOutcome, StopRecordingOutcome,
+    SupplierChain, SupplyAudioRequest, SupplyMidiRequest, SupplyRequestGeneralInfo,
+    SupplyRequestInfo, SupplyResponse, SupplyResponseStatus, WithMaterialInfo, WriteAudioRequest,
+    WriteMidiRequest, MIDI_BASE_BPM, MIDI_FRAME_RATE,
 };
 use crate::rt::tempo_util::{calc_tempo_factor, determine_tempo_from_time_base};
 use crate::rt::{ColumnSettings, OverridableMatrixSettings};
@@ -23,7 +23,7 @@ use helgoboss_learn::UnitValue;
 use playtime_api::persistence as api;
 use playtime_api::persistence::{
     ClipAudioSettings, ClipPlayStartTiming, ClipPlayStopTiming, ClipTimeBase, Db, EvenQuantization,
-    MatrixClipRecordSettings, PositiveSecond,
+    MatrixClipRecordSettings, PositiveBeat, PositiveSecond,
 };
 use playtime_api::runtime::ClipPlayState;
 use reaper_high::Project;
@@ -31,6 +31,7 @@ use reaper_medium::{
     BorrowedMidiEventList, Bpm, DurationInSeconds, Hz, OnAudioBufferArgs, PcmSourceTransfer,
     PositionInSeconds,
 };
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicIsize, Ordering};
 use std::sync::Arc;
 
@@ -40,6 +41,9 @@ pub struct Clip {
     state: ClipState,
     project: Option<Project>,
     shared_pos: SharedPos,
+    /// Audio settings of the clip itself. Settings which are not defined here are inherited from
+    /// the column or matrix.
+    audio_settings: api::ClipAudioSettings,
 }
 
 /// Contains only the state that's relevant for playing *and* not kept or not kept sufficiently in
@@ -50,6 +54,7 @@ struct PlaySettings {
     start_timing: Option<ClipPlayStartTiming>,
     stop_timing: Option<ClipPlayStopTiming>,
     looped: bool,
+    reversed: bool,
     time_base: ClipTimeBase,
 }
 
@@ -201,12 +206,16 @@ impl Clip {
         )?;
         let chain_settings = clip_settings.create_chain_settings(matrix_settings, column_settings);
         supplier_chain.configure_complete_chain(chain_settings)?;
+        let mut envelopes = clip_settings.envelopes.clone();
+        reserve_envelope_points_for_writing(&mut envelopes);
+        supplier_chain.set_envelopes(envelopes);
         supplier_chain.pre_buffer_simple(0);
         let clip = Self {
             supplier_chain,
             state: ClipState::Ready(ready_state),
             project: permanent_project,
             shared_pos: Default::default(),
+            audio_settings: clip_settings.audio_settings,
         };
         Ok(clip)
     }
@@ -222,6 +231,14 @@ impl Clip {
             state: ClipState::Recording(recording_state),
             project: instruction.project,
             shared_pos: instruction.shared_pos,
+            // A new recording doesn't override any inherited audio settings.
+            audio_settings: api::ClipAudioSettings {
+                apply_source_fades: true,
+                time_stretch_mode: None,
+                resample_mode: None,
+                cache_behavior: None,
+                fades: Default::default(),
+            },
         }
     }
 
@@ -262,7 +279,13 @@ impl Clip {
         use ClipState::*;
         let instruction = match &mut self.state {
             Ready(s) => {
+                let was_overdubbing = s.is_overdubbing();
                 s.stop(args, &mut self.supplier_chain);
+                if was_overdubbing && !s.is_overdubbing() {
+                    if let Some(outcome) = self.supplier_chain.finish_audio_overdub() {
+                        event_handler.audio_overdub_finished(outcome);
+                    }
+                }
                 None
             }
             Recording(s) => {
@@ -291,6 +314,17 @@ impl Clip {
         }
     }
 
+    pub fn set_reversed(&mut self, reversed: bool) -> ClipEngineResult<()> {
+        use ClipState::*;
+        match &mut self.state {
+            Ready(s) => {
+                s.set_reversed(reversed, &mut self.supplier_chain);
+                Ok(())
+            }
+            Recording(_) => Err("can't set reversed while recording"),
+        }
+    }
+
     pub fn set_section(&mut self, section: api::Section) -> ClipEngineResult<()> {
         use ClipState::*;
         match &mut self.state {
@@ -302,6 +336,17 @@ impl Clip {
         }
     }
 
+    pub fn set_canvas(&mut self, canvas: Option<api::Canvas>) -> ClipEngineResult<()> {
+        use ClipState::*;
+        match &mut self.state {
+            Ready(s) => {
+                s.set_canvas(canvas, &mut self.supplier_chain);
+                Ok(())
+            }
+            Recording(_) => Err("can't set canvas while recording"),
+        }
+    }
+
     pub fn looped(&self) -> bool {
         use ClipState::*;
         match self.state {
@@ -310,6 +355,14 @@ impl Clip {
         }
     }
 
+    pub fn reversed(&self) -> bool {
+        use ClipState::*;
+        match self.state {
+            Ready(s) => s.play_settings.reversed,
+            Recording(_) => false,
+        }
+    }
+
     pub fn midi_overdub(
         &mut self,
         args: MidiOverdubInstruction,
@@ -321,6 +374,52 @@ impl Clip {
         }
     }
 
+    pub fn audio_overdub(
+        &mut self,
+        args: AudioOverdubInstruction,
+    ) -> Result<(), ErrorWithPayload<AudioOverdubInstruction>> {
+        use ClipState::*;
+        match &mut self.state {
+            Ready(s) => s.audio_overdub(args, &mut self.supplier_chain),
+            Recording(_) => Err(ErrorWithPayload::new("clip is recording", args)),
+        }
+    }
+
+    /// Applies the audio settings which the clip inherits from the given matrix and column
+    /// settings, e.g. after the clip has been moved to another column.
+    pub fn inherit_audio_settings(
+        &mut self,
+        matrix_settings: &OverridableMatrixSettings,
+        column_settings: &ColumnSettings,
+    ) {
+        let own = &self.audio_settings;
+        self.supplier_chain.set_inherited_audio_settings(
+            own.time_stretch_mode
+                .or(column_settings.audio_time_stretch_mode)
+                .unwrap_or(matrix_settings.audio_time_stretch_mode),
+            own.resample_mode
+                .or(column_settings.audio_resample_mode)
+                .unwrap_or(matrix_settings.audio_resample_mode),
+            own.cache_behavior
+                .or(column_settings.audio_cache_behavior)
+                .unwrap_or(matrix_settings.audio_cache_behavior),
+        );
+    }
+
+    /// Swaps the given source with the current one, keeping the cached material. The given
+    /// source must contain exactly the same material.
+    pub fn replace_source(
+        &mut self,
+        source: &mut ClipSource,
+        cache_file_path: &mut PathBuf,
+    ) -> ClipEngineResult<()> {
+        use ClipState::*;
+        match &mut self.state {
+            Ready(_) => self.supplier_chain.replace_source(source, cache_file_path),
+            Recording(_) => Err("clip is recording"),
+        }
+    }
+
     pub fn record(
         &mut self,
         args: ClipRecordArgs,
@@ -420,18 +519,61 @@ impl Clip {
         self.supplier_chain.write_midi(request, play_pos).unwrap();
     }
 
-    /// Writes the samples in the given request into the currently recording audio source.
+    /// Writes the samples in the given request into the currently recording audio source or, if
+    /// overdubbing, mixes them into the played material.
     ///
     /// Also drives processing during recording because it's called exactly once per audio block
     /// anyway.
     pub fn write_audio(&mut self, request: impl WriteAudioRequest) {
-        self.supplier_chain.write_audio(request);
+        use ClipState::*;
+        match &self.state {
+            Ready(s) => {
+                if let ReadySubState::Playing(PlayingState {
+                    overdubbing: true,
+                    pos: Some(pos),
+                    ..
+                }) = s.state
+                {
+                    // Overdub material is written after processing, so the shared position
+                    // still marks the start of the block which has just been played.
+                    let block_start_pos = self.shared_pos.get();
+                    self.supplier_chain
+                        .overdub_audio(request, block_start_pos, pos);
+                }
+            }
+            Recording(_) => {
+                self.supplier_chain.write_audio(request);
+            }
+        }
     }
 
     pub fn set_volume(&mut self, volume: Db) {
         self.supplier_chain.set_volume(volume);
     }
 
+    pub fn set_pitch(&mut self, pitch: api::ClipPitch) -> ClipEngineResult<()> {
+        self.supplier_chain.set_pitch(pitch)
+    }
+
+    pub fn set_midi_transformation(&mut self, transformation: api::MidiTransformation) {
+        self.supplier_chain.set_midi_transformation(transformation);
+    }
+
+    /// Returns the previous envelopes.
+    pub fn set_envelopes(&mut self, envelopes: api::ClipEnvelopes) -> api::ClipEnvelopes {
+        self.supplier_chain.set_envelopes(envelopes)
+    }
+
+    pub fn write_volume_envelope_point(
+        &mut self,
+        pos: PositiveBeat,
+        volume: Db,
+        prev_write_pos: Option<f64>,
+    ) {
+        self.supplier_chain
+            .write_volume_envelope_point(pos, volume, prev_write_pos);
+    }
+
     pub fn shared_pos(&self) -> SharedPos {
         self.shared_pos.clone()
     }
@@ -521,10 +663,19 @@ impl ReadyState {
         supplier_chain.set_looped(self.play_settings.looped);
     }
 
+    pub fn set_reversed(&mut self, reversed: bool, supplier_chain: &mut SupplierChain) {
+        self.play_settings.reversed = reversed;
+        supplier_chain.set_reversed(reversed);
+    }
+
     pub fn set_section(&mut self, section: api::Section, supplier_chain: &mut SupplierChain) {
         supplier_chain.set_section(section.start_pos, section.length);
     }
 
+    pub fn set_canvas(&mut self, canvas: Option<api::Canvas>, supplier_chain: &mut SupplierChain) {
+        supplier_chain.set_canvas(canvas);
+    }
+
     pub fn play(&mut self, args: ClipPlayArgs, supplier_chain: &mut SupplierChain) -> PlayOutcome {
         let virtual_pos = self.calculate_virtual_play_pos(&args);
         use ReadySubState::*;
@@ -1101,6 +1252,34 @@ impl ReadyState {
         supplier_chain.reset_for_play(self.play_settings.looped);
     }
 
+    fn is_overdubbing(&self) -> bool {
+        matches!(
+            self.state,
+            ReadySubState::Playing(PlayingState {
+                overdubbing: true,
+                ..
+            })
+        )
+    }
+
+    pub fn audio_overdub(
+        &mut self,
+        args: AudioOverdubInstruction,
+        supplier_chain: &mut SupplierChain,
+    ) -> Result<(), ErrorWithPayload<AudioOverdubInstruction>> {
+        use ReadySubState::*;
+        if let Playing(s) = self.state {
+            supplier_chain.start_audio_overdub(args)?;
+            self.state = Playing(PlayingState {
+                overdubbing: true,
+                ..s
+            });
+            Ok(())
+        } else {
+            Err(ErrorWithPayload::new("clip not playing", args))
+        }
+    }
+
     pub fn midi_overdub(
         &mut self,
         args: MidiOverdubInstruction,
@@ -1482,6 +1661,7 @@ pub enum SlotRecordInstruction {
     NewClip(RecordNewClipInstruction),
     ExistingClip(ClipRecordArgs),
     MidiOverdub(MidiOverdubInstruction),
+    AudioOverdub(AudioOverdubInstruction),
 }
 
 #[derive(Debug)]
@@ -1503,6 +1683,17 @@ pub struct MidiOverdubInstruction {
     pub settings: MidiOverdubSettings,
 }
 
+#[derive(Debug)]
+pub struct AudioOverdubInstruction {
+    /// File into which the overdubbed material is going to be written.
+    pub file: PathBuf,
+    /// Pre-allocated buffer with the same channel and frame count as the clip material. Receives
+    /// a copy of the overdubbed material when overdubbing stops.
+    pub commit_buffer: OwnedAudioBuffer,
+    /// Factor by which the existing material is attenuated while overdubbing.
+    pub feedback: f64,
+}
+
 #[derive(Debug)]
 pub struct ClipRecordArgs {
     pub recording_equipment: RecordingEquipment,
@@ -1641,7 +1832,10 @@ impl Default for InternalClipPlayState {
 pub enum ClipChangeEvent {
     PlayState(InternalClipPlayState),
     ClipVolume(Db),
+    ClipPitch(api::ClipPitch),
+    ClipMidiTransformation(api::MidiTransformation),
     ClipLooped(bool),
+    ClipReversed(bool),
     ClipPosition(UnitValue),
     Removed,
     RecordingFinished,
@@ -1708,6 +1902,7 @@ struct FillSamplesOutcome {
 
 pub trait HandleSlotEvent {
     fn midi_overdub_finished(&self, mirror_source: ClipSource);
+    fn audio_overdub_finished(&self, outcome: AudioOverdubOutcome);
     fn normal_recording_finished(&self, outcome: NormalRecordingOutcome);
     fn slot_cleared(&self, clip: Clip);
 }
@@ -1737,12 +1932,16 @@ pub struct CommittedRecording {
 pub struct ProcessingRelevantClipSettings {
     pub time_base: api::ClipTimeBase,
     pub looped: bool,
+    pub reversed: bool,
     pub volume: api::Db,
+    pub pitch: api::ClipPitch,
     pub section: api::Section,
+    pub canvas: Option<api::Canvas>,
     pub start_timing: Option<api::ClipPlayStartTiming>,
     pub stop_timing: Option<api::ClipPlayStopTiming>,
     pub audio_settings: api::ClipAudioSettings,
     pub midi_settings: api::ClipMidiSettings,
+    pub envelopes: api::ClipEnvelopes,
 }
 
 impl ProcessingRelevantClipSettings {
@@ -1750,12 +1949,16 @@ impl ProcessingRelevantClipSettings {
         Self {
             time_base: clip.time_base,
             looped: clip.looped,
+            reversed: clip.reversed,
             volume: clip.volume,
+            pitch: clip.pitch,
             section: clip.section,
+            canvas: clip.canvas,
             start_timing: clip.start_timing,
             stop_timing: clip.stop_timing,
             audio_settings: clip.audio_settings,
             midi_settings: clip.midi_settings,
+            envelopes: clip.envelopes.clone(),
         }
     }
 
@@ -1778,6 +1981,7 @@ impl ProcessingRelevantClipSettings {
                 current_play_start_timing,
             ),
             looped: record_settings.looped,
+            reversed: false,
             time_base: {
                 let audio_tempo = if data.is_midi {
                     None
@@ -1795,20 +1999,24 @@ impl ProcessingRelevantClipSettings {
                 )
             },
             volume: api::Db::ZERO,
+            pitch: api::ClipPitch::ZERO,
             section: api::Section {
                 start_pos: PositiveSecond::new(data.section_start_pos_in_seconds().get())?,
                 length: data
                     .section_length_in_seconds()
                     .map(|l| PositiveSecond::new(l.get()).unwrap()),
             },
+            canvas: None,
             audio_settings: ClipAudioSettings {
                 // In general, a recording won't be automatically cut correctly, so we apply fades.
                 apply_source_fades: true,
                 time_stretch_mode: None,
                 resample_mode: None,
                 cache_behavior: None,
+                fades: Default::default(),
             },
             midi_settings: record_settings.midi_settings.clip_settings,
+            envelopes: Default::default(),
         };
         Ok(settings)
     }
@@ -1820,10 +2028,14 @@ impl ProcessingRelevantClipSettings {
     ) -> ChainSettings {
         ChainSettings {
             looped: self.looped,
+            reversed: self.reversed,
             time_base: self.time_base,
             volume: self.volume,
+            pitch: self.pitch,
             section: self.section,
+            canvas: self.canvas,
             audio_apply_source_fades: self.audio_settings.apply_source_fades,
+            audio_fades: self.audio_settings.fades,
             midi_settings: self.midi_settings,
             audio_time_stretch_mode: self
                 .audio_settings
@@ -1848,6 +2060,7 @@ impl ProcessingRelevantClipSettings {
             start_timing: self.start_timing,
             stop_timing: self.stop_timing,
             looped: self.looped,
+            reversed: self.reversed,
             time_base: self.time_base,
         }
     }
@@ -1,5 +1,8 @@
 use crate::mutex_util::{blocking_lock, non_blocking_lock};
-use crate::rt::supplier::{ClipSource, MaterialInfo, WriteAudioRequest, WriteMidiRequest};
+use crate::rt::supplier::{
+    reserve_envelope_points_for_writing, AudioOverdubOutcome, ClipSource, MaterialInfo,
+    WriteAudioRequest, WriteMidiRequest,
+};
 use crate::rt::{
     AudioBufMut, BasicAudioRequestProps, Clip, ClipPlayArgs, ClipProcessArgs,
     ClipRecordingPollArgs, ClipStopArgs, HandleSlotEvent, InternalClipPlayState,
@@ -14,7 +17,7 @@ use helgoboss_learn::UnitValue;
 use playtime_api::persistence as api;
 use playtime_api::persistence::{
     AudioCacheBehavior, AudioTimeStretchMode, ClipPlayStartTiming, ClipPlayStopTiming,
-    ColumnPlayMode, Db, VirtualResampleMode,
+    ColumnPlayMode, Db, PositiveBeat, VirtualResampleMode,
 };
 use reaper_high::Project;
 use reaper_medium::{
@@ -24,6 +27,7 @@ use reaper_medium::{
     SetFileNameArgs, SetSourceArgs,
 };
 use std::error::Error;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex, MutexGuard, Weak};
 
 /// Only such methods are public which are allowed to use from real-time threads. Other ones
@@ -110,6 +114,35 @@ impl ColumnCommandSender {
         self.send_task(ColumnCommand::ClearSlot(slot_index));
     }
 
+    pub fn insert_slot(&self, slot_index: usize) {
+        self.send_task(ColumnCommand::InsertSlot(slot_index));
+    }
+
+    pub fn remove_slot(&self, slot_index: usize) {
+        self.send_task(ColumnCommand::RemoveSlot(slot_index));
+    }
+
+    pub fn move_slot(&self, from_index: usize, to_index: usize) {
+        let args = ColumnMoveSlotArgs {
+            from_index,
+            to_index,
+        };
+        self.send_task(ColumnCommand::MoveSlot(args));
+    }
+
+    pub fn swap_slots(&self, index_a: usize, index_b: usize) {
+        let args = ColumnSwapSlotsArgs { index_a, index_b };
+        self.send_task(ColumnCommand::SwapSlots(args));
+    }
+
+    pub fn hand_over_clip(&self, args: Box<Option<ColumnHandOverClipArgs>>) {
+        self.send_task(ColumnCommand::HandOverClip(args));
+    }
+
+    fn take_over_clip(&self, args: Box<Option<ColumnHandOverClipArgs>>) {
+        self.send_task(ColumnCommand::TakeOverClip(args));
+    }
+
     pub fn play_clip(&self, args: ColumnPlayClipArgs) {
         self.send_task(ColumnCommand::PlayClip(args));
     }
@@ -130,6 +163,10 @@ impl ColumnCommandSender {
         self.send_task(ColumnCommand::SetClipLooped(args));
     }
 
+    pub fn set_clip_reversed(&self, args: ColumnSetClipReversedArgs) {
+        self.send_task(ColumnCommand::SetClipReversed(args));
+    }
+
     pub fn pause_clip(&self, index: usize) {
         let args = ColumnPauseClipArgs { index };
         self.send_task(ColumnCommand::PauseClip(args));
@@ -145,6 +182,65 @@ impl ColumnCommandSender {
         self.send_task(ColumnCommand::SetClipVolume(args));
     }
 
+    pub fn set_clip_pitch(&self, slot_index: usize, pitch: api::ClipPitch) {
+        let args = ColumnSetClipPitchArgs { slot_index, pitch };
+        self.send_task(ColumnCommand::SetClipPitch(args));
+    }
+
+    pub fn set_clip_envelopes(&self, slot_index: usize, mut envelopes: api::ClipEnvelopes) {
+        reserve_envelope_points_for_writing(&mut envelopes);
+        let args = ColumnSetClipEnvelopesArgs {
+            slot_index,
+            envelopes,
+        };
+        self.send_task(ColumnCommand::SetClipEnvelopes(Box::new(Some(args))));
+    }
+
+    /// Lets the real-time clip read its material from the given source, which must contain
+    /// exactly the same material as the current one (e.g. after an audio overdub has been
+    /// written to the given file).
+    pub fn replace_clip_source(&self, slot_index: usize, source: ClipSource, file_path: PathBuf) {
+        let args = ColumnReplaceClipSourceArgs {
+            slot_index,
+            source,
+            cache_file_path: file_path,
+        };
+        self.send_task(ColumnCommand::ReplaceClipSource(Box::new(Some(args))));
+    }
+
+    /// Writes a single point into the volume envelope of the real-time clip.
+    ///
+    /// Don't send more than
+    /// [`MAX_ENVELOPE_POINT_COUNT_WITHOUT_REALLOCATION`](crate::rt::supplier::MAX_ENVELOPE_POINT_COUNT_WITHOUT_REALLOCATION)
+    /// points without sending the complete envelopes in between.
+    pub fn write_clip_volume_envelope_point(
+        &self,
+        slot_index: usize,
+        pos: PositiveBeat,
+        volume: Db,
+        prev_write_pos: Option<f64>,
+    ) {
+        let args = ColumnWriteClipVolumeEnvelopePointArgs {
+            slot_index,
+            pos,
+            volume,
+            prev_write_pos,
+        };
+        self.send_task(ColumnCommand::WriteClipVolumeEnvelopePoint(args));
+    }
+
+    pub fn set_clip_midi_transformation(
+        &self,
+        slot_index: usize,
+        transformation: api::MidiTransformation,
+    ) {
+        let args = ColumnSetClipMidiTransformationArgs {
+            slot_index,
+            transformation,
+        };
+        self.send_task(ColumnCommand::SetClipMidiTransformation(args));
+    }
+
     pub fn set_clip_section(&self, slot_index: usize, section: api::Section) {
         let args = ColumnSetClipSectionArgs {
             slot_index,
@@ -153,6 +249,11 @@ impl ColumnCommandSender {
         self.send_task(ColumnCommand::SetClipSection(args));
     }
 
+    pub fn set_clip_canvas(&self, slot_index: usize, canvas: Option<api::Canvas>) {
+        let args = ColumnSetClipCanvasArgs { slot_index, canvas };
+        self.send_task(ColumnCommand::SetClipCanvas(args));
+    }
+
     pub fn record_clip(&self, slot_index: usize, instruction: SlotRecordInstruction) {
         let args = ColumnRecordClipArgs {
             slot_index,
@@ -170,6 +271,13 @@ impl ColumnCommandSender {
 pub enum ColumnCommand {
     ClearSlots,
     ClearSlot(usize),
+    InsertSlot(usize),
+    RemoveSlot(usize),
+    MoveSlot(ColumnMoveSlotArgs),
+    SwapSlots(ColumnSwapSlotsArgs),
+    // Boxed because comparatively large.
+    HandOverClip(Box<Option<ColumnHandOverClipArgs>>),
+    TakeOverClip(Box<Option<ColumnHandOverClipArgs>>),
     UpdateSettings(ColumnSettings),
     UpdateMatrixSettings(OverridableMatrixSettings),
     // Boxed because comparatively large.
@@ -182,8 +290,16 @@ pub enum ColumnCommand {
     PauseClip(ColumnPauseClipArgs),
     SeekClip(ColumnSeekClipArgs),
     SetClipVolume(ColumnSetClipVolumeArgs),
+    SetClipPitch(ColumnSetClipPitchArgs),
+    SetClipMidiTransformation(ColumnSetClipMidiTransformationArgs),
+    SetClipEnvelopes(Box<Option<ColumnSetClipEnvelopesArgs>>),
+    WriteClipVolumeEnvelopePoint(ColumnWriteClipVolumeEnvelopePointArgs),
+    // Boxed because comparatively large.
+    ReplaceClipSource(Box<Option<ColumnReplaceClipSourceArgs>>),
     SetClipLooped(ColumnSetClipLoopedArgs),
+    SetClipReversed(ColumnSetClipReversedArgs),
     SetClipSection(ColumnSetClipSectionArgs),
+    SetClipCanvas(ColumnSetClipCanvasArgs),
     RecordClip(Box<Option<ColumnRecordClipArgs>>),
 }
 
@@ -202,6 +318,8 @@ pub trait ColumnEventSender {
 
     fn midi_overdub_finished(&self, slot_index: usize, mirror_source: ClipSource);
 
+    fn audio_overdub_finished(&self, slot_index: usize, outcome: AudioOverdubOutcome);
+
     fn normal_recording_finished(&self, slot_index: usize, outcome: NormalRecordingOutcome);
 
     fn interaction_failed(&self, failure: InteractionFailure);
@@ -250,6 +368,14 @@ impl ColumnEventSender for Sender<ColumnEvent> {
         self.send_event(event);
     }
 
+    fn audio_overdub_finished(&self, slot_index: usize, outcome: AudioOverdubOutcome) {
+        let event = ColumnEvent::AudioOverdubFinished {
+            slot_index,
+            outcome,
+        };
+        self.send_event(event);
+    }
+
     fn normal_recording_finished(&self, slot_index: usize, outcome: NormalRecordingOutcome) {
         let event = ColumnEvent::NormalRecordingFinished {
             slot_index,
@@ -320,7 +446,10 @@ impl Column {
 
     fn fill_slot(&mut self, args: ColumnFillSlotArgs) {
         let material_info = args.clip.material_info().unwrap();
-        get_slot_mut_insert(&mut self.slots, args.slot_index).fill(args.clip);
+        let previous_clip = get_slot_mut_insert(&mut self.slots, args.slot_index).fill(args.clip);
+        if let Some(clip) = previous_clip {
+            self.event_sender.dispose(ColumnGarbage::Clip(clip));
+        }
         self.event_sender
             .clip_material_info_changed(args.slot_index, material_info);
     }
@@ -461,10 +590,18 @@ impl Column {
         get_slot_mut_insert(&mut self.slots, args.slot_index).set_clip_looped(args.looped)
     }
 
+    pub fn set_clip_reversed(&mut self, args: ColumnSetClipReversedArgs) -> ClipEngineResult<()> {
+        get_slot_mut_insert(&mut self.slots, args.slot_index).set_clip_reversed(args.reversed)
+    }
+
     pub fn set_clip_section(&mut self, args: ColumnSetClipSectionArgs) -> ClipEngineResult<()> {
         get_slot_mut_insert(&mut self.slots, args.slot_index).set_clip_section(args.section)
     }
 
+    pub fn set_clip_canvas(&mut self, args: ColumnSetClipCanvasArgs) -> ClipEngineResult<()> {
+        get_slot_mut_insert(&mut self.slots, args.slot_index).set_clip_canvas(args.canvas)
+    }
+
     pub fn clip_play_state(&self, slot_index: usize) -> ClipEngineResult<InternalClipPlayState> {
         Ok(get_slot(&self.slots, slot_index)?.clip()?.play_state())
     }
@@ -545,6 +682,46 @@ impl Column {
         get_slot_mut_insert(&mut self.slots, slot_index).set_clip_volume(volume)
     }
 
+    fn set_clip_pitch(&mut self, slot_index: usize, pitch: api::ClipPitch) -> ClipEngineResult<()> {
+        get_slot_mut_insert(&mut self.slots, slot_index).set_clip_pitch(pitch)
+    }
+
+    /// Returns the envelopes which are not needed anymore.
+    fn set_clip_envelopes(
+        &mut self,
+        slot_index: usize,
+        envelopes: api::ClipEnvelopes,
+    ) -> api::ClipEnvelopes {
+        get_slot_mut_insert(&mut self.slots, slot_index).set_clip_envelopes(envelopes)
+    }
+
+    fn write_clip_volume_envelope_point(
+        &mut self,
+        args: ColumnWriteClipVolumeEnvelopePointArgs,
+    ) -> ClipEngineResult<()> {
+        get_slot_mut_insert(&mut self.slots, args.slot_index).write_clip_volume_envelope_point(
+            args.pos,
+            args.volume,
+            args.prev_write_pos,
+        )
+    }
+
+    fn replace_clip_source(
+        &mut self,
+        args: &mut ColumnReplaceClipSourceArgs,
+    ) -> ClipEngineResult<()> {
+        get_slot_mut_insert(&mut self.slots, args.slot_index).replace_clip_source(args)
+    }
+
+    fn set_clip_midi_transformation(
+        &mut self,
+        slot_index: usize,
+        transformation: api::MidiTransformation,
+    ) -> ClipEngineResult<()> {
+        get_slot_mut_insert(&mut self.slots, slot_index)
+            .set_clip_midi_transformation(transformation)
+    }
+
     fn process_transport_change(&mut self, args: ColumnProcessTransportChangeArgs) {
         let args = SlotProcessTransportChangeArgs {
             column_args: &args,
@@ -572,6 +749,82 @@ impl Column {
         Ok(())
     }
 
+    /// Inserts an empty slot, shifting all following slots down by one.
+    fn insert_slot(&mut self, index: usize) {
+        if index > self.slots.len() {
+            // Slot doesn't exist physically yet, so there's nothing to shift.
+            return;
+        }
+        self.slots.insert(index, Slot::default());
+    }
+
+    /// Removes the slot, shifting all following slots up by one.
+    ///
+    /// A clip in that slot stops immediately. It's handed over to the main thread for disposal.
+    fn remove_slot(&mut self, index: usize) {
+        if index >= self.slots.len() {
+            return;
+        }
+        let slot = self.slots.remove(index);
+        if let Some(clip) = slot.into_clip() {
+            self.event_sender.dispose(ColumnGarbage::Clip(clip));
+        }
+    }
+
+    /// Moves the slot to the given index. Clips keep playing.
+    fn move_slot(&mut self, args: ColumnMoveSlotArgs) {
+        let max_index = args.from_index.max(args.to_index);
+        get_slot_mut_insert(&mut self.slots, max_index);
+        let slot = self.slots.remove(args.from_index);
+        self.slots.insert(args.to_index, slot);
+    }
+
+    fn swap_slots(&mut self, args: ColumnSwapSlotsArgs) {
+        let max_index = args.index_a.max(args.index_b);
+        get_slot_mut_insert(&mut self.slots, max_index);
+        self.slots.swap(args.index_a, args.index_b);
+    }
+
+    /// Takes the clip out of the source slot and sends it to the destination column, where it
+    /// continues playing.
+    ///
+    /// The box is allocated in the main thread and travels from column to column, so this doesn't
+    /// allocate.
+    fn hand_over_clip(&mut self, mut boxed_args: Box<Option<ColumnHandOverClipArgs>>) {
+        let args = (*boxed_args).as_mut().unwrap();
+        args.clip = get_slot_mut_insert(&mut self.slots, args.slot_index).take_clip();
+        let dest_column = args.dest_column.clone();
+        dest_column.take_over_clip(boxed_args);
+    }
+
+    /// Puts the clip which has been handed over by another column into the destination slot.
+    ///
+    /// When swapping, the clip which was in the destination slot before is sent back.
+    fn take_over_clip(&mut self, mut boxed_args: Box<Option<ColumnHandOverClipArgs>>) {
+        let args = (*boxed_args).as_mut().unwrap();
+        let slot = get_slot_mut_insert(&mut self.slots, args.dest_slot_index);
+        let previous_clip = slot.take_clip();
+        if let Some(mut clip) = args.clip.take() {
+            // The clip might inherit other audio settings from this column.
+            clip.inherit_audio_settings(&self.matrix_settings, &self.settings);
+            let material_info = clip.material_info().unwrap();
+            slot.fill(clip);
+            self.event_sender
+                .clip_material_info_changed(args.dest_slot_index, material_info);
+        }
+        if let Some(swap_column) = args.swap_column.take() {
+            args.clip = previous_clip;
+            args.dest_slot_index = args.slot_index;
+            swap_column.take_over_clip(boxed_args);
+        } else {
+            if let Some(clip) = previous_clip {
+                self.event_sender.dispose(ColumnGarbage::Clip(clip));
+            }
+            self.event_sender
+                .dispose(ColumnGarbage::HandOverClipArgs(boxed_args));
+        }
+    }
+
     fn process_commands(&mut self, audio_request_props: BasicAudioRequestProps) {
         while let Ok(task) = self.command_receiver.try_recv() {
             use ColumnCommand::*;
@@ -583,6 +836,24 @@ impl Column {
                     let result = self.clear_slot(slot_index);
                     self.notify_user_about_failed_interaction(result);
                 }
+                InsertSlot(slot_index) => {
+                    self.insert_slot(slot_index);
+                }
+                RemoveSlot(slot_index) => {
+                    self.remove_slot(slot_index);
+                }
+                MoveSlot(args) => {
+                    self.move_slot(args);
+                }
+                SwapSlots(args) => {
+                    self.swap_slots(args);
+                }
+                HandOverClip(boxed_args) => {
+                    self.hand_over_clip(boxed_args);
+                }
+                TakeOverClip(boxed_args) => {
+                    self.take_over_clip(boxed_args);
+                }
                 UpdateSettings(s) => {
                     self.settings = s;
                 }
@@ -619,15 +890,49 @@ impl Column {
                 SetClipVolume(args) => {
                     self.set_clip_volume(args.slot_index, args.volume).unwrap();
                 }
+                SetClipPitch(args) => {
+                    self.set_clip_pitch(args.slot_index, args.pitch).unwrap();
+                }
+                SetClipEnvelopes(mut boxed_args) => {
+                    let mut args = boxed_args.take().unwrap();
+                    args.envelopes = self.set_clip_envelopes(args.slot_index, args.envelopes);
+                    // The replaced envelopes must not be deallocated in the real-time thread.
+                    *boxed_args = Some(args);
+                    self.event_sender
+                        .dispose(ColumnGarbage::SetClipEnvelopesArgs(boxed_args));
+                }
+                WriteClipVolumeEnvelopePoint(args) => {
+                    self.write_clip_volume_envelope_point(args).unwrap();
+                }
+                ReplaceClipSource(mut boxed_args) => {
+                    let mut args = boxed_args.take().unwrap();
+                    if let Err(e) = self.replace_clip_source(&mut args) {
+                        debug!("Couldn't replace clip source: {}", e);
+                    }
+                    // The replaced source must not be deallocated in the real-time thread.
+                    *boxed_args = Some(args);
+                    self.event_sender
+                        .dispose(ColumnGarbage::ReplaceClipSourceArgs(boxed_args));
+                }
+                SetClipMidiTransformation(args) => {
+                    self.set_clip_midi_transformation(args.slot_index, args.transformation)
+                        .unwrap();
+                }
                 SeekClip(args) => {
                     self.seek_clip(args.index, args.desired_pos).unwrap();
                 }
                 SetClipLooped(args) => {
                     self.set_clip_looped(args).unwrap();
                 }
+                SetClipReversed(args) => {
+                    self.set_clip_reversed(args).unwrap();
+                }
                 SetClipSection(args) => {
                     self.set_clip_section(args).unwrap();
                 }
+                SetClipCanvas(args) => {
+                    self.set_clip_canvas(args).unwrap();
+                }
                 RecordClip(mut boxed_args) => {
                     let args = boxed_args.take().unwrap();
                     let result =
@@ -934,6 +1239,30 @@ pub struct ColumnStopArgs {
     pub ref_pos: Option<PositionInSeconds>,
 }
 
+#[derive(Debug)]
+pub struct ColumnMoveSlotArgs {
+    pub from_index: usize,
+    pub to_index: usize,
+}
+
+#[derive(Debug)]
+pub struct ColumnSwapSlotsArgs {
+    pub index_a: usize,
+    pub index_b: usize,
+}
+
+#[derive(Debug)]
+pub struct ColumnHandOverClipArgs {
+    /// Slot in the source column.
+    pub slot_index: usize,
+    pub dest_column: ColumnCommandSender,
+    pub dest_slot_index: usize,
+    /// If set, the clip in the destination slot is sent back to this column (the source column).
+    pub swap_column: Option<ColumnCommandSender>,
+    /// Filled in by the source column.
+    pub clip: Option<Clip>,
+}
+
 #[derive(Debug)]
 pub struct ColumnPauseClipArgs {
     pub index: usize,
@@ -951,6 +1280,40 @@ pub struct ColumnSetClipVolumeArgs {
     pub volume: Db,
 }
 
+#[derive(Debug)]
+pub struct ColumnSetClipPitchArgs {
+    pub slot_index: usize,
+    pub pitch: api::ClipPitch,
+}
+
+#[derive(Debug)]
+pub struct ColumnSetClipEnvelopesArgs {
+    pub slot_index: usize,
+    pub envelopes: api::ClipEnvelopes,
+}
+
+#[derive(Debug)]
+pub struct ColumnReplaceClipSourceArgs {
+    pub slot_index: usize,
+    pub source: ClipSource,
+    /// File which contains the material of the new source.
+    pub cache_file_path: PathBuf,
+}
+
+#[derive(Debug)]
+pub struct ColumnWriteClipVolumeEnvelopePointArgs {
+    pub slot_index: usize,
+    pub pos: PositiveBeat,
+    pub volume: Db,
+    pub prev_write_pos: Option<f64>,
+}
+
+#[derive(Debug)]
+pub struct ColumnSetClipMidiTransformationArgs {
+    pub slot_index: usize,
+    pub transformation: api::MidiTransformation,
+}
+
 #[derive(Debug)]
 pub struct ColumnRecordClipArgs {
     pub slot_index: usize,
@@ -963,12 +1326,24 @@ pub struct ColumnSetClipLoopedArgs {
     pub looped: bool,
 }
 
+#[derive(Debug)]
+pub struct ColumnSetClipReversedArgs {
+    pub slot_index: usize,
+    pub reversed: bool,
+}
+
 #[derive(Debug)]
 pub struct ColumnSetClipSectionArgs {
     pub slot_index: usize,
     pub section: api::Section,
 }
 
+#[derive(Debug)]
+pub struct ColumnSetClipCanvasArgs {
+    pub slot_index: usize,
+    pub canvas: Option<api::Canvas>,
+}
+
 pub struct ColumnWithSlotArgs<'a> {
     pub index: usize,
     pub use_slot: &'a dyn Fn(),
@@ -1015,6 +1390,10 @@ pub enum ColumnEvent {
         slot_index: usize,
         mirror_source: ClipSource,
     },
+    AudioOverdubFinished {
+        slot_index: usize,
+        outcome: AudioOverdubOutcome,
+    },
     NormalRecordingFinished {
         slot_index: usize,
         outcome: NormalRecordingOutcome,
@@ -1034,6 +1413,9 @@ pub enum ColumnGarbage {
     FillSlotArgs(Box<Option<ColumnFillSlotArgs>>),
     Clip(Clip),
     RecordClipArgs(Box<Option<ColumnRecordClipArgs>>),
+    HandOverClipArgs(Box<Option<ColumnHandOverClipArgs>>),
+    SetClipEnvelopesArgs(Box<Option<ColumnSetClipEnvelopesArgs>>),
+    ReplaceClipSourceArgs(Box<Option<ColumnReplaceClipSourceArgs>>),
 }
 
 struct ClipEventHandler<'a> {
@@ -1056,6 +1438,11 @@ impl<'a> HandleSlotEvent for ClipEventHandler<'a> {
             .midi_overdub_finished(self.slot_index, mirror_source);
     }
 
+    fn audio_overdub_finished(&self, outcome: AudioOverdubOutcome) {
+        self.event_sender
+            .audio_overdub_finished(self.slot_index, outcome);
+    }
+
     fn normal_recording_finished(&self, outcome: NormalRecordingOutcome) {
         self.event_sender
             .normal_recording_finished(self.slot_index, outcome);
@@ -119,6 +119,10 @@ impl Matrix {
                     let handle = self.column_handles.remove(index);
                     self.main_command_sender.throw_away(handle);
                 }
+                MoveColumn(from_index, to_index) => {
+                    let handle = self.column_handles.remove(from_index);
+                    self.column_handles.insert(to_index, handle);
+                }
                 ClearColumns => {
                     for handles in self.column_handles.drain(..) {
                         self.main_command_sender.throw_away(handles);
@@ -288,12 +292,14 @@ impl Matrix {
 pub enum MatrixCommand {
     InsertColumn(usize, ColumnHandle),
     RemoveColumn(usize),
+    MoveColumn(usize, usize),
     ClearColumns,
 }
 
 pub trait RtMatrixCommandSender {
     fn insert_column(&self, index: usize, handle: ColumnHandle);
     fn remove_column(&self, index: usize);
+    fn move_column(&self, from_index: usize, to_index: usize);
     fn clear_columns(&self);
     fn send_command(&self, command: MatrixCommand);
 }
@@ -307,6 +313,10 @@ impl RtMatrixCommandSender for Sender<MatrixCommand> {
         self.send_command(MatrixCommand::RemoveColumn(index));
     }
 
+    fn move_column(&self, from_index: usize, to_index: usize) {
+        self.send_command(MatrixCommand::MoveColumn(from_index, to_index));
+    }
+
     fn clear_columns(&self) {
         self.send_command(MatrixCommand::ClearColumns);
     }
@@ -2,13 +2,14 @@ use crate::metrics_util::measure_time;
 use crate::rt::supplier::{MaterialInfo, WriteAudioRequest, WriteMidiRequest};
 use crate::rt::{
     Clip, ClipPlayArgs, ClipProcessArgs, ClipRecordingPollArgs, ClipStopArgs,
-    ColumnProcessTransportChangeArgs, ColumnSettings, HandleSlotEvent, InternalClipPlayState,
-    OverridableMatrixSettings, SharedPos, SlotInstruction, SlotRecordInstruction,
+    ColumnProcessTransportChangeArgs, ColumnReplaceClipSourceArgs, ColumnSettings, HandleSlotEvent,
+    InternalClipPlayState, OverridableMatrixSettings, SharedPos, SlotInstruction,
+    SlotRecordInstruction,
 };
 use crate::{ClipEngineResult, ErrorWithPayload};
 use helgoboss_learn::UnitValue;
 use playtime_api::persistence as api;
-use playtime_api::persistence::{ClipPlayStopTiming, Db};
+use playtime_api::persistence::{ClipPlayStopTiming, Db, PositiveBeat};
 use playtime_api::runtime::ClipPlayState;
 use reaper_medium::PlayState;
 
@@ -25,9 +26,24 @@ struct InternalRuntimeData {
 }
 
 impl Slot {
-    pub fn fill(&mut self, clip: Clip) {
+    /// Puts the given clip into this slot and returns the previous clip (if any).
+    ///
+    /// The previous clip must not be deallocated in the real-time thread.
+    pub fn fill(&mut self, clip: Clip) -> Option<Clip> {
         // TODO-medium Suspend previous clip if playing.
-        self.clip = Some(clip);
+        self.runtime_data = InternalRuntimeData::default();
+        self.clip.replace(clip)
+    }
+
+    /// Takes the clip out of this slot (without stopping it), leaving the slot empty.
+    pub fn take_clip(&mut self) -> Option<Clip> {
+        self.runtime_data = InternalRuntimeData::default();
+        self.clip.take()
+    }
+
+    /// Consumes the slot and returns its clip (if any).
+    pub fn into_clip(self) -> Option<Clip> {
+        self.clip
     }
 
     pub fn is_filled(&self) -> bool {
@@ -110,10 +126,18 @@ impl Slot {
         self.clip_mut_internal()?.set_looped(repeated)
     }
 
+    pub fn set_clip_reversed(&mut self, reversed: bool) -> ClipEngineResult<()> {
+        self.clip_mut_internal()?.set_reversed(reversed)
+    }
+
     pub fn set_clip_section(&mut self, section: api::Section) -> ClipEngineResult<()> {
         self.clip_mut_internal()?.set_section(section)
     }
 
+    pub fn set_clip_canvas(&mut self, canvas: Option<api::Canvas>) -> ClipEngineResult<()> {
+        self.clip_mut_internal()?.set_canvas(canvas)
+    }
+
     /// # Errors
     ///
     /// Returns an error either if the instruction is to record on the given new clip but the slot
@@ -173,6 +197,22 @@ impl Slot {
                     Err(e) => Err(e.map_payload(MidiOverdub)),
                 }
             }
+            AudioOverdub(instruction) => {
+                debug!("Audio overdub");
+                let clip = match self.clip.as_mut() {
+                    None => {
+                        return Err(ErrorWithPayload::new(
+                            "slot empty",
+                            AudioOverdub(instruction),
+                        ));
+                    }
+                    Some(c) => c,
+                };
+                match clip.audio_overdub(instruction) {
+                    Ok(_) => Ok(None),
+                    Err(e) => Err(e.map_payload(AudioOverdub)),
+                }
+            }
         }
     }
 
@@ -200,6 +240,48 @@ impl Slot {
         Ok(())
     }
 
+    pub fn set_clip_pitch(&mut self, pitch: api::ClipPitch) -> ClipEngineResult<()> {
+        self.clip_mut_internal()?.set_pitch(pitch)
+    }
+
+    /// Returns the envelopes which are not needed anymore (the previous ones or, if the slot is
+    /// empty, the given ones).
+    pub fn set_clip_envelopes(&mut self, envelopes: api::ClipEnvelopes) -> api::ClipEnvelopes {
+        match self.clip_mut_internal() {
+            Ok(clip) => clip.set_envelopes(envelopes),
+            Err(_) => envelopes,
+        }
+    }
+
+    pub fn write_clip_volume_envelope_point(
+        &mut self,
+        pos: PositiveBeat,
+        volume: Db,
+        prev_write_pos: Option<f64>,
+    ) -> ClipEngineResult<()> {
+        self.clip_mut_internal()?
+            .write_volume_envelope_point(pos, volume, prev_write_pos);
+        Ok(())
+    }
+
+    /// Swaps the source and cache file path in the given arguments with the current ones.
+    pub fn replace_clip_source(
+        &mut self,
+        args: &mut ColumnReplaceClipSourceArgs,
+    ) -> ClipEngineResult<()> {
+        self.clip_mut_internal()?
+            .replace_source(&mut args.source, &mut args.cache_file_path)
+    }
+
+    pub fn set_clip_midi_transformation(
+        &mut self,
+        transformation: api::MidiTransformation,
+    ) -> ClipEngineResult<()> {
+        self.clip_mut_internal()?
+            .set_midi_transformation(transformation);
+        Ok(())
+    }
+
     pub fn process_transport_change<H: HandleSlotEvent>(
         &mut self,
         args: &SlotProcessTransportChangeArgs,
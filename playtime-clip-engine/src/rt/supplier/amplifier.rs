@@ -7,15 +7,18 @@ use crate::ClipEngineResult;
 use helgoboss_midi::{
     RawShortMessage, ShortMessage, ShortMessageFactory, StructuredShortMessage, U7,
 };
-use reaper_high::Reaper;
-use reaper_medium::{BorrowedMidiEventList, Db, MidiFrameOffset, VolumeSliderValue};
-use std::cmp;
+use playtime_api::persistence as api;
+use reaper_medium::{BorrowedMidiEventList, Db, MidiFrameOffset};
+use std::{cmp, mem};
 
 #[derive(Debug)]
 pub struct Amplifier<S> {
     supplier: S,
     volume: Db,
     derived_volume_factor: f64,
+    /// Envelope positions are relative to the start of the loop cycle (the material).
+    envelopes: api::ClipEnvelopes,
+    frames_per_beat: f64,
 }
 
 impl<S> Amplifier<S> {
@@ -24,6 +27,8 @@ impl<S> Amplifier<S> {
             supplier,
             volume: Db::ZERO_DB,
             derived_volume_factor: 1.0,
+            envelopes: Default::default(),
+            frames_per_beat: 1.0,
         }
     }
 
@@ -37,20 +42,117 @@ impl<S> Amplifier<S> {
 
     pub fn set_volume(&mut self, volume: Db) {
         self.volume = volume;
-        // TODO-medium Maybe improve the volume factor
-        self.derived_volume_factor = Reaper::get().medium_reaper().db2slider(volume).get()
-            / VolumeSliderValue::ZERO_DB.get();
+        self.derived_volume_factor = volume_factor(volume);
+    }
+
+    /// Returns the previous envelopes (so they can be disposed outside of the real-time thread).
+    pub fn set_envelopes(&mut self, envelopes: api::ClipEnvelopes) -> api::ClipEnvelopes {
+        mem::replace(&mut self.envelopes, envelopes)
+    }
+
+    pub fn set_frames_per_beat(&mut self, frames_per_beat: f64) {
+        self.frames_per_beat = frames_per_beat;
+    }
+
+    /// Writes the given volume into the volume envelope at the given position.
+    ///
+    /// Doesn't allocate as long as the envelopes have been prepared with
+    /// [`reserve_envelope_points_for_writing`] and not more than
+    /// [`MAX_ENVELOPE_POINT_COUNT_WITHOUT_REALLOCATION`] points have been written since then.
+    pub fn write_volume_envelope_point(
+        &mut self,
+        pos: api::PositiveBeat,
+        volume: Db,
+        prev_write_pos: Option<f64>,
+    ) {
+        self.envelopes
+            .volume
+            .write_point(pos, volume.get(), prev_write_pos);
+    }
+
+    /// Returns the volume factor at the given position, taking the volume envelope into account.
+    fn volume_factor_at(&self, pos_in_beats: f64) -> f64 {
+        match self.envelopes.volume.value_at(pos_in_beats) {
+            None => self.derived_volume_factor,
+            Some(db) => volume_factor(Db::new(db)),
+        }
+    }
+
+    /// Returns the gain factors of the left and right channel at the given position.
+    fn pan_factors_at(&self, pos_in_beats: f64) -> (f64, f64) {
+        let pan = match self.envelopes.pan.value_at(pos_in_beats) {
+            None => return (1.0, 1.0),
+            Some(p) => p.clamp(-1.0, 1.0),
+        };
+        ((1.0 - pan).min(1.0), (1.0 + pan).min(1.0))
     }
 }
 
-impl<S: AudioSupplier> AudioSupplier for Amplifier<S> {
+impl<S: WithMaterialInfo> Amplifier<S> {
+    /// Converts the given play position to a position within the loop cycle in beats.
+    fn pos_in_beats(&self, frame: isize) -> f64 {
+        if frame <= 0 {
+            return 0.0;
+        }
+        let cycle_frame_count = self
+            .supplier
+            .material_info()
+            .map(|i| i.frame_count())
+            .unwrap_or(0);
+        let frame_in_cycle = if cycle_frame_count > 0 {
+            frame as usize % cycle_frame_count
+        } else {
+            frame as usize
+        };
+        frame_in_cycle as f64 / self.frames_per_beat
+    }
+
+    fn apply_envelopes_to_audio(
+        &self,
+        start_frame: isize,
+        num_frames_consumed: usize,
+        dest_buffer: &mut AudioBufMut,
+    ) {
+        let start_pos = self.pos_in_beats(start_frame);
+        let end_pos = self
+            .pos_in_beats(start_frame + num_frames_consumed as isize)
+            // Crossing the cycle boundary. Hold the value for this block.
+            .max(start_pos);
+        let start_volume = self.volume_factor_at(start_pos);
+        let end_volume = self.volume_factor_at(end_pos);
+        let start_pan = self.pan_factors_at(start_pos);
+        let end_pan = self.pan_factors_at(end_pos);
+        let pan_applies = dest_buffer.channel_count() == 2;
+        let frame_count = dest_buffer.frame_count() as f64;
+        dest_buffer.modify_frames(|sample| {
+            let ratio = sample.index.frame as f64 / frame_count;
+            let volume = start_volume + (end_volume - start_volume) * ratio;
+            let pan = if !pan_applies {
+                1.0
+            } else if sample.index.channel == 0 {
+                start_pan.0 + (end_pan.0 - start_pan.0) * ratio
+            } else {
+                start_pan.1 + (end_pan.1 - start_pan.1) * ratio
+            };
+            sample.value * volume * pan
+        });
+    }
+}
+
+impl<S: AudioSupplier + WithMaterialInfo> AudioSupplier for Amplifier<S> {
     fn supply_audio(
         &mut self,
         request: &SupplyAudioRequest,
         dest_buffer: &mut AudioBufMut,
     ) -> SupplyResponse {
         let response = self.supplier.supply_audio(request, dest_buffer);
-        if self.volume != Db::ZERO_DB {
+        if !self.envelopes.is_empty() {
+            self.apply_envelopes_to_audio(
+                request.start_frame,
+                response.num_frames_consumed,
+                dest_buffer,
+            );
+        } else if self.volume != Db::ZERO_DB {
             // TODO-medium Maybe improve the volume factor
             dest_buffer.modify_frames(|sample| sample.value * self.derived_volume_factor);
         }
@@ -58,30 +160,35 @@ impl<S: AudioSupplier> AudioSupplier for Amplifier<S> {
     }
 }
 
-impl<S: MidiSupplier> MidiSupplier for Amplifier<S> {
+impl<S: MidiSupplier + WithMaterialInfo> MidiSupplier for Amplifier<S> {
     fn supply_midi(
         &mut self,
         request: &SupplyMidiRequest,
         event_list: &mut BorrowedMidiEventList,
     ) -> SupplyResponse {
         let response = self.supplier.supply_midi(request, event_list);
-        if self.volume != Db::ZERO_DB {
-            for event in event_list.iter_mut() {
-                if let StructuredShortMessage::NoteOn {
+        // Pan doesn't apply to MIDI. The volume envelope is evaluated once per block.
+        let volume_factor = if !self.envelopes.volume.is_empty() {
+            self.volume_factor_at(self.pos_in_beats(request.start_frame))
+        } else if self.volume != Db::ZERO_DB {
+            self.derived_volume_factor
+        } else {
+            return response;
+        };
+        for event in event_list.iter_mut() {
+            if let StructuredShortMessage::NoteOn {
+                channel,
+                key_number,
+                velocity,
+            } = event.message().to_structured()
+            {
+                let adjusted_velocity = (volume_factor * velocity.get() as f64).round() as u8;
+                let amplified_msg = RawShortMessage::note_on(
                     channel,
                     key_number,
-                    velocity,
-                } = event.message().to_structured()
-                {
-                    let adjusted_velocity =
-                        (self.derived_volume_factor * velocity.get() as f64).round() as u8;
-                    let amplified_msg = RawShortMessage::note_on(
-                        channel,
-                        key_number,
-                        U7::new(cmp::min(127u8, adjusted_velocity)),
-                    );
-                    event.set_message(amplified_msg);
-                }
+                    U7::new(cmp::min(127u8, adjusted_velocity)),
+                );
+                event.set_message(amplified_msg);
             }
         }
         response
@@ -113,3 +220,24 @@ impl<S: PositionTranslationSkill> PositionTranslationSkill for Amplifier<S> {
         self.supplier.translate_play_pos_to_source_pos(play_pos)
     }
 }
+
+/// Number of volume envelope points which can be written in the real-time thread without
+/// reallocation.
+pub const MAX_ENVELOPE_POINT_COUNT_WITHOUT_REALLOCATION: usize = 1000;
+
+/// Makes room for writing volume envelope points in the real-time thread.
+///
+/// Must not be called in the real-time thread.
+pub fn reserve_envelope_points_for_writing(envelopes: &mut api::ClipEnvelopes) {
+    envelopes
+        .volume
+        .points
+        .reserve(MAX_ENVELOPE_POINT_COUNT_WITHOUT_REALLOCATION);
+}
+
+/// Converts the given volume to an amplitude factor.
+///
+/// Doesn't need REAPER, so the amplifier also works when rendering offline.
+fn volume_factor(volume: Db) -> f64 {
+    10f64.powf(volume.get() / 20.0)
+}
@@ -4,6 +4,7 @@ use crate::rt::buffer::AudioBufMut;
 use crate::rt::supplier::{get_cycle_at_frame, ClipSource, MIDI_BASE_BPM, MIDI_FRAME_RATE};
 use crate::rt::tempo_util::calc_tempo_factor;
 use crate::ClipEngineResult;
+use playtime_api::persistence::{ClipPitch, VirtualTimeStretchMode};
 use reaper_medium::{
     BorrowedMidiEventList, Bpm, DurationInSeconds, Hz, MidiFrameOffset, PositionInSeconds,
 };
@@ -29,6 +30,26 @@ pub trait PreBufferSourceSkill: Debug {
     fn pre_buffer(&mut self, request: PreBufferFillRequest);
 }
 
+pub trait PreStretchSkill: Debug {
+    /// Does its best to make sure that material stretched with the given parameters is available
+    /// from the given start frame on.
+    ///
+    /// It must be asynchronous and cheap enough to call from a real-time thread.
+    fn pre_stretch(&mut self, start_frame: isize, params: PreStretchParams);
+
+    /// Writes material which has been stretched in advance with the given parameters into the
+    /// destination buffer.
+    ///
+    /// Returns `None` if no suitable pre-stretched material is available. In that case, the
+    /// caller needs to stretch the material itself.
+    fn supply_pre_stretched_audio(
+        &mut self,
+        request: &SupplyAudioRequest,
+        dest_buffer: &mut AudioBufMut,
+        params: PreStretchParams,
+    ) -> Option<SupplyResponse>;
+}
+
 pub trait PositionTranslationSkill: Debug {
     fn translate_play_pos_to_source_pos(&self, play_pos: isize) -> isize;
 }
@@ -243,6 +264,28 @@ pub struct PreBufferFillRequest {
     pub start_frame: isize,
 }
 
+/// Everything that influences the outcome of time stretching.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PreStretchParams {
+    pub tempo_factor: f64,
+    pub pitch: ClipPitch,
+    pub mode: VirtualTimeStretchMode,
+}
+
+impl PreStretchParams {
+    /// Tempo factors which differ by less than this are considered equal. Tempo factors are
+    /// calculated from tempos and are prone to rounding errors.
+    const TEMPO_FACTOR_TOLERANCE: f64 = 0.000_001;
+
+    /// Returns whether material stretched with the given parameters can be used instead of
+    /// material stretched with these parameters.
+    pub fn is_compatible_with(&self, other: &PreStretchParams) -> bool {
+        (self.tempo_factor - other.tempo_factor).abs() < Self::TEMPO_FACTOR_TOLERANCE
+            && self.pitch == other.pitch
+            && self.mode == other.mode
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct SupplyMidiRequest<'a> {
     /// Position within the most inner material that marks the start of the desired portion.
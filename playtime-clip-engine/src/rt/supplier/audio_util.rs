@@ -110,7 +110,7 @@ pub struct SourceMaterialRequest<'a, 'b> {
 }
 
 pub fn transfer_samples_from_buffer(buf: AudioBuf, req: SourceMaterialRequest) -> SupplyResponse {
-    let num_remaining_frames_in_source = buf.frame_count() - req.start_frame;
+    let num_remaining_frames_in_source = buf.frame_count().saturating_sub(req.start_frame);
     let num_frames_written = cmp::min(
         num_remaining_frames_in_source,
         req.dest_buffer.frame_count(),
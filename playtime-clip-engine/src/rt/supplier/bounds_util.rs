@@ -0,0 +1,89 @@
+use crate::rt::supplier::{SupplyRequest, SupplyRequestInfo, SupplyResponse, SupplyResponseStatus};
+
+/// Describes how a supplier which plays a bounded portion of its inner supplier (e.g. section or
+/// canvas) should query the inner supplier.
+pub struct BoundedRequestData {
+    /// Start frame within the inner supplier.
+    pub start_frame: isize,
+    pub info: SupplyRequestInfo,
+    pub num_frames_to_be_written: usize,
+    /// `None` if there's no right bound.
+    pub bounded: Option<Bounded>,
+}
+
+#[derive(Clone, Copy)]
+pub struct Bounded {
+    pub reached_bound: bool,
+    pub bounded_num_frames: usize,
+    pub ideal_num_frames: usize,
+}
+
+/// Returns `None` if the request starts at or after the right bound.
+///
+/// The request start frame and the length are given relative to the start of the bounded portion.
+pub fn create_bounded_request_data(
+    request: &impl SupplyRequest,
+    dest_frame_count: usize,
+    inner_start_frame: isize,
+    length: Option<usize>,
+    requester: &'static str,
+) -> Option<BoundedRequestData> {
+    let bounded = match length {
+        None => None,
+        Some(length) => {
+            if request.start_frame() >= length as isize {
+                return None;
+            }
+            let ideal_end_frame = request.start_frame() + dest_frame_count as isize;
+            let (reached_bound, effective_end_frame) = if ideal_end_frame <= length as isize {
+                // End of block is located before or on the bound
+                (false, ideal_end_frame)
+            } else {
+                // End of block is located behind the bound
+                (true, length as isize)
+            };
+            let bounded = Bounded {
+                reached_bound,
+                bounded_num_frames: (effective_end_frame - request.start_frame()) as usize,
+                ideal_num_frames: dest_frame_count,
+            };
+            Some(bounded)
+        }
+    };
+    let data = BoundedRequestData {
+        start_frame: inner_start_frame,
+        info: SupplyRequestInfo {
+            audio_block_frame_offset: request.info().audio_block_frame_offset,
+            requester,
+            note: "",
+            is_realtime: request.info().is_realtime,
+        },
+        num_frames_to_be_written: bounded
+            .map(|b| b.bounded_num_frames)
+            .unwrap_or(dest_frame_count),
+        bounded,
+    };
+    Some(data)
+}
+
+/// Derives the response of the bounded supplier from the response of the inner supplier.
+pub fn generate_bounded_response(
+    inner_response: SupplyResponse,
+    bounded: Option<Bounded>,
+) -> SupplyResponse {
+    let bounded = match bounded {
+        // Open end. In that case the inner response is valid.
+        None => return inner_response,
+        Some(b) => b,
+    };
+    if bounded.reached_bound {
+        return SupplyResponse::reached_end(bounded.bounded_num_frames, bounded.bounded_num_frames);
+    }
+    use SupplyResponseStatus::*;
+    match inner_response.status {
+        // Inner supplier has more material.
+        PleaseContinue => SupplyResponse::please_continue(bounded.bounded_num_frames),
+        // Inner supplier has reached its end (but the bound is not reached yet).
+        ReachedEnd { .. } => SupplyResponse::please_continue(bounded.ideal_num_frames),
+    }
+}
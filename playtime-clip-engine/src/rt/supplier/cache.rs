@@ -1,23 +1,26 @@
 use std::fmt::Debug;
+use std::mem;
 use std::path::PathBuf;
 
 use crossbeam_channel::{Receiver, Sender};
 use playtime_api::persistence::AudioCacheBehavior;
-use reaper_medium::{BorrowedMidiEventList, MidiFrameOffset};
+use reaper_medium::{BorrowedMidiEventList, Hz, MidiFrameOffset};
 
-use crate::rt::buffer::{AudioBufMut, OwnedAudioBuffer};
-use crate::rt::source_util::pcm_source_is_midi;
+use crate::rt::buffer::{AudioBuf, AudioBufMut, OwnedAudioBuffer};
 use crate::rt::supplier::audio_util::{supply_audio_material, transfer_samples_from_buffer};
 use crate::rt::supplier::{
     AudioMaterialInfo, AudioSupplier, ClipSource, MaterialInfo, MidiSupplier,
     PositionTranslationSkill, SupplyAudioRequest, SupplyMidiRequest, SupplyRequestInfo,
     SupplyResponse, WithMaterialInfo, WithSource,
 };
-use crate::ClipEngineResult;
+use crate::rt::AudioOverdubInstruction;
+use crate::{ClipEngineResult, ErrorWithPayload};
 
 #[derive(Debug)]
 pub struct Cache<S> {
     cached_data: Option<CachedData>,
+    /// Set while overdubbing audio material into the cached data.
+    audio_overdub: Option<AudioOverdubInstruction>,
     request_sender: Sender<CacheRequest>,
     response_channel: CacheResponseChannel,
     supplier: S,
@@ -49,6 +52,7 @@ pub enum CacheRequest {
         response_sender: Sender<CacheResponse>,
     },
     DiscardCachedData(CachedData),
+    DiscardAudioOverdub(AudioOverdubInstruction),
 }
 
 #[derive(Debug)]
@@ -63,9 +67,22 @@ pub struct CachedData {
     content: OwnedAudioBuffer,
 }
 
+/// Result of an audio overdub, ready to be written to a file.
+#[derive(Debug)]
+pub struct AudioOverdubOutcome {
+    pub file: PathBuf,
+    /// Copy of the complete overdubbed material.
+    pub content: OwnedAudioBuffer,
+    pub frame_rate: Hz,
+}
+
 impl CachedData {
     fn is_still_valid(&self, source: &ClipSource) -> bool {
-        source.reaper_source().get_file_name(|path| {
+        let source = match source.reaper_source() {
+            Ok(s) => s,
+            Err(_) => return false,
+        };
+        source.get_file_name(|path| {
             if let Some(path) = path {
                 path == self.file_path
             } else {
@@ -79,6 +96,7 @@ impl<S: WithSource> Cache<S> {
     pub fn new(supplier: S, request_sender: Sender<CacheRequest>) -> Self {
         Self {
             cached_data: None,
+            audio_overdub: None,
             request_sender,
             response_channel: CacheResponseChannel::new(),
             supplier,
@@ -123,7 +141,7 @@ impl<S: WithSource> Cache<S> {
             }
             self.request_sender.discard_cached_data(cached_data);
         }
-        if pcm_source_is_midi(source.reaper_source()) {
+        if source.is_midi() || source.is_in_memory() {
             return;
         }
         self.request_sender
@@ -137,6 +155,113 @@ impl<S: WithSource> Cache<S> {
         }
     }
 
+    /// Starts mixing incoming audio into the cached data.
+    ///
+    /// Returns an error if the material is not completely cached in memory.
+    pub fn start_audio_overdub(
+        &mut self,
+        instruction: AudioOverdubInstruction,
+    ) -> Result<(), ErrorWithPayload<AudioOverdubInstruction>> {
+        self.process_worker_response();
+        let d = match &self.cached_data {
+            None => {
+                return Err(ErrorWithPayload::new(
+                    "audio overdub requires the material to be cached in memory",
+                    instruction,
+                ))
+            }
+            Some(d) => d,
+        };
+        let content = d.content.to_buf();
+        let commit_buffer = instruction.commit_buffer.to_buf();
+        if commit_buffer.channel_count() != content.channel_count()
+            || commit_buffer.frame_count() != content.frame_count()
+        {
+            return Err(ErrorWithPayload::new(
+                "commit buffer doesn't match cached material",
+                instruction,
+            ));
+        }
+        if let Some(obsolete_overdub) = self.audio_overdub.replace(instruction) {
+            // A previous overdub was interrupted without being committed. The material mixed in
+            // so far stays in the cache and will be part of the next commit.
+            self.request_sender.discard_audio_overdub(obsolete_overdub);
+        }
+        Ok(())
+    }
+
+    pub fn is_overdubbing_audio(&self) -> bool {
+        self.audio_overdub.is_some()
+    }
+
+    /// Returns the number of channels of the material into which audio is overdubbed or `None`
+    /// if not overdubbing audio.
+    pub fn audio_overdub_channel_count(&self) -> Option<usize> {
+        self.audio_overdub.as_ref()?;
+        let d = self.cached_data.as_ref()?;
+        Some(d.content.to_buf().channel_count())
+    }
+
+    /// Mixes the given input frames into consecutive frames of the cached material, starting at
+    /// the given source frame. The existing material is attenuated by the overdub feedback.
+    pub fn overdub_frames(
+        &mut self,
+        input_channels: &[Option<AudioBuf>],
+        source_start_frame: isize,
+        input_frames: impl Iterator<Item = usize>,
+    ) {
+        let (d, overdub) = match (&mut self.cached_data, &self.audio_overdub) {
+            (Some(d), Some(o)) => (d, o),
+            _ => return,
+        };
+        mix_overdub_frames(
+            d.content.to_buf_mut(),
+            input_channels,
+            overdub.feedback,
+            source_start_frame,
+            input_frames,
+        );
+    }
+
+    /// Stops overdubbing and returns a copy of the overdubbed material.
+    ///
+    /// The cached data stays associated with the original file until the source is replaced
+    /// with one reading the file into which the material is written (see [`Self::rekey`]).
+    ///
+    /// Returns `None` if not overdubbing audio.
+    pub fn finish_audio_overdub(&mut self) -> Option<AudioOverdubOutcome> {
+        let mut overdub = self.audio_overdub.take()?;
+        let d = match &self.cached_data {
+            None => {
+                // Cache was disabled in the meantime. Nothing to commit.
+                self.request_sender.discard_audio_overdub(overdub);
+                return None;
+            }
+            Some(d) => d,
+        };
+        d.content
+            .to_buf()
+            .copy_to(&mut overdub.commit_buffer.to_buf_mut());
+        let outcome = AudioOverdubOutcome {
+            file: overdub.file,
+            content: overdub.commit_buffer,
+            frame_rate: d.material_info.frame_rate,
+        };
+        Some(outcome)
+    }
+
+    /// Associates the cached data with the given file, swapping in the path of the file it was
+    /// associated with before.
+    ///
+    /// Must be called when the source has been replaced with one which reads a file containing
+    /// exactly the cached material (e.g. after committing an audio overdub). Otherwise the cache
+    /// would be considered invalid and rebuilt from the wrong file.
+    pub fn rekey(&mut self, file_path: &mut PathBuf) {
+        if let Some(d) = &mut self.cached_data {
+            mem::swap(&mut d.file_path, file_path);
+        }
+    }
+
     fn process_worker_response(&mut self) {
         let response = match self.response_channel.receiver.try_recv() {
             Ok(r) => r,
@@ -162,6 +287,42 @@ pub fn keep_processing_cache_requests(receiver: Receiver<CacheRequest>) {
                 let _ = cache_source(&mut source, response_sender);
             }
             DiscardCachedData(_) => {}
+            DiscardAudioOverdub(_) => {}
+        }
+    }
+}
+
+/// Mixes the given input frames into consecutive frames of the given material, starting at the
+/// given frame. Frames outside of the material are skipped.
+///
+/// Channels without input are attenuated only.
+fn mix_overdub_frames(
+    mut content: AudioBufMut,
+    input_channels: &[Option<AudioBuf>],
+    feedback: f64,
+    start_frame: isize,
+    input_frames: impl Iterator<Item = usize>,
+) {
+    let channel_count = content.channel_count();
+    let frame_count = content.frame_count();
+    let data = content.data_as_mut_slice();
+    for (frame, input_frame) in (start_frame..).zip(input_frames) {
+        if frame < 0 {
+            continue;
+        }
+        let frame = frame as usize;
+        if frame >= frame_count {
+            break;
+        }
+        let frame_offset = frame * channel_count;
+        for ch in 0..channel_count {
+            let input_sample = input_channels
+                .get(ch)
+                .and_then(|input_channel| input_channel.as_ref())
+                .and_then(|buf| buf.data_as_slice().get(input_frame).copied())
+                .unwrap_or(0.0);
+            let sample = &mut data[frame_offset + ch];
+            *sample = *sample * feedback + input_sample;
         }
     }
 }
@@ -175,7 +336,7 @@ fn cache_source(
         _ => return Err("no audio source"),
     };
     let file_path = source
-        .reaper_source()
+        .reaper_source()?
         .get_file_name(|path| path.map(|p| p.to_path_buf()))
         .ok_or("source without file name")?;
     let mut content = OwnedAudioBuffer::new(
@@ -211,6 +372,8 @@ trait CacheRequestSender {
 
     fn discard_cached_data(&self, data: CachedData);
 
+    fn discard_audio_overdub(&self, overdub: AudioOverdubInstruction);
+
     fn send_request(&self, request: CacheRequest);
 }
 
@@ -228,6 +391,11 @@ impl CacheRequestSender for Sender<CacheRequest> {
         self.send_request(request);
     }
 
+    fn discard_audio_overdub(&self, overdub: AudioOverdubInstruction) {
+        let request = CacheRequest::DiscardAudioOverdub(overdub);
+        self.send_request(request);
+    }
+
     fn send_request(&self, request: CacheRequest) {
         self.try_send(request).unwrap();
     }
@@ -285,3 +453,71 @@ impl<S: PositionTranslationSkill> PositionTranslationSkill for Cache<S> {
         self.supplier.translate_play_pos_to_source_pos(play_pos)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overdub_attenuates_existing_material_by_feedback() {
+        // Given
+        let mut content = OwnedAudioBuffer::new(2, 4);
+        content.to_buf_mut().data_as_mut_slice().fill(1.0);
+        let left = [0.1, 0.2, 0.3, 0.4];
+        let right = [0.5, 0.6, 0.7, 0.8];
+        let input_channels = [
+            Some(AudioBuf::from_slice(&left, 1, 4).unwrap()),
+            Some(AudioBuf::from_slice(&right, 1, 4).unwrap()),
+        ];
+        // When
+        mix_overdub_frames(content.to_buf_mut(), &input_channels, 0.5, 1, 0..2);
+        // Then
+        assert_eq!(
+            content.to_buf().data_as_slice(),
+            &[1.0, 1.0, 0.6, 1.0, 0.7, 1.1, 1.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn overdub_skips_frames_outside_of_material() {
+        // Given
+        let mut content = OwnedAudioBuffer::new(1, 3);
+        let input = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let input_channels = [Some(AudioBuf::from_slice(&input, 1, 5).unwrap())];
+        // When
+        mix_overdub_frames(content.to_buf_mut(), &input_channels, 1.0, -1, 0..5);
+        // Then
+        assert_eq!(content.to_buf().data_as_slice(), &[2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn overdub_reads_stretched_input_frames() {
+        // Given
+        let mut content = OwnedAudioBuffer::new(1, 4);
+        let input = [1.0, 2.0];
+        let input_channels = [Some(AudioBuf::from_slice(&input, 1, 2).unwrap())];
+        // When
+        mix_overdub_frames(
+            content.to_buf_mut(),
+            &input_channels,
+            1.0,
+            0,
+            (0..4).map(|i| i * 2 / 4),
+        );
+        // Then
+        assert_eq!(content.to_buf().data_as_slice(), &[1.0, 1.0, 2.0, 2.0]);
+    }
+
+    #[test]
+    fn overdub_attenuates_channels_without_input() {
+        // Given
+        let mut content = OwnedAudioBuffer::new(2, 1);
+        content.to_buf_mut().data_as_mut_slice().fill(1.0);
+        let input = [0.5];
+        let input_channels = [Some(AudioBuf::from_slice(&input, 1, 1).unwrap())];
+        // When
+        mix_overdub_frames(content.to_buf_mut(), &input_channels, 0.5, 0, 0..1);
+        // Then
+        assert_eq!(content.to_buf().data_as_slice(), &[1.0, 0.5]);
+    }
+}
@@ -0,0 +1,274 @@
+use crate::conversion_util::convert_duration_in_seconds_to_frames;
+use crate::rt::buffer::AudioBufMut;
+use crate::rt::supplier::bounds_util::{
+    create_bounded_request_data, generate_bounded_response, Bounded, BoundedRequestData,
+};
+use crate::rt::supplier::fade_util::{apply_fade_out_ending_at, Fade, DEFAULT_FADE};
+use crate::rt::supplier::midi_util::SilenceMidiBlockMode;
+use crate::rt::supplier::{
+    midi_util, AudioMaterialInfo, AudioSupplier, MaterialInfo, MidiMaterialInfo, MidiSupplier,
+    PositionTranslationSkill, SupplyAudioRequest, SupplyMidiRequest, SupplyRequest, SupplyResponse,
+    WithMaterialInfo,
+};
+use crate::ClipEngineResult;
+use playtime_api::persistence as api;
+use playtime_api::persistence::MidiResetMessageRange;
+use reaper_medium::{BorrowedMidiEventList, DurationInSeconds, MidiFrameOffset};
+
+/// Places the material on a canvas, which can add silence before and after it.
+///
+/// In contrast to the section, this doesn't affect beat alignment because the canvas start is
+/// what's aligned to the beat, not the start of the material.
+#[derive(Debug)]
+pub struct Canvas<S> {
+    supplier: S,
+    bounds: CanvasBounds,
+    fade_out: Fade,
+    midi_reset_msg_range: MidiResetMessageRange,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct CanvasBounds {
+    /// Frame within the canvas at which the material starts.
+    material_offset: usize,
+    /// Total number of frames of the canvas.
+    length: Option<usize>,
+}
+
+impl CanvasBounds {
+    pub fn new(material_offset: usize, length: Option<usize>) -> Self {
+        Self {
+            material_offset,
+            length,
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        self == &Default::default()
+    }
+
+    pub fn calculate_frame_count(&self, supplier_frame_count: usize) -> usize {
+        if let Some(length) = self.length {
+            length
+        } else {
+            self.material_offset + supplier_frame_count
+        }
+    }
+
+    pub fn material_offset(&self) -> usize {
+        self.material_offset
+    }
+
+    pub fn length(&self) -> Option<usize> {
+        self.length
+    }
+}
+
+impl<S> Canvas<S> {
+    pub fn new(supplier: S) -> Self {
+        Self {
+            supplier,
+            bounds: Default::default(),
+            fade_out: DEFAULT_FADE,
+            midi_reset_msg_range: Default::default(),
+        }
+    }
+
+    pub fn set_midi_reset_msg_range(&mut self, range: MidiResetMessageRange) {
+        self.midi_reset_msg_range = range;
+    }
+
+    pub fn set_fade_out(&mut self, fade: Fade) {
+        self.fade_out = fade;
+    }
+
+    pub fn bounds(&self) -> CanvasBounds {
+        self.bounds
+    }
+
+    pub fn set_bounds(&mut self, material_offset: usize, length: Option<usize>) {
+        self.bounds.material_offset = material_offset;
+        self.bounds.length = length;
+    }
+
+    pub fn reset(&mut self) {
+        self.bounds = Default::default();
+    }
+
+    pub fn supplier(&self) -> &S {
+        &self.supplier
+    }
+
+    pub fn supplier_mut(&mut self) -> &mut S {
+        &mut self.supplier
+    }
+
+    pub fn set_bounds_in_seconds(
+        &mut self,
+        canvas: Option<api::Canvas>,
+        material_info: &MaterialInfo,
+    ) {
+        let canvas = match canvas {
+            None => {
+                self.reset();
+                return;
+            }
+            Some(c) => c,
+        };
+        let source_frame_rate = material_info.frame_rate();
+        let material_offset = convert_duration_in_seconds_to_frames(
+            DurationInSeconds::new(canvas.section_pos.get()),
+            source_frame_rate,
+        );
+        let frame_count = canvas.length.map(|l| {
+            convert_duration_in_seconds_to_frames(
+                DurationInSeconds::new(l.get()),
+                source_frame_rate,
+            )
+        });
+        self.set_bounds(material_offset, frame_count);
+    }
+
+    fn get_instruction(
+        &mut self,
+        request: &impl SupplyRequest,
+        dest_frame_count: usize,
+    ) -> Instruction {
+        if self.bounds.is_default() {
+            return Instruction::Bypass;
+        }
+        // Moving the start frame to the left is all we need to do in order to insert silence
+        // before the material. Suppliers below handle negative positions as count-in phase. For
+        // MIDI, this also makes sure that the frame offsets of the events are written correctly.
+        let start_frame_in_material = request.start_frame() - self.bounds.material_offset as isize;
+        match create_bounded_request_data(
+            request,
+            dest_frame_count,
+            start_frame_in_material,
+            self.bounds.length,
+            "canvas-request",
+        ) {
+            // We exceeded the canvas boundary. Return silence.
+            None => Instruction::Return(SupplyResponse::exceeded_end()),
+            Some(d) => Instruction::ApplyCanvas(d),
+        }
+    }
+}
+
+impl<S: AudioSupplier> AudioSupplier for Canvas<S> {
+    fn supply_audio(
+        &mut self,
+        request: &SupplyAudioRequest,
+        dest_buffer: &mut AudioBufMut,
+    ) -> SupplyResponse {
+        let data = match self.get_instruction(request, dest_buffer.frame_count()) {
+            Instruction::Bypass => {
+                return self.supplier.supply_audio(request, dest_buffer);
+            }
+            Instruction::Return(r) => return r,
+            Instruction::ApplyCanvas(d) => d,
+        };
+        let inner_request = SupplyAudioRequest {
+            start_frame: data.start_frame,
+            dest_sample_rate: request.dest_sample_rate,
+            info: data.info,
+            parent_request: Some(request),
+            general_info: request.general_info,
+        };
+        let mut inner_dest_buffer = dest_buffer.slice_mut(0..data.num_frames_to_be_written);
+        let inner_response = self
+            .supplier
+            .supply_audio(&inner_request, &mut inner_dest_buffer);
+        if let Some(length) = self.bounds.length {
+            // The canvas end might cut the material.
+            apply_fade_out_ending_at(dest_buffer, request.start_frame, length, self.fade_out);
+        }
+        generate_bounded_response(inner_response, data.bounded)
+    }
+}
+
+impl<S: MidiSupplier> MidiSupplier for Canvas<S> {
+    fn supply_midi(
+        &mut self,
+        request: &SupplyMidiRequest,
+        event_list: &mut BorrowedMidiEventList,
+    ) -> SupplyResponse {
+        let data = match self.get_instruction(request, request.dest_frame_count) {
+            Instruction::Bypass => {
+                return self.supplier.supply_midi(request, event_list);
+            }
+            Instruction::Return(r) => return r,
+            Instruction::ApplyCanvas(d) => d,
+        };
+        let inner_request = SupplyMidiRequest {
+            start_frame: data.start_frame,
+            dest_frame_count: data.num_frames_to_be_written,
+            info: data.info.clone(),
+            dest_sample_rate: request.dest_sample_rate,
+            parent_request: Some(request),
+            general_info: request.general_info,
+        };
+        let inner_response = self.supplier.supply_midi(&inner_request, event_list);
+        if let Some(Bounded {
+            reached_bound: true,
+            ..
+        }) = data.bounded
+        {
+            debug!("Silence MIDI at canvas end");
+            midi_util::silence_midi(
+                event_list,
+                self.midi_reset_msg_range.right,
+                SilenceMidiBlockMode::Append,
+                &mut self.supplier,
+            );
+        }
+        generate_bounded_response(inner_response, data.bounded)
+    }
+
+    fn release_notes(
+        &mut self,
+        frame_offset: MidiFrameOffset,
+        event_list: &mut BorrowedMidiEventList,
+    ) {
+        self.supplier.release_notes(frame_offset, event_list);
+    }
+}
+
+impl<S: WithMaterialInfo> WithMaterialInfo for Canvas<S> {
+    fn material_info(&self) -> ClipEngineResult<MaterialInfo> {
+        let inner_material_info = self.supplier.material_info()?;
+        if self.bounds.is_default() {
+            return Ok(inner_material_info);
+        }
+        let material_info = match inner_material_info {
+            MaterialInfo::Audio(i) => {
+                let i = AudioMaterialInfo {
+                    frame_count: self.bounds.calculate_frame_count(i.frame_count),
+                    ..i
+                };
+                MaterialInfo::Audio(i)
+            }
+            MaterialInfo::Midi(i) => {
+                let i = MidiMaterialInfo {
+                    frame_count: self.bounds.calculate_frame_count(i.frame_count),
+                };
+                MaterialInfo::Midi(i)
+            }
+        };
+        Ok(material_info)
+    }
+}
+
+impl<S: PositionTranslationSkill> PositionTranslationSkill for Canvas<S> {
+    fn translate_play_pos_to_source_pos(&self, play_pos: isize) -> isize {
+        let effective_play_pos = play_pos - self.bounds.material_offset as isize;
+        self.supplier
+            .translate_play_pos_to_source_pos(effective_play_pos)
+    }
+}
+
+enum Instruction {
+    Bypass,
+    ApplyCanvas(BoundedRequestData),
+    Return(SupplyResponse),
+}
@@ -1,16 +1,20 @@
 use crate::mutex_util::non_blocking_lock;
+use crate::rt::supplier::fade_util::{Fade, StartEndFades};
 use crate::rt::supplier::{
-    Amplifier, AudioSupplier, Cache, CacheRequest, ClipSource, CommandProcessor, Downbeat,
-    InteractionHandler, LoopBehavior, Looper, MaterialInfo, MidiOverdubSettings, MidiSupplier,
-    PollRecordingOutcome, PositionTranslationSkill, PreBuffer, PreBufferCacheMissBehavior,
-    PreBufferFillRequest, PreBufferOptions, PreBufferRequest, PreBufferSourceSkill, RecordState,
-    Recorder, RecordingArgs, Resampler, Section, SectionBounds, StartEndHandler,
-    StopRecordingOutcome, SupplyAudioRequest, SupplyMidiRequest, SupplyResponse, TimeStretcher,
-    WithMaterialInfo, WriteAudioRequest, WriteMidiRequest,
+    Amplifier, AudioOverdubOutcome, AudioSupplier, Cache, CacheRequest, Canvas, ClipSource,
+    CommandProcessor, Downbeat, InteractionHandler, LoopBehavior, Looper, MaterialInfo,
+    MidiOverdubSettings, MidiSupplier, MidiTransformer, PollRecordingOutcome,
+    PositionTranslationSkill, PreBuffer, PreBufferCacheMissBehavior, PreBufferFillRequest,
+    PreBufferOptions, PreBufferRequest, PreBufferSourceSkill, RecordState, Recorder, RecordingArgs,
+    Resampler, Reverser, Section, SectionBounds, StartEndHandler, StopRecordingOutcome,
+    SupplyAudioRequest, SupplyMidiRequest, SupplyResponse, TimeStretcher, WithMaterialInfo,
+    WriteAudioRequest, WriteMidiRequest,
 };
-use crate::rt::tempo_util::determine_tempo_from_beat_time_base;
-use crate::rt::{AudioBufMut, BasicAudioRequestProps};
-use crate::{ClipEngineResult, HybridTimeline};
+use crate::rt::tempo_util::{
+    determine_envelope_frames_per_beat, determine_tempo_from_beat_time_base,
+};
+use crate::rt::{AudioBuf, AudioBufMut, AudioOverdubInstruction, BasicAudioRequestProps};
+use crate::{ClipEngineResult, ErrorWithPayload, HybridTimeline};
 use crossbeam_channel::Sender;
 use playtime_api::persistence as api;
 use playtime_api::persistence::{
@@ -18,8 +22,12 @@ use playtime_api::persistence::{
     PositiveBeat, PositiveSecond, VirtualResampleMode,
 };
 use reaper_medium::{BorrowedMidiEventList, Bpm, MidiFrameOffset, PositionInSeconds};
+use std::ops::Range;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex, MutexGuard};
 
+const MAX_AUDIO_CHANNEL_COUNT: usize = 64;
+
 /// The head of the supplier chain (just an alias).
 type Head = AmplifierTail;
 
@@ -46,7 +54,14 @@ type ResamplerTail = Resampler<InteractionHandlerTail>;
 ///
 /// It sits on top of almost everything because it's fast and shouldn't be cached (because
 /// interactions are by definition very sudden events).
-type InteractionHandlerTail = InteractionHandler<TimeStretcherTail>;
+type InteractionHandlerTail = InteractionHandler<MidiTransformerTail>;
+
+/// MIDI transformer changes velocities, channels and note lengths and filters notes.
+///
+/// It sits below the interaction handler so that reset messages and interaction note-offs are not
+/// filtered. It doesn't need to be pre-buffered because it's fast, and it's good to have it high
+/// up because then changes of the transformation settings are audible immediately.
+type MidiTransformerTail = MidiTransformer<TimeStretcherTail>;
 
 /// Time stretcher is responsible for stretching audio material while preserving its pitch.
 ///
@@ -55,9 +70,10 @@ type InteractionHandlerTail = InteractionHandler<TimeStretcherTail>;
 /// end of material and we want to start the next loop cycle, it wouldn't have material ready and
 /// would need to start pre-buffering from scratch. Not good.
 ///
-/// It sits above the pre-buffer at the moment although time-stretching results also should be
-/// pre-buffered (because time stretching is slow). Pre-buffering time-stretching results probably
-/// has some special needs that we don't handle yet. Let's see.
+/// It sits above the pre-buffer but asks it for material which the pre-buffer worker has already
+/// stretched with the current tempo factor, pitch and mode (because time stretching is slow). Only
+/// if there's no such material (e.g. right after a tempo change or a jump), it stretches the
+/// material itself.
 type TimeStretcherTail = TimeStretcher<DownbeatTail>;
 
 /// Downbeat handler sits on top of the looper because it moves the complete loop to the left
@@ -80,6 +96,9 @@ type DownbeatTail = Downbeat<PreBufferTail>;
 /// material in advance. The looper knows best which material comes next. If it would sit below
 /// the looper and it would reach end of material, it doesn't have anything in hand to decide what
 /// needs to be pre-buffered next.
+///
+/// In addition to the raw material, it provides time-stretched material for the time stretcher
+/// above. The worker uses its own time stretcher instance for that.
 type PreBufferTail =
     PreBuffer<SharedLooperTail, ChainPreBufferCommandProcessor, ChainPreBufferCommand>;
 
@@ -89,8 +108,21 @@ type SharedLooperTail = Arc<Mutex<LooperTail>>;
 
 /// Looper optionally repeats the material.
 ///
-/// It sits above the section because the section needs to be looped, not the full source.
-type LooperTail = Looper<SectionTail>;
+/// It sits above the reverser because the reversed section needs to be looped.
+type LooperTail = Looper<ReverserTail>;
+
+/// Reverser optionally plays the material backwards.
+///
+/// It sits above the canvas because the complete canvas should be played backwards, not the full
+/// source. It sits below the pre-buffer because reading backwards from the source is slow
+/// unless the material is cached.
+type ReverserTail = Reverser<CanvasTail>;
+
+/// Canvas optionally adds silence before and after the section.
+///
+/// It sits above the section because it places the section within a larger (or smaller) range. It
+/// sits below the looper because the canvas is what should be looped.
+type CanvasTail = Canvas<SectionTail>;
 
 /// Section handler optionally plays just a certain portion of the material. It can also be used to
 /// add silence after end of material.
@@ -130,10 +162,46 @@ type RecorderTail = Recorder;
 #[derive(Debug)]
 pub struct SupplierChain {
     head: Head,
+    /// `None` until configured.
+    audio_cache_behavior: Option<AudioCacheBehavior>,
+    /// Whether audio material which is cached in memory should be stretched in advance by the
+    /// pre-buffer worker.
+    pre_stretch_cached_audio: bool,
 }
 
 impl SupplierChain {
     pub fn new(recorder: Recorder, equipment: ChainEquipment) -> ClipEngineResult<Self> {
+        Self::assemble(
+            recorder,
+            equipment,
+            Resampler::new,
+            TimeStretcher::new,
+            true,
+        )
+    }
+
+    /// Creates a chain which doesn't need REAPER, e.g. for rendering offline.
+    ///
+    /// The recorder should contain an in-memory source. Audio is resampled and stretched linearly
+    /// (without preserving the pitch) and not pitch-shifted. The pre-buffer stays inactive, so the
+    /// material is read synchronously and rendering is deterministic.
+    pub fn headless(recorder: Recorder, equipment: ChainEquipment) -> ClipEngineResult<Self> {
+        Self::assemble(
+            recorder,
+            equipment,
+            Resampler::headless,
+            TimeStretcher::headless,
+            false,
+        )
+    }
+
+    fn assemble(
+        recorder: Recorder,
+        equipment: ChainEquipment,
+        create_resampler: impl FnOnce(InteractionHandlerTail) -> ResamplerTail,
+        create_time_stretcher: impl FnOnce(DownbeatTail) -> TimeStretcherTail,
+        pre_stretch_cached_audio: bool,
+    ) -> ClipEngineResult<Self> {
         let pre_buffer_options = PreBufferOptions {
             // We know we sit below the downbeat handler, so the underlying suppliers won't deliver
             // material in the count-in phase.
@@ -141,22 +209,23 @@ impl SupplierChain {
             cache_miss_behavior: PreBufferCacheMissBehavior::OutputSilence,
             recalibrate_on_cache_miss: false,
         };
-        let mut looper = Looper::new(Section::new(StartEndHandler::new(Cache::new(
-            recorder,
-            equipment.cache_request_sender,
+        let mut looper = Looper::new(Reverser::new(Canvas::new(Section::new(
+            StartEndHandler::new(Cache::new(recorder, equipment.cache_request_sender)),
         ))));
         looper.set_enabled(true);
         let mut chain = Self {
             head: {
-                Amplifier::new(Resampler::new(InteractionHandler::new(TimeStretcher::new(
-                    Downbeat::new(PreBuffer::new(
+                Amplifier::new(create_resampler(InteractionHandler::new(
+                    MidiTransformer::new(create_time_stretcher(Downbeat::new(PreBuffer::new(
                         Arc::new(Mutex::new(looper)),
                         equipment.pre_buffer_request_sender,
                         pre_buffer_options,
                         ChainPreBufferCommandProcessor,
-                    )),
-                ))))
+                    )))),
+                )))
             },
+            audio_cache_behavior: None,
+            pre_stretch_cached_audio,
         };
         // Configure resampler
         let resampler = chain.resampler_mut();
@@ -178,10 +247,14 @@ impl SupplierChain {
     pub fn configure_complete_chain(&mut self, settings: ChainSettings) -> ClipEngineResult<()> {
         let material_info = self.material_info()?;
         self.set_looped(settings.looped);
-        self.set_time_base(&settings.time_base, material_info.is_midi())?;
+        self.set_reversed(settings.reversed);
+        self.set_time_base(&settings.time_base, &material_info)?;
         self.set_volume(settings.volume);
+        self.set_pitch(settings.pitch)?;
         self.set_section(settings.section.start_pos, settings.section.length);
+        self.set_canvas(settings.canvas);
         self.set_audio_fades_enabled_for_source(settings.audio_apply_source_fades);
+        self.set_audio_fades(settings.audio_fades, &material_info);
         self.set_audio_time_stretch_mode(settings.audio_time_stretch_mode);
         self.set_audio_resample_mode(settings.audio_resample_mode);
         self.set_audio_cache_behavior(settings.cache_behavior);
@@ -207,7 +280,14 @@ impl SupplierChain {
         self.pre_buffer(req);
     }
 
-    fn set_time_base(&mut self, time_base: &ClipTimeBase, is_midi: bool) -> ClipEngineResult<()> {
+    /// Also updates everything that depends on the tempo of the clip.
+    fn set_time_base(
+        &mut self,
+        time_base: &ClipTimeBase,
+        material_info: &MaterialInfo,
+    ) -> ClipEngineResult<()> {
+        // Envelope positions are measured in beats.
+        self.set_envelope_frames_per_beat(time_base, material_info);
         match time_base {
             ClipTimeBase::Time => {
                 debug!("Disable tempo adjustments");
@@ -219,7 +299,7 @@ impl SupplierChain {
                 debug!("Enable tempo adjustments");
                 self.time_stretcher_mut().set_active(true);
                 self.resampler_mut().set_tempo_adjustments_enabled(true);
-                let tempo = determine_tempo_from_beat_time_base(b, is_midi);
+                let tempo = determine_tempo_from_beat_time_base(b, material_info.is_midi());
                 self.set_downbeat_in_beats(b.downbeat, tempo)?;
             }
         }
@@ -240,11 +320,28 @@ impl SupplierChain {
         self.pre_buffer_supplier().send_command(command);
     }
 
+    fn set_audio_fades(&mut self, fades: api::ClipAudioFades, material_info: &MaterialInfo) {
+        let interaction_fade = Fade::new(
+            fades.interaction_fade_length,
+            fades.shape,
+            material_info.frame_rate(),
+        );
+        self.interaction_handler_mut().set_fade(interaction_fade);
+        let command = ChainPreBufferCommand::SetAudioFades(fades);
+        self.pre_buffer_supplier().send_command(command);
+    }
+
     fn set_midi_settings(&mut self, settings: api::ClipMidiSettings) {
         self.set_midi_reset_msg_range_for_interaction(settings.interaction_reset_settings);
         self.set_midi_reset_msg_range_for_source(settings.source_reset_settings);
         self.set_midi_reset_msg_range_for_section(settings.section_reset_settings);
         self.set_midi_reset_msg_range_for_loop(settings.loop_reset_settings);
+        self.set_midi_transformation(settings.transformation);
+    }
+
+    pub fn set_midi_transformation(&mut self, transformation: api::MidiTransformation) {
+        self.midi_transformer_mut()
+            .set_transformation(transformation);
     }
 
     fn set_midi_reset_msg_range_for_section(&mut self, range: MidiResetMessageRange) {
@@ -272,6 +369,52 @@ impl SupplierChain {
             .set_volume(reaper_medium::Db::new(volume.get()));
     }
 
+    /// Returns the previous envelopes.
+    pub fn set_envelopes(&mut self, envelopes: api::ClipEnvelopes) -> api::ClipEnvelopes {
+        self.amplifier_mut().set_envelopes(envelopes)
+    }
+
+    pub fn write_volume_envelope_point(
+        &mut self,
+        pos: PositiveBeat,
+        volume: Db,
+        prev_write_pos: Option<f64>,
+    ) {
+        self.amplifier_mut().write_volume_envelope_point(
+            pos,
+            reaper_medium::Db::new(volume.get()),
+            prev_write_pos,
+        );
+    }
+
+    fn set_envelope_frames_per_beat(
+        &mut self,
+        time_base: &ClipTimeBase,
+        material_info: &MaterialInfo,
+    ) {
+        let frames_per_beat = determine_envelope_frames_per_beat(
+            time_base,
+            material_info.is_midi(),
+            material_info.frame_rate(),
+        );
+        self.amplifier_mut().set_frames_per_beat(frames_per_beat);
+    }
+
+    /// Returns an error if the pitch of audio material should be shifted but the chain is
+    /// headless, which would silently play the audio at the original pitch.
+    pub fn set_pitch(&mut self, pitch: api::ClipPitch) -> ClipEngineResult<()> {
+        let is_audio = self
+            .material_info()
+            .map(|info| !info.is_midi())
+            .unwrap_or(false);
+        if is_audio && !pitch.is_zero() && !self.time_stretcher().can_shift_audio_pitch() {
+            return Err("headless supplier chain can't shift the pitch of audio material");
+        }
+        self.time_stretcher_mut().set_pitch(pitch);
+        self.update_pre_buffer_activation();
+        Ok(())
+    }
+
     fn set_downbeat_in_beats(&mut self, beat: PositiveBeat, tempo: Bpm) -> ClipEngineResult<()> {
         self.downbeat_mut().set_downbeat_in_beats(beat, tempo)
     }
@@ -329,6 +472,111 @@ impl SupplierChain {
             .unwrap();
     }
 
+    pub fn start_audio_overdub(
+        &mut self,
+        instruction: AudioOverdubInstruction,
+    ) -> Result<(), ErrorWithPayload<AudioOverdubInstruction>> {
+        if self.audio_cache_behavior != Some(AudioCacheBehavior::CacheInMemory) {
+            return Err(ErrorWithPayload::new(
+                "audio overdub requires the material to be cached in memory",
+                instruction,
+            ));
+        }
+        // The pre-buffer might be active in order to stretch the cached material in advance.
+        // Overdubbing changes the material continuously, so we stretch live while overdubbing.
+        self.pre_buffer_mut().deactivate();
+        // Pre-buffer is inactive, so there's no contention.
+        let result = self
+            .pre_buffer_wormhole()
+            .cache()
+            .start_audio_overdub(instruction);
+        self.update_pre_buffer_activation();
+        result
+    }
+
+    /// Mixes the samples in the given request into the cached material.
+    ///
+    /// The given positions mark the part of the material which has been played in the current
+    /// block. If it's longer or shorter than the block (because of tempo adjustments or
+    /// resampling), the input is stretched accordingly.
+    pub fn overdub_audio(
+        &mut self,
+        request: impl WriteAudioRequest,
+        block_start_pos: isize,
+        block_end_pos: isize,
+    ) {
+        let num_frames_consumed = block_end_pos - block_start_pos;
+        let block_length = request.audio_request_props().block_length;
+        if num_frames_consumed <= 0 || block_length == 0 {
+            return;
+        }
+        let downbeat = self.downbeat();
+        let effective_start_pos = downbeat.effective_play_pos(block_start_pos);
+        // Audio overdub runs with the pre-buffer inactive only, so there's no contention.
+        let mut entrance = self.pre_buffer_wormhole();
+        let channel_count = match entrance.cache().audio_overdub_channel_count() {
+            None => return,
+            Some(c) => c.min(MAX_AUDIO_CHANNEL_COUNT),
+        };
+        // Fetch the input channels only once per block.
+        let mut input_channels: [Option<AudioBuf>; MAX_AUDIO_CHANNEL_COUNT] =
+            [None; MAX_AUDIO_CHANNEL_COUNT];
+        for (ch, input_channel) in input_channels[..channel_count].iter_mut().enumerate() {
+            *input_channel = request.get_channel_buffer(ch);
+        }
+        let input_channels = &input_channels[..channel_count];
+        for_each_contiguous_source_run(
+            &mut entrance,
+            num_frames_consumed,
+            |entrance, i| {
+                entrance
+                    .looper()
+                    .translate_play_pos_to_source_pos(effective_start_pos + i)
+            },
+            |entrance, source_start_frame, consumed_frames| {
+                let input_frames = consumed_frames
+                    .map(|i| i as usize * block_length / num_frames_consumed as usize);
+                entrance
+                    .cache()
+                    .overdub_frames(input_channels, source_start_frame, input_frames);
+            },
+        );
+    }
+
+    /// Returns `None` if not overdubbing audio.
+    pub fn finish_audio_overdub(&mut self) -> Option<AudioOverdubOutcome> {
+        let outcome = self.pre_buffer_wormhole().cache().finish_audio_overdub();
+        // Starts stretching the overdubbed material in advance, if appropriate.
+        self.update_pre_buffer_activation();
+        outcome
+    }
+
+    /// Replaces the source with one containing exactly the same material, e.g. the file into
+    /// which an audio overdub has been written. The cached material is kept.
+    ///
+    /// Swaps in the previous source and file path of the cached material. If the source can't be
+    /// replaced, the given ones are left untouched.
+    pub fn replace_source(
+        &mut self,
+        source: &mut ClipSource,
+        cache_file_path: &mut PathBuf,
+    ) -> ClipEngineResult<()> {
+        if self.audio_cache_behavior != Some(AudioCacheBehavior::CacheInMemory) {
+            return Err("can't replace source while pre-buffering");
+        }
+        // The pre-buffer might be active in order to stretch the cached material in advance.
+        self.pre_buffer_mut().deactivate();
+        // Pre-buffer is inactive, so there's no contention.
+        let result = {
+            let mut entrance = self.pre_buffer_wormhole();
+            entrance.recorder().replace_source(source).map(|_| {
+                entrance.cache().rekey(cache_file_path);
+            })
+        };
+        self.update_pre_buffer_activation();
+        result
+    }
+
     pub fn record_state(&self) -> Option<RecordState> {
         self.pre_buffer_wormhole().recorder().record_state()
     }
@@ -363,24 +611,61 @@ impl SupplierChain {
             .unwrap();
     }
 
+    /// Applies the audio settings which a clip might inherit from its column.
+    ///
+    /// The cache behavior is only touched if it changes.
+    pub fn set_inherited_audio_settings(
+        &mut self,
+        time_stretch_mode: AudioTimeStretchMode,
+        resample_mode: VirtualResampleMode,
+        cache_behavior: AudioCacheBehavior,
+    ) {
+        self.set_audio_time_stretch_mode(time_stretch_mode);
+        self.set_audio_resample_mode(resample_mode);
+        if self.audio_cache_behavior != Some(cache_behavior) {
+            self.set_audio_cache_behavior(cache_behavior);
+        }
+    }
+
     fn set_audio_cache_behavior(&mut self, cache_behavior: AudioCacheBehavior) {
-        use AudioCacheBehavior::*;
-        let pre_buffer_enabled = match &cache_behavior {
-            DirectFromDisk => true,
-            CacheInMemory => false,
-        };
         let command = ChainPreBufferCommand::SetAudioCacheBehavior(cache_behavior);
         self.pre_buffer_supplier().send_command(command);
-        // Enable/disable pre-buffer accordingly (pre-buffering not necessary if we have the
-        // complete source material in memory already).
+        self.audio_cache_behavior = Some(cache_behavior);
+        self.update_pre_buffer_activation();
+    }
+
+    /// Activates or deactivates the pre-buffer depending on the cache behavior and on whether
+    /// audio might be stretched.
+    fn update_pre_buffer_activation(&mut self) {
+        use AudioCacheBehavior::*;
+        let pre_buffer_active = match self.audio_cache_behavior {
+            None => return,
+            Some(DirectFromDisk) => true,
+            // Pre-buffering is not necessary if we have the complete source material in memory
+            // already. But stretching it in advance still takes load off the real-time thread.
+            Some(CacheInMemory) => {
+                self.pre_stretch_cached_audio
+                    && self.time_stretcher().may_stretch_audio()
+                    && !self.is_overdubbing_audio()
+            }
+        };
         let pre_buffer = self.pre_buffer_mut();
-        if pre_buffer_enabled {
+        if pre_buffer_active {
             let _ = pre_buffer.activate();
         } else {
             pre_buffer.deactivate();
         }
     }
 
+    fn is_overdubbing_audio(&self) -> bool {
+        if self.pre_buffer_supplier().is_active() {
+            // Audio overdub runs with the pre-buffer inactive only.
+            return false;
+        }
+        // Pre-buffer is inactive, so there's no contention.
+        self.pre_buffer_wormhole().cache().is_overdubbing_audio()
+    }
+
     fn set_audio_time_stretch_mode(&mut self, mode: AudioTimeStretchMode) {
         use AudioTimeStretchMode::*;
         let use_vari_speed = match mode {
@@ -394,6 +679,7 @@ impl SupplierChain {
             .set_responsible_for_audio_tempo_adjustments(use_vari_speed);
         self.time_stretcher_mut()
             .set_responsible_for_audio_time_stretching(!use_vari_speed);
+        self.update_pre_buffer_activation();
     }
 
     pub fn set_looped(&mut self, looped: bool) {
@@ -401,6 +687,11 @@ impl SupplierChain {
         self.pre_buffer_supplier().send_command(command);
     }
 
+    pub fn set_reversed(&mut self, reversed: bool) {
+        let command = ChainPreBufferCommand::SetReversed(reversed);
+        self.pre_buffer_supplier().send_command(command);
+    }
+
     pub fn set_tempo_factor(&mut self, tempo_factor: f64) {
         self.resampler_mut().set_tempo_factor(tempo_factor);
         self.time_stretcher_mut().set_tempo_factor(tempo_factor);
@@ -447,6 +738,11 @@ impl SupplierChain {
         self.pre_buffer_supplier().send_command(command);
     }
 
+    pub fn set_canvas(&mut self, canvas: Option<api::Canvas>) {
+        let command = ChainPreBufferCommand::SetCanvas(canvas);
+        self.pre_buffer_supplier().send_command(command);
+    }
+
     fn amplifier(&self) -> &AmplifierTail {
         &self.head
     }
@@ -471,14 +767,22 @@ impl SupplierChain {
         self.amplifier_mut().supplier_mut()
     }
 
-    fn time_stretcher(&self) -> &TimeStretcherTail {
+    fn midi_transformer(&self) -> &MidiTransformerTail {
         self.interaction_handler().supplier()
     }
 
-    fn time_stretcher_mut(&mut self) -> &mut TimeStretcherTail {
+    fn midi_transformer_mut(&mut self) -> &mut MidiTransformerTail {
         self.interaction_handler_mut().supplier_mut()
     }
 
+    fn time_stretcher(&self) -> &TimeStretcherTail {
+        self.midi_transformer().supplier()
+    }
+
+    fn time_stretcher_mut(&mut self) -> &mut TimeStretcherTail {
+        self.midi_transformer_mut().supplier_mut()
+    }
+
     fn downbeat(&self) -> &DownbeatTail {
         self.time_stretcher().supplier()
     }
@@ -514,6 +818,10 @@ impl SupplierChain {
 trait Entrance {
     fn looper(&mut self) -> &mut LooperTail;
 
+    fn reverser(&mut self) -> &mut ReverserTail;
+
+    fn canvas(&mut self) -> &mut CanvasTail;
+
     fn section(&mut self) -> &mut SectionTail;
 
     fn start_end_handler(&mut self) -> &mut StartEndHandlerTail;
@@ -528,10 +836,18 @@ impl<'a> Entrance for MutexGuard<'a, LooperTail> {
         self
     }
 
-    fn section(&mut self) -> &mut SectionTail {
+    fn reverser(&mut self) -> &mut ReverserTail {
         self.supplier_mut()
     }
 
+    fn canvas(&mut self) -> &mut CanvasTail {
+        self.reverser().supplier_mut()
+    }
+
+    fn section(&mut self) -> &mut SectionTail {
+        self.canvas().supplier_mut()
+    }
+
     fn start_end_handler(&mut self) -> &mut StartEndHandlerTail {
         self.section().supplier_mut()
     }
@@ -596,11 +912,13 @@ pub type ChainPreBufferRequest = PreBufferRequest<SharedLooperTail, ChainPreBuff
 #[derive(Debug)]
 pub enum ChainPreBufferCommand {
     SetAudioFadesEnabledForSource(bool),
+    SetAudioFades(api::ClipAudioFades),
     SetMidiResetMsgRangeForSection(MidiResetMessageRange),
     SetMidiResetMsgRangeForLoop(MidiResetMessageRange),
     SetMidiResetMsgRangeForSource(MidiResetMessageRange),
     SetAudioCacheBehavior(AudioCacheBehavior),
     SetLooped(bool),
+    SetReversed(bool),
     KeepPlayingUntilEndOfCurrentCycle {
         pos: isize,
     },
@@ -608,6 +926,7 @@ pub enum ChainPreBufferCommand {
         start: PositiveSecond,
         length: Option<PositiveSecond>,
     },
+    SetCanvas(Option<api::Canvas>),
 }
 
 #[derive(Debug)]
@@ -626,8 +945,22 @@ impl CommandProcessor for ChainPreBufferCommandProcessor {
                     .start_end_handler()
                     .set_audio_fades_enabled(enabled);
             }
+            SetAudioFades(fades) => {
+                let source_frame_rate = match entrance.recorder().material_info() {
+                    Ok(info) => info.frame_rate(),
+                    Err(e) => {
+                        debug!("Couldn't apply audio fades: {}", e);
+                        return;
+                    }
+                };
+                let start_end_fades = StartEndFades::new(&fades, source_frame_rate);
+                entrance.section().set_fades(start_end_fades);
+                entrance.canvas().set_fade_out(start_end_fades.fade_out);
+                entrance.start_end_handler().set_fades(start_end_fades);
+            }
             SetMidiResetMsgRangeForSection(range) => {
                 entrance.section().set_midi_reset_msg_range(range);
+                entrance.canvas().set_midi_reset_msg_range(range);
             }
             SetMidiResetMsgRangeForLoop(range) => {
                 entrance.looper().set_midi_reset_msg_range(range);
@@ -641,6 +974,7 @@ impl CommandProcessor for ChainPreBufferCommandProcessor {
             SetLooped(looped) => entrance
                 .looper()
                 .set_loop_behavior(LoopBehavior::from_bool(looped)),
+            SetReversed(reversed) => entrance.reverser().set_enabled(reversed),
             KeepPlayingUntilEndOfCurrentCycle { pos } => {
                 entrance
                     .looper()
@@ -660,6 +994,50 @@ impl CommandProcessor for ChainPreBufferCommandProcessor {
                     source_material_info.frame_count(),
                 );
             }
+            SetCanvas(canvas) => {
+                let source_material_info = match entrance.recorder().material_info() {
+                    Ok(info) => info,
+                    Err(e) => {
+                        debug!("Couldn't apply canvas: {}", e);
+                        return;
+                    }
+                };
+                entrance
+                    .canvas()
+                    .set_bounds_in_seconds(canvas, &source_material_info);
+            }
+        }
+    }
+}
+
+/// Splits the consumed frames `0..num_frames_consumed` into runs which map to contiguous source
+/// frames and processes each run at once.
+///
+/// Usually, that's just one run. At the end of a loop cycle, it's two.
+fn for_each_contiguous_source_run<C>(
+    context: &mut C,
+    num_frames_consumed: isize,
+    translate: impl Fn(&mut C, isize) -> isize,
+    mut process_run: impl FnMut(&mut C, isize, Range<isize>),
+) {
+    if num_frames_consumed <= 0 {
+        return;
+    }
+    let mut run_start = 0;
+    let mut run_source_start = translate(context, 0);
+    for i in 1..=num_frames_consumed {
+        let source_frame = if i < num_frames_consumed {
+            Some(translate(context, i))
+        } else {
+            None
+        };
+        if source_frame == Some(run_source_start + (i - run_start)) {
+            continue;
+        }
+        process_run(context, run_source_start, run_start..i);
+        if let Some(f) = source_frame {
+            run_start = i;
+            run_source_start = f;
         }
     }
 }
@@ -693,10 +1071,71 @@ pub struct ChainSettings {
     pub time_base: api::ClipTimeBase,
     pub midi_settings: api::ClipMidiSettings,
     pub looped: bool,
+    pub reversed: bool,
     pub volume: api::Db,
+    pub pitch: api::ClipPitch,
     pub section: api::Section,
+    pub canvas: Option<api::Canvas>,
     pub audio_apply_source_fades: bool,
+    pub audio_fades: api::ClipAudioFades,
     pub audio_time_stretch_mode: AudioTimeStretchMode,
     pub audio_resample_mode: VirtualResampleMode,
     pub cache_behavior: AudioCacheBehavior,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contiguous_source_frames_form_one_run() {
+        // Given
+        let mut runs = vec![];
+        // When
+        for_each_contiguous_source_run(
+            &mut runs,
+            4,
+            |_, i| 10 + i,
+            |runs, source_start_frame, consumed_frames| {
+                runs.push((source_start_frame, consumed_frames))
+            },
+        );
+        // Then
+        assert_eq!(runs, vec![(10, 0..4)]);
+    }
+
+    #[test]
+    fn loop_end_splits_source_frames_into_two_runs() {
+        // Given
+        let frame_count = 100;
+        let mut runs = vec![];
+        // When
+        for_each_contiguous_source_run(
+            &mut runs,
+            6,
+            |_, i| (97 + i) % frame_count,
+            |runs, source_start_frame, consumed_frames| {
+                runs.push((source_start_frame, consumed_frames))
+            },
+        );
+        // Then
+        assert_eq!(runs, vec![(97, 0..3), (0, 3..6)]);
+    }
+
+    #[test]
+    fn no_consumed_frames_form_no_run() {
+        // Given
+        let mut runs: Vec<(isize, Range<isize>)> = vec![];
+        // When
+        for_each_contiguous_source_run(
+            &mut runs,
+            0,
+            |_, i| i,
+            |runs, source_start_frame, consumed_frames| {
+                runs.push((source_start_frame, consumed_frames))
+            },
+        );
+        // Then
+        assert!(runs.is_empty());
+    }
+}
@@ -2,8 +2,8 @@ use crate::conversion_util::convert_duration_in_seconds_to_frames;
 use crate::rt::buffer::AudioBufMut;
 use crate::rt::supplier::{
     AudioSupplier, MaterialInfo, MidiSupplier, PositionTranslationSkill, PreBufferFillRequest,
-    PreBufferSourceSkill, SupplyAudioRequest, SupplyMidiRequest, SupplyRequest, SupplyRequestInfo,
-    SupplyResponse, WithMaterialInfo,
+    PreBufferSourceSkill, PreStretchParams, PreStretchSkill, SupplyAudioRequest, SupplyMidiRequest,
+    SupplyRequest, SupplyRequestInfo, SupplyResponse, WithMaterialInfo,
 };
 use crate::ClipEngineResult;
 use playtime_api::persistence::PositiveBeat;
@@ -42,6 +42,15 @@ impl<S> Downbeat<S> {
         self.downbeat_frame
     }
 
+    /// Returns the position which is passed to the supplier when the given position is requested.
+    pub fn effective_play_pos(&self, play_pos: isize) -> isize {
+        if self.enabled {
+            play_pos + self.downbeat_frame as isize
+        } else {
+            play_pos
+        }
+    }
+
     pub fn set_downbeat_in_beats(&mut self, beat: PositiveBeat, tempo: Bpm) -> ClipEngineResult<()>
     where
         S: WithMaterialInfo,
@@ -145,15 +154,45 @@ impl<S: PreBufferSourceSkill> PreBufferSourceSkill for Downbeat<S> {
     }
 }
 
+impl<S: PreStretchSkill> PreStretchSkill for Downbeat<S> {
+    fn pre_stretch(&mut self, start_frame: isize, params: PreStretchParams) {
+        if !self.enabled || self.downbeat_frame == 0 {
+            return self.supplier.pre_stretch(start_frame, params);
+        }
+        self.supplier
+            .pre_stretch(start_frame + self.downbeat_frame as isize, params);
+    }
+
+    fn supply_pre_stretched_audio(
+        &mut self,
+        request: &SupplyAudioRequest,
+        dest_buffer: &mut AudioBufMut,
+        params: PreStretchParams,
+    ) -> Option<SupplyResponse> {
+        let data = match self.get_data(request) {
+            None => {
+                return self
+                    .supplier
+                    .supply_pre_stretched_audio(request, dest_buffer, params);
+            }
+            Some(d) => d,
+        };
+        let inner_request = SupplyAudioRequest {
+            start_frame: data.start_frame,
+            info: data.info,
+            dest_sample_rate: request.dest_sample_rate,
+            parent_request: Some(request),
+            general_info: request.general_info,
+        };
+        self.supplier
+            .supply_pre_stretched_audio(&inner_request, dest_buffer, params)
+    }
+}
+
 impl<S: PositionTranslationSkill> PositionTranslationSkill for Downbeat<S> {
     fn translate_play_pos_to_source_pos(&self, play_pos: isize) -> isize {
-        let effective_play_pos = if self.enabled {
-            play_pos + self.downbeat_frame as isize
-        } else {
-            play_pos
-        };
         self.supplier
-            .translate_play_pos_to_source_pos(effective_play_pos)
+            .translate_play_pos_to_source_pos(self.effective_play_pos(play_pos))
     }
 }
 
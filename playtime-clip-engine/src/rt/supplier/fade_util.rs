@@ -1,4 +1,52 @@
+use crate::conversion_util::convert_duration_in_seconds_to_frames;
 use crate::rt::buffer::AudioBufMut;
+use playtime_api::persistence::{AudioFadeShape, ClipAudioFades, PositiveSecond};
+use reaper_medium::{DurationInSeconds, Hz};
+use std::f64::consts::{FRAC_PI_2, PI};
+
+/// Length and shape of one fade.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Fade {
+    pub length: usize,
+    pub shape: AudioFadeShape,
+}
+
+impl Fade {
+    pub fn new(length: PositiveSecond, shape: AudioFadeShape, frame_rate: Hz) -> Self {
+        Self {
+            length: convert_duration_in_seconds_to_frames(
+                DurationInSeconds::new(length.get()),
+                frame_rate,
+            ),
+            shape,
+        }
+    }
+}
+
+/// Fades for the start and end of some audio material, converted to frames.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct StartEndFades {
+    pub fade_in: Fade,
+    pub fade_out: Fade,
+}
+
+impl StartEndFades {
+    pub fn new(fades: &ClipAudioFades, frame_rate: Hz) -> Self {
+        Self {
+            fade_in: Fade::new(fades.fade_in_length, fades.shape, frame_rate),
+            fade_out: Fade::new(fades.fade_out_length, fades.shape, frame_rate),
+        }
+    }
+}
+
+impl Default for StartEndFades {
+    fn default() -> Self {
+        Self {
+            fade_in: DEFAULT_FADE,
+            fade_out: DEFAULT_FADE,
+        }
+    }
+}
 
 /// Takes care of applying a fade-in starting at frame zero.
 ///
@@ -9,15 +57,15 @@ use crate::rt::buffer::AudioBufMut;
 pub fn apply_fade_in_starting_at_zero(
     block: &mut AudioBufMut,
     block_start_frame: isize,
-    fade_length: usize,
+    fade: Fade,
 ) {
     use BlockLocation::*;
-    match block_location(block_start_frame, block.frame_count(), fade_length) {
+    match block_location(block_start_frame, block.frame_count(), fade.length) {
         ContainingFadePortion => {
             block.modify_frames(|sample| {
                 let factor = calc_fade_in_volume_factor_at(
                     block_start_frame + sample.index.frame as isize,
-                    fade_length,
+                    fade,
                 );
                 sample.value * factor
             });
@@ -39,11 +87,11 @@ pub fn apply_fade_out_ending_at(
     block: &mut AudioBufMut,
     block_start_frame: isize,
     frame_count: usize,
-    fade_length: usize,
+    fade: Fade,
 ) {
     let adjusted_block_start_frame =
-        block_start_frame - frame_count as isize + fade_length as isize;
-    apply_fade_out_starting_at_zero(block, adjusted_block_start_frame, fade_length);
+        block_start_frame - frame_count as isize + fade.length as isize;
+    apply_fade_out_starting_at_zero(block, adjusted_block_start_frame, fade);
 }
 
 /// Takes care of applying a fade-out starting at frame zero.
@@ -55,15 +103,15 @@ pub fn apply_fade_out_ending_at(
 pub fn apply_fade_out_starting_at_zero(
     block: &mut AudioBufMut,
     block_start_frame: isize,
-    fade_length: usize,
+    fade: Fade,
 ) {
     use BlockLocation::*;
-    match block_location(block_start_frame, block.frame_count(), fade_length) {
+    match block_location(block_start_frame, block.frame_count(), fade.length) {
         ContainingFadePortion => {
             block.modify_frames(|sample| {
                 let factor = calc_fade_out_volume_factor_at(
                     block_start_frame + sample.index.frame as isize,
-                    fade_length,
+                    fade,
                 );
                 sample.value * factor
             });
@@ -75,28 +123,41 @@ pub fn apply_fade_out_starting_at_zero(
     }
 }
 
-fn calc_fade_in_volume_factor_at(frame: isize, fade_length: usize) -> f64 {
+fn calc_fade_in_volume_factor_at(frame: isize, fade: Fade) -> f64 {
     if frame < 0 {
         // Left of fade
         return 0.0;
     }
-    if frame >= fade_length as isize {
+    if frame >= fade.length as isize {
         // Right of fade
         return 1.0;
     }
-    frame as f64 / fade_length as f64
+    shape_fade_in_progress(frame as f64 / fade.length as f64, fade.shape)
 }
 
-fn calc_fade_out_volume_factor_at(frame: isize, fade_length: usize) -> f64 {
+fn calc_fade_out_volume_factor_at(frame: isize, fade: Fade) -> f64 {
     if frame < 0 {
         // Left of fade
         return 1.0;
     }
-    if frame >= fade_length as isize {
+    if frame >= fade.length as isize {
         // Right of fade
         return 0.0;
     }
-    (frame - fade_length as isize).abs() as f64 / fade_length as f64
+    let progress = (frame - fade.length as isize).abs() as f64 / fade.length as f64;
+    shape_fade_in_progress(progress, fade.shape)
+}
+
+/// Converts the linear progress of a fade-in (0.0 to 1.0) into a volume factor.
+///
+/// A fade-out is the mirrored fade-in, so this works for both.
+fn shape_fade_in_progress(progress: f64, shape: AudioFadeShape) -> f64 {
+    use AudioFadeShape::*;
+    match shape {
+        Linear => progress,
+        EqualPower => (progress * FRAC_PI_2).sin(),
+        SCurve => (1.0 - (progress * PI).cos()) / 2.0,
+    }
 }
 
 fn block_location(
@@ -120,8 +181,101 @@ enum BlockLocation {
     RightOfFade,
 }
 
-// 240 frames = 5ms at 48 kHz
-const FADE_LENGTH: usize = 240;
-pub const SECTION_FADE_LENGTH: usize = FADE_LENGTH;
-pub const INTERACTION_FADE_LENGTH: usize = FADE_LENGTH;
-pub const START_END_FADE_LENGTH: usize = FADE_LENGTH;
+/// Used until the fades are configured. 240 frames = 5ms at 48 kHz.
+pub const DEFAULT_FADE: Fade = Fade {
+    length: 240,
+    shape: AudioFadeShape::Linear,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FADE_LENGTH: usize = 100;
+
+    fn fade(shape: AudioFadeShape) -> Fade {
+        Fade {
+            length: FADE_LENGTH,
+            shape,
+        }
+    }
+
+    #[test]
+    fn shapes_start_at_zero_and_end_at_one() {
+        for shape in [
+            AudioFadeShape::Linear,
+            AudioFadeShape::EqualPower,
+            AudioFadeShape::SCurve,
+        ] {
+            // Given
+            // When
+            let start = shape_fade_in_progress(0.0, shape);
+            let end = shape_fade_in_progress(1.0, shape);
+            // Then
+            assert!(start.abs() < 1e-9, "{:?}", shape);
+            assert!((end - 1.0).abs() < 1e-9, "{:?}", shape);
+        }
+    }
+
+    #[test]
+    fn shapes_at_half_progress() {
+        // Given
+        let progress = 0.5;
+        // When
+        let linear = shape_fade_in_progress(progress, AudioFadeShape::Linear);
+        let equal_power = shape_fade_in_progress(progress, AudioFadeShape::EqualPower);
+        let s_curve = shape_fade_in_progress(progress, AudioFadeShape::SCurve);
+        // Then
+        assert_eq!(linear, 0.5);
+        // Equal power keeps the sum of squares of a crossfade at 1.0.
+        assert!((equal_power - 0.5f64.sqrt()).abs() < 1e-9);
+        assert!((s_curve - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn s_curve_changes_slowly_at_edges() {
+        // Given
+        let progress = 0.1;
+        // When
+        let s_curve = shape_fade_in_progress(progress, AudioFadeShape::SCurve);
+        // Then
+        assert!(s_curve < progress);
+    }
+
+    #[test]
+    fn fade_in_volume_factors() {
+        // Given
+        let fade = fade(AudioFadeShape::Linear);
+        // When
+        // Then
+        assert_eq!(calc_fade_in_volume_factor_at(-1, fade), 0.0);
+        assert_eq!(calc_fade_in_volume_factor_at(0, fade), 0.0);
+        assert_eq!(calc_fade_in_volume_factor_at(25, fade), 0.25);
+        assert_eq!(
+            calc_fade_in_volume_factor_at(FADE_LENGTH as isize, fade),
+            1.0
+        );
+    }
+
+    #[test]
+    fn fade_out_mirrors_fade_in() {
+        // Given
+        let fade = fade(AudioFadeShape::EqualPower);
+        for frame in 0..FADE_LENGTH as isize {
+            // When
+            let fade_out_factor = calc_fade_out_volume_factor_at(frame, fade);
+            let fade_in_factor = calc_fade_in_volume_factor_at(FADE_LENGTH as isize - frame, fade);
+            // Then
+            assert!(
+                (fade_out_factor - fade_in_factor).abs() < 1e-9,
+                "frame {}",
+                frame
+            );
+        }
+        assert_eq!(calc_fade_out_volume_factor_at(-1, fade), 1.0);
+        assert_eq!(
+            calc_fade_out_volume_factor_at(FADE_LENGTH as isize, fade),
+            0.0
+        );
+    }
+}
@@ -0,0 +1,292 @@
+use crate::conversion_util::convert_position_in_frames_to_seconds;
+use crate::rt::buffer::OwnedAudioBuffer;
+use crate::rt::supplier::{
+    AudioSupplier, SupplyAudioRequest, SupplyRequestGeneralInfo, SupplyRequestInfo,
+};
+use crate::ClipEngineResult;
+use reaper_medium::Hz;
+
+/// Renders audio material block by block into a buffer, just like the audio thread would do it.
+///
+/// This works without a running REAPER as long as the given supplier doesn't depend on REAPER,
+/// e.g. a [`SupplierChain`](crate::rt::supplier::SupplierChain) created with
+/// [`SupplierChain::headless`](crate::rt::supplier::SupplierChain::headless) or single suppliers
+/// (looper, section, canvas, ...) on top of an
+/// [`InMemoryAudioSource`](crate::rt::supplier::InMemoryAudioSource).
+///
+/// Rendering happens in the material's native frame rate. Stops early if the material has
+/// reached its end, in which case the rest of the buffer stays silent.
+pub fn render_audio_offline(
+    supplier: &mut impl AudioSupplier,
+    start_frame: isize,
+    frame_count: usize,
+    block_length: usize,
+) -> ClipEngineResult<OwnedAudioBuffer> {
+    render_audio_offline_internal(supplier, start_frame, frame_count, block_length, None)
+}
+
+/// Like [`render_audio_offline`] but renders in the given output frame rate.
+///
+/// The supplier must be able to resample (e.g. a complete supplier chain).
+pub fn render_resampled_audio_offline(
+    supplier: &mut impl AudioSupplier,
+    start_frame: isize,
+    frame_count: usize,
+    block_length: usize,
+    output_frame_rate: Hz,
+) -> ClipEngineResult<OwnedAudioBuffer> {
+    render_audio_offline_internal(
+        supplier,
+        start_frame,
+        frame_count,
+        block_length,
+        Some(output_frame_rate),
+    )
+}
+
+fn render_audio_offline_internal(
+    supplier: &mut impl AudioSupplier,
+    start_frame: isize,
+    frame_count: usize,
+    block_length: usize,
+    output_frame_rate: Option<Hz>,
+) -> ClipEngineResult<OwnedAudioBuffer> {
+    if block_length == 0 {
+        return Err("block length must be greater than zero");
+    }
+    let material_info = supplier.material_info()?;
+    let frame_rate = output_frame_rate.unwrap_or_else(|| material_info.frame_rate());
+    let mut output = OwnedAudioBuffer::new(material_info.channel_count(), frame_count);
+    let mut output_buf = output.to_buf_mut();
+    let mut num_frames_rendered = 0;
+    let mut pos = start_frame;
+    while num_frames_rendered < frame_count {
+        let num_frames_in_block = block_length.min(frame_count - num_frames_rendered);
+        let general_info = SupplyRequestGeneralInfo {
+            audio_block_timeline_cursor_pos: convert_position_in_frames_to_seconds(
+                num_frames_rendered as isize,
+                frame_rate,
+            ),
+            audio_block_length: num_frames_in_block,
+            output_frame_rate: frame_rate,
+            clip_tempo_factor: 1.0,
+            ..Default::default()
+        };
+        let request = SupplyAudioRequest {
+            start_frame: pos,
+            dest_sample_rate: output_frame_rate,
+            info: SupplyRequestInfo {
+                audio_block_frame_offset: 0,
+                requester: "offline-render-request",
+                note: "",
+                is_realtime: false,
+            },
+            parent_request: None,
+            general_info: &general_info,
+        };
+        let mut block =
+            output_buf.slice_mut(num_frames_rendered..num_frames_rendered + num_frames_in_block);
+        let response = supplier.supply_audio(&request, &mut block);
+        num_frames_rendered += num_frames_in_block;
+        pos += response.num_frames_consumed as isize;
+        if response.status.reached_end() {
+            break;
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rt::supplier::{
+        Canvas, ChainEquipment, ClipSource, InMemoryAudioSource, LoopBehavior, Looper, Recorder,
+        Section, SupplierChain, WithMaterialInfo,
+    };
+    use playtime_api::persistence::{Db, PositiveSecond};
+
+    const SOURCE_FRAME_COUNT: usize = 1000;
+    const BLOCK_LENGTH: usize = 64;
+
+    fn ramp_source() -> InMemoryAudioSource {
+        let samples = (0..SOURCE_FRAME_COUNT)
+            .map(|i| i as f64 / SOURCE_FRAME_COUNT as f64)
+            .collect();
+        InMemoryAudioSource::from_samples(samples, 1, Hz::new(48000.0)).unwrap()
+    }
+
+    fn constant_source() -> InMemoryAudioSource {
+        InMemoryAudioSource::from_samples(vec![1.0; SOURCE_FRAME_COUNT], 1, Hz::new(48000.0))
+            .unwrap()
+    }
+
+    fn ramp_value(frame: usize) -> f64 {
+        frame as f64 / SOURCE_FRAME_COUNT as f64
+    }
+
+    fn headless_chain(source: InMemoryAudioSource) -> SupplierChain {
+        let (recorder_request_sender, _) = crossbeam_channel::unbounded();
+        let (pre_buffer_request_sender, _) = crossbeam_channel::unbounded();
+        let (cache_request_sender, _) = crossbeam_channel::unbounded();
+        let recorder =
+            Recorder::ready(ClipSource::in_memory_audio(source), recorder_request_sender);
+        let equipment = ChainEquipment {
+            pre_buffer_request_sender,
+            cache_request_sender,
+        };
+        SupplierChain::headless(recorder, equipment).unwrap()
+    }
+
+    #[test]
+    fn source_plays_once() {
+        // Given
+        let mut source = ramp_source();
+        // When
+        let output = render_audio_offline(&mut source, 0, 1500, BLOCK_LENGTH).unwrap();
+        // Then
+        let data = output.to_buf().data_as_slice().to_vec();
+        for (i, value) in data.iter().enumerate() {
+            let expected = if i < SOURCE_FRAME_COUNT {
+                ramp_value(i)
+            } else {
+                0.0
+            };
+            assert_eq!(*value, expected, "frame {}", i);
+        }
+    }
+
+    #[test]
+    fn looper_repeats_material() {
+        // Given
+        let mut looper = Looper::new(ramp_source());
+        looper.set_enabled(true);
+        looper.set_loop_behavior(LoopBehavior::Infinitely);
+        // When
+        let output = render_audio_offline(&mut looper, 0, 2500, BLOCK_LENGTH).unwrap();
+        // Then
+        let data = output.to_buf().data_as_slice().to_vec();
+        for (i, value) in data.iter().enumerate() {
+            assert_eq!(*value, ramp_value(i % SOURCE_FRAME_COUNT), "frame {}", i);
+        }
+    }
+
+    #[test]
+    fn section_fades_and_cuts() {
+        // Given
+        let mut section = Section::new(constant_source());
+        section.set_bounds(100, Some(500));
+        // When
+        let output = render_audio_offline(&mut section, 0, 800, BLOCK_LENGTH).unwrap();
+        // Then
+        let data = output.to_buf().data_as_slice().to_vec();
+        assert_eq!(data[0], 0.0);
+        assert!(data[100] > 0.0 && data[100] < 1.0);
+        assert_eq!(data[300], 1.0);
+        assert!(data[499] > 0.0 && data[499] < 0.01);
+        assert!(data[500..].iter().all(|v| *v == 0.0));
+    }
+
+    #[test]
+    fn canvas_inserts_silence_before_material() {
+        // Given
+        let mut canvas = Canvas::new(ramp_source());
+        canvas.set_bounds(200, None);
+        // When
+        let output = render_audio_offline(&mut canvas, 0, 1400, BLOCK_LENGTH).unwrap();
+        // Then
+        let data = output.to_buf().data_as_slice().to_vec();
+        assert!(data[..200].iter().all(|v| *v == 0.0));
+        for i in 0..SOURCE_FRAME_COUNT {
+            assert_eq!(data[200 + i], ramp_value(i), "material frame {}", i);
+        }
+        assert!(data[1200..].iter().all(|v| *v == 0.0));
+    }
+
+    #[test]
+    fn load_wav_file() {
+        // Given
+        let path = std::env::temp_dir().join("playtime-headless-test.wav");
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for i in 0..100i16 {
+            writer.write_sample(i * 100).unwrap();
+            writer.write_sample(-i * 100).unwrap();
+        }
+        writer.finalize().unwrap();
+        // When
+        let mut source = InMemoryAudioSource::from_file(&path).unwrap();
+        let output = render_audio_offline(&mut source, 0, 100, BLOCK_LENGTH).unwrap();
+        // Then
+        let info = source.material_info().unwrap();
+        assert_eq!(info.channel_count(), 2);
+        assert_eq!(info.frame_count(), 100);
+        assert_eq!(info.frame_rate(), Hz::new(44100.0));
+        let data = output.to_buf().data_as_slice().to_vec();
+        assert_eq!(data[2 * 10], 1000.0 / 32768.0);
+        assert_eq!(data[2 * 10 + 1], -1000.0 / 32768.0);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn chain_loops_section() {
+        // Given
+        let samples = (0..4800).map(|i| i as f64 / 4800.0).collect();
+        let source = InMemoryAudioSource::from_samples(samples, 1, Hz::new(48000.0)).unwrap();
+        let mut chain = headless_chain(source);
+        // Frame 480 to 2400
+        chain.set_section(
+            PositiveSecond::new(0.01).unwrap(),
+            Some(PositiveSecond::new(0.04).unwrap()),
+        );
+        chain.set_looped(true);
+        // When
+        let output = render_audio_offline(&mut chain, 0, 3 * 1920, BLOCK_LENGTH).unwrap();
+        // Then
+        let data = output.to_buf().data_as_slice().to_vec();
+        for cycle in 0..3 {
+            // Leave out the section fades
+            for i in 240..1680 {
+                let frame = cycle * 1920 + i;
+                let expected = (480 + i) as f64 / 4800.0;
+                assert_eq!(data[frame], expected, "frame {}", frame);
+            }
+        }
+    }
+
+    #[test]
+    fn chain_resamples() {
+        // Given
+        let mut chain = headless_chain(ramp_source());
+        // When
+        let output =
+            render_resampled_audio_offline(&mut chain, 0, 2500, BLOCK_LENGTH, Hz::new(96000.0))
+                .unwrap();
+        // Then
+        let data = output.to_buf().data_as_slice().to_vec();
+        // The ramp is interpolated linearly, so each output frame is exactly in the middle of
+        // two source frames or on a source frame.
+        for (i, value) in data.iter().enumerate().take(2 * SOURCE_FRAME_COUNT - 2) {
+            let expected = i as f64 / 2.0 / SOURCE_FRAME_COUNT as f64;
+            assert!((value - expected).abs() < 1e-9, "frame {}", i);
+        }
+        assert!(data[2 * SOURCE_FRAME_COUNT..].iter().all(|v| *v == 0.0));
+    }
+
+    #[test]
+    fn chain_applies_volume() {
+        // Given
+        let mut chain = headless_chain(constant_source());
+        chain.set_volume(Db::new(-6.0).unwrap());
+        // When
+        let output = render_audio_offline(&mut chain, 0, 100, BLOCK_LENGTH).unwrap();
+        // Then
+        let data = output.to_buf().data_as_slice().to_vec();
+        let expected = 10f64.powf(-6.0 / 20.0);
+        assert!(data.iter().all(|v| (v - expected).abs() < 1e-9));
+    }
+}
@@ -0,0 +1,438 @@
+use crate::conversion_util::adjust_proportionally;
+use crate::rt::buffer::{AudioBufMut, OwnedAudioBuffer};
+use crate::rt::supplier::audio_util::{supply_audio_material, transfer_samples_from_buffer};
+use crate::rt::supplier::{
+    AudioMaterialInfo, AudioSupplier, MaterialInfo, MidiMaterialInfo, PositionTranslationSkill,
+    SupplyAudioRequest, SupplyResponse, WithMaterialInfo, MIDI_BASE_BPM, MIDI_FRAME_RATE,
+};
+use crate::ClipEngineResult;
+use helgoboss_midi::{RawShortMessage, ShortMessageFactory, U7};
+use reaper_medium::Hz;
+use std::path::Path;
+
+/// Audio source which holds the complete decoded material in memory.
+///
+/// In contrast to [`ClipSource`](crate::rt::supplier::ClipSource), it doesn't depend on REAPER,
+/// which makes it suitable for offline rendering and tests.
+#[derive(Clone, Debug)]
+pub struct InMemoryAudioSource {
+    frame_rate: Hz,
+    content: OwnedAudioBuffer,
+}
+
+impl InMemoryAudioSource {
+    /// Creates a source from interleaved samples.
+    pub fn from_samples(
+        samples: Vec<f64>,
+        channel_count: usize,
+        frame_rate: Hz,
+    ) -> ClipEngineResult<Self> {
+        if channel_count == 0 {
+            return Err("channel count must be greater than zero");
+        }
+        if samples.len() % channel_count != 0 {
+            return Err("sample count must be a multiple of the channel count");
+        }
+        let frame_count = samples.len() / channel_count;
+        let source = Self {
+            frame_rate,
+            content: OwnedAudioBuffer::try_recycle(samples, channel_count, frame_count)?,
+        };
+        Ok(source)
+    }
+
+    /// Loads a WAV or FLAC file, depending on the file extension.
+    pub fn from_file(path: &Path) -> ClipEngineResult<Self> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .ok_or("file without extension")?;
+        match extension.to_lowercase().as_str() {
+            "wav" => Self::from_wav_file(path),
+            "flac" => Self::from_flac_file(path),
+            _ => Err("unsupported file type"),
+        }
+    }
+
+    pub fn from_wav_file(path: &Path) -> ClipEngineResult<Self> {
+        let mut reader = hound::WavReader::open(path).map_err(|_| "couldn't open WAV file")?;
+        let spec = reader.spec();
+        let samples: Result<Vec<f64>, _> = match spec.sample_format {
+            hound::SampleFormat::Float => reader
+                .samples::<f32>()
+                .map(|s| s.map(|s| s as f64))
+                .collect(),
+            hound::SampleFormat::Int => {
+                let factor = int_sample_factor(spec.bits_per_sample as u32);
+                reader
+                    .samples::<i32>()
+                    .map(|s| s.map(|s| s as f64 * factor))
+                    .collect()
+            }
+        };
+        let samples = samples.map_err(|_| "couldn't decode WAV file")?;
+        Self::from_samples(
+            samples,
+            spec.channels as usize,
+            Hz::new(spec.sample_rate as f64),
+        )
+    }
+
+    pub fn from_flac_file(path: &Path) -> ClipEngineResult<Self> {
+        let mut reader = claxon::FlacReader::open(path).map_err(|_| "couldn't open FLAC file")?;
+        let info = reader.streaminfo();
+        let factor = int_sample_factor(info.bits_per_sample);
+        let samples: Result<Vec<f64>, _> = reader
+            .samples()
+            .map(|s| s.map(|s| s as f64 * factor))
+            .collect();
+        let samples = samples.map_err(|_| "couldn't decode FLAC file")?;
+        Self::from_samples(
+            samples,
+            info.channels as usize,
+            Hz::new(info.sample_rate as f64),
+        )
+    }
+
+    fn audio_material_info(&self) -> AudioMaterialInfo {
+        AudioMaterialInfo {
+            channel_count: self.content.to_buf().channel_count(),
+            frame_count: self.content.to_buf().frame_count(),
+            frame_rate: self.frame_rate,
+        }
+    }
+}
+
+impl AudioSupplier for InMemoryAudioSource {
+    fn supply_audio(
+        &mut self,
+        request: &SupplyAudioRequest,
+        dest_buffer: &mut AudioBufMut,
+    ) -> SupplyResponse {
+        let buf = self.content.to_buf();
+        supply_audio_material(request, dest_buffer, self.frame_rate, |input| {
+            transfer_samples_from_buffer(buf, input)
+        })
+    }
+}
+
+impl WithMaterialInfo for InMemoryAudioSource {
+    fn material_info(&self) -> ClipEngineResult<MaterialInfo> {
+        Ok(MaterialInfo::Audio(self.audio_material_info()))
+    }
+}
+
+impl PositionTranslationSkill for InMemoryAudioSource {
+    fn translate_play_pos_to_source_pos(&self, play_pos: isize) -> isize {
+        play_pos
+    }
+}
+
+/// MIDI source which holds all its events in memory.
+///
+/// Like [`InMemoryAudioSource`], it doesn't depend on REAPER. Positions are measured in MIDI
+/// frames (see [`MIDI_FRAME_RATE`]), assuming [`MIDI_BASE_BPM`] just like the REAPER MIDI sources
+/// used by [`ClipSource`](crate::rt::supplier::ClipSource).
+#[derive(Clone, Debug)]
+pub struct InMemoryMidiSource {
+    /// Sorted by position.
+    events: Vec<(usize, RawShortMessage)>,
+    frame_count: usize,
+}
+
+impl InMemoryMidiSource {
+    /// Creates a source from events with positions in MIDI frames.
+    ///
+    /// Events at or after the given frame count are ignored.
+    pub fn from_events(mut events: Vec<(usize, RawShortMessage)>, frame_count: usize) -> Self {
+        events.retain(|(pos, _)| *pos < frame_count);
+        // Stable, so that the original order of simultaneous events is preserved.
+        events.sort_by_key(|(pos, _)| *pos);
+        Self {
+            events,
+            frame_count,
+        }
+    }
+
+    /// Loads a Standard MIDI File (format 0 or 1).
+    ///
+    /// Only channel messages are kept. The tempo map of the file is ignored, ticks are interpreted
+    /// as fractions of a beat in [`MIDI_BASE_BPM`].
+    pub fn from_smf_file(path: &Path) -> ClipEngineResult<Self> {
+        let bytes = std::fs::read(path).map_err(|_| "couldn't read MIDI file")?;
+        Self::from_smf_bytes(&bytes)
+    }
+
+    pub fn from_smf_bytes(bytes: &[u8]) -> ClipEngineResult<Self> {
+        let mut reader = SmfReader { bytes, pos: 0 };
+        if reader.read_bytes(4)? != b"MThd" {
+            return Err("not a Standard MIDI File");
+        }
+        let header_length = reader.read_u32()? as usize;
+        let format = reader.read_u16()?;
+        if format > 1 {
+            return Err("only MIDI files of format 0 or 1 are supported");
+        }
+        let _track_count = reader.read_u16()?;
+        let division = reader.read_u16()?;
+        if division & 0x8000 != 0 {
+            return Err("MIDI files with SMPTE time division are not supported");
+        }
+        if division == 0 {
+            return Err("MIDI file without ticks per quarter note");
+        }
+        reader.read_bytes(header_length.saturating_sub(6))?;
+        let frames_per_tick = MIDI_FRAME_RATE.get() * 60.0 / MIDI_BASE_BPM.get() / division as f64;
+        let mut events = Vec::new();
+        let mut end_tick = 0;
+        while !reader.is_at_end() {
+            let chunk_type = reader.read_bytes(4)?;
+            let chunk_length = reader.read_u32()? as usize;
+            let chunk = reader.read_bytes(chunk_length)?;
+            if chunk_type != b"MTrk" {
+                // Unknown chunks must be ignored
+                continue;
+            }
+            let track_end_tick = read_smf_track(chunk, |tick, msg| events.push((tick, msg)))?;
+            end_tick = end_tick.max(track_end_tick);
+        }
+        let to_frame = |tick: u64| adjust_proportionally(tick as f64, frames_per_tick) as usize;
+        let events = events
+            .into_iter()
+            .map(|(tick, msg)| (to_frame(tick), msg))
+            .collect();
+        // Events at the very end of the track (e.g. final note-offs) should still be played.
+        Ok(Self::from_events(events, to_frame(end_tick) + 1))
+    }
+
+    /// Returns all events within the given portion together with their frame offset relative to
+    /// the start of the portion.
+    ///
+    /// Start frame and frame count are given in the destination frame rate, which is also the
+    /// unit of the returned frame offsets.
+    pub fn events_in_portion(
+        &self,
+        start_frame: isize,
+        frame_count: usize,
+        frame_rate: Hz,
+    ) -> impl Iterator<Item = (usize, RawShortMessage)> + '_ {
+        let factor = frame_rate.get() / MIDI_FRAME_RATE.get();
+        let to_dest_frame = move |pos: usize| adjust_proportionally(pos as f64, factor);
+        let end_frame = start_frame + frame_count as isize;
+        let first_index = self
+            .events
+            .partition_point(|(pos, _)| to_dest_frame(*pos) < start_frame);
+        self.events[first_index..]
+            .iter()
+            .map(move |(pos, msg)| (to_dest_frame(*pos), *msg))
+            .take_while(move |(dest_frame, _)| *dest_frame < end_frame)
+            .map(move |(dest_frame, msg)| ((dest_frame - start_frame) as usize, msg))
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+}
+
+impl WithMaterialInfo for InMemoryMidiSource {
+    fn material_info(&self) -> ClipEngineResult<MaterialInfo> {
+        let info = MidiMaterialInfo {
+            frame_count: self.frame_count,
+        };
+        Ok(MaterialInfo::Midi(info))
+    }
+}
+
+impl PositionTranslationSkill for InMemoryMidiSource {
+    fn translate_play_pos_to_source_pos(&self, play_pos: isize) -> isize {
+        play_pos
+    }
+}
+
+struct SmfReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> SmfReader<'a> {
+    fn is_at_end(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    fn read_bytes(&mut self, count: usize) -> ClipEngineResult<&'a [u8]> {
+        let end = self.pos + count;
+        let bytes = self
+            .bytes
+            .get(self.pos..end)
+            .ok_or("unexpected end of MIDI file")?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> ClipEngineResult<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> ClipEngineResult<u16> {
+        let b = self.read_bytes(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn read_u32(&mut self) -> ClipEngineResult<u32> {
+        let b = self.read_bytes(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn read_variable_length_quantity(&mut self) -> ClipEngineResult<u32> {
+        let mut value = 0u32;
+        // At most 4 bytes are allowed
+        for _ in 0..4 {
+            let byte = self.read_u8()?;
+            value = (value << 7) | (byte & 0x7f) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("variable-length quantity in MIDI file too long")
+    }
+}
+
+/// Reads the channel messages of the given track chunk and returns the tick at which the track
+/// ends.
+fn read_smf_track(
+    chunk: &[u8],
+    mut on_msg: impl FnMut(u64, RawShortMessage),
+) -> ClipEngineResult<u64> {
+    let mut reader = SmfReader {
+        bytes: chunk,
+        pos: 0,
+    };
+    let mut tick = 0u64;
+    let mut running_status: Option<u8> = None;
+    while !reader.is_at_end() {
+        tick += reader.read_variable_length_quantity()? as u64;
+        let first_byte = reader.read_u8()?;
+        let (status_byte, data_byte_1) = if first_byte & 0x80 == 0 {
+            let status_byte =
+                running_status.ok_or("MIDI file uses running status without status")?;
+            (status_byte, first_byte)
+        } else {
+            match first_byte {
+                0xff => {
+                    let meta_type = reader.read_u8()?;
+                    let length = reader.read_variable_length_quantity()? as usize;
+                    reader.read_bytes(length)?;
+                    if meta_type == 0x2f {
+                        // End of track
+                        break;
+                    }
+                    continue;
+                }
+                0xf0 | 0xf7 => {
+                    let length = reader.read_variable_length_quantity()? as usize;
+                    reader.read_bytes(length)?;
+                    running_status = None;
+                    continue;
+                }
+                0xf1..=0xfe => return Err("unexpected system message in MIDI file"),
+                _ => {
+                    running_status = Some(first_byte);
+                    (first_byte, reader.read_u8()?)
+                }
+            }
+        };
+        let data_byte_2 = match status_byte & 0xf0 {
+            // Program change and channel pressure have only one data byte
+            0xc0 | 0xd0 => 0,
+            _ => reader.read_u8()?,
+        };
+        if data_byte_1 & 0x80 != 0 || data_byte_2 & 0x80 != 0 {
+            return Err("invalid data byte in MIDI file");
+        }
+        let msg =
+            RawShortMessage::from_bytes((status_byte, U7::new(data_byte_1), U7::new(data_byte_2)))
+                .map_err(|_| "invalid channel message in MIDI file")?;
+        on_msg(tick, msg);
+    }
+    Ok(tick)
+}
+
+/// Returns the factor which converts integer samples of the given bit depth to -1.0..1.0.
+fn int_sample_factor(bits_per_sample: u32) -> f64 {
+    1.0 / (1u64 << (bits_per_sample - 1)) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helgoboss_midi::{Channel, KeyNumber};
+
+    /// Format 0, 480 ticks per quarter note, one note with a length of one beat (using running
+    /// status for the note-off), a tempo change in between and the end of track one beat later.
+    const SMF: &[u8] = &[
+        b'M', b'T', b'h', b'd', 0, 0, 0, 6, 0, 0, 0, 1, 0x01, 0xe0, b'M', b'T', b'r', b'k', 0, 0,
+        0, 20, 0x00, 0x90, 0x3c, 0x64, 0x83, 0x60, 0x3c, 0x00, 0x00, 0xff, 0x51, 0x03, 0x07, 0xa1,
+        0x20, 0x83, 0x60, 0xff, 0x2f, 0x00,
+    ];
+
+    fn note_on(velocity: u8) -> RawShortMessage {
+        RawShortMessage::note_on(Channel::new(0), KeyNumber::new(60), U7::new(velocity))
+    }
+
+    #[test]
+    fn load_smf() {
+        // Given
+        let bytes = SMF;
+        // When
+        let source = InMemoryMidiSource::from_smf_bytes(bytes).unwrap();
+        // Then
+        // Two beats in MIDI_BASE_BPM are one second
+        assert_eq!(source.frame_count(), MIDI_FRAME_RATE.get() as usize + 1);
+        assert_eq!(
+            source.events,
+            vec![
+                (0, note_on(100)),
+                (MIDI_FRAME_RATE.get() as usize / 2, note_on(0))
+            ]
+        );
+    }
+
+    #[test]
+    fn reject_invalid_smf() {
+        // Given
+        let mut smpte = SMF.to_vec();
+        smpte[12] = 0xe7;
+        let mut truncated = SMF.to_vec();
+        truncated.pop();
+        // When
+        let smpte_result = InMemoryMidiSource::from_smf_bytes(&smpte);
+        let truncated_result = InMemoryMidiSource::from_smf_bytes(&truncated);
+        let garbage_result = InMemoryMidiSource::from_smf_bytes(b"RIFF");
+        // Then
+        assert!(smpte_result.is_err());
+        assert!(truncated_result.is_err());
+        assert!(garbage_result.is_err());
+    }
+
+    #[test]
+    fn events_in_portion() {
+        // Given
+        let source = InMemoryMidiSource::from_smf_bytes(SMF).unwrap();
+        let frame_rate = Hz::new(48000.0);
+        let events_in = |start_frame, frame_count| -> Vec<_> {
+            source
+                .events_in_portion(start_frame, frame_count, frame_rate)
+                .collect()
+        };
+        // When
+        let complete = events_in(0, 48000);
+        let note_off_only = events_in(24000, 64);
+        let nothing = events_in(1, 23999);
+        let count_in = events_in(-64, 128);
+        // Then
+        assert_eq!(complete, vec![(0, note_on(100)), (24000, note_on(0))]);
+        assert_eq!(note_off_only, vec![(0, note_on(0))]);
+        assert!(nothing.is_empty());
+        assert_eq!(count_in, vec![(64, note_on(100))]);
+    }
+}
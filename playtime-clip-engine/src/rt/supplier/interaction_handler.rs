@@ -1,6 +1,6 @@
 use crate::rt::buffer::AudioBufMut;
 use crate::rt::supplier::fade_util::{
-    apply_fade_in_starting_at_zero, apply_fade_out_starting_at_zero, INTERACTION_FADE_LENGTH,
+    apply_fade_in_starting_at_zero, apply_fade_out_starting_at_zero, Fade, DEFAULT_FADE,
 };
 use crate::rt::supplier::midi_util::SilenceMidiBlockMode;
 use crate::rt::supplier::{
@@ -17,6 +17,7 @@ use std::cmp;
 pub struct InteractionHandler<S> {
     supplier: S,
     interaction: Option<Interaction>,
+    fade: Fade,
     midi_reset_msg_range: MidiResetMessageRange,
 }
 
@@ -31,21 +32,29 @@ struct Interaction {
     /// For audio material, fades are inserted. For a start interaction, this frame marks the fade
     /// beginning. For a stop interaction, it marks the fade end.
     frame: isize,
+    /// The fade is memorized per interaction so that changing the fade settings doesn't affect
+    /// an ongoing fade.
+    fade: Fade,
 }
 
 impl Interaction {
-    pub fn new(kind: InteractionKind, frame: isize) -> Self {
-        Interaction { kind, frame }
+    pub fn new(kind: InteractionKind, frame: isize, fade: Fade) -> Self {
+        Interaction { kind, frame, fade }
     }
 
-    pub fn immediate(kind: InteractionKind, current_frame: isize, is_midi: bool) -> Self {
+    pub fn immediate(
+        kind: InteractionKind,
+        current_frame: isize,
+        fade: Fade,
+        is_midi: bool,
+    ) -> Self {
         if is_midi {
-            Self::new(kind, current_frame)
+            Self::new(kind, current_frame, fade)
         } else {
             use InteractionKind::*;
             match kind {
-                Start => Self::new(kind, current_frame),
-                Stop => Self::new(kind, current_frame + INTERACTION_FADE_LENGTH as isize),
+                Start => Self::new(kind, current_frame, fade),
+                Stop => Self::new(kind, current_frame + fade.length as isize, fade),
             }
         }
     }
@@ -54,14 +63,14 @@ impl Interaction {
         use InteractionKind::*;
         match self.kind {
             Start => self.frame,
-            Stop => self.frame - INTERACTION_FADE_LENGTH as isize,
+            Stop => self.frame - self.fade.length as isize,
         }
     }
 
     pub fn fade_end_frame(&self) -> isize {
         use InteractionKind::*;
         match self.kind {
-            Start => self.frame + INTERACTION_FADE_LENGTH as isize,
+            Start => self.frame + self.fade.length as isize,
             Stop => self.frame,
         }
     }
@@ -78,10 +87,16 @@ impl<S> InteractionHandler<S> {
         Self {
             interaction: None,
             supplier,
+            fade: DEFAULT_FADE,
             midi_reset_msg_range: Default::default(),
         }
     }
 
+    /// Sets the fade used for start and stop interactions (audio only).
+    pub fn set_fade(&mut self, fade: Fade) {
+        self.fade = fade;
+    }
+
     pub fn set_midi_reset_msg_range(&mut self, range: MidiResetMessageRange) {
         self.midi_reset_msg_range = range;
     }
@@ -143,7 +158,11 @@ impl<S> InteractionHandler<S> {
     /// MIDI:
     /// - Installs some stop interaction reset messages at the given frame.
     pub fn schedule_stop_at(&mut self, end_frame: isize) {
-        self.interaction = Some(Interaction::new(InteractionKind::Stop, end_frame))
+        self.interaction = Some(Interaction::new(
+            InteractionKind::Stop,
+            end_frame,
+            self.fade,
+        ))
     }
 
     fn install_immediate_interaction(
@@ -155,7 +174,7 @@ impl<S> InteractionHandler<S> {
         S: WithMaterialInfo,
     {
         let is_midi = self.material_info()?.is_midi();
-        let new_interaction = Interaction::immediate(kind, current_frame, is_midi);
+        let new_interaction = Interaction::immediate(kind, current_frame, self.fade, is_midi);
         let new_interaction = if is_midi {
             Some(new_interaction)
         } else {
@@ -192,10 +211,13 @@ impl<S> InteractionHandler<S> {
         let begin_frame_of_ongoing_fade = ongoing_interaction.fade_begin_frame();
         let current_pos_in_fade = begin_frame_of_new_fade - begin_frame_of_ongoing_fade;
         // If current_pos_in_fade is zero, we should skip the fade (move it completely to left).
-        // If it's FADE_LENGTH, we should apply the complete fade.
-        let adjustment = current_pos_in_fade - INTERACTION_FADE_LENGTH as isize;
-        let fixed_interaction =
-            Interaction::new(new_interaction.kind, new_interaction.frame + adjustment);
+        // If it's the fade length, we should apply the complete fade.
+        let adjustment = current_pos_in_fade - new_interaction.fade.length as isize;
+        let fixed_interaction = Interaction::new(
+            new_interaction.kind,
+            new_interaction.frame + adjustment,
+            new_interaction.fade,
+        );
         Some(fixed_interaction)
     }
 
@@ -244,7 +266,7 @@ impl<S: AudioSupplier> AudioSupplier for InteractionHandler<S> {
                 apply_fade_in_starting_at_zero(
                     dest_buffer,
                     distance_from_fade_begin,
-                    INTERACTION_FADE_LENGTH,
+                    interaction.fade,
                 );
                 let end_frame = request.start_frame + inner_response.num_frames_consumed as isize;
                 if end_frame >= interaction.fade_end_frame() || inner_response.status.reached_end()
@@ -272,7 +294,7 @@ impl<S: AudioSupplier> AudioSupplier for InteractionHandler<S> {
                         apply_fade_out_starting_at_zero(
                             dest_buffer,
                             distance_from_fade_begin,
-                            INTERACTION_FADE_LENGTH,
+                            interaction.fade,
                         );
                         let end_frame =
                             request.start_frame + inner_response.num_frames_consumed as isize;
@@ -0,0 +1,438 @@
+use crate::conversion_util::convert_duration_in_seconds_to_frames;
+use crate::rt::buffer::AudioBufMut;
+use crate::rt::supplier::midi_util::add_midi_event;
+use crate::rt::supplier::{
+    AudioSupplier, MaterialInfo, MidiSupplier, PositionTranslationSkill, PreBufferFillRequest,
+    PreBufferSourceSkill, SupplyAudioRequest, SupplyMidiRequest, SupplyResponse, WithMaterialInfo,
+    MIDI_BASE_BPM,
+};
+use crate::ClipEngineResult;
+use helgoboss_midi::{
+    Channel, KeyNumber, RawShortMessage, ShortMessage, ShortMessageFactory, StructuredShortMessage,
+    U7,
+};
+use playtime_api::persistence::MidiTransformation;
+use reaper_medium::{BorrowedMidiEventList, DurationInSeconds, MidiFrameOffset};
+use std::ptr;
+
+/// Changes velocities, channels and note lengths of MIDI material and filters notes.
+///
+/// Audio passes through unchanged.
+#[derive(Debug)]
+pub struct MidiTransformer<S> {
+    supplier: S,
+    transformation: MidiTransformation,
+    /// Note-offs which we still need to send in fixed-length note mode, indexed by the channel
+    /// and key number of the original note-on.
+    pending_note_offs: [[Option<PendingNoteOff>; 128]; 16],
+    pending_note_off_count: usize,
+    /// Output channels of the notes which are currently playing, indexed by the channel and key
+    /// number of the original note-on.
+    ///
+    /// Needed to end notes with the settings they were started with if the transformation
+    /// changes while they are playing.
+    active_notes: [[Option<Channel>; 128]; 16],
+}
+
+#[derive(Copy, Clone, Debug)]
+struct PendingNoteOff {
+    /// Number of frames from the start of the next block until the note-off is due.
+    remaining_frames: usize,
+    /// Output channel at the time the note-on was sent.
+    channel: Channel,
+}
+
+impl<S> MidiTransformer<S> {
+    pub fn new(supplier: S) -> Self {
+        Self {
+            supplier,
+            transformation: Default::default(),
+            pending_note_offs: [[None; 128]; 16],
+            pending_note_off_count: 0,
+            active_notes: [[None; 128]; 16],
+        }
+    }
+
+    pub fn supplier(&self) -> &S {
+        &self.supplier
+    }
+
+    pub fn supplier_mut(&mut self) -> &mut S {
+        &mut self.supplier
+    }
+
+    pub fn set_transformation(&mut self, transformation: MidiTransformation) {
+        let old_transformation = std::mem::replace(&mut self.transformation, transformation);
+        if old_transformation.output_channel != transformation.output_channel
+            || old_transformation.note_range != transformation.note_range
+        {
+            self.end_rerouted_notes();
+        }
+    }
+
+    /// Schedules note-offs for playing notes which the current transformation would send on
+    /// another channel or filter out. Otherwise their original note-offs wouldn't reach them.
+    ///
+    /// The note-offs are sent at the start of the next block, with the old output channel.
+    fn end_rerouted_notes(&mut self) {
+        let transformation = self.transformation;
+        for (channel_index, keys) in self.active_notes.iter_mut().enumerate() {
+            for (key, active_note) in keys.iter_mut().enumerate() {
+                let old_output_channel = match *active_note {
+                    None => continue,
+                    Some(ch) => ch,
+                };
+                let new_output_channel =
+                    output_channel(&transformation, Channel::new(channel_index as u8));
+                if new_output_channel == old_output_channel
+                    && transformation.lets_note_pass(key as u8)
+                {
+                    continue;
+                }
+                *active_note = None;
+                let pending = &mut self.pending_note_offs[channel_index][key];
+                if pending.is_none() {
+                    self.pending_note_off_count += 1;
+                }
+                *pending = Some(PendingNoteOff {
+                    remaining_frames: 0,
+                    channel: old_output_channel,
+                });
+            }
+        }
+    }
+
+    /// Removes filtered notes and, in fixed-length note mode, the original note-offs of notes
+    /// that we end ourselves. Starts counting down the fixed note length for new notes.
+    fn filter_and_schedule(
+        &mut self,
+        event_list: &mut BorrowedMidiEventList,
+        first_new_bpos: u32,
+        fixed_note_length: Option<usize>,
+    ) {
+        // Events before the given byte position come from previous suppliers. Deleting an item
+        // doesn't move the items in front of it, so we can start right at the first new one.
+        let mut bpos = first_new_bpos;
+        while let Some(res) = event_list.enum_items(bpos) {
+            let msg = res.midi_event.message();
+            let frame_offset = res.midi_event.frame_offset().get() as usize;
+            let next_bpos = res.next_bpos;
+            if self.process_original_msg(msg, frame_offset, fixed_note_length) {
+                bpos = next_bpos;
+            } else {
+                event_list.delete_item(bpos);
+            }
+        }
+    }
+
+    /// Returns whether the message should be kept.
+    fn process_original_msg(
+        &mut self,
+        msg: &impl ShortMessage,
+        frame_offset: usize,
+        fixed_note_length: Option<usize>,
+    ) -> bool {
+        match msg.to_structured() {
+            StructuredShortMessage::NoteOn {
+                channel,
+                key_number,
+                velocity,
+            } if velocity.get() > 0 => {
+                if !self.transformation.lets_note_pass(key_number.get()) {
+                    return false;
+                }
+                let output_channel = self.output_channel(channel);
+                self.active_notes[channel.get() as usize][key_number.get() as usize] =
+                    Some(output_channel);
+                if let Some(length) = fixed_note_length {
+                    let pending = &mut self.pending_note_offs[channel.get() as usize]
+                        [key_number.get() as usize];
+                    if pending.is_none() {
+                        self.pending_note_off_count += 1;
+                    }
+                    *pending = Some(PendingNoteOff {
+                        remaining_frames: frame_offset + length,
+                        channel: output_channel,
+                    });
+                }
+                true
+            }
+            StructuredShortMessage::NoteOn {
+                channel,
+                key_number,
+                ..
+            }
+            | StructuredShortMessage::NoteOff {
+                channel,
+                key_number,
+                ..
+            } => {
+                if !self.transformation.lets_note_pass(key_number.get()) {
+                    return false;
+                }
+                // If we are going to end the note ourselves, the original note-off must go.
+                let pending_note_off =
+                    self.pending_note_offs[channel.get() as usize][key_number.get() as usize];
+                if pending_note_off.is_some() {
+                    return false;
+                }
+                self.active_notes[channel.get() as usize][key_number.get() as usize] = None;
+                true
+            }
+            _ => true,
+        }
+    }
+
+    /// Applies velocity and channel changes to new events.
+    fn transform_msgs(&self, event_list: &mut BorrowedMidiEventList, first_new_bpos: u32) {
+        // The list can only be mutated via its iterator, so we identify the first new event by
+        // address instead of counting the events in front of it.
+        let first_new_event: *const _ = match event_list.enum_items(first_new_bpos) {
+            None => return,
+            Some(res) => res.midi_event,
+        };
+        let new_events = event_list
+            .iter_mut()
+            .skip_while(|event| !ptr::eq(&**event, first_new_event));
+        for event in new_events {
+            if let Some(transformed_msg) = self.transform_msg(event.message()) {
+                event.set_message(transformed_msg);
+            }
+        }
+    }
+
+    fn transform_msg(&self, msg: &impl ShortMessage) -> Option<RawShortMessage> {
+        let new_msg = match msg.to_structured() {
+            StructuredShortMessage::NoteOn {
+                channel,
+                key_number,
+                velocity,
+            } if velocity.get() > 0 => {
+                let new_velocity = self.transformation.transform_velocity(velocity.get());
+                RawShortMessage::note_on(
+                    self.output_channel(channel),
+                    key_number,
+                    U7::new(new_velocity),
+                )
+            }
+            _ => {
+                let channel = msg.channel()?;
+                let output_channel = self.output_channel(channel);
+                if output_channel == channel {
+                    return None;
+                }
+                let status_byte = (msg.status_byte() & 0xf0) | output_channel.get();
+                RawShortMessage::from_bytes((status_byte, msg.data_byte_1(), msg.data_byte_2()))
+                    .ok()?
+            }
+        };
+        Some(new_msg)
+    }
+
+    fn output_channel(&self, channel: Channel) -> Channel {
+        output_channel(&self.transformation, channel)
+    }
+
+    /// Sends the note-offs which are due within the current block.
+    fn send_due_note_offs(&mut self, event_list: &mut BorrowedMidiEventList, frame_count: usize) {
+        if self.pending_note_off_count == 0 {
+            return;
+        }
+        for (channel_index, keys) in self.pending_note_offs.iter_mut().enumerate() {
+            for (key, pending) in keys.iter_mut().enumerate() {
+                let p = match pending {
+                    None => continue,
+                    Some(p) => p,
+                };
+                if p.remaining_frames < frame_count {
+                    let msg =
+                        RawShortMessage::note_off(p.channel, KeyNumber::new(key as u8), U7::MIN);
+                    add_midi_event(
+                        event_list,
+                        MidiFrameOffset::new(p.remaining_frames as _),
+                        msg,
+                    );
+                    *pending = None;
+                    self.pending_note_off_count -= 1;
+                    self.active_notes[channel_index][key] = None;
+                } else {
+                    p.remaining_frames -= frame_count;
+                }
+            }
+        }
+    }
+
+    /// Sends all pending note-offs right now.
+    fn send_pending_note_offs(
+        &mut self,
+        event_list: &mut BorrowedMidiEventList,
+        frame_offset: MidiFrameOffset,
+    ) {
+        self.active_notes = [[None; 128]; 16];
+        if self.pending_note_off_count == 0 {
+            return;
+        }
+        for (key, pending) in self
+            .pending_note_offs
+            .iter_mut()
+            .flat_map(|keys| keys.iter_mut().enumerate())
+        {
+            if let Some(p) = pending.take() {
+                let msg = RawShortMessage::note_off(p.channel, KeyNumber::new(key as u8), U7::MIN);
+                add_midi_event(event_list, frame_offset, msg);
+            }
+        }
+        self.pending_note_off_count = 0;
+    }
+}
+
+fn output_channel(transformation: &MidiTransformation, channel: Channel) -> Channel {
+    match transformation.output_channel {
+        None => channel,
+        Some(ch) => Channel::new(ch.min(15)),
+    }
+}
+
+impl<S: MidiSupplier> MidiSupplier for MidiTransformer<S> {
+    fn supply_midi(
+        &mut self,
+        request: &SupplyMidiRequest,
+        event_list: &mut BorrowedMidiEventList,
+    ) -> SupplyResponse {
+        // Even if the transformation is neutral, we need to keep track of the playing notes, so
+        // that we can end them correctly when the transformation changes.
+        // The event list might already contain events from previous suppliers. We must leave them
+        // alone.
+        let first_new_bpos = event_list.get_size();
+        let response = self.supplier.supply_midi(request, event_list);
+        let fixed_note_length = self.transformation.fixed_note_length.map(|l| {
+            let seconds = l.get() * 60.0 / MIDI_BASE_BPM.get();
+            convert_duration_in_seconds_to_frames(
+                DurationInSeconds::new(seconds),
+                request.dest_sample_rate,
+            )
+        });
+        self.filter_and_schedule(event_list, first_new_bpos, fixed_note_length);
+        if !self.transformation.is_neutral() {
+            self.transform_msgs(event_list, first_new_bpos);
+        }
+        self.send_due_note_offs(event_list, request.dest_frame_count);
+        response
+    }
+
+    fn release_notes(
+        &mut self,
+        frame_offset: MidiFrameOffset,
+        event_list: &mut BorrowedMidiEventList,
+    ) {
+        let first_new_bpos = event_list.get_size();
+        self.supplier.release_notes(frame_offset, event_list);
+        self.transform_msgs(event_list, first_new_bpos);
+        self.send_pending_note_offs(event_list, frame_offset);
+    }
+}
+
+impl<S: AudioSupplier> AudioSupplier for MidiTransformer<S> {
+    fn supply_audio(
+        &mut self,
+        request: &SupplyAudioRequest,
+        dest_buffer: &mut AudioBufMut,
+    ) -> SupplyResponse {
+        self.supplier.supply_audio(request, dest_buffer)
+    }
+}
+
+impl<S: PreBufferSourceSkill> PreBufferSourceSkill for MidiTransformer<S> {
+    fn pre_buffer(&mut self, request: PreBufferFillRequest) {
+        self.supplier.pre_buffer(request);
+    }
+}
+
+impl<S: PositionTranslationSkill> PositionTranslationSkill for MidiTransformer<S> {
+    fn translate_play_pos_to_source_pos(&self, play_pos: isize) -> isize {
+        self.supplier.translate_play_pos_to_source_pos(play_pos)
+    }
+}
+
+impl<S: WithMaterialInfo> WithMaterialInfo for MidiTransformer<S> {
+    fn material_info(&self) -> ClipEngineResult<MaterialInfo> {
+        self.supplier.material_info()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use playtime_api::persistence::MidiNoteRange;
+
+    fn note_on(key_number: u8) -> RawShortMessage {
+        RawShortMessage::note_on(Channel::new(0), KeyNumber::new(key_number), U7::new(100))
+    }
+
+    fn note_off(key_number: u8) -> RawShortMessage {
+        RawShortMessage::note_off(Channel::new(0), KeyNumber::new(key_number), U7::MIN)
+    }
+
+    #[test]
+    fn end_playing_notes_when_output_channel_changes() {
+        // Given
+        let mut transformer = MidiTransformer::new(());
+        assert!(transformer.process_original_msg(&note_on(60), 0, None));
+        // When
+        transformer.set_transformation(MidiTransformation {
+            output_channel: Some(5),
+            ..Default::default()
+        });
+        // Then
+        let pending_note_off = transformer.pending_note_offs[0][60].unwrap();
+        assert_eq!(pending_note_off.channel, Channel::new(0));
+        assert_eq!(pending_note_off.remaining_frames, 0);
+        assert_eq!(transformer.pending_note_off_count, 1);
+    }
+
+    #[test]
+    fn end_playing_notes_which_leave_note_range() {
+        // Given
+        let mut transformer = MidiTransformer::new(());
+        assert!(transformer.process_original_msg(&note_on(50), 0, None));
+        assert!(transformer.process_original_msg(&note_on(60), 0, None));
+        // When
+        transformer.set_transformation(MidiTransformation {
+            note_range: Some(MidiNoteRange { min: 0, max: 55 }),
+            ..Default::default()
+        });
+        // Then
+        assert!(transformer.pending_note_offs[0][50].is_none());
+        assert!(transformer.pending_note_offs[0][60].is_some());
+        assert_eq!(transformer.pending_note_off_count, 1);
+    }
+
+    #[test]
+    fn keep_playing_notes_if_routing_stays_the_same() {
+        // Given
+        let mut transformer = MidiTransformer::new(());
+        assert!(transformer.process_original_msg(&note_on(60), 0, None));
+        // When
+        transformer.set_transformation(MidiTransformation {
+            velocity_scale: 0.5,
+            ..Default::default()
+        });
+        // Then
+        assert_eq!(transformer.pending_note_off_count, 0);
+    }
+
+    #[test]
+    fn dont_end_notes_which_already_ended() {
+        // Given
+        let mut transformer = MidiTransformer::new(());
+        assert!(transformer.process_original_msg(&note_on(60), 0, None));
+        assert!(transformer.process_original_msg(&note_off(60), 10, None));
+        // When
+        transformer.set_transformation(MidiTransformation {
+            output_channel: Some(5),
+            ..Default::default()
+        });
+        // Then
+        assert_eq!(transformer.pending_note_off_count, 0);
+    }
+}
@@ -62,7 +62,7 @@ pub enum SilenceMidiBlockMode {
     Append,
 }
 
-fn add_midi_event(
+pub fn add_midi_event(
     event_list: &mut BorrowedMidiEventList,
     frame_offset: MidiFrameOffset,
     msg: RawShortMessage,
@@ -34,11 +34,28 @@ pub use amplifier::*;
 mod section;
 pub use section::*;
 
+mod reverser;
+pub use reverser::*;
+
+mod canvas;
+pub use canvas::*;
+
+mod in_memory_source;
+pub use in_memory_source::*;
+
+mod headless;
+pub use headless::*;
+
 mod downbeat;
 pub use downbeat::*;
 
+mod midi_transformer;
+pub use midi_transformer::*;
+
 mod fade_util;
 
+mod bounds_util;
+
 mod midi_util;
 
 mod api;
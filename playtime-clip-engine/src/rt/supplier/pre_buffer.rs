@@ -1,8 +1,9 @@
 use crate::rt::buffer::{AudioBufMut, OwnedAudioBuffer};
 use crate::rt::supplier::{
     AudioMaterialInfo, AudioSupplier, MaterialInfo, MidiSupplier, PositionTranslationSkill,
-    PreBufferFillRequest, PreBufferSourceSkill, SupplyAudioRequest, SupplyMidiRequest,
-    SupplyRequestInfo, SupplyResponse, SupplyResponseStatus, WithMaterialInfo,
+    PreBufferFillRequest, PreBufferSourceSkill, PreStretchParams, PreStretchSkill,
+    SupplyAudioRequest, SupplyMidiRequest, SupplyRequestInfo, SupplyResponse, SupplyResponseStatus,
+    TimeStretcher, WithMaterialInfo,
 };
 use crate::ClipEngineResult;
 use core::cmp;
@@ -55,6 +56,291 @@ impl State {
 struct ActiveState {
     consumer: Consumer<PreBufferedBlock>,
     cached_material_info: AudioMaterialInfo,
+    pre_stretch_state: PreStretchState,
+}
+
+/// Consumer side of the material which the worker stretches in advance.
+///
+/// In contrast to normal pre-buffered blocks, stretched blocks contain a different number of
+/// frames than they consumed from the source. That's why they are not matched by position only
+/// but form a continuous stream which is only valid for one set of stretch parameters.
+#[derive(Debug)]
+struct PreStretchState {
+    consumer: Consumer<PreBufferedBlock>,
+    stream: Option<PreStretchStream>,
+    /// Incremented whenever the worker is asked to start a new stream.
+    generation: usize,
+}
+
+#[derive(Debug)]
+struct PreStretchStream {
+    params: PreStretchParams,
+    generation: usize,
+    /// Source frame at which the next block of the stream starts.
+    next_block_start_frame: isize,
+    /// Exact position within the next block, if the previous request stopped in the middle of it.
+    ///
+    /// Continuing from here instead of mapping the source position to a block position avoids
+    /// rounding errors.
+    cursor: Option<PreStretchCursor>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct PreStretchCursor {
+    /// Source frame which the next request is expected to start at.
+    start_frame: isize,
+    /// Frame offset within the stretched block.
+    block_frame_offset: usize,
+}
+
+struct PreStretchCopyOutcome {
+    num_frames_written: usize,
+    num_exhausted_blocks: usize,
+    /// Frame offset within the last used block after copying.
+    block_frame_offset: usize,
+    reached_end: bool,
+    /// Source frame which corresponds to the end of the copied material.
+    end_frame: isize,
+}
+
+enum PreStretchMiss {
+    /// Material is not there yet but the worker is on the right track.
+    Wait,
+    /// The worker produces the wrong material and needs to start over.
+    Restart,
+}
+
+impl PreStretchState {
+    pub fn pre_stretch<S, C>(
+        &mut self,
+        start_frame: isize,
+        params: PreStretchParams,
+        instance_id: PreBufferInstanceId,
+        request_sender: &Sender<PreBufferRequest<S, C>>,
+    ) {
+        self.generation += 1;
+        let stream = PreStretchStream {
+            params,
+            generation: self.generation,
+            next_block_start_frame: start_frame,
+            cursor: None,
+        };
+        self.stream = Some(stream);
+        let args = PreStretchFillRequest {
+            start_frame,
+            params,
+            generation: self.generation,
+        };
+        request_sender.keep_stretching(instance_id, args);
+        self.recycle_next_n_blocks(self.consumer.slots(), request_sender);
+    }
+
+    pub fn supply_pre_stretched_audio<S, C>(
+        &mut self,
+        request: &SupplyAudioRequest,
+        dest_buffer: &mut AudioBufMut,
+        params: PreStretchParams,
+        instance_id: PreBufferInstanceId,
+        request_sender: &Sender<PreBufferRequest<S, C>>,
+    ) -> Option<SupplyResponse> {
+        let stream_is_suitable = self
+            .stream
+            .as_ref()
+            .map(|s| s.params.is_compatible_with(&params))
+            .unwrap_or(false);
+        if request.start_frame < 0 {
+            // Count-in phase. We leave this to live stretching but make sure that stretched
+            // material is ready as soon as the actual material starts.
+            if !stream_is_suitable {
+                self.pre_stretch(0, params, instance_id, request_sender);
+            }
+            return None;
+        }
+        if !stream_is_suitable {
+            // Tempo factor, pitch or mode changed. Pre-stretched material is useless.
+            self.pre_stretch(request.start_frame, params, instance_id, request_sender);
+            return None;
+        }
+        match self.use_stream(request, dest_buffer, request_sender) {
+            Ok(response) => Some(response),
+            Err(PreStretchMiss::Wait) => None,
+            Err(PreStretchMiss::Restart) => {
+                debug!("Pre-stretched stream doesn't match position. Restarting.");
+                self.pre_stretch(request.start_frame, params, instance_id, request_sender);
+                None
+            }
+        }
+    }
+
+    fn use_stream<S, C>(
+        &mut self,
+        request: &SupplyAudioRequest,
+        dest_buffer: &mut AudioBufMut,
+        request_sender: &Sender<PreBufferRequest<S, C>>,
+    ) -> Result<SupplyResponse, PreStretchMiss> {
+        let stream = self.stream.as_mut().ok_or(PreStretchMiss::Restart)?;
+        // Get rid of blocks which belong to an old stream or contain past material only.
+        let block_frame_offset = loop {
+            let block = match self.consumer.peek() {
+                Ok(b) => b,
+                Err(_) => {
+                    let too_far_behind = request.start_frame - stream.next_block_start_frame
+                        > (PRE_BUFFERED_BLOCK_LENGTH as f64 * stream.params.tempo_factor) as isize;
+                    return if request.start_frame < stream.next_block_start_frame || too_far_behind
+                    {
+                        Err(PreStretchMiss::Restart)
+                    } else {
+                        Err(PreStretchMiss::Wait)
+                    };
+                }
+            };
+            if block.generation != stream.generation {
+                let block = self.consumer.pop().map_err(|_| PreStretchMiss::Restart)?;
+                request_sender.recycle_block(block);
+                continue;
+            }
+            if let Some(cursor) = stream.cursor {
+                if cursor.start_frame == request.start_frame {
+                    // Happy path. Continue exactly where we stopped.
+                    break cursor.block_frame_offset;
+                }
+            }
+            if request.start_frame < block.start_frame {
+                return Err(PreStretchMiss::Restart);
+            }
+            let source_frame_offset = (request.start_frame - block.start_frame) as usize;
+            if source_frame_offset >= block.response.num_frames_consumed {
+                // Block contains only past material.
+                stream.next_block_start_frame =
+                    calculate_next_reasonable_frame(block.start_frame, &block.response);
+                stream.cursor = None;
+                let block = self.consumer.pop().map_err(|_| PreStretchMiss::Restart)?;
+                request_sender.recycle_block(block);
+                continue;
+            }
+            // Jump into the block.
+            break block.stretched_frame_offset(source_frame_offset);
+        };
+        let dest_frame_count = dest_buffer.frame_count();
+        let copy_outcome = {
+            // Make sure that the available blocks are sufficient to satisfy the complete request.
+            let read_chunk = self
+                .consumer
+                .read_chunk(self.consumer.slots())
+                .map_err(|_| PreStretchMiss::Wait)?;
+            let (slice_one, slice_two) = read_chunk.as_slices();
+            let generation = stream.generation;
+            let blocks = || {
+                slice_one
+                    .iter()
+                    .chain(slice_two.iter())
+                    .take_while(|b| b.generation == generation)
+            };
+            let mut num_available_frames = 0;
+            let mut offset = block_frame_offset;
+            let mut contains_end = false;
+            for block in blocks() {
+                num_available_frames += block.stretched_frame_count() - offset;
+                offset = 0;
+                if block.response.status.reached_end() {
+                    contains_end = true;
+                    break;
+                }
+                if num_available_frames >= dest_frame_count {
+                    break;
+                }
+            }
+            if num_available_frames < dest_frame_count && !contains_end {
+                return Err(PreStretchMiss::Wait);
+            }
+            // Copy the stretched material.
+            let mut outcome = PreStretchCopyOutcome {
+                num_frames_written: 0,
+                num_exhausted_blocks: 0,
+                block_frame_offset,
+                reached_end: false,
+                end_frame: request.start_frame,
+            };
+            for block in blocks() {
+                let block_frame_count = block.stretched_frame_count();
+                let num_frames_to_copy = cmp::min(
+                    dest_frame_count - outcome.num_frames_written,
+                    block_frame_count - outcome.block_frame_offset,
+                );
+                let range =
+                    outcome.block_frame_offset..outcome.block_frame_offset + num_frames_to_copy;
+                let dest_range =
+                    outcome.num_frames_written..outcome.num_frames_written + num_frames_to_copy;
+                let mut sliced_dest_buffer = dest_buffer.slice_mut(dest_range);
+                block
+                    .buffer
+                    .to_buf()
+                    .slice(range.clone())
+                    .copy_to(&mut sliced_dest_buffer);
+                outcome.num_frames_written += num_frames_to_copy;
+                outcome.end_frame =
+                    block.start_frame + block.source_frame_offset(range.end) as isize;
+                outcome.block_frame_offset = range.end;
+                if range.end == block_frame_count {
+                    outcome.num_exhausted_blocks += 1;
+                    outcome.block_frame_offset = 0;
+                    if block.response.status.reached_end() {
+                        outcome.reached_end = true;
+                        break;
+                    }
+                }
+                if outcome.num_frames_written == dest_frame_count {
+                    break;
+                }
+            }
+            outcome
+        };
+        // Recycle exhausted blocks and remember where we are.
+        let exhausted_blocks = self
+            .consumer
+            .read_chunk(copy_outcome.num_exhausted_blocks)
+            .map_err(|_| PreStretchMiss::Restart)?;
+        for block in exhausted_blocks.into_iter() {
+            stream.next_block_start_frame =
+                calculate_next_reasonable_frame(block.start_frame, &block.response);
+            request_sender.recycle_block(block);
+        }
+        let num_frames_consumed =
+            cmp::max(0, copy_outcome.end_frame - request.start_frame) as usize;
+        let response = if copy_outcome.reached_end {
+            stream.cursor = None;
+            SupplyResponse {
+                num_frames_consumed,
+                status: SupplyResponseStatus::ReachedEnd {
+                    num_frames_written: copy_outcome.num_frames_written,
+                },
+            }
+        } else {
+            stream.cursor = Some(PreStretchCursor {
+                start_frame: copy_outcome.end_frame,
+                block_frame_offset: copy_outcome.block_frame_offset,
+            });
+            SupplyResponse::please_continue(num_frames_consumed)
+        };
+        Ok(response)
+    }
+
+    pub fn recycle_next_n_blocks<S, C>(
+        &mut self,
+        count: usize,
+        request_sender: &Sender<PreBufferRequest<S, C>>,
+    ) {
+        let chunk = match self.consumer.read_chunk(count) {
+            Ok(c) => c,
+            Err(_) => {
+                debug!("Couldn't recycle {} pre-buffered block(s).", count);
+                return;
+            }
+        };
+        for block in chunk.into_iter() {
+            request_sender.recycle_block(block);
+        }
+    }
 }
 
 impl ActiveState {
@@ -108,7 +394,10 @@ impl ActiveState {
             Ok(apply_outcome) => {
                 // Consume block if exhausted.
                 if apply_outcome.block_exhausted {
-                    let block = self.consumer.pop().unwrap();
+                    let block = self.consumer.pop().map_err(|_| StepFailure {
+                        frame_offset,
+                        non_matching_block_count: 0,
+                    })?;
                     request_sender.recycle_block(block);
                 }
                 let success = process_pre_buffered_response(
@@ -125,7 +414,10 @@ impl ActiveState {
                 // and consuming immediately would make the producer produce further probably
                 // unnecessary blocks. We defer consumption until we know what's going on.
                 let slots = self.consumer.slots();
-                let read_chunk = self.consumer.read_chunk(slots).unwrap();
+                let read_chunk = self.consumer.read_chunk(slots).map_err(|_| StepFailure {
+                    frame_offset,
+                    non_matching_block_count: 0,
+                })?;
                 let (slice_one, slice_two) = read_chunk.as_slices();
                 let outcome = slice_one
                     .iter()
@@ -195,7 +487,14 @@ impl ActiveState {
         count: usize,
         request_sender: &Sender<PreBufferRequest<S, C>>,
     ) {
-        for block in self.consumer.read_chunk(count).unwrap().into_iter() {
+        let chunk = match self.consumer.read_chunk(count) {
+            Ok(c) => c,
+            Err(_) => {
+                debug!("Couldn't recycle {} pre-buffered block(s).", count);
+                return;
+            }
+        };
+        for block in chunk.into_iter() {
             request_sender.recycle_block(block);
         }
     }
@@ -241,6 +540,7 @@ trait PreBufferSender {
         &self,
         id: PreBufferInstanceId,
         producer: Producer<PreBufferedBlock>,
+        pre_stretch_producer: Producer<PreBufferedBlock>,
         supplier: Self::Supplier,
     );
 
@@ -250,6 +550,8 @@ trait PreBufferSender {
 
     fn keep_filling(&self, id: PreBufferInstanceId, args: PreBufferFillRequest);
 
+    fn keep_stretching(&self, id: PreBufferInstanceId, args: PreStretchFillRequest);
+
     fn send_command(&self, id: PreBufferInstanceId, command: Self::Command);
 
     fn send_request(&self, request: PreBufferRequest<Self::Supplier, Self::Command>);
@@ -260,6 +562,8 @@ pub struct PreBufferedBlock {
     start_frame: isize,
     buffer: OwnedAudioBuffer,
     response: SupplyResponse,
+    /// Stream to which this block belongs. Only relevant for pre-stretched blocks.
+    generation: usize,
 }
 
 struct MatchCriteria {
@@ -286,6 +590,7 @@ pub enum PreBufferRequest<S, C> {
     RegisterInstance {
         id: PreBufferInstanceId,
         producer: Producer<PreBufferedBlock>,
+        pre_stretch_producer: Producer<PreBufferedBlock>,
         supplier: S,
     },
     UnregisterInstance(PreBufferInstanceId),
@@ -294,9 +599,20 @@ pub enum PreBufferRequest<S, C> {
         id: PreBufferInstanceId,
         args: crate::rt::supplier::PreBufferFillRequest,
     },
+    KeepStretchingFrom {
+        id: PreBufferInstanceId,
+        args: PreStretchFillRequest,
+    },
     SendCommand(PreBufferInstanceId, C),
 }
 
+#[derive(Clone, PartialEq, Debug)]
+pub struct PreStretchFillRequest {
+    start_frame: isize,
+    params: PreStretchParams,
+    generation: usize,
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display)]
 pub struct PreBufferInstanceId(usize);
 
@@ -360,6 +676,10 @@ where
         self.enabled = enabled;
     }
 
+    pub fn is_active(&self) -> bool {
+        self.state.is_active()
+    }
+
     /// # Errors
     ///
     /// Returns an error if the material can't or doesn't need to be buffered. In that case
@@ -368,16 +688,26 @@ where
         if !self.enabled {
             return Err("disabled");
         }
-        if !self.state.is_active() {
-            return Err("inactive");
+        if self.state.is_active() {
+            return Err("already active");
         }
         let audio_material_info = require_audio_material_info(self.supplier.material_info()?)?;
         let (producer, consumer) = RingBuffer::new(RING_BUFFER_BLOCK_COUNT);
-        self.request_sender
-            .register_instance(self.id, producer, self.supplier.clone());
+        let (pre_stretch_producer, pre_stretch_consumer) = RingBuffer::new(RING_BUFFER_BLOCK_COUNT);
+        self.request_sender.register_instance(
+            self.id,
+            producer,
+            pre_stretch_producer,
+            self.supplier.clone(),
+        );
         let enabled_state = ActiveState {
             consumer,
             cached_material_info: audio_material_info,
+            pre_stretch_state: PreStretchState {
+                consumer: pre_stretch_consumer,
+                stream: None,
+                generation: 0,
+            },
         };
         self.state = State::Active(enabled_state);
         Ok(())
@@ -455,6 +785,49 @@ where
     }
 }
 
+impl<S, F, C> PreStretchSkill for PreBuffer<S, F, C>
+where
+    S: AudioSupplier + Clone + Send + 'static,
+    F: Debug + CommandProcessor<Supplier = S, Command = C>,
+    C: Debug,
+{
+    fn pre_stretch(&mut self, start_frame: isize, params: PreStretchParams) {
+        if !self.enabled {
+            return;
+        }
+        match &mut self.state {
+            State::Inactive => {}
+            State::Active(s) => {
+                s.pre_stretch_state
+                    .pre_stretch(start_frame, params, self.id, &self.request_sender);
+            }
+        }
+    }
+
+    fn supply_pre_stretched_audio(
+        &mut self,
+        request: &SupplyAudioRequest,
+        dest_buffer: &mut AudioBufMut,
+        params: PreStretchParams,
+    ) -> Option<SupplyResponse> {
+        if !self.enabled {
+            return None;
+        }
+        match &mut self.state {
+            // Inactive means that the material is in memory and not stretched in advance (e.g.
+            // because audio is overdubbed). Live stretching is the way to go.
+            State::Inactive => None,
+            State::Active(s) => s.pre_stretch_state.supply_pre_stretched_audio(
+                request,
+                dest_buffer,
+                params,
+                self.id,
+                &self.request_sender,
+            ),
+        }
+    }
+}
+
 impl<S, F, C> PositionTranslationSkill for PreBuffer<S, F, C>
 where
     S: PositionTranslationSkill,
@@ -596,12 +969,14 @@ struct PreBufferWorker<S, F, C> {
     instances: HashMap<PreBufferInstanceId, Instance<S>, BuildHasherDefault<XxHash64>>,
     spare_buffer_chunks: Vec<Vec<f64>>,
     command_processor: F,
+    /// Creates the time stretchers which stretch material in advance.
+    create_time_stretcher: fn(S) -> TimeStretcher<S>,
     phantom: PhantomData<C>,
 }
 
 impl<S, F, C> PreBufferWorker<S, F, C>
 where
-    S: AudioSupplier + WithMaterialInfo,
+    S: AudioSupplier + WithMaterialInfo + Clone,
     F: CommandProcessor<Supplier = S, Command = C>,
 {
     pub fn process_request(&mut self, request: PreBufferRequest<S, C>) {
@@ -610,12 +985,18 @@ where
             RegisterInstance {
                 id,
                 producer,
+                pre_stretch_producer,
                 supplier,
             } => {
                 let instance = Instance {
                     producer,
                     supplier,
                     state: InstanceState::Initialized,
+                    stretching: StretchingInstance {
+                        producer: pre_stretch_producer,
+                        stretcher: None,
+                        state: None,
+                    },
                 };
                 self.register_instance(id, instance);
             }
@@ -628,6 +1009,9 @@ where
             KeepFillingFrom { id, args } => {
                 let _ = self.keep_filling_from(id, args);
             }
+            KeepStretchingFrom { id, args } => {
+                let _ = self.keep_stretching_from(id, args);
+            }
             SendCommand(id, command) => {
                 let instance = match self.instances.get(&id) {
                     None => return,
@@ -651,7 +1035,16 @@ where
                 })
                 .unwrap_or_else(|| OwnedAudioBuffer::new(channel_count, PRE_BUFFERED_BLOCK_LENGTH))
         };
-        self.instances.retain(|_, instance| {
+        self.instances.retain(|id, instance| {
+            match instance.fill_stretched(&mut get_spare_buffer) {
+                Ok(_) | Err(FillError::NotFilling | FillError::Full | FillError::ConsumerGone) => {}
+                Err(FillError::MaterialUnavailable) => {
+                    debug!(
+                        "Pre-stretching for instance {} failed: material unavailable",
+                        id
+                    );
+                }
+            }
             let outcome = instance.fill(&mut get_spare_buffer);
             // Unregister instance if consumer gone.
             !matches!(outcome, Err(FillError::ConsumerGone))
@@ -686,15 +1079,111 @@ where
         instance.state = InstanceState::Filling(filling_state);
         Ok(())
     }
+
+    fn keep_stretching_from(
+        &mut self,
+        id: PreBufferInstanceId,
+        args: PreStretchFillRequest,
+    ) -> ClipEngineResult<()> {
+        debug!("Pre-stretch request for instance {}: {:?}", id, &args);
+        let instance = self
+            .instances
+            .get_mut(&id)
+            .ok_or("instance doesn't exist")?;
+        let supplier = &instance.supplier;
+        let create_time_stretcher = self.create_time_stretcher;
+        let stretcher = instance.stretching.stretcher.get_or_insert_with(|| {
+            let mut stretcher = create_time_stretcher(supplier.clone());
+            stretcher.set_enabled(true);
+            stretcher.set_active(true);
+            stretcher.set_responsible_for_audio_time_stretching(true);
+            stretcher
+        });
+        stretcher.set_tempo_factor(args.params.tempo_factor);
+        stretcher.set_pitch(args.params.pitch);
+        stretcher.set_mode(args.params.mode);
+        stretcher.reset_buffers_and_latency();
+        let stretching_state = StretchingState {
+            next_start_frame: args.start_frame,
+            generation: args.generation,
+        };
+        instance.stretching.state = Some(stretching_state);
+        Ok(())
+    }
 }
 
 struct Instance<S> {
     producer: Producer<PreBufferedBlock>,
     supplier: S,
     state: InstanceState,
+    stretching: StretchingInstance<S>,
+}
+
+/// Worker side of the material which is stretched in advance.
+struct StretchingInstance<S> {
+    producer: Producer<PreBufferedBlock>,
+    /// Created as soon as stretching is requested for the first time.
+    stretcher: Option<TimeStretcher<S>>,
+    state: Option<StretchingState>,
+}
+
+struct StretchingState {
+    next_start_frame: isize,
+    generation: usize,
 }
 
 impl<S: AudioSupplier + WithMaterialInfo> Instance<S> {
+    pub fn fill_stretched(
+        &mut self,
+        mut get_spare_buffer: impl FnMut(usize) -> OwnedAudioBuffer,
+    ) -> Result<(), FillError> {
+        let stretching = &mut self.stretching;
+        if stretching.producer.is_abandoned() {
+            return Err(FillError::ConsumerGone);
+        }
+        if stretching.producer.is_full() {
+            return Err(FillError::Full);
+        }
+        let (state, stretcher) = match (&mut stretching.state, &mut stretching.stretcher) {
+            (Some(state), Some(stretcher)) => (state, stretcher),
+            _ => return Err(FillError::NotFilling),
+        };
+        let material_info = self
+            .supplier
+            .material_info()
+            .map_err(|_| FillError::MaterialUnavailable)?;
+        let mut buffer = get_spare_buffer(material_info.channel_count());
+        let request = SupplyAudioRequest {
+            start_frame: state.next_start_frame,
+            dest_sample_rate: None,
+            info: SupplyRequestInfo {
+                audio_block_frame_offset: 0,
+                requester: "pre-stretch",
+                note: "",
+                is_realtime: false,
+            },
+            parent_request: None,
+            general_info: &Default::default(),
+        };
+        let response = stretcher.stretch_audio(&request, &mut buffer.to_buf_mut());
+        if response.status.reached_end() {
+            // Next block starts over, so the buffered material is not relevant anymore.
+            stretcher.reset_buffers_and_latency();
+        }
+        let block = PreBufferedBlock {
+            start_frame: state.next_start_frame,
+            buffer,
+            response,
+            generation: state.generation,
+        };
+        state.next_start_frame = calculate_next_reasonable_frame(state.next_start_frame, &response);
+        stretching
+            .producer
+            .push(block)
+            .expect("ring buffer should not be full");
+        Ok(())
+    }
+
     pub fn fill(
         &mut self,
         mut get_spare_buffer: impl FnMut(usize) -> OwnedAudioBuffer,
@@ -735,6 +1224,7 @@ impl<S: AudioSupplier + WithMaterialInfo> Instance<S> {
             start_frame: state.next_start_frame,
             buffer,
             response,
+            generation: 0,
         };
         state.next_start_frame = calculate_next_reasonable_frame(state.next_start_frame, &response);
         // dbg!(&block);
@@ -774,12 +1264,13 @@ pub fn keep_processing_pre_buffer_requests<S, C>(
     receiver: Receiver<PreBufferRequest<S, C>>,
     command_processor: impl CommandProcessor<Supplier = S, Command = C>,
 ) where
-    S: AudioSupplier,
+    S: AudioSupplier + Clone,
 {
     let mut worker = PreBufferWorker {
         instances: Default::default(),
         spare_buffer_chunks: vec![],
         command_processor,
+        create_time_stretcher: TimeStretcher::new,
         phantom: PhantomData,
     };
     loop {
@@ -869,11 +1360,13 @@ impl<S, C> PreBufferSender for Sender<PreBufferRequest<S, C>> {
         &self,
         id: PreBufferInstanceId,
         producer: Producer<PreBufferedBlock>,
+        pre_stretch_producer: Producer<PreBufferedBlock>,
         supplier: Self::Supplier,
     ) {
         let request = PreBufferRequest::RegisterInstance {
             id,
             producer,
+            pre_stretch_producer,
             supplier,
         };
         self.send_request(request);
@@ -894,6 +1387,11 @@ impl<S, C> PreBufferSender for Sender<PreBufferRequest<S, C>> {
         self.send_request(request);
     }
 
+    fn keep_stretching(&self, id: PreBufferInstanceId, args: PreStretchFillRequest) {
+        let request = PreBufferRequest::KeepStretchingFrom { id, args };
+        self.send_request(request);
+    }
+
     fn send_command(&self, id: PreBufferInstanceId, command: Self::Command) {
         self.send_request(PreBufferRequest::SendCommand(id, command));
     }
@@ -903,6 +1401,33 @@ impl<S, C> PreBufferSender for Sender<PreBufferRequest<S, C>> {
     }
 }
 impl PreBufferedBlock {
+    /// Number of usable frames in this block.
+    ///
+    /// For pre-stretched blocks, this differs from the number of consumed source frames.
+    fn stretched_frame_count(&self) -> usize {
+        match self.response.status {
+            SupplyResponseStatus::PleaseContinue => self.buffer.to_buf().frame_count(),
+            SupplyResponseStatus::ReachedEnd { num_frames_written } => num_frames_written,
+        }
+    }
+
+    /// Maps a source frame offset to the corresponding frame offset in this pre-stretched block.
+    fn stretched_frame_offset(&self, source_frame_offset: usize) -> usize {
+        if self.response.num_frames_consumed == 0 {
+            return 0;
+        }
+        source_frame_offset * self.stretched_frame_count() / self.response.num_frames_consumed
+    }
+
+    /// Maps a frame offset in this pre-stretched block to the corresponding source frame offset.
+    fn source_frame_offset(&self, stretched_frame_offset: usize) -> usize {
+        let stretched_frame_count = self.stretched_frame_count();
+        if stretched_frame_count == 0 {
+            return self.response.num_frames_consumed;
+        }
+        stretched_frame_offset * self.response.num_frames_consumed / stretched_frame_count
+    }
+
     fn try_apply_to(
         &self,
         remaining_dest_buffer: &mut AudioBufMut,
@@ -1052,3 +1577,219 @@ fn query_supplier_for_remaining_portion<S: AudioSupplier>(
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rt::supplier::InMemoryAudioSource;
+    use playtime_api::persistence::{ClipPitch, VirtualTimeStretchMode};
+    use reaper_medium::Hz;
+
+    const SOURCE_FRAME_COUNT: usize = 480_000;
+    const BLOCK_LENGTH: usize = 512;
+
+    #[derive(Debug)]
+    struct NoopCommandProcessor;
+
+    impl CommandProcessor for NoopCommandProcessor {
+        type Supplier = InMemoryAudioSource;
+        type Command = ();
+
+        fn process_command(&self, _: (), _: &InMemoryAudioSource) {}
+    }
+
+    type TestPreBuffer = PreBuffer<InMemoryAudioSource, NoopCommandProcessor, ()>;
+    type TestRequest = PreBufferRequest<InMemoryAudioSource, ()>;
+    type TestWorker = PreBufferWorker<InMemoryAudioSource, NoopCommandProcessor, ()>;
+
+    enum Event {
+        Seek(isize),
+        ChangeTempo(f64),
+    }
+
+    fn ramp_source() -> InMemoryAudioSource {
+        let samples = (0..SOURCE_FRAME_COUNT)
+            .map(|i| i as f64 / SOURCE_FRAME_COUNT as f64)
+            .collect();
+        InMemoryAudioSource::from_samples(samples, 1, Hz::new(48000.0)).unwrap()
+    }
+
+    fn params(tempo_factor: f64) -> PreStretchParams {
+        PreStretchParams {
+            tempo_factor,
+            pitch: ClipPitch::default(),
+            mode: VirtualTimeStretchMode::ProjectDefault,
+        }
+    }
+
+    /// Lets the worker process all pending requests and fill the ring buffers.
+    fn run_worker(worker: &mut TestWorker, receiver: &Receiver<TestRequest>) {
+        while let Ok(request) = receiver.try_recv() {
+            worker.process_request(request);
+        }
+        for _ in 0..RING_BUFFER_BLOCK_COUNT {
+            worker.fill_all();
+        }
+    }
+
+    /// Plays the ramp source block by block, once using pre-stretched material and once stretching
+    /// live, and compares the output whenever pre-stretched material is available.
+    ///
+    /// Returns the number of blocks served from pre-stretched material.
+    fn compare_pre_stretched_with_live(
+        initial_tempo_factor: f64,
+        block_count: usize,
+        events: &[(usize, Event)],
+    ) -> usize {
+        let (request_sender, request_receiver) = crossbeam_channel::unbounded();
+        let mut worker = TestWorker {
+            instances: Default::default(),
+            spare_buffer_chunks: vec![],
+            command_processor: NoopCommandProcessor,
+            create_time_stretcher: TimeStretcher::headless,
+            phantom: PhantomData,
+        };
+        let options = PreBufferOptions {
+            skip_count_in_phase_material: true,
+            cache_miss_behavior: PreBufferCacheMissBehavior::OutputSilence,
+            recalibrate_on_cache_miss: false,
+        };
+        let mut pre_buffer: TestPreBuffer =
+            PreBuffer::new(ramp_source(), request_sender, options, NoopCommandProcessor);
+        pre_buffer.set_enabled(true);
+        pre_buffer.activate().unwrap();
+        let mut live_stretcher = TimeStretcher::headless(ramp_source());
+        live_stretcher.set_enabled(true);
+        live_stretcher.set_active(true);
+        live_stretcher.set_responsible_for_audio_time_stretching(true);
+        live_stretcher.set_tempo_factor(initial_tempo_factor);
+        let mut tempo_factor = initial_tempo_factor;
+        let mut start_frame = 0isize;
+        let mut pre_stretched_block_count = 0;
+        // A few frames of difference are okay. They come from mapping positions between source
+        // and stretched material.
+        let tolerance = 4.0 / SOURCE_FRAME_COUNT as f64;
+        for block_index in 0..block_count {
+            for (_, event) in events.iter().filter(|(i, _)| *i == block_index) {
+                match event {
+                    Event::Seek(frame) => {
+                        start_frame = *frame;
+                        live_stretcher.reset_buffers_and_latency();
+                    }
+                    Event::ChangeTempo(factor) => {
+                        tempo_factor = *factor;
+                        live_stretcher.set_tempo_factor(tempo_factor);
+                    }
+                }
+            }
+            let request = SupplyAudioRequest {
+                start_frame,
+                dest_sample_rate: None,
+                info: SupplyRequestInfo {
+                    audio_block_frame_offset: 0,
+                    requester: "test",
+                    note: "",
+                    is_realtime: false,
+                },
+                parent_request: None,
+                general_info: &Default::default(),
+            };
+            let mut live_buffer = OwnedAudioBuffer::new(1, BLOCK_LENGTH);
+            let live_response =
+                live_stretcher.stretch_audio(&request, &mut live_buffer.to_buf_mut());
+            let mut pre_stretched_buffer = OwnedAudioBuffer::new(1, BLOCK_LENGTH);
+            let pre_stretched_response = pre_buffer.supply_pre_stretched_audio(
+                &request,
+                &mut pre_stretched_buffer.to_buf_mut(),
+                params(tempo_factor),
+            );
+            let response = match pre_stretched_response {
+                None => live_response,
+                Some(response) => {
+                    pre_stretched_block_count += 1;
+                    let consumed_diff = response.num_frames_consumed as isize
+                        - live_response.num_frames_consumed as isize;
+                    assert!(consumed_diff.abs() <= 4, "block {}", block_index);
+                    let live_samples = live_buffer.to_buf().data_as_slice().to_vec();
+                    let pre_stretched_samples =
+                        pre_stretched_buffer.to_buf().data_as_slice().to_vec();
+                    for (frame, (pre_stretched, live)) in pre_stretched_samples
+                        .iter()
+                        .zip(live_samples.iter())
+                        .enumerate()
+                    {
+                        assert!(
+                            (pre_stretched - live).abs() <= tolerance * tempo_factor,
+                            "block {}, frame {}: pre-stretched {} != live {}",
+                            block_index,
+                            frame,
+                            pre_stretched,
+                            live
+                        );
+                    }
+                    response
+                }
+            };
+            start_frame = calculate_next_reasonable_frame(start_frame, &response);
+            run_worker(&mut worker, &request_receiver);
+        }
+        pre_stretched_block_count
+    }
+
+    #[test]
+    fn pre_stretched_material_matches_live_stretching() {
+        // Given
+        let block_count = 100;
+        // When
+        let pre_stretched_block_count = compare_pre_stretched_with_live(1.5, block_count, &[]);
+        // Then
+        // Only the first block needs to be stretched live.
+        assert_eq!(pre_stretched_block_count, block_count - 1);
+    }
+
+    #[test]
+    fn pre_stretched_material_matches_live_stretching_across_seeks() {
+        // Given
+        let block_count = 100;
+        let events = [
+            (20, Event::Seek(200_000)),
+            (40, Event::Seek(5_000)),
+            // Jump forward within the already stretched material.
+            (60, Event::Seek(23_000)),
+        ];
+        // When
+        let pre_stretched_block_count = compare_pre_stretched_with_live(1.5, block_count, &events);
+        // Then
+        // Each seek costs at most one live-stretched block.
+        assert!(pre_stretched_block_count >= block_count - 1 - events.len());
+    }
+
+    #[test]
+    fn pre_stretched_material_matches_live_stretching_across_tempo_changes() {
+        // Given
+        let block_count = 100;
+        let events = [
+            (30, Event::ChangeTempo(0.75)),
+            (60, Event::ChangeTempo(2.0)),
+        ];
+        // When
+        let pre_stretched_block_count = compare_pre_stretched_with_live(1.5, block_count, &events);
+        // Then
+        assert_eq!(pre_stretched_block_count, block_count - 1 - events.len());
+    }
+
+    #[test]
+    fn pre_stretched_material_survives_tiny_tempo_changes() {
+        // Given
+        let block_count = 100;
+        let events = [
+            (30, Event::ChangeTempo(1.5 + 1e-9)),
+            (60, Event::ChangeTempo(1.5 - 1e-9)),
+        ];
+        // When
+        let pre_stretched_block_count = compare_pre_stretched_with_live(1.5, block_count, &events);
+        // Then
+        // Rounding errors in the tempo factor don't make the worker start over.
+        assert_eq!(pre_stretched_block_count, block_count - 1);
+    }
+}
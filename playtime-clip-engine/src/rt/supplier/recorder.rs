@@ -321,6 +321,19 @@ impl Recorder {
         }
     }
 
+    /// Swaps the given source with the current one. Only possible when not recording.
+    ///
+    /// Okay to call in real-time thread.
+    pub fn replace_source(&mut self, source: &mut ClipSource) -> ClipEngineResult<()> {
+        match self.state.as_mut().unwrap() {
+            State::Ready(s) => {
+                mem::swap(&mut s.source, source);
+                Ok(())
+            }
+            State::Recording(_) => Err("can't replace source while recording"),
+        }
+    }
+
     pub fn recording(args: RecordingArgs, request_sender: Sender<RecorderRequest>) -> Self {
         let response_channel = ResponseChannel::new();
         let kind_state = KindState::new(args.equipment, &response_channel.sender);
@@ -1534,6 +1547,11 @@ fn write_midi(
     record_mode: MidiClipRecordMode,
     quantization_settings: Option<&QuantizationSettings>,
 ) {
+    let reaper_source = match source.reaper_source() {
+        Ok(s) => s,
+        // In-memory sources don't support recording
+        Err(_) => return,
+    };
     let global_time = convert_duration_in_frames_to_seconds(block_pos_frame, MIDI_FRAME_RATE);
     let overwrite_mode = match record_mode {
         MidiClipRecordMode::Normal => -1,
@@ -1588,7 +1606,7 @@ fn write_midi(
         }
     );
     unsafe {
-        source.reaper_source().extended(
+        reaper_source.extended(
             PCM_SOURCE_EXT_ADDMIDIEVENTS as _,
             &mut write_struct as *mut _ as _,
             quantize_mode_ptr,
@@ -22,13 +22,39 @@ pub struct Resampler<S> {
     tempo_adjustments_enabled: bool,
     responsible_for_audio_tempo_adjustments: bool,
     supplier: S,
-    api: OwnedReaperResample,
+    /// `None` if headless. Then we resample linearly.
+    api: Option<OwnedReaperResample>,
     tempo_factor: f64,
+    /// Used instead of the API if headless.
+    linear_interpolator: LinearInterpolator,
+}
+
+/// Changes the playback speed of audio material by interpolating linearly between neighboring
+/// source frames.
+///
+/// Doesn't need REAPER. Good enough for offline rendering and tests.
+#[derive(Debug, Default)]
+pub struct LinearInterpolator {
+    /// Position between two source frames at which the next block starts.
+    phase: f64,
+    /// Holds the source material.
+    buffer: Vec<f64>,
 }
 
 impl<S> Resampler<S> {
     pub fn new(supplier: S) -> Self {
         let api = Reaper::get().medium_reaper().resampler_create();
+        Self::with_api(supplier, Some(api))
+    }
+
+    /// Creates a resampler which doesn't need REAPER.
+    ///
+    /// It resamples audio linearly, which is good enough for offline rendering and tests.
+    pub fn headless(supplier: S) -> Self {
+        Self::with_api(supplier, None)
+    }
+
+    fn with_api(supplier: S, api: Option<OwnedReaperResample>) -> Self {
         Self {
             enabled: false,
             tempo_adjustments_enabled: false,
@@ -36,11 +62,15 @@ impl<S> Resampler<S> {
             supplier,
             api,
             tempo_factor: 1.0,
+            linear_interpolator: Default::default(),
         }
     }
 
     pub fn reset_buffers_and_latency(&mut self) {
-        self.api.as_mut().as_mut().Reset();
+        self.linear_interpolator.reset();
+        if let Some(api) = &mut self.api {
+            api.as_mut().as_mut().Reset();
+        }
     }
 
     pub fn supplier(&self) -> &S {
@@ -61,8 +91,12 @@ impl<S> Resampler<S> {
             ProjectDefault => -1,
             ReaperMode(m) => m.mode as i32,
         };
+        let api = match &mut self.api {
+            None => return,
+            Some(api) => api,
+        };
         unsafe {
-            self.api.as_mut().as_mut().Extended(
+            api.as_mut().as_mut().Extended(
                 raw::RESAMPLE_EXT_SETRSMODE,
                 raw_mode as *const c_void as *mut _,
                 null_mut(),
@@ -113,10 +147,23 @@ impl<S: AudioSupplier + WithMaterialInfo> AudioSupplier for Resampler<S> {
         if source_frame_rate == dest_frame_rate {
             return self.supplier.supply_audio(request, dest_buffer);
         }
+        let source_channel_count = material_info.channel_count();
+        let api = match &mut self.api {
+            None => {
+                let ratio = source_frame_rate.get() / dest_frame_rate.get();
+                return self.linear_interpolator.supply_audio(
+                    &mut self.supplier,
+                    request,
+                    dest_buffer,
+                    source_channel_count,
+                    ratio,
+                    "resampler-linear-audio",
+                );
+            }
+            Some(api) => api.as_mut().as_mut(),
+        };
         let mut total_num_frames_consumed = 0usize;
         let mut total_num_frames_written = 0usize;
-        let source_channel_count = material_info.channel_count();
-        let api = self.api.as_mut().as_mut();
         api.SetRates(source_frame_rate.get(), dest_frame_rate.get());
         // Set ResamplePrepare's out_samples to refer to request a specific number of input samples.
         // const RESAMPLE_EXT_SETFEEDMODE: i32 = 0x1001;
@@ -200,6 +247,85 @@ impl<S: AudioSupplier + WithMaterialInfo> AudioSupplier for Resampler<S> {
     }
 }
 
+impl LinearInterpolator {
+    /// Makes the next block start exactly at the requested source frame.
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+
+    /// Writes the material of the supplier into the destination buffer, playing it `ratio` times
+    /// as fast (`ratio` is the number of source frames per destination frame).
+    pub fn supply_audio<S: AudioSupplier>(
+        &mut self,
+        supplier: &mut S,
+        request: &SupplyAudioRequest,
+        dest_buffer: &mut AudioBufMut,
+        channel_count: usize,
+        ratio: f64,
+        requester: &'static str,
+    ) -> SupplyResponse {
+        let dest_frame_count = dest_buffer.frame_count();
+        if dest_frame_count == 0 {
+            return SupplyResponse::please_continue(0);
+        }
+        let phase = self.phase;
+        let source_pos_at = |dest_frame: usize| phase + dest_frame as f64 * ratio;
+        // We need the frame after the last interpolation position as well.
+        let source_frame_count = source_pos_at(dest_frame_count - 1) as usize + 2;
+        self.buffer.clear();
+        self.buffer.resize(source_frame_count * channel_count, 0.0);
+        let mut source_buffer =
+            AudioBufMut::from_slice(&mut self.buffer, channel_count, source_frame_count).unwrap();
+        let inner_request = SupplyAudioRequest {
+            start_frame: request.start_frame,
+            dest_sample_rate: None,
+            info: SupplyRequestInfo {
+                audio_block_frame_offset: request.info.audio_block_frame_offset,
+                requester,
+                note: "",
+                is_realtime: false,
+            },
+            parent_request: Some(request),
+            general_info: request.general_info,
+        };
+        let inner_response = supplier.supply_audio(&inner_request, &mut source_buffer);
+        let num_source_frames_available = match inner_response.status {
+            SupplyResponseStatus::PleaseContinue => source_frame_count,
+            SupplyResponseStatus::ReachedEnd { num_frames_written } => num_frames_written,
+        };
+        let source_samples = &self.buffer;
+        let dest_samples = dest_buffer.data_as_mut_slice();
+        let mut num_frames_written = 0;
+        for dest_frame in 0..dest_frame_count {
+            let source_pos = source_pos_at(dest_frame);
+            let left_frame = source_pos as usize;
+            if left_frame >= num_source_frames_available {
+                break;
+            }
+            let right_frame = left_frame + 1;
+            let weight = source_pos - left_frame as f64;
+            for ch in 0..channel_count {
+                let left = source_samples[left_frame * channel_count + ch];
+                let right = if right_frame < num_source_frames_available {
+                    source_samples[right_frame * channel_count + ch]
+                } else {
+                    0.0
+                };
+                dest_samples[dest_frame * channel_count + ch] = left + (right - left) * weight;
+            }
+            num_frames_written += 1;
+        }
+        let end_pos = source_pos_at(dest_frame_count);
+        let num_frames_consumed = end_pos as usize;
+        self.phase = end_pos - num_frames_consumed as f64;
+        if inner_response.status.reached_end() && num_frames_consumed >= num_source_frames_available
+        {
+            return SupplyResponse::reached_end(num_source_frames_available, num_frames_written);
+        }
+        SupplyResponse::please_continue(num_frames_consumed)
+    }
+}
+
 impl<S: WithMaterialInfo> WithMaterialInfo for Resampler<S> {
     fn material_info(&self) -> ClipEngineResult<MaterialInfo> {
         self.supplier.material_info()
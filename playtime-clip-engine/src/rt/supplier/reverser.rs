@@ -0,0 +1,462 @@
+use crate::rt::buffer::AudioBufMut;
+use crate::rt::supplier::{
+    AudioSupplier, MaterialInfo, MidiSupplier, PositionTranslationSkill, SupplyAudioRequest,
+    SupplyMidiRequest, SupplyRequestInfo, SupplyResponse, WithMaterialInfo,
+};
+use crate::ClipEngineResult;
+use helgoboss_midi::{
+    Channel, KeyNumber, RawShortMessage, ShortMessage, ShortMessageFactory, StructuredShortMessage,
+    U7,
+};
+use reaper_medium::{BorrowedMidiEventList, MidiEvent, MidiFrameOffset, MidiMessage};
+use std::cmp;
+
+/// Plays the material backwards.
+///
+/// Blocks are read from the end of the material towards its start and then reversed. With MIDI,
+/// events are mirrored within the block and note-ons and note-offs are swapped.
+#[derive(Debug)]
+pub struct Reverser<S> {
+    supplier: S,
+    enabled: bool,
+    /// Velocity of the most recent note-on per channel and key. When playing backwards, a note
+    /// starts at its original note-off, so we don't know its original velocity yet at that point.
+    note_velocities: [[u8; 128]; 16],
+    /// Notes which are currently playing in the reversed output (one bit per key).
+    on_notes: [u128; 16],
+    /// Reused for reversing the order of the short messages supplied in one block.
+    ///
+    /// Never grows beyond [`MAX_EVENT_COUNT_PER_BLOCK`], so it doesn't reallocate.
+    reversed_events: Vec<(MidiFrameOffset, RawShortMessage)>,
+}
+
+const DEFAULT_VELOCITY: u8 = 100;
+
+/// Maximum number of short messages which can be reversed within one block. Further ones are
+/// dropped.
+const MAX_EVENT_COUNT_PER_BLOCK: usize = 1000;
+
+impl<S> Reverser<S> {
+    pub fn new(supplier: S) -> Self {
+        Self {
+            supplier,
+            enabled: false,
+            note_velocities: [[DEFAULT_VELOCITY; 128]; 16],
+            on_notes: [0; 16],
+            reversed_events: Vec::with_capacity(MAX_EVENT_COUNT_PER_BLOCK),
+        }
+    }
+
+    pub fn supplier(&self) -> &S {
+        &self.supplier
+    }
+
+    pub fn supplier_mut(&mut self) -> &mut S {
+        &mut self.supplier
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn reversed_range(&self, start_frame: isize, frame_count: usize) -> Option<ReversedRange>
+    where
+        S: WithMaterialInfo,
+    {
+        let material_frame_count = self.supplier.material_info().ok()?.frame_count();
+        let end_frame = start_frame + frame_count as isize;
+        if end_frame <= 0 || start_frame >= material_frame_count as isize {
+            // Pure count-in phase or exceeded end. Let the supplier handle it as usual.
+            return None;
+        }
+        let count_in_frame_count = (-start_frame).max(0) as usize;
+        let start_frame_in_material = start_frame.max(0) as usize;
+        let end_frame_in_material = cmp::min(end_frame as usize, material_frame_count);
+        let range = ReversedRange {
+            count_in_frame_count,
+            inner_start_frame: (material_frame_count - end_frame_in_material) as isize,
+            inner_frame_count: end_frame_in_material - start_frame_in_material,
+            material_frame_count,
+        };
+        Some(range)
+    }
+
+    /// Memorizes the given event of the current block at its mirrored position, with note-on and
+    /// note-off swapped. Events must be passed in their original order.
+    ///
+    /// Returns `false` if the maximum number of events per block is reached. Then the event must
+    /// be dropped.
+    fn memorize_reversed_event(
+        &mut self,
+        frame_offset: usize,
+        msg: RawShortMessage,
+        range: &ReversedRange,
+    ) -> bool {
+        if self.reversed_events.len() >= MAX_EVENT_COUNT_PER_BLOCK {
+            return false;
+        }
+        let mirrored_offset =
+            range.count_in_frame_count + range.inner_frame_count.saturating_sub(frame_offset + 1);
+        let msg = self.swap_note_on_off(&msg).unwrap_or(msg);
+        self.reversed_events
+            .push((MidiFrameOffset::new(mirrored_offset as _), msg));
+        true
+    }
+
+    /// Brings the memorized events into the reversed order and updates the playing notes.
+    fn finish_reversal(&mut self) {
+        self.reversed_events.reverse();
+        for (_, msg) in &self.reversed_events {
+            update_on_notes(&mut self.on_notes, msg);
+        }
+    }
+
+    /// Turns note-ons into note-offs and vice versa.
+    fn swap_note_on_off(&mut self, msg: &impl ShortMessage) -> Option<RawShortMessage> {
+        match msg.to_structured() {
+            StructuredShortMessage::NoteOn {
+                channel,
+                key_number,
+                velocity,
+            } if velocity.get() > 0 => {
+                self.note_velocities[channel.get() as usize][key_number.get() as usize] =
+                    velocity.get();
+                Some(RawShortMessage::note_off(channel, key_number, U7::MIN))
+            }
+            StructuredShortMessage::NoteOn {
+                channel,
+                key_number,
+                ..
+            }
+            | StructuredShortMessage::NoteOff {
+                channel,
+                key_number,
+                ..
+            } => {
+                let velocity =
+                    self.note_velocities[channel.get() as usize][key_number.get() as usize];
+                Some(RawShortMessage::note_on(
+                    channel,
+                    key_number,
+                    U7::new(velocity),
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<S: AudioSupplier + WithMaterialInfo> AudioSupplier for Reverser<S> {
+    fn supply_audio(
+        &mut self,
+        request: &SupplyAudioRequest,
+        dest_buffer: &mut AudioBufMut,
+    ) -> SupplyResponse {
+        if !self.enabled {
+            return self.supplier.supply_audio(request, dest_buffer);
+        }
+        let range = match self.reversed_range(request.start_frame, dest_buffer.frame_count()) {
+            None => return self.supplier.supply_audio(request, dest_buffer),
+            Some(r) => r,
+        };
+        dest_buffer.slice_mut(0..range.count_in_frame_count).clear();
+        let inner_request = SupplyAudioRequest {
+            start_frame: range.inner_start_frame,
+            dest_sample_rate: request.dest_sample_rate,
+            info: SupplyRequestInfo {
+                audio_block_frame_offset: request.info.audio_block_frame_offset
+                    + range.count_in_frame_count,
+                requester: "reverser-audio",
+                note: "",
+                is_realtime: request.info.is_realtime,
+            },
+            parent_request: Some(request),
+            general_info: request.general_info,
+        };
+        let mut inner_dest_buffer = dest_buffer.slice_mut(
+            range.count_in_frame_count..range.count_in_frame_count + range.inner_frame_count,
+        );
+        self.supplier
+            .supply_audio(&inner_request, &mut inner_dest_buffer);
+        reverse_frames(&mut inner_dest_buffer);
+        range.create_response(request.start_frame)
+    }
+}
+
+impl<S: MidiSupplier + WithMaterialInfo> MidiSupplier for Reverser<S> {
+    fn supply_midi(
+        &mut self,
+        request: &SupplyMidiRequest,
+        event_list: &mut BorrowedMidiEventList,
+    ) -> SupplyResponse {
+        if !self.enabled {
+            return self.supplier.supply_midi(request, event_list);
+        }
+        let range = match self.reversed_range(request.start_frame, request.dest_frame_count) {
+            None => return self.supplier.supply_midi(request, event_list),
+            Some(r) => r,
+        };
+        let inner_request = SupplyMidiRequest {
+            start_frame: range.inner_start_frame,
+            dest_frame_count: range.inner_frame_count,
+            dest_sample_rate: request.dest_sample_rate,
+            info: SupplyRequestInfo {
+                audio_block_frame_offset: request.info.audio_block_frame_offset
+                    + range.count_in_frame_count,
+                requester: "reverser-midi",
+                note: "",
+                is_realtime: request.info.is_realtime,
+            },
+            parent_request: Some(request),
+            general_info: request.general_info,
+        };
+        // The event list might already contain events from previous suppliers (e.g. from the end
+        // of the previous loop cycle). We must leave them alone.
+        let first_new_index = event_list.iter().count();
+        self.supplier.supply_midi(&inner_request, event_list);
+        // Mirror positions and swap note-ons/offs. This must happen in the original order
+        // because we memorize the velocities of the original note-ons. Only short messages are
+        // reversed. Other messages (e.g. sysex) have a different size, so they can't change places
+        // with short messages. They pass through unchanged.
+        self.reversed_events.clear();
+        let mut bpos = 0;
+        let mut index = 0;
+        while let Some(res) = event_list.enum_items(bpos) {
+            let next_bpos = res.next_bpos;
+            let keep = if index >= first_new_index && is_short_message(res.midi_event.message()) {
+                let offset = res.midi_event.frame_offset().get() as usize;
+                let msg: RawShortMessage = res.midi_event.message().to_other();
+                self.memorize_reversed_event(offset, msg, &range)
+            } else {
+                true
+            };
+            if keep {
+                bpos = next_bpos;
+            } else {
+                // Dropping is better than allocating in the real-time thread.
+                event_list.delete_item(bpos);
+            }
+            index += 1;
+        }
+        self.finish_reversal();
+        // Write them back in reversed order
+        let short_events = event_list
+            .iter_mut()
+            .skip(first_new_index)
+            .filter(|event| is_short_message(event.message()));
+        for (event, (offset, msg)) in short_events.zip(self.reversed_events.iter()) {
+            event.set_frame_offset(*offset);
+            event.set_message(*msg);
+        }
+        range.create_response(request.start_frame)
+    }
+
+    fn release_notes(
+        &mut self,
+        frame_offset: MidiFrameOffset,
+        event_list: &mut BorrowedMidiEventList,
+    ) {
+        // The source keeps track of playing notes in the original direction, which doesn't
+        // reflect what's playing in the reversed output. So we release our own notes as well.
+        for (ch, notes) in self.on_notes.iter().enumerate() {
+            for key in (0u8..128u8).filter(|key| notes & (1u128 << key) > 0) {
+                let msg =
+                    RawShortMessage::note_off(Channel::new(ch as _), KeyNumber::new(key), U7::MIN);
+                let mut event = MidiEvent::default();
+                event.set_frame_offset(frame_offset);
+                event.set_message(msg);
+                event_list.add_item(&event);
+            }
+        }
+        self.on_notes = [0; 16];
+        self.supplier.release_notes(frame_offset, event_list);
+    }
+}
+
+impl<S: WithMaterialInfo> WithMaterialInfo for Reverser<S> {
+    fn material_info(&self) -> ClipEngineResult<MaterialInfo> {
+        self.supplier.material_info()
+    }
+}
+
+impl<S: PositionTranslationSkill + WithMaterialInfo> PositionTranslationSkill for Reverser<S> {
+    fn translate_play_pos_to_source_pos(&self, play_pos: isize) -> isize {
+        if !self.enabled {
+            return self.supplier.translate_play_pos_to_source_pos(play_pos);
+        }
+        let material_frame_count = match self.supplier.material_info() {
+            Ok(i) => i.frame_count() as isize,
+            Err(_) => return self.supplier.translate_play_pos_to_source_pos(play_pos),
+        };
+        if play_pos < 0 || play_pos >= material_frame_count {
+            return self.supplier.translate_play_pos_to_source_pos(play_pos);
+        }
+        self.supplier
+            .translate_play_pos_to_source_pos(material_frame_count - 1 - play_pos)
+    }
+}
+
+struct ReversedRange {
+    /// Number of frames at the start of the block which are still in the count-in phase.
+    count_in_frame_count: usize,
+    /// Start of the portion of the inner material that needs to be played backwards.
+    inner_start_frame: isize,
+    inner_frame_count: usize,
+    material_frame_count: usize,
+}
+
+impl ReversedRange {
+    fn create_response(&self, start_frame: isize) -> SupplyResponse {
+        let num_frames = self.count_in_frame_count + self.inner_frame_count;
+        SupplyResponse::limited_by_total_frame_count(
+            num_frames,
+            num_frames,
+            start_frame,
+            self.material_frame_count,
+        )
+    }
+}
+
+fn reverse_frames(buffer: &mut AudioBufMut) {
+    let channel_count = buffer.channel_count();
+    let frame_count = buffer.frame_count();
+    let data = buffer.data_as_mut_slice();
+    for frame in 0..frame_count / 2 {
+        let mirrored_frame = frame_count - 1 - frame;
+        for ch in 0..channel_count {
+            data.swap(
+                frame * channel_count + ch,
+                mirrored_frame * channel_count + ch,
+            );
+        }
+    }
+}
+
+/// Returns `false` for messages which are longer than a short message, e.g. sysex.
+fn is_short_message(msg: &MidiMessage) -> bool {
+    msg.as_slice().len() <= 3
+}
+
+/// Keeps track of the notes which are playing in the reversed output.
+fn update_on_notes(on_notes: &mut [u128; 16], msg: &impl ShortMessage) {
+    match msg.to_structured() {
+        StructuredShortMessage::NoteOn {
+            channel,
+            key_number,
+            velocity,
+        } if velocity.get() > 0 => {
+            on_notes[channel.get() as usize] |= 1u128 << key_number.get();
+        }
+        StructuredShortMessage::NoteOn {
+            channel,
+            key_number,
+            ..
+        }
+        | StructuredShortMessage::NoteOff {
+            channel,
+            key_number,
+            ..
+        } => {
+            on_notes[channel.get() as usize] &= !(1u128 << key_number.get());
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note_on(key_number: u8, velocity: u8) -> RawShortMessage {
+        RawShortMessage::note_on(
+            Channel::new(0),
+            KeyNumber::new(key_number),
+            U7::new(velocity),
+        )
+    }
+
+    fn note_off(key_number: u8) -> RawShortMessage {
+        RawShortMessage::note_off(Channel::new(0), KeyNumber::new(key_number), U7::MIN)
+    }
+
+    fn range() -> ReversedRange {
+        ReversedRange {
+            count_in_frame_count: 0,
+            inner_start_frame: 0,
+            inner_frame_count: 100,
+            material_frame_count: 1000,
+        }
+    }
+
+    fn is_playing(reverser: &Reverser<()>, key_number: u8) -> bool {
+        reverser.on_notes[0] & (1u128 << key_number) > 0
+    }
+
+    #[test]
+    fn swap_note_on_and_off() {
+        // Given
+        let mut reverser = Reverser::new(());
+        // When
+        reverser.memorize_reversed_event(10, note_on(60, 77), &range());
+        reverser.memorize_reversed_event(50, note_off(60), &range());
+        reverser.finish_reversal();
+        // Then
+        assert_eq!(
+            reverser.reversed_events,
+            vec![
+                (MidiFrameOffset::new(49), note_on(60, 77)),
+                (MidiFrameOffset::new(89), note_off(60)),
+            ]
+        );
+        assert!(!is_playing(&reverser, 60));
+    }
+
+    #[test]
+    fn track_note_which_ends_in_later_block() {
+        // Given
+        let mut reverser = Reverser::new(());
+        // When
+        // The original note-off comes first when playing backwards. Its note-on follows in a later
+        // block.
+        reverser.memorize_reversed_event(20, note_off(60), &range());
+        reverser.finish_reversal();
+        // Then
+        assert_eq!(
+            reverser.reversed_events,
+            vec![(MidiFrameOffset::new(79), note_on(60, DEFAULT_VELOCITY))]
+        );
+        assert!(is_playing(&reverser, 60));
+    }
+
+    #[test]
+    fn end_hanging_note_at_original_note_on() {
+        // Given
+        let mut reverser = Reverser::new(());
+        reverser.memorize_reversed_event(20, note_off(60), &range());
+        reverser.finish_reversal();
+        // When
+        reverser.reversed_events.clear();
+        reverser.memorize_reversed_event(80, note_on(60, 90), &range());
+        reverser.finish_reversal();
+        // Then
+        assert_eq!(
+            reverser.reversed_events,
+            vec![(MidiFrameOffset::new(19), note_off(60))]
+        );
+        assert!(!is_playing(&reverser, 60));
+    }
+
+    #[test]
+    fn drop_events_exceeding_capacity() {
+        // Given
+        let mut reverser = Reverser::new(());
+        for _ in 0..MAX_EVENT_COUNT_PER_BLOCK {
+            assert!(reverser.memorize_reversed_event(0, note_on(60, 100), &range()));
+        }
+        // When
+        let memorized = reverser.memorize_reversed_event(0, note_off(60), &range());
+        // Then
+        assert!(!memorized);
+        assert_eq!(reverser.reversed_events.len(), MAX_EVENT_COUNT_PER_BLOCK);
+    }
+}
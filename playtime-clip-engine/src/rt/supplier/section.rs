@@ -1,13 +1,16 @@
 use crate::conversion_util::convert_duration_in_seconds_to_frames;
 use crate::rt::buffer::AudioBufMut;
+use crate::rt::supplier::bounds_util::{
+    create_bounded_request_data, generate_bounded_response, Bounded, BoundedRequestData,
+};
 use crate::rt::supplier::fade_util::{
-    apply_fade_in_starting_at_zero, apply_fade_out_ending_at, SECTION_FADE_LENGTH,
+    apply_fade_in_starting_at_zero, apply_fade_out_ending_at, StartEndFades,
 };
 use crate::rt::supplier::midi_util::SilenceMidiBlockMode;
 use crate::rt::supplier::{
     midi_util, AudioMaterialInfo, AudioSupplier, MaterialInfo, MidiMaterialInfo, MidiSupplier,
-    PositionTranslationSkill, SupplyAudioRequest, SupplyMidiRequest, SupplyRequest,
-    SupplyRequestInfo, SupplyResponse, SupplyResponseStatus, WithMaterialInfo,
+    PositionTranslationSkill, SupplyAudioRequest, SupplyMidiRequest, SupplyRequest, SupplyResponse,
+    WithMaterialInfo,
 };
 use crate::ClipEngineResult;
 use playtime_api::persistence::{MidiResetMessageRange, PositiveSecond};
@@ -17,6 +20,7 @@ use reaper_medium::{BorrowedMidiEventList, DurationInSeconds, MidiFrameOffset};
 pub struct Section<S> {
     supplier: S,
     bounds: SectionBounds,
+    fades: StartEndFades,
     midi_reset_msg_range: MidiResetMessageRange,
 }
 
@@ -60,6 +64,7 @@ impl<S> Section<S> {
         Self {
             supplier,
             bounds: Default::default(),
+            fades: Default::default(),
             midi_reset_msg_range: Default::default(),
         }
     }
@@ -68,6 +73,10 @@ impl<S> Section<S> {
         self.midi_reset_msg_range = range;
     }
 
+    pub fn set_fades(&mut self, fades: StartEndFades) {
+        self.fades = fades;
+    }
+
     pub fn bounds(&self) -> SectionBounds {
         self.bounds
     }
@@ -141,96 +150,16 @@ impl<S> Section<S> {
         //             }
         // Determine source range
         let start_frame_in_source = self.bounds.start_frame as isize + request.start_frame();
-        let (phase_two, num_frames_to_be_written) = match self.bounds.length {
-            None => {
-                // Section doesn't have right bound (easy).
-                (PhaseTwo::Unbounded, dest_frame_count)
-            }
-            Some(length) => {
-                // Section has right bound.
-                if request.start_frame() >= length as isize {
-                    // We exceeded the section boundary. Return silence.
-                    return Instruction::Return(SupplyResponse::exceeded_end());
-                }
-                // We are still within the section.
-                let right_bound_in_source = self.bounds.start_frame + length;
-                let ideal_end_frame_in_source =
-                    start_frame_in_source + ideal_num_frames_to_be_consumed as isize;
-                let (reached_bound, effective_end_frame_in_source) =
-                    if ideal_end_frame_in_source <= right_bound_in_source as isize {
-                        // End of block is located before or on end of section end
-                        (false, ideal_end_frame_in_source)
-                    } else {
-                        // End of block is located behind section end
-                        (true, right_bound_in_source as isize)
-                    };
-                let bounded_num_frames_to_be_consumed =
-                    (effective_end_frame_in_source - start_frame_in_source) as usize;
-                let bounded_num_frames_to_be_written = bounded_num_frames_to_be_consumed;
-                let phase_two = PhaseTwo::Bounded {
-                    reached_bound,
-                    bounded_num_frames_to_be_consumed,
-                    bounded_num_frames_to_be_written,
-                    ideal_num_frames_to_be_consumed,
-                };
-                (phase_two, bounded_num_frames_to_be_written)
-            }
-        };
-        let data = SectionRequestData {
-            phase_one: PhaseOne {
-                start_frame: start_frame_in_source,
-                info: SupplyRequestInfo {
-                    audio_block_frame_offset: request.info().audio_block_frame_offset,
-                    requester: "section-request",
-                    note: "",
-                    is_realtime: request.info().is_realtime,
-                },
-                num_frames_to_be_written,
-            },
-            phase_two,
-        };
-        Instruction::ApplySection(data)
-    }
-
-    fn generate_outer_response(
-        &self,
-        inner_response: SupplyResponse,
-        phase_two: PhaseTwo,
-    ) -> SupplyResponse {
-        use PhaseTwo::*;
-        match phase_two {
-            Unbounded => {
-                // Section has open end. In that case the inner response is valid.
-                inner_response
-            }
-            Bounded {
-                reached_bound,
-                bounded_num_frames_to_be_consumed,
-                bounded_num_frames_to_be_written,
-                ideal_num_frames_to_be_consumed,
-            } => {
-                // Section has right bound.
-                if reached_bound {
-                    // Bound reached.
-                    SupplyResponse::reached_end(
-                        bounded_num_frames_to_be_consumed,
-                        bounded_num_frames_to_be_written,
-                    )
-                } else {
-                    // Bound not yet reached.
-                    use SupplyResponseStatus::*;
-                    match inner_response.status {
-                        PleaseContinue => {
-                            // Source has more material.
-                            SupplyResponse::please_continue(bounded_num_frames_to_be_consumed)
-                        }
-                        ReachedEnd { .. } => {
-                            // Source has reached its end (but the boundary is not reached yet).
-                            SupplyResponse::please_continue(ideal_num_frames_to_be_consumed)
-                        }
-                    }
-                }
-            }
+        match create_bounded_request_data(
+            request,
+            dest_frame_count,
+            start_frame_in_source,
+            self.bounds.length,
+            "section-request",
+        ) {
+            // We exceeded the section boundary. Return silence.
+            None => Instruction::Return(SupplyResponse::exceeded_end()),
+            Some(d) => Instruction::ApplySection(d),
         }
     }
 }
@@ -249,29 +178,28 @@ impl<S: AudioSupplier> AudioSupplier for Section<S> {
             Instruction::ApplySection(d) => d,
         };
         let inner_request = SupplyAudioRequest {
-            start_frame: data.phase_one.start_frame,
+            start_frame: data.start_frame,
             dest_sample_rate: request.dest_sample_rate,
-            info: data.phase_one.info,
+            info: data.info,
             parent_request: Some(request),
             general_info: request.general_info,
         };
-        let mut inner_dest_buffer =
-            dest_buffer.slice_mut(0..data.phase_one.num_frames_to_be_written);
+        let mut inner_dest_buffer = dest_buffer.slice_mut(0..data.num_frames_to_be_written);
         let inner_response = self
             .supplier
             .supply_audio(&inner_request, &mut inner_dest_buffer);
         if self.bounds.start_frame > 0 {
-            apply_fade_in_starting_at_zero(dest_buffer, request.start_frame, SECTION_FADE_LENGTH);
+            apply_fade_in_starting_at_zero(dest_buffer, request.start_frame, self.fades.fade_in);
         }
         if let Some(length) = self.bounds.length {
             apply_fade_out_ending_at(
                 dest_buffer,
                 request.start_frame,
                 length,
-                SECTION_FADE_LENGTH,
+                self.fades.fade_out,
             );
         }
-        self.generate_outer_response(inner_response, data.phase_two)
+        generate_bounded_response(inner_response, data.bounded)
     }
 }
 
@@ -289,9 +217,9 @@ impl<S: MidiSupplier> MidiSupplier for Section<S> {
             Instruction::ApplySection(d) => d,
         };
         let inner_request = SupplyMidiRequest {
-            start_frame: data.phase_one.start_frame,
-            dest_frame_count: data.phase_one.num_frames_to_be_written,
-            info: data.phase_one.info.clone(),
+            start_frame: data.start_frame,
+            dest_frame_count: data.num_frames_to_be_written,
+            info: data.info.clone(),
             dest_sample_rate: request.dest_sample_rate,
             parent_request: request.parent_request,
             general_info: request.general_info,
@@ -308,10 +236,10 @@ impl<S: MidiSupplier> MidiSupplier for Section<S> {
             );
         }
         // Reset MIDI at end if necessary
-        if let PhaseTwo::Bounded {
+        if let Some(Bounded {
             reached_bound: true,
             ..
-        } = &data.phase_two
+        }) = data.bounded
         {
             debug!("Silence MIDI at section end");
             midi_util::silence_midi(
@@ -321,7 +249,7 @@ impl<S: MidiSupplier> MidiSupplier for Section<S> {
                 &mut self.supplier,
             );
         }
-        self.generate_outer_response(inner_response, data.phase_two)
+        generate_bounded_response(inner_response, data.bounded)
     }
 
     fn release_notes(
@@ -360,31 +288,10 @@ impl<S: WithMaterialInfo> WithMaterialInfo for Section<S> {
 
 enum Instruction {
     Bypass,
-    ApplySection(SectionRequestData),
+    ApplySection(BoundedRequestData),
     Return(SupplyResponse),
 }
 
-struct SectionRequestData {
-    phase_one: PhaseOne,
-    phase_two: PhaseTwo,
-}
-
-struct PhaseOne {
-    start_frame: isize,
-    info: SupplyRequestInfo,
-    num_frames_to_be_written: usize,
-}
-
-enum PhaseTwo {
-    Unbounded,
-    Bounded {
-        reached_bound: bool,
-        bounded_num_frames_to_be_consumed: usize,
-        bounded_num_frames_to_be_written: usize,
-        ideal_num_frames_to_be_consumed: usize,
-    },
-}
-
 impl<S: PositionTranslationSkill> PositionTranslationSkill for Section<S> {
     fn translate_play_pos_to_source_pos(&self, play_pos: isize) -> isize {
         let effective_play_pos = self.bounds.start_frame as isize + play_pos;
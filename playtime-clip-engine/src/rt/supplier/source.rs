@@ -7,8 +7,9 @@ use crate::rt::source_util::pcm_source_is_midi;
 use crate::rt::supplier::audio_util::{supply_audio_material, SourceMaterialRequest};
 use crate::rt::supplier::log_util::print_distance_from_beat_start_at;
 use crate::rt::supplier::{
-    AudioMaterialInfo, AudioSupplier, MaterialInfo, MidiMaterialInfo, MidiSupplier,
-    SupplyAudioRequest, SupplyMidiRequest, SupplyResponse, WithMaterialInfo, WithSource,
+    AudioMaterialInfo, AudioSupplier, InMemoryAudioSource, InMemoryMidiSource, MaterialInfo,
+    MidiMaterialInfo, MidiSupplier, SupplyAudioRequest, SupplyMidiRequest, SupplyResponse,
+    WithMaterialInfo, WithSource,
 };
 use crate::ClipEngineResult;
 use helgoboss_midi::{
@@ -22,10 +23,19 @@ use reaper_medium::{
 
 #[derive(Clone, Debug)]
 pub struct ClipSource {
-    source: OwnedPcmSource,
+    kind: ClipSourceKind,
     midi_state: MidiState,
 }
 
+#[derive(Clone, Debug)]
+enum ClipSourceKind {
+    Reaper(OwnedPcmSource),
+    /// Doesn't need REAPER, used for offline rendering and tests.
+    InMemoryAudio(InMemoryAudioSource),
+    /// Doesn't need REAPER, used for offline rendering and tests.
+    InMemoryMidi(InMemoryMidiSource),
+}
+
 #[derive(Clone, Debug, Default)]
 struct MidiState {
     note_states_by_channel: [NoteState; 16],
@@ -103,105 +113,151 @@ impl NoteState {
 
 impl ClipSource {
     pub fn new(reaper_source: OwnedPcmSource) -> Self {
-        Self {
-            source: reaper_source,
-            midi_state: MidiState::default(),
-        }
+        Self::with_kind(ClipSourceKind::Reaper(reaper_source))
     }
 
-    pub fn reaper_source(&self) -> &BorrowedPcmSource {
-        &self.source
+    pub fn in_memory_audio(source: InMemoryAudioSource) -> Self {
+        Self::with_kind(ClipSourceKind::InMemoryAudio(source))
     }
 
-    pub fn into_reaper_source(self) -> OwnedPcmSource {
-        self.source
+    pub fn in_memory_midi(source: InMemoryMidiSource) -> Self {
+        Self::with_kind(ClipSourceKind::InMemoryMidi(source))
     }
 
-    fn get_audio_source_frame_rate(&self) -> Hz {
-        self.source
-            .get_sample_rate()
-            .expect("audio source should expose frame rate")
+    fn with_kind(kind: ClipSourceKind) -> Self {
+        Self {
+            kind,
+            midi_state: MidiState::default(),
+        }
     }
 
-    fn transfer_audio(&self, req: SourceMaterialRequest) -> SupplyResponse {
-        let source_sample_rate = self.source.get_sample_rate().unwrap();
-        let time_s = convert_duration_in_frames_to_seconds(req.start_frame, source_sample_rate);
-        let num_frames_written = unsafe {
-            let mut transfer = PcmSourceTransfer::default();
-            // Both channel count and sample rate should be the one from the source itself!
-            transfer.set_nch(self.get_audio_source_channel_count() as _);
-            transfer.set_sample_rate(source_sample_rate);
-            // The rest depends on the given parameters
-            transfer.set_length(req.dest_buffer.frame_count() as _);
-            transfer.set_samples(req.dest_buffer.data_as_mut_ptr());
-            transfer.set_time_s(time_s.into());
-            self.source.get_samples(&transfer);
-            transfer.samples_out() as usize
-        };
-        // The lower the sample rate, the higher the tempo, the more inner source material we
-        // effectively grabbed.
-        SupplyResponse::limited_by_total_frame_count(
-            num_frames_written,
-            num_frames_written,
-            req.start_frame as isize,
-            self.calculate_audio_frame_count(source_sample_rate),
-        )
+    /// Returns an error if this is an in-memory source.
+    pub fn reaper_source(&self) -> ClipEngineResult<&BorrowedPcmSource> {
+        match &self.kind {
+            ClipSourceKind::Reaper(s) => Ok(s),
+            _ => Err("in-memory source is not a REAPER source"),
+        }
     }
 
-    fn get_audio_source_channel_count(&self) -> usize {
-        self.source
-            .get_num_channels()
-            .expect("audio source should report channel count") as usize
+    /// Returns an error if this is an in-memory source.
+    pub fn into_reaper_source(self) -> ClipEngineResult<OwnedPcmSource> {
+        match self.kind {
+            ClipSourceKind::Reaper(s) => Ok(s),
+            _ => Err("in-memory source is not a REAPER source"),
+        }
     }
 
-    fn calculate_audio_frame_count(&self, sample_rate: Hz) -> usize {
-        let length_in_seconds = self.source.get_length().unwrap_or(DurationInSeconds::ZERO);
-        convert_duration_in_seconds_to_frames(length_in_seconds, sample_rate)
+    pub fn is_midi(&self) -> bool {
+        match &self.kind {
+            ClipSourceKind::Reaper(s) => pcm_source_is_midi(s),
+            ClipSourceKind::InMemoryAudio(_) => false,
+            ClipSourceKind::InMemoryMidi(_) => true,
+        }
     }
 
-    fn calculate_midi_frame_count(&self) -> usize {
-        let length_in_seconds = if let Some(length_in_beats) = self.source.get_length_beats() {
-            // For MIDI, get_length() takes the current project tempo in account ... which is not
-            // what we want because we want to do all the tempo calculations ourselves and treat
-            // MIDI/audio the same wherever possible.
-            let beats_per_minute = MIDI_BASE_BPM;
-            let beats_per_second = beats_per_minute.get() / 60.0;
-            DurationInSeconds::new(length_in_beats.get() / beats_per_second)
-        } else {
-            // If we don't get a length in beats, this either means we have set a preview tempo
-            // on the source or the source has IGNTEMPO set to 1. Either way we will take the
-            // reported length.
-            self.source.get_length().unwrap()
-        };
-        convert_duration_in_seconds_to_frames(length_in_seconds, MIDI_FRAME_RATE)
+    /// Returns whether the complete material is held in memory already (so caching it doesn't
+    /// make sense).
+    pub fn is_in_memory(&self) -> bool {
+        !matches!(&self.kind, ClipSourceKind::Reaper(_))
     }
 }
 
+fn get_audio_source_frame_rate(source: &BorrowedPcmSource) -> Hz {
+    source
+        .get_sample_rate()
+        .expect("audio source should expose frame rate")
+}
+
+fn transfer_audio(source: &BorrowedPcmSource, req: SourceMaterialRequest) -> SupplyResponse {
+    let source_sample_rate = source.get_sample_rate().unwrap();
+    let time_s = convert_duration_in_frames_to_seconds(req.start_frame, source_sample_rate);
+    let num_frames_written = unsafe {
+        let mut transfer = PcmSourceTransfer::default();
+        // Both channel count and sample rate should be the one from the source itself!
+        transfer.set_nch(get_audio_source_channel_count(source) as _);
+        transfer.set_sample_rate(source_sample_rate);
+        // The rest depends on the given parameters
+        transfer.set_length(req.dest_buffer.frame_count() as _);
+        transfer.set_samples(req.dest_buffer.data_as_mut_ptr());
+        transfer.set_time_s(time_s.into());
+        source.get_samples(&transfer);
+        transfer.samples_out() as usize
+    };
+    // The lower the sample rate, the higher the tempo, the more inner source material we
+    // effectively grabbed.
+    SupplyResponse::limited_by_total_frame_count(
+        num_frames_written,
+        num_frames_written,
+        req.start_frame as isize,
+        calculate_audio_frame_count(source, source_sample_rate),
+    )
+}
+
+fn get_audio_source_channel_count(source: &BorrowedPcmSource) -> usize {
+    source
+        .get_num_channels()
+        .expect("audio source should report channel count") as usize
+}
+
+fn calculate_audio_frame_count(source: &BorrowedPcmSource, sample_rate: Hz) -> usize {
+    let length_in_seconds = source.get_length().unwrap_or(DurationInSeconds::ZERO);
+    convert_duration_in_seconds_to_frames(length_in_seconds, sample_rate)
+}
+
+fn calculate_midi_frame_count(source: &BorrowedPcmSource) -> usize {
+    let length_in_seconds = if let Some(length_in_beats) = source.get_length_beats() {
+        // For MIDI, get_length() takes the current project tempo in account ... which is not
+        // what we want because we want to do all the tempo calculations ourselves and treat
+        // MIDI/audio the same wherever possible.
+        let beats_per_minute = MIDI_BASE_BPM;
+        let beats_per_second = beats_per_minute.get() / 60.0;
+        DurationInSeconds::new(length_in_beats.get() / beats_per_second)
+    } else {
+        // If we don't get a length in beats, this either means we have set a preview tempo
+        // on the source or the source has IGNTEMPO set to 1. Either way we will take the
+        // reported length.
+        source.get_length().unwrap()
+    };
+    convert_duration_in_seconds_to_frames(length_in_seconds, MIDI_FRAME_RATE)
+}
+
 impl AudioSupplier for ClipSource {
     fn supply_audio(
         &mut self,
         request: &SupplyAudioRequest,
         dest_buffer: &mut AudioBufMut,
     ) -> SupplyResponse {
-        let source_frame_rate = self.get_audio_source_frame_rate();
-        supply_audio_material(request, dest_buffer, source_frame_rate, |input| {
-            self.transfer_audio(input)
-        })
+        match &mut self.kind {
+            ClipSourceKind::Reaper(s) => {
+                let source_frame_rate = get_audio_source_frame_rate(s);
+                supply_audio_material(request, dest_buffer, source_frame_rate, |input| {
+                    transfer_audio(s, input)
+                })
+            }
+            ClipSourceKind::InMemoryAudio(s) => s.supply_audio(request, dest_buffer),
+            // MIDI material doesn't provide audio
+            ClipSourceKind::InMemoryMidi(_) => SupplyResponse::exceeded_end(),
+        }
     }
 }
 
 impl WithMaterialInfo for ClipSource {
     fn material_info(&self) -> ClipEngineResult<MaterialInfo> {
-        let info = if pcm_source_is_midi(&self.source) {
+        let source = match &self.kind {
+            ClipSourceKind::Reaper(s) => s,
+            ClipSourceKind::InMemoryAudio(s) => return s.material_info(),
+            ClipSourceKind::InMemoryMidi(s) => return s.material_info(),
+        };
+        let info = if pcm_source_is_midi(source) {
             let info = MidiMaterialInfo {
-                frame_count: self.calculate_midi_frame_count(),
+                frame_count: calculate_midi_frame_count(source),
             };
             MaterialInfo::Midi(info)
         } else {
-            let sample_rate = self.get_audio_source_frame_rate();
+            let sample_rate = get_audio_source_frame_rate(source);
             let info = AudioMaterialInfo {
-                channel_count: self.get_audio_source_channel_count(),
-                frame_count: self.calculate_audio_frame_count(sample_rate),
+                channel_count: get_audio_source_channel_count(source),
+                frame_count: calculate_audio_frame_count(source, sample_rate),
                 frame_rate: sample_rate,
             };
             MaterialInfo::Audio(info)
@@ -237,28 +293,51 @@ impl MidiSupplier for ClipSource {
                 "(MIDI, start_frame < 0)",
             );
         }
-        // For MIDI it seems to be okay to start at a negative position. The source
-        // will ignore positions < 0.0 and add events >= 0.0 with the correct frame
-        // offset.
-        let time_s = convert_position_in_frames_to_seconds(request.start_frame, frame_rate);
-        let num_midi_frames_consumed = unsafe {
-            let mut transfer = PcmSourceTransfer::default();
-            transfer.set_sample_rate(frame_rate);
-            transfer.set_length(num_frames_to_be_consumed as i32);
-            transfer.set_time_s(time_s);
-            transfer.set_midi_event_list(event_list);
-            self.source.get_samples(&transfer);
-            // In the past, we did the following in order to deal with on-the-fly tempo changes that
-            // occur while playing instead of REAPER letting use its generic mechanism that leads
-            // to repeated notes, probably through internal position changes.
-            //
-            //      transfer.set_force_bpm(MIDI_BASE_BPM);
-            //      transfer.set_absolute_time_s(PositionInSeconds::ZERO);
-            //
-            // However, now we set the constant preview tempo at source creation time, which makes
-            // the source completely project tempo/pos-independent, also when doing recording via
-            // midi_realtime_write_struct_t. So that's not necessary anymore
-            transfer.samples_out() as usize
+        let (num_midi_frames_consumed, total_frame_count) = match &self.kind {
+            ClipSourceKind::Reaper(source) => {
+                // For MIDI it seems to be okay to start at a negative position. The source
+                // will ignore positions < 0.0 and add events >= 0.0 with the correct frame
+                // offset.
+                let time_s = convert_position_in_frames_to_seconds(request.start_frame, frame_rate);
+                let num_midi_frames_consumed = unsafe {
+                    let mut transfer = PcmSourceTransfer::default();
+                    transfer.set_sample_rate(frame_rate);
+                    transfer.set_length(num_frames_to_be_consumed as i32);
+                    transfer.set_time_s(time_s);
+                    transfer.set_midi_event_list(event_list);
+                    source.get_samples(&transfer);
+                    // In the past, we did the following in order to deal with on-the-fly tempo
+                    // changes that occur while playing instead of REAPER letting use its generic
+                    // mechanism that leads to repeated notes, probably through internal position
+                    // changes.
+                    //
+                    //      transfer.set_force_bpm(MIDI_BASE_BPM);
+                    //      transfer.set_absolute_time_s(PositionInSeconds::ZERO);
+                    //
+                    // However, now we set the constant preview tempo at source creation time,
+                    // which makes the source completely project tempo/pos-independent, also when
+                    // doing recording via midi_realtime_write_struct_t. So that's not necessary
+                    // anymore
+                    transfer.samples_out() as usize
+                };
+                (num_midi_frames_consumed, calculate_midi_frame_count(source))
+            }
+            ClipSourceKind::InMemoryMidi(source) => {
+                let events = source.events_in_portion(
+                    request.start_frame,
+                    num_frames_to_be_consumed,
+                    frame_rate,
+                );
+                for (frame_offset, msg) in events {
+                    let mut event = MidiEvent::default();
+                    event.set_frame_offset(MidiFrameOffset::new(frame_offset as _));
+                    event.set_message(msg);
+                    event_list.add_item(&event);
+                }
+                (num_frames_to_be_consumed, source.frame_count())
+            }
+            // Audio material doesn't provide MIDI
+            ClipSourceKind::InMemoryAudio(_) => return SupplyResponse::exceeded_end(),
         };
         // Track playing notes
         for evt in event_list {
@@ -270,7 +349,7 @@ impl MidiSupplier for ClipSource {
             num_midi_frames_consumed,
             num_midi_frames_consumed,
             request.start_frame,
-            self.calculate_midi_frame_count(),
+            total_frame_count,
         )
     }
 
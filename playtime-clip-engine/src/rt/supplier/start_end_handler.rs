@@ -1,6 +1,6 @@
 use crate::rt::buffer::AudioBufMut;
 use crate::rt::supplier::fade_util::{
-    apply_fade_in_starting_at_zero, apply_fade_out_ending_at, START_END_FADE_LENGTH,
+    apply_fade_in_starting_at_zero, apply_fade_out_ending_at, StartEndFades,
 };
 use crate::rt::supplier::midi_util::SilenceMidiBlockMode;
 use crate::rt::supplier::{
@@ -15,6 +15,7 @@ use reaper_medium::{BorrowedMidiEventList, MidiFrameOffset};
 pub struct StartEndHandler<S> {
     supplier: S,
     audio_fades_enabled: bool,
+    fades: StartEndFades,
     enabled_for_start: bool,
     enabled_for_end: bool,
     midi_reset_msg_range: MidiResetMessageRange,
@@ -25,6 +26,7 @@ impl<S> StartEndHandler<S> {
         Self {
             supplier,
             audio_fades_enabled: false,
+            fades: Default::default(),
             enabled_for_start: false,
             enabled_for_end: false,
             midi_reset_msg_range: Default::default(),
@@ -35,6 +37,10 @@ impl<S> StartEndHandler<S> {
         self.audio_fades_enabled = enabled;
     }
 
+    pub fn set_fades(&mut self, fades: StartEndFades) {
+        self.fades = fades;
+    }
+
     pub fn set_midi_reset_msg_range(&mut self, range: MidiResetMessageRange) {
         self.midi_reset_msg_range = range;
     }
@@ -67,7 +73,7 @@ impl<S: AudioSupplier + WithMaterialInfo> AudioSupplier for StartEndHandler<S> {
             return response;
         }
         if self.enabled_for_start {
-            apply_fade_in_starting_at_zero(dest_buffer, request.start_frame, START_END_FADE_LENGTH);
+            apply_fade_in_starting_at_zero(dest_buffer, request.start_frame, self.fades.fade_in);
         }
         let frame_count = self.supplier.material_info().unwrap().frame_count();
         if self.enabled_for_end {
@@ -75,7 +81,7 @@ impl<S: AudioSupplier + WithMaterialInfo> AudioSupplier for StartEndHandler<S> {
                 dest_buffer,
                 request.start_frame,
                 frame_count,
-                START_END_FADE_LENGTH,
+                self.fades.fade_out,
             );
         }
         response
@@ -1,25 +1,47 @@
 use crate::rt::buffer::AudioBufMut;
+use crate::rt::supplier::LinearInterpolator;
 use crate::rt::supplier::{
     AudioSupplier, MaterialInfo, PositionTranslationSkill, SupplyAudioRequest, SupplyResponse,
     SupplyResponseStatus, WithMaterialInfo,
 };
 use crate::rt::supplier::{
-    MidiSupplier, PreBufferFillRequest, PreBufferSourceSkill, SupplyMidiRequest, SupplyRequestInfo,
+    MidiSupplier, PreBufferFillRequest, PreBufferSourceSkill, PreStretchParams, PreStretchSkill,
+    SupplyMidiRequest, SupplyRequestInfo,
 };
 use crate::ClipEngineResult;
-use playtime_api::persistence::VirtualTimeStretchMode;
+use helgoboss_midi::{
+    KeyNumber, RawShortMessage, ShortMessage, ShortMessageFactory, StructuredShortMessage,
+};
+use playtime_api::persistence::{ClipPitch, VirtualTimeStretchMode};
 use reaper_high::Reaper;
 use reaper_low::raw::REAPER_PITCHSHIFT_API_VER;
 use reaper_medium::{BorrowedMidiEventList, MidiFrameOffset, OwnedReaperPitchShift};
 
 #[derive(Debug)]
 pub struct TimeStretcher<S> {
-    api: OwnedReaperPitchShift,
+    /// `None` if headless. Then audio is stretched by linear interpolation.
+    api: Option<OwnedReaperPitchShift>,
+    /// Used instead of the API if headless.
+    linear_interpolator: LinearInterpolator,
     supplier: S,
     enabled: bool,
     active: bool,
     responsible_for_audio_time_stretching: bool,
     tempo_factor: f64,
+    pitch: ClipPitch,
+    mode: VirtualTimeStretchMode,
+    /// Whether the previous block was served from material which has been stretched in advance.
+    ///
+    /// When falling back to live stretching, the buffers of the pitch shift API contain outdated
+    /// material, so we need to reset them.
+    used_pre_stretched_material: bool,
+    /// Frequency factor for pitch shifting audio (1.0 means no pitch shifting).
+    pitch_factor: f64,
+    /// Transposition of MIDI notes in semitones (MIDI can't be transposed by cents).
+    midi_transposition: i32,
+    /// Remembers by how many semitones each currently playing note was transposed so that its
+    /// note-off matches, even if the transposition changes while the note is playing.
+    note_transpositions: [[i8; 128]; 16],
 }
 
 impl<S> TimeStretcher<S> {
@@ -28,13 +50,33 @@ impl<S> TimeStretcher<S> {
             .medium_reaper()
             .reaper_get_pitch_shift_api(REAPER_PITCHSHIFT_API_VER)
             .expect("couldn't get pitch shift API in correct version");
+        Self::with_api(supplier, Some(api))
+    }
+
+    /// Creates a time stretcher which doesn't need REAPER.
+    ///
+    /// It stretches audio by interpolating linearly, which changes the pitch along with the tempo
+    /// (like VariSpeed). It can't pitch-shift audio (see [`Self::can_shift_audio_pitch`]) but it
+    /// transposes MIDI.
+    pub fn headless(supplier: S) -> Self {
+        Self::with_api(supplier, None)
+    }
+
+    fn with_api(supplier: S, api: Option<OwnedReaperPitchShift>) -> Self {
         Self {
             api,
+            linear_interpolator: Default::default(),
             supplier,
             enabled: false,
             active: false,
             responsible_for_audio_time_stretching: false,
             tempo_factor: 1.0,
+            pitch: Default::default(),
+            mode: VirtualTimeStretchMode::ProjectDefault,
+            used_pre_stretched_material: false,
+            pitch_factor: 1.0,
+            midi_transposition: 0,
+            note_transpositions: [[0; 128]; 16],
         }
     }
 
@@ -61,36 +103,101 @@ impl<S> TimeStretcher<S> {
     }
 
     pub fn set_mode(&mut self, mode: VirtualTimeStretchMode) {
+        self.mode = mode;
         use VirtualTimeStretchMode::*;
         let raw_quality_param = match mode {
             ProjectDefault => -1i32,
             ReaperMode(m) => (m.mode << (16 + m.sub_mode)) as i32,
         };
-        self.api
-            .as_mut()
-            .as_mut()
-            .SetQualityParameter(raw_quality_param);
+        if let Some(api) = &mut self.api {
+            api.as_mut().as_mut().SetQualityParameter(raw_quality_param);
+        }
     }
 
     pub fn set_tempo_factor(&mut self, tempo_factor: f64) {
         self.tempo_factor = tempo_factor;
     }
 
+    /// Returns `false` if headless. Then only MIDI can be transposed.
+    pub fn can_shift_audio_pitch(&self) -> bool {
+        self.api.is_some()
+    }
+
+    /// Sets the transposition, which is applied independently of the tempo.
+    pub fn set_pitch(&mut self, pitch: ClipPitch) {
+        self.pitch = pitch;
+        self.pitch_factor = pitch.frequency_factor();
+        self.midi_transposition = pitch.in_semitones().round() as i32;
+    }
+
     pub fn reset_buffers_and_latency(&mut self) {
-        self.api.as_mut().as_mut().Reset();
+        self.linear_interpolator.reset();
+        if let Some(api) = &mut self.api {
+            api.as_mut().as_mut().Reset();
+        }
     }
-}
 
-impl<S: AudioSupplier + WithMaterialInfo> AudioSupplier for TimeStretcher<S> {
-    fn supply_audio(
+    /// Returns whether audio might be stretched or pitch-shifted, depending on the settings only
+    /// (not on the current tempo).
+    pub fn may_stretch_audio(&self) -> bool {
+        let shift_pitch = self.api.is_some() && self.pitch_factor != 1.0;
+        self.enabled && (self.responsible_for_audio_time_stretching || shift_pitch)
+    }
+
+    fn stretches_tempo(&self) -> bool {
+        self.active && self.responsible_for_audio_time_stretching
+    }
+
+    /// Returns `None` if audio passes through unchanged.
+    fn pre_stretch_params(&self) -> Option<PreStretchParams> {
+        let stretch_tempo = self.stretches_tempo();
+        // Without API, there's no pitch shifting.
+        let shift_pitch = self.api.is_some() && self.pitch_factor != 1.0;
+        if !self.enabled || (!stretch_tempo && !shift_pitch) {
+            return None;
+        }
+        let params = PreStretchParams {
+            tempo_factor: if stretch_tempo {
+                self.tempo_factor
+            } else {
+                1.0
+            },
+            pitch: self.pitch,
+            mode: self.mode,
+        };
+        Some(params)
+    }
+
+    /// Stretches the material of the supplier right now, without looking for pre-stretched
+    /// material.
+    ///
+    /// This is also used by the pre-buffer worker in order to stretch material in advance.
+    pub fn stretch_audio(
         &mut self,
         request: &SupplyAudioRequest,
         dest_buffer: &mut AudioBufMut,
-    ) -> SupplyResponse {
-        if !self.enabled || !self.active || !self.responsible_for_audio_time_stretching {
-            return self.supplier.supply_audio(request, dest_buffer);
-        }
+    ) -> SupplyResponse
+    where
+        S: AudioSupplier,
+    {
+        let stretch_tempo = self.stretches_tempo();
         let material_info = self.supplier.material_info().unwrap();
+        let api = match &mut self.api {
+            None => {
+                if !stretch_tempo {
+                    return self.supplier.supply_audio(request, dest_buffer);
+                }
+                return self.linear_interpolator.supply_audio(
+                    &mut self.supplier,
+                    request,
+                    dest_buffer,
+                    material_info.channel_count(),
+                    self.tempo_factor,
+                    "time-stretcher-linear-audio",
+                );
+            }
+            Some(api) => api.as_mut().as_mut(),
+        };
         let source_frame_rate = material_info.frame_rate();
         #[cfg(debug_assertions)]
         {
@@ -102,11 +209,15 @@ impl<S: AudioSupplier + WithMaterialInfo> AudioSupplier for TimeStretcher<S> {
         // rate of the source. Then the result could be even cached and sample rate & play-rate
         // changes don't need to invalidate the cache.
         // TODO-medium Setting this right at the beginning should be enough.
-        let api = self.api.as_mut().as_mut();
         api.set_srate(source_frame_rate.get());
         let source_channel_count = material_info.channel_count();
         api.set_nch(source_channel_count as _);
-        api.set_tempo(self.tempo_factor);
+        api.set_tempo(if stretch_tempo {
+            self.tempo_factor
+        } else {
+            1.0
+        });
+        api.set_shift(self.pitch_factor);
         let reached_end = loop {
             // Get time stretcher buffer.
             let buffer_frame_count = 128usize;
@@ -184,6 +295,32 @@ impl<S: AudioSupplier + WithMaterialInfo> AudioSupplier for TimeStretcher<S> {
     }
 }
 
+impl<S: AudioSupplier + PreStretchSkill> AudioSupplier for TimeStretcher<S> {
+    fn supply_audio(
+        &mut self,
+        request: &SupplyAudioRequest,
+        dest_buffer: &mut AudioBufMut,
+    ) -> SupplyResponse {
+        let params = match self.pre_stretch_params() {
+            None => return self.supplier.supply_audio(request, dest_buffer),
+            Some(p) => p,
+        };
+        if let Some(response) =
+            self.supplier
+                .supply_pre_stretched_audio(request, dest_buffer, params)
+        {
+            self.used_pre_stretched_material = true;
+            return response;
+        }
+        // Cache miss. Fall back to stretching in the real-time thread.
+        if self.used_pre_stretched_material {
+            self.reset_buffers_and_latency();
+            self.used_pre_stretched_material = false;
+        }
+        self.stretch_audio(request, dest_buffer)
+    }
+}
+
 impl<S: MidiSupplier> MidiSupplier for TimeStretcher<S> {
     fn supply_midi(
         &mut self,
@@ -191,8 +328,12 @@ impl<S: MidiSupplier> MidiSupplier for TimeStretcher<S> {
         event_list: &mut BorrowedMidiEventList,
     ) -> SupplyResponse {
         // With MIDI, the resampler takes care of adjusting the tempo (since it needs to adjust
-        // the frame rate anyway).
-        self.supplier.supply_midi(request, event_list)
+        // the frame rate anyway). We just take care of transposing.
+        let response = self.supplier.supply_midi(request, event_list);
+        if self.enabled {
+            self.transpose_notes(event_list);
+        }
+        response
     }
 
     fn release_notes(
@@ -201,11 +342,76 @@ impl<S: MidiSupplier> MidiSupplier for TimeStretcher<S> {
         event_list: &mut BorrowedMidiEventList,
     ) {
         self.supplier.release_notes(frame_offset, event_list);
+        if self.enabled {
+            self.transpose_notes(event_list);
+        }
     }
 }
 
-impl<S: PreBufferSourceSkill> PreBufferSourceSkill for TimeStretcher<S> {
+impl<S> TimeStretcher<S> {
+    /// Transposes note-on and note-off events. Notes which would leave the valid key range are
+    /// left alone.
+    fn transpose_notes(&mut self, event_list: &mut BorrowedMidiEventList) {
+        for event in event_list.iter_mut() {
+            let transposed_msg = match event.message().to_structured() {
+                StructuredShortMessage::NoteOn {
+                    channel,
+                    key_number,
+                    velocity,
+                } if velocity.get() > 0 => {
+                    let transposition = &mut self.note_transpositions[channel.get() as usize]
+                        [key_number.get() as usize];
+                    let new_key = key_number.get() as i32 + self.midi_transposition;
+                    if (0..128).contains(&new_key) {
+                        *transposition = self.midi_transposition as i8;
+                    } else {
+                        *transposition = 0;
+                    }
+                    if *transposition == 0 {
+                        continue;
+                    }
+                    RawShortMessage::note_on(
+                        channel,
+                        KeyNumber::new((key_number.get() as i32 + *transposition as i32) as u8),
+                        velocity,
+                    )
+                }
+                StructuredShortMessage::NoteOn {
+                    channel,
+                    key_number,
+                    velocity,
+                }
+                | StructuredShortMessage::NoteOff {
+                    channel,
+                    key_number,
+                    velocity,
+                } => {
+                    let transposition = std::mem::replace(
+                        &mut self.note_transpositions[channel.get() as usize]
+                            [key_number.get() as usize],
+                        0,
+                    );
+                    if transposition == 0 {
+                        continue;
+                    }
+                    RawShortMessage::note_off(
+                        channel,
+                        KeyNumber::new((key_number.get() as i32 + transposition as i32) as u8),
+                        velocity,
+                    )
+                }
+                _ => continue,
+            };
+            event.set_message(transposed_msg);
+        }
+    }
+}
+
+impl<S: PreBufferSourceSkill + PreStretchSkill> PreBufferSourceSkill for TimeStretcher<S> {
     fn pre_buffer(&mut self, request: PreBufferFillRequest) {
+        if let Some(params) = self.pre_stretch_params() {
+            self.supplier.pre_stretch(request.start_frame, params);
+        }
         self.supplier.pre_buffer(request);
     }
 }
@@ -1,7 +1,7 @@
 use crate::rt::supplier::MIDI_BASE_BPM;
 use playtime_api::persistence::{BeatTimeBase, ClipTimeBase, TempoRange};
 use reaper_high::{Project, Reaper};
-use reaper_medium::{Bpm, DurationInSeconds, PositionInSeconds};
+use reaper_medium::{Bpm, DurationInSeconds, Hz, PositionInSeconds};
 
 #[allow(dead_code)]
 pub fn detect_tempo(
@@ -43,6 +43,18 @@ pub fn determine_tempo_from_beat_time_base(beat_time_base: &BeatTimeBase, is_mid
     }
 }
 
+/// Returns the number of frames per beat which is used for placing envelope points.
+///
+/// Clips with time base "Time" don't have a tempo, so we use the MIDI base tempo for them.
+pub fn determine_envelope_frames_per_beat(
+    time_base: &ClipTimeBase,
+    is_midi: bool,
+    frame_rate: Hz,
+) -> f64 {
+    let tempo = determine_tempo_from_time_base(time_base, is_midi).unwrap_or(MIDI_BASE_BPM);
+    frame_rate.get() * 60.0 / tempo.get()
+}
+
 pub fn calc_tempo_factor(clip_tempo: Bpm, timeline_tempo: Bpm) -> f64 {
     let timeline_tempo_factor = timeline_tempo.get() / clip_tempo.get();
     timeline_tempo_factor.max(MIN_TEMPO_FACTOR)
@@ -1,11 +1,12 @@
 use crate::file_util::get_path_for_new_media_file;
 use crate::rt::source_util::{get_pcm_source_type, PcmSourceType};
 use crate::rt::supplier::{ClipSource, MIDI_BASE_BPM};
+use crate::rt::AudioBuf;
 use crate::{rt, ClipEngineResult};
 use playtime_api::persistence as api;
 use playtime_api::persistence::{FileSource, MidiChunkSource};
 use reaper_high::{BorrowedSource, Item, OwnedSource, Project, Reaper, ReaperSource};
-use reaper_medium::{BorrowedPcmSource, MidiImportBehavior};
+use reaper_medium::{BorrowedPcmSource, Hz, MidiImportBehavior};
 use std::borrow::Cow;
 use std::error::Error;
 use std::path::{Path, PathBuf};
@@ -14,7 +15,6 @@ use std::path::{Path, PathBuf};
 ///
 /// If the item uses pooled MIDI instead of a file, this method exports the MIDI data to a new
 /// file in the recording directory and uses that one.
-#[allow(dead_code)]
 pub fn create_api_source_from_item(
     item: Item,
     force_export_to_file: bool,
@@ -36,7 +36,6 @@ pub fn create_api_source_from_item(
 }
 
 /// Determines how to handle MIDI PCM sources.
-#[allow(dead_code)]
 pub enum CreateApiSourceMode {
     AllowEmbeddedData,
     ForceExportToFile { file_base_name: String },
@@ -89,6 +88,30 @@ pub fn create_file_api_source(project: Option<Project>, file: &Path) -> api::Sou
     })
 }
 
+/// Writes the given audio material to a new 32-bit float WAV file.
+pub fn write_audio_file(file: &Path, buffer: AudioBuf, frame_rate: Hz) -> ClipEngineResult<()> {
+    if let Some(dir) = file.parent() {
+        std::fs::create_dir_all(dir).map_err(|_| "couldn't create directory for audio file")?;
+    }
+    let spec = hound::WavSpec {
+        channels: buffer.channel_count() as u16,
+        sample_rate: frame_rate.get() as u32,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let mut writer =
+        hound::WavWriter::create(file, spec).map_err(|_| "couldn't create WAV file")?;
+    for sample in buffer.data_as_slice() {
+        writer
+            .write_sample(*sample as f32)
+            .map_err(|_| "couldn't write WAV file")?;
+    }
+    writer
+        .finalize()
+        .map_err(|_| "couldn't finalize WAV file")?;
+    Ok(())
+}
+
 fn create_midi_chunk_source(chunk: String) -> api::Source {
     api::Source::MidiChunk(api::MidiChunkSource { chunk })
 }